@@ -0,0 +1,308 @@
+//! Derives for `bevy_boa_reflect`'s direct conversions, which convert each field in turn rather
+//! than going through reflection. See `bevy_boa_reflect::IntoJs` and `bevy_boa_reflect::FromJs`.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{
+    parse_macro_input, parse_quote, Data, DeriveInput, Error, Field, Fields, Generics, Ident,
+    Result,
+};
+
+/// Derive `IntoJs`, converting the type to the same shape reflection would. Fields marked
+/// `#[js(reflect)]` are converted through reflection, for field types without `IntoJs`.
+#[proc_macro_derive(IntoJs, attributes(js))]
+pub fn derive_into_js(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    into_js(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// Derive `FromJs`, converting the type from the same shapes reflection would. Fields marked
+/// `#[js(reflect)]` are converted through reflection, for field types without `FromJs`.
+#[proc_macro_derive(FromJs, attributes(js))]
+pub fn derive_from_js(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    from_js(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn into_js(input: &DeriveInput) -> Result<TokenStream2> {
+    let name = &input.ident;
+    let generics = bounded(&input.generics, quote!(::bevy_boa_reflect::IntoJs));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let body = match &input.data {
+        Data::Struct(data) => {
+            let (bindings, values) = field_values(&data.fields)?;
+            let pattern = pattern(quote!(Self), &data.fields, &bindings);
+            let convert = match &data.fields {
+                Fields::Unnamed(_) => quote!(p::tuple_to_js(values, ctx)),
+                fields => {
+                    let names = field_names(fields);
+                    quote!(p::struct_to_js::<Self>(&[#(#names),*], values, ctx))
+                }
+            };
+            quote! {
+                let #pattern = self;
+                let values = ::std::vec![#(#values),*];
+                Ok(#convert)
+            }
+        }
+        Data::Enum(data) => {
+            let arms = data
+                .variants
+                .iter()
+                .map(|variant| {
+                    let ident = &variant.ident;
+                    let variant_name = ident.to_string();
+                    let (bindings, values) = field_values(&variant.fields)?;
+                    let pattern = pattern(quote!(Self::#ident), &variant.fields, &bindings);
+                    let names = variant.fields.iter().map(|field| match &field.ident {
+                        Some(ident) => {
+                            let name = ident.to_string();
+                            quote!(Some(#name))
+                        }
+                        None => quote!(None),
+                    });
+                    Ok(quote! {
+                        #pattern => {
+                            let values = ::std::vec![#(#values),*];
+                            p::variant_to_js(#variant_name, &[#(#names),*], values, ctx)
+                        }
+                    })
+                })
+                .collect::<Result<Vec<_>>>()?;
+            quote! {
+                Ok(match self {
+                    #(#arms)*
+                })
+            }
+        }
+        Data::Union(_) => {
+            return Err(Error::new_spanned(
+                name,
+                "IntoJs can't be derived for unions",
+            ));
+        }
+    };
+
+    Ok(quote! {
+        impl #impl_generics ::bevy_boa_reflect::IntoJs for #name #ty_generics #where_clause {
+            fn to_js(
+                &self,
+                ctx: &mut ::bevy_boa_reflect::__private::Context,
+            ) -> ::core::result::Result<
+                ::bevy_boa_reflect::__private::JsValue,
+                ::bevy_boa_reflect::ConversionError,
+            > {
+                use ::bevy_boa_reflect::__private as p;
+                #body
+            }
+        }
+    })
+}
+
+fn from_js(input: &DeriveInput) -> Result<TokenStream2> {
+    let name = &input.ident;
+    let generics = bounded(&input.generics, quote!(::bevy_boa_reflect::FromJs));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let body = match &input.data {
+        Data::Struct(data) => {
+            let construct = match &data.fields {
+                Fields::Unnamed(fields) => {
+                    let values = fields
+                        .unnamed
+                        .iter()
+                        .enumerate()
+                        .map(|(idx, field)| {
+                            let value = quote!(&p::item(&items, #idx));
+                            field_from_js(field, value, quote!(p::at_index), quote!(#idx))
+                        })
+                        .collect::<Result<Vec<_>>>()?;
+                    quote! {
+                        let items = p::expect_array(value, type_path, ctx)?;
+                        Ok(Self(#(#values),*))
+                    }
+                }
+                fields => {
+                    let values = named_from_js(fields)?;
+                    quote! {
+                        let obj = p::expect_object(value, type_path)?;
+                        Ok(Self { #(#values),* })
+                    }
+                }
+            };
+            quote! {
+                let type_path = ::core::any::type_name::<Self>();
+                #construct
+            }
+        }
+        Data::Enum(data) => {
+            let arms = data
+                .variants
+                .iter()
+                .map(|variant| {
+                    let ident = &variant.ident;
+                    let variant_name = ident.to_string();
+                    let construct = match &variant.fields {
+                        Fields::Unit => quote!(Ok(Self::#ident)),
+                        Fields::Unnamed(fields) => {
+                            let values = fields
+                                .unnamed
+                                .iter()
+                                .enumerate()
+                                .map(|(idx, field)| {
+                                    let key = idx.to_string();
+                                    let value = quote!(&p::get(&obj, #key, ctx)?);
+                                    field_from_js(field, value, quote!(p::at_index), quote!(#idx))
+                                })
+                                .collect::<Result<Vec<_>>>()?;
+                            quote! {
+                                let obj = p::variant_fields(obj, type_path, &variant)?;
+                                Ok(Self::#ident(#(#values),*))
+                            }
+                        }
+                        Fields::Named(_) => {
+                            let values = named_from_js(&variant.fields)?;
+                            quote! {
+                                let obj = p::variant_fields(obj, type_path, &variant)?;
+                                Ok(Self::#ident { #(#values),* })
+                            }
+                        }
+                    };
+                    Ok(quote!(#variant_name => { #construct }))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            quote! {
+                let type_path = ::core::any::type_name::<Self>();
+                let (variant, obj) = p::read_variant(value, type_path, ctx)?;
+                match variant.as_str() {
+                    #(#arms)*
+                    _ => Err(p::unknown_variant(type_path, variant)),
+                }
+            }
+        }
+        Data::Union(_) => {
+            return Err(Error::new_spanned(
+                name,
+                "FromJs can't be derived for unions",
+            ));
+        }
+    };
+
+    Ok(quote! {
+        impl #impl_generics ::bevy_boa_reflect::FromJs for #name #ty_generics #where_clause {
+            #[allow(unused_variables)]
+            fn from_js(
+                value: &::bevy_boa_reflect::__private::JsValue,
+                ctx: &mut ::bevy_boa_reflect::__private::Context,
+            ) -> ::core::result::Result<Self, ::bevy_boa_reflect::ConversionError> {
+                use ::bevy_boa_reflect::__private as p;
+                #body
+            }
+        }
+    })
+}
+
+/// The generics of the type, with each type parameter bound by the derived trait. Conversions
+/// cache per type, so parameters must be `'static` too.
+fn bounded(generics: &Generics, bound: TokenStream2) -> Generics {
+    let mut generics = generics.clone();
+    for param in generics.type_params_mut() {
+        param.bounds.push(parse_quote!(#bound));
+        param.bounds.push(parse_quote!('static));
+    }
+    generics
+}
+
+/// Names to bind each field to when destructuring, and the expressions converting them.
+fn field_values(fields: &Fields) -> Result<(Vec<Ident>, Vec<TokenStream2>)> {
+    let mut bindings = Vec::new();
+    let mut values = Vec::new();
+    for (idx, field) in fields.iter().enumerate() {
+        let binding = format_ident!("field_{}", idx);
+        let convert = if is_reflect(field)? {
+            quote!(p::reflect_to_js(#binding, ctx))
+        } else {
+            quote!(::bevy_boa_reflect::IntoJs::to_js(#binding, ctx))
+        };
+        values.push(match &field.ident {
+            Some(ident) => {
+                let name = ident.to_string();
+                quote!(p::at_field(#convert, #name)?)
+            }
+            None => quote!(p::at_index(#convert, #idx)?),
+        });
+        bindings.push(binding);
+    }
+    Ok((bindings, values))
+}
+
+/// A pattern destructuring `path` into the bindings of its fields.
+fn pattern(path: TokenStream2, fields: &Fields, bindings: &[Ident]) -> TokenStream2 {
+    match fields {
+        Fields::Named(named) => {
+            let idents = named.named.iter().map(|field| &field.ident);
+            quote!(#path { #(#idents: #bindings),* })
+        }
+        Fields::Unnamed(_) => quote!(#path(#(#bindings),*)),
+        Fields::Unit => path,
+    }
+}
+
+fn field_names(fields: &Fields) -> Vec<String> {
+    fields
+        .iter()
+        .filter_map(|field| field.ident.as_ref())
+        .map(Ident::to_string)
+        .collect()
+}
+
+/// The field initializers of named fields, read from `obj`.
+fn named_from_js(fields: &Fields) -> Result<Vec<TokenStream2>> {
+    fields
+        .iter()
+        .filter_map(|field| field.ident.as_ref().map(|ident| (ident, field)))
+        .map(|(ident, field)| {
+            let name = ident.to_string();
+            let value = quote!(&p::get(&obj, #name, ctx)?);
+            let converted = field_from_js(field, value, quote!(p::at_field), quote!(#name))?;
+            Ok(quote!(#ident: #converted))
+        })
+        .collect()
+}
+
+/// The expression converting a field from `value`, with errors placed at the field.
+fn field_from_js(
+    field: &Field,
+    value: TokenStream2,
+    at: TokenStream2,
+    segment: TokenStream2,
+) -> Result<TokenStream2> {
+    let convert = if is_reflect(field)? {
+        quote!(p::reflect_from_js(#value, ctx))
+    } else {
+        quote!(::bevy_boa_reflect::FromJs::from_js(#value, ctx))
+    };
+    Ok(quote!(#at(#convert, #segment)?))
+}
+
+/// Whether the field is marked `#[js(reflect)]`.
+fn is_reflect(field: &Field) -> Result<bool> {
+    let mut reflect = false;
+    for attr in field.attrs.iter().filter(|attr| attr.path().is_ident("js")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("reflect") {
+                reflect = true;
+                Ok(())
+            } else {
+                Err(meta.error("expected `reflect`"))
+            }
+        })?;
+    }
+    Ok(reflect)
+}
@@ -0,0 +1,79 @@
+use std::cell::Cell;
+use std::ptr::NonNull;
+
+use bevy::ecs::component::Tick;
+use bevy::prelude::*;
+use boa_engine::{JsNativeError, JsResult};
+
+thread_local! {
+    static WORLD: Cell<Option<NonNull<World>>> = const { Cell::new(None) };
+    static INSTANCE: Cell<Option<(Entity, Tick)>> = const { Cell::new(None) };
+}
+
+/// Run `f` with the world available to script bindings, which need it to read and change the
+/// world while a script is running. The script runtime has to be taken out of the world first,
+/// since bindings may access any resource.
+pub fn provide_world<R>(world: &mut World, f: impl FnOnce() -> R) -> R {
+    /// Restores the previously provided world, even if `f` panics.
+    struct Restore(Option<NonNull<World>>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            WORLD.set(self.0);
+        }
+    }
+
+    let _restore = Restore(WORLD.replace(Some(NonNull::from(world))));
+    f()
+}
+
+/// Access the world provided by [`provide_world`]. Fails when called outside of it, e.g. from a
+/// script evaluated directly through the [`Context`](boa_engine::Context), or from within another
+/// call to `with_world`.
+pub(crate) fn with_world<R>(f: impl FnOnce(&mut World) -> R) -> JsResult<R> {
+    /// Puts the world back once the access ends, even if `f` panics.
+    struct Release(NonNull<World>);
+
+    impl Drop for Release {
+        fn drop(&mut self) {
+            WORLD.set(Some(self.0));
+        }
+    }
+
+    let mut world = WORLD.take().ok_or_else(|| {
+        JsNativeError::error().with_message("The world is not available to this script")
+    })?;
+    let _release = Release(world);
+    // SAFETY: the pointer was created from a `&mut World` that outlives the `provide_world` call
+    // we are inside of, and taking it out of the cell for the duration of `f` makes this the only
+    // reference to it.
+    Ok(f(unsafe { world.as_mut() }))
+}
+
+/// Run `f` for the script instance of an entity, which last ran at `last_run`. Change detection
+/// in the bindings compares against `last_run`, and per-instance state is kept for the entity.
+pub(crate) fn run_instance<R>(entity: Entity, last_run: Tick, f: impl FnOnce() -> R) -> R {
+    struct Restore(Option<(Entity, Tick)>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            INSTANCE.set(self.0);
+        }
+    }
+
+    let _restore = Restore(INSTANCE.replace(Some((entity, last_run))));
+    f()
+}
+
+/// The entity of the running script instance, if any.
+pub(crate) fn current_instance() -> Option<Entity> {
+    INSTANCE.get().map(|(entity, _)| entity)
+}
+
+/// The tick the running script instance last ran at. Outside of an instance, everything counts
+/// as changed.
+pub(crate) fn last_run() -> Tick {
+    INSTANCE
+        .get()
+        .map_or(Tick::new(0), |(_, last_run)| last_run)
+}
@@ -0,0 +1,715 @@
+use std::time::Duration;
+
+use bevy::animation::prelude::{AnimationGraph, AnimationNodeIndex, AnimationTransitions};
+use bevy::animation::{advance_animations, ActiveAnimation, AnimationPlayer, RepeatAnimation};
+use bevy::prelude::*;
+use boa_engine::object::builtins::JsFunction;
+use boa_engine::object::ObjectInitializer;
+use boa_engine::property::Attribute;
+use boa_engine::realm::Realm;
+use boa_engine::{
+    js_str, js_string, Context, Finalize, JsData, JsNativeError, JsObject, JsResult, JsValue,
+    NativeFunction, Trace,
+};
+
+use crate::access::with_world;
+use crate::bindings::{arg, in_realm};
+use crate::plugin::with_runtime;
+use crate::runtime::{entity_to_js_value, js_value_to_entity, ScriptRuntime};
+
+/// The global the animation API is installed under.
+pub const ANIMATION_BINDING: &str = "animation";
+
+/// Gives scripts control of the clips an entity's [`AnimationPlayer`] plays, through an
+/// `animation` global. Clips are named by their node in the entity's [`AnimationGraph`], or by
+/// the asset path of the clip, like `"models/fox.glb#Animation0"`.
+///
+/// - `animation.play(entity, clip, { speed, repeat, weight, seek, transition })` plays a clip,
+///   replaying it if it finished. `repeat` is `true` to loop forever or a number of times, and
+///   `transition` fades out the other clips over that many seconds, through the entity's
+///   [`AnimationTransitions`].
+/// - `animation.pause(entity, clip)`, `resume` and `stop` act on one clip, or on every clip
+///   without one.
+/// - `animation.seek(entity, clip, seconds)`, `setSpeed(entity, clip, speed)` and
+///   `blend(entity, clip, weight)`, which starts the clip if it isn't playing.
+/// - `animation.status(entity, clip)` returns the clip's `time`, `speed`, `weight`, `elapsed`,
+///   `completions`, `paused` and `finished`, or `null` if it isn't playing.
+/// - `animation.on(entity, clip, time, callback)` calls `callback({ entity, clip, time })` each
+///   time the clip passes a point in its timeline, or once it finishes with `"finish"` in place
+///   of the time. It returns an id for `animation.off(id)`.
+///
+/// Callbacks are called each frame once animations have advanced. Add it next to
+/// [`BoaScriptPlugin`](crate::BoaScriptPlugin).
+#[derive(Default)]
+pub struct ScriptAnimationPlugin;
+
+impl Plugin for ScriptAnimationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, register_animation).add_systems(
+            PostUpdate,
+            dispatch_animation_events.after(advance_animations),
+        );
+    }
+}
+
+/// The animation callbacks scripts registered, kept outside the runtime so they can be called
+/// each frame.
+struct ScriptAnimations(JsObject);
+
+#[derive(Trace, Finalize, JsData)]
+struct AnimationListeners {
+    #[unsafe_ignore_trace]
+    next_id: u32,
+    listeners: Vec<AnimationListener>,
+}
+
+#[derive(Trace, Finalize)]
+struct AnimationListener {
+    #[unsafe_ignore_trace]
+    id: u32,
+    #[unsafe_ignore_trace]
+    entity: Entity,
+    #[unsafe_ignore_trace]
+    node: AnimationNodeIndex,
+    /// The clip as the script named it, handed back to the callback.
+    clip: JsValue,
+    #[unsafe_ignore_trace]
+    trigger: Trigger,
+    callback: JsFunction,
+    /// The realm of the script that subscribed, which events are made in.
+    realm: Realm,
+    /// Where the clip was when the listener last checked, or `None` if it wasn't playing.
+    #[unsafe_ignore_trace]
+    last: Option<Progress>,
+}
+
+#[derive(Clone, Copy)]
+enum Trigger {
+    At(f32),
+    Finish,
+}
+
+/// Where a playing clip is in its timeline.
+#[derive(Clone, Copy)]
+struct Progress {
+    seek_time: f32,
+    completions: u32,
+    speed: f32,
+    finished: bool,
+}
+
+impl Progress {
+    fn of(animation: &ActiveAnimation) -> Self {
+        Self {
+            seek_time: animation.seek_time(),
+            completions: animation.completions(),
+            speed: animation.speed(),
+            finished: animation.is_finished(),
+        }
+    }
+}
+
+impl Trigger {
+    /// Whether a clip reached the trigger between two checks.
+    fn passed(self, last: Option<Progress>, current: Progress) -> bool {
+        let time = match self {
+            Self::Finish => return current.finished && !last.is_some_and(|last| last.finished),
+            Self::At(time) => time,
+        };
+        let reversed = current.speed < 0.0;
+        let Some(last) = last else {
+            // The clip started since the last check.
+            return if reversed {
+                time >= current.seek_time
+            } else {
+                time <= current.seek_time
+            };
+        };
+        let laps = current.completions.saturating_sub(last.completions);
+        match (laps, reversed) {
+            (0, false) => last.seek_time < time && time <= current.seek_time,
+            (0, true) => current.seek_time <= time && time < last.seek_time,
+            // A finished clip stops at its end rather than wrapping around.
+            (_, false) if current.finished => last.seek_time < time,
+            (_, true) if current.finished => time < last.seek_time,
+            (1, false) => last.seek_time < time || time <= current.seek_time,
+            (1, true) => time < last.seek_time || current.seek_time <= time,
+            _ => true,
+        }
+    }
+}
+
+/// A binding function with the animation listeners.
+type ListenersFn = fn(&JsValue, &[JsValue], &JsObject, &mut Context) -> JsResult<JsValue>;
+
+/// A clip as scripts name it.
+enum ClipRef {
+    Node(AnimationNodeIndex),
+    Path(String),
+}
+
+impl ClipRef {
+    fn from_js(value: &JsValue, ctx: &mut Context) -> JsResult<Self> {
+        if let Some(path) = value.as_string() {
+            return Ok(Self::Path(path.to_std_string_escaped()));
+        }
+        if value.is_number() {
+            let index = value.to_number(ctx)?;
+            if index >= 0.0 && index.fract() == 0.0 {
+                return Ok(Self::Node(AnimationNodeIndex::new(index as usize)));
+            }
+        }
+        Err(JsNativeError::typ()
+            .with_message("Expected an animation graph node or the asset path of a clip")
+            .into())
+    }
+
+    /// The node of the entity's animation graph playing the clip.
+    fn node(&self, world: &World, entity: Entity) -> JsResult<AnimationNodeIndex> {
+        let path = match self {
+            Self::Node(node) => return Ok(*node),
+            Self::Path(path) => path,
+        };
+        let not_found =
+            || JsNativeError::error().with_message(format!("{entity} has no animation {path}"));
+        let graph = world
+            .get::<Handle<AnimationGraph>>(entity)
+            .and_then(|graph| world.get_resource::<Assets<AnimationGraph>>()?.get(graph))
+            .ok_or_else(not_found)?;
+        let asset_server = world.resource::<AssetServer>();
+        graph
+            .nodes()
+            .find(|node| {
+                graph
+                    .get(*node)
+                    .and_then(|node| node.clip.as_ref())
+                    .and_then(|clip| asset_server.get_path(clip.id()))
+                    .is_some_and(|clip_path| clip_path.to_string() == *path)
+            })
+            .ok_or_else(|| not_found().into())
+    }
+}
+
+fn register_animation(world: &mut World) {
+    let Some(mut runtime) = world.get_non_send_resource_mut::<ScriptRuntime>() else {
+        warn!("Script animation needs the BoaScriptPlugin");
+        return;
+    };
+    let listeners = JsObject::from_proto_and_data(
+        None,
+        AnimationListeners {
+            next_id: 0,
+            listeners: Vec::new(),
+        },
+    );
+    let animation_listeners = listeners.clone();
+    let result = runtime.register_global(ANIMATION_BINDING, move |ctx| {
+        Ok(animation_binding(&animation_listeners, ctx))
+    });
+    if let Err(err) = result {
+        error!("Error registering script animation: {err}");
+        return;
+    }
+    world.insert_non_send_resource(ScriptAnimations(listeners));
+}
+
+/// Build the `animation` object, with the functions that subscribe listeners sharing `listeners`.
+fn animation_binding(listeners: &JsObject, ctx: &mut Context) -> JsObject {
+    let with_listeners = |function: ListenersFn| {
+        NativeFunction::from_copy_closure_with_captures(function, listeners.clone())
+    };
+    ObjectInitializer::new(ctx)
+        .function(with_listeners(play), js_string!("play"), 3)
+        .function(NativeFunction::from_fn_ptr(pause), js_string!("pause"), 2)
+        .function(NativeFunction::from_fn_ptr(resume), js_string!("resume"), 2)
+        .function(with_listeners(stop), js_string!("stop"), 2)
+        .function(with_listeners(seek), js_string!("seek"), 3)
+        .function(
+            NativeFunction::from_fn_ptr(set_speed),
+            js_string!("setSpeed"),
+            3,
+        )
+        .function(NativeFunction::from_fn_ptr(blend), js_string!("blend"), 3)
+        .function(NativeFunction::from_fn_ptr(status), js_string!("status"), 2)
+        .function(with_listeners(on), js_string!("on"), 4)
+        .function(with_listeners(off), js_string!("off"), 1)
+        .build()
+}
+
+/// Run `f` with an entity's animation player and the node of a clip, if one was named.
+fn with_player<R>(
+    args: &[JsValue],
+    named: bool,
+    ctx: &mut Context,
+    f: impl FnOnce(&mut World, Entity, Option<AnimationNodeIndex>) -> R,
+) -> JsResult<R> {
+    let entity = js_value_to_entity(&arg(args, 0))?;
+    let clip = match arg(args, 1) {
+        clip if !named && clip.is_undefined() => None,
+        clip => Some(ClipRef::from_js(&clip, ctx)?),
+    };
+    with_world(|world| {
+        if !world
+            .get_entity(entity)
+            .is_some_and(|e| e.contains::<AnimationPlayer>())
+        {
+            return Err(JsNativeError::error()
+                .with_message(format!("{entity} has no AnimationPlayer"))
+                .into());
+        }
+        let node = clip.map(|clip| clip.node(world, entity)).transpose()?;
+        Ok(f(world, entity, node))
+    })?
+}
+
+fn player(world: &mut World, entity: Entity) -> Mut<'_, AnimationPlayer> {
+    world
+        .get_mut::<AnimationPlayer>(entity)
+        .expect("entity was checked to have an AnimationPlayer")
+}
+
+/// `animation.play(entity, clip, options)`.
+fn play(
+    _: &JsValue,
+    args: &[JsValue],
+    listeners: &JsObject,
+    ctx: &mut Context,
+) -> JsResult<JsValue> {
+    let options = arg(args, 2);
+    let options = options.as_object();
+    let option = |name, ctx: &mut Context| match options {
+        Some(options) => options.get(name, ctx),
+        None => Ok(JsValue::undefined()),
+    };
+    let number = |value: JsValue, ctx: &mut Context| {
+        (!value.is_undefined())
+            .then(|| value.to_number(ctx).map(|n| n as f32))
+            .transpose()
+    };
+    let speed = number(option(js_str!("speed"), ctx)?, ctx)?;
+    let weight = number(option(js_str!("weight"), ctx)?, ctx)?;
+    let seek = number(option(js_str!("seek"), ctx)?, ctx)?;
+    let transition = number(option(js_str!("transition"), ctx)?, ctx)?;
+    let repeat = match option(js_str!("repeat"), ctx)? {
+        JsValue::Undefined => None,
+        JsValue::Boolean(true) => Some(RepeatAnimation::Forever),
+        JsValue::Boolean(false) => Some(RepeatAnimation::Never),
+        count => Some(RepeatAnimation::Count(count.to_u32(ctx)?)),
+    };
+    let (entity, node, progress) = with_player(args, true, ctx, |world, entity, node| {
+        let node = node.expect("a clip was named");
+        let mut players =
+            world.query::<(&mut AnimationPlayer, Option<&mut AnimationTransitions>)>();
+        let (mut player, transitions) = players
+            .get_mut(world, entity)
+            .expect("entity was checked to have an AnimationPlayer");
+        // Whether the clip starts over, rather than carrying on from where it was.
+        let restarted = match (transitions, transition) {
+            (Some(mut transitions), Some(seconds)) => {
+                let duration = Duration::try_from_secs_f32(seconds).unwrap_or_default();
+                transitions.play(&mut player, node, duration);
+                true
+            }
+            _ => {
+                let was_playing = player.animation(node).is_some();
+                let active = player.play(node);
+                let finished = active.is_finished();
+                if finished {
+                    active.replay();
+                }
+                finished || !was_playing
+            }
+        };
+        let active = player
+            .animation_mut(node)
+            .expect("the clip was just played");
+        if let Some(speed) = speed {
+            active.set_speed(speed);
+        }
+        if let Some(weight) = weight {
+            active.set_weight(weight);
+        }
+        if let Some(repeat) = repeat {
+            active.set_repeat(repeat);
+        }
+        if let Some(seek) = seek {
+            active.seek_to(seek);
+        }
+        let progress = (!restarted || seek.is_some()).then(|| Progress::of(active));
+        (entity, node, progress)
+    })?;
+    reset_listeners(listeners, entity, Some(node), progress);
+    Ok(JsValue::undefined())
+}
+
+fn pause(_: &JsValue, args: &[JsValue], ctx: &mut Context) -> JsResult<JsValue> {
+    with_player(args, false, ctx, |world, entity, node| {
+        let mut player = player(world, entity);
+        match node {
+            Some(node) => {
+                if let Some(active) = player.animation_mut(node) {
+                    active.pause();
+                }
+            }
+            None => {
+                player.pause_all();
+            }
+        }
+    })?;
+    Ok(JsValue::undefined())
+}
+
+fn resume(_: &JsValue, args: &[JsValue], ctx: &mut Context) -> JsResult<JsValue> {
+    with_player(args, false, ctx, |world, entity, node| {
+        let mut player = player(world, entity);
+        match node {
+            Some(node) => {
+                if let Some(active) = player.animation_mut(node) {
+                    active.resume();
+                }
+            }
+            None => {
+                player.resume_all();
+            }
+        }
+    })?;
+    Ok(JsValue::undefined())
+}
+
+fn stop(
+    _: &JsValue,
+    args: &[JsValue],
+    listeners: &JsObject,
+    ctx: &mut Context,
+) -> JsResult<JsValue> {
+    let (entity, node) = with_player(args, false, ctx, |world, entity, node| {
+        let mut player = player(world, entity);
+        match node {
+            Some(node) => {
+                player.stop(node);
+            }
+            None => {
+                player.stop_all();
+            }
+        }
+        (entity, node)
+    })?;
+    reset_listeners(listeners, entity, node, None);
+    Ok(JsValue::undefined())
+}
+
+/// `animation.seek(entity, clip, seconds)`, returning whether the clip is playing.
+fn seek(
+    _: &JsValue,
+    args: &[JsValue],
+    listeners: &JsObject,
+    ctx: &mut Context,
+) -> JsResult<JsValue> {
+    let time = arg(args, 2).to_number(ctx)? as f32;
+    let sought = with_player(args, true, ctx, |world, entity, node| {
+        let node = node.expect("a clip was named");
+        let mut player = player(world, entity);
+        let active = player.animation_mut(node)?;
+        active.seek_to(time);
+        Some((entity, node, Progress::of(active)))
+    })?;
+    let Some((entity, node, progress)) = sought else {
+        return Ok(false.into());
+    };
+    reset_listeners(listeners, entity, Some(node), Some(progress));
+    Ok(true.into())
+}
+
+fn set_speed(_: &JsValue, args: &[JsValue], ctx: &mut Context) -> JsResult<JsValue> {
+    let speed = arg(args, 2).to_number(ctx)? as f32;
+    let playing = with_player(args, true, ctx, |world, entity, node| {
+        let node = node.expect("a clip was named");
+        player(world, entity)
+            .animation_mut(node)
+            .map(|active| active.set_speed(speed))
+            .is_some()
+    })?;
+    Ok(playing.into())
+}
+
+/// `animation.blend(entity, clip, weight)`: set how much a clip contributes, starting it if it
+/// isn't playing.
+fn blend(_: &JsValue, args: &[JsValue], ctx: &mut Context) -> JsResult<JsValue> {
+    let weight = arg(args, 2).to_number(ctx)? as f32;
+    with_player(args, true, ctx, |world, entity, node| {
+        let node = node.expect("a clip was named");
+        player(world, entity).play(node).set_weight(weight);
+    })?;
+    Ok(JsValue::undefined())
+}
+
+fn status(_: &JsValue, args: &[JsValue], ctx: &mut Context) -> JsResult<JsValue> {
+    let active = with_player(args, true, ctx, |world, entity, node| {
+        let node = node.expect("a clip was named");
+        player(world, entity).animation(node).copied()
+    })?;
+    let Some(active) = active else {
+        return Ok(JsValue::null());
+    };
+    let status = ObjectInitializer::new(ctx)
+        .property(js_str!("time"), active.seek_time(), Attribute::all())
+        .property(js_str!("speed"), active.speed(), Attribute::all())
+        .property(js_str!("weight"), active.weight(), Attribute::all())
+        .property(js_str!("elapsed"), active.elapsed(), Attribute::all())
+        .property(
+            js_str!("completions"),
+            active.completions(),
+            Attribute::all(),
+        )
+        .property(js_str!("paused"), active.is_paused(), Attribute::all())
+        .property(js_str!("finished"), active.is_finished(), Attribute::all())
+        .build();
+    Ok(status.into())
+}
+
+/// `animation.on(entity, clip, time, callback)`, returning the listener's id.
+fn on(_: &JsValue, args: &[JsValue], listeners: &JsObject, ctx: &mut Context) -> JsResult<JsValue> {
+    let trigger = match arg(args, 2) {
+        JsValue::String(event) if event == js_str!("finish") => Trigger::Finish,
+        time if time.is_number() => Trigger::At(time.to_number(ctx)? as f32),
+        _ => {
+            return Err(JsNativeError::typ()
+                .with_message("Expected a time in the clip or \"finish\"")
+                .into())
+        }
+    };
+    let callback = arg(args, 3)
+        .as_object()
+        .and_then(|callback| JsFunction::from_object(callback.clone()))
+        .ok_or_else(|| JsNativeError::typ().with_message("Expected a callback function"))?;
+    let (entity, node, last) = with_player(args, true, ctx, |world, entity, node| {
+        let node = node.expect("a clip was named");
+        // A clip that hasn't advanced yet, e.g. one played this frame, still passes its start.
+        let last = player(world, entity)
+            .animation(node)
+            .filter(|active| active.elapsed() > 0.0)
+            .map(Progress::of);
+        (entity, node, last)
+    })?;
+    let mut listeners = listeners
+        .downcast_mut::<AnimationListeners>()
+        .ok_or_else(|| JsNativeError::typ().with_message("animation has no listeners"))?;
+    let id = listeners.next_id;
+    listeners.next_id += 1;
+    listeners.listeners.push(AnimationListener {
+        id,
+        entity,
+        node,
+        clip: arg(args, 1),
+        trigger,
+        callback,
+        realm: ctx.realm().clone(),
+        last,
+    });
+    Ok(id.into())
+}
+
+/// `animation.off(id)`, returning whether the listener existed.
+fn off(
+    _: &JsValue,
+    args: &[JsValue],
+    listeners: &JsObject,
+    ctx: &mut Context,
+) -> JsResult<JsValue> {
+    let id = arg(args, 0).to_u32(ctx)?;
+    let mut listeners = listeners
+        .downcast_mut::<AnimationListeners>()
+        .ok_or_else(|| JsNativeError::typ().with_message("animation has no listeners"))?;
+    let count = listeners.listeners.len();
+    listeners.listeners.retain(|listener| listener.id != id);
+    Ok((listeners.listeners.len() != count).into())
+}
+
+/// Move the listeners of a clip, or of every clip of an entity, to where a script moved the clip
+/// to, so jumps don't count as passing through the timeline.
+fn reset_listeners(
+    listeners: &JsObject,
+    entity: Entity,
+    node: Option<AnimationNodeIndex>,
+    progress: Option<Progress>,
+) {
+    let Some(mut listeners) = listeners.downcast_mut::<AnimationListeners>() else {
+        return;
+    };
+    for listener in &mut listeners.listeners {
+        if listener.entity == entity && node.is_none_or(|node| listener.node == node) {
+            listener.last = progress;
+        }
+    }
+}
+
+/// Call the callbacks of the clips that passed their points since the last frame, and forget
+/// the listeners of entities that were despawned.
+fn dispatch_animation_events(world: &mut World) {
+    let Some(listeners) = world
+        .get_non_send_resource::<ScriptAnimations>()
+        .map(|animations| animations.0.clone())
+    else {
+        return;
+    };
+    let fired = {
+        let Some(mut listeners) = listeners.downcast_mut::<AnimationListeners>() else {
+            return;
+        };
+        let mut fired = Vec::new();
+        listeners.listeners.retain_mut(|listener| {
+            let Some(entity) = world.get_entity(listener.entity) else {
+                return false;
+            };
+            let current = entity
+                .get::<AnimationPlayer>()
+                .and_then(|player| player.animation(listener.node))
+                .map(Progress::of);
+            let last = std::mem::replace(&mut listener.last, current);
+            if let Some(current) = current {
+                if listener.trigger.passed(last, current) {
+                    let time = match listener.trigger {
+                        Trigger::At(time) => time,
+                        Trigger::Finish => current.seek_time,
+                    };
+                    fired.push((
+                        listener.callback.clone(),
+                        listener.realm.clone(),
+                        listener.entity,
+                        listener.clip.clone(),
+                        time,
+                    ));
+                }
+            }
+            true
+        });
+        fired
+    };
+    if fired.is_empty() {
+        return;
+    }
+    with_runtime(world, |runtime| {
+        let ctx = runtime.context();
+        for (callback, realm, entity, clip, time) in fired {
+            let result = in_realm(&realm, ctx, |ctx| {
+                let event = ObjectInitializer::new(ctx)
+                    .property(
+                        js_str!("entity"),
+                        entity_to_js_value(entity),
+                        Attribute::all(),
+                    )
+                    .property(js_str!("clip"), clip, Attribute::all())
+                    .property(js_str!("time"), time, Attribute::all())
+                    .build();
+                callback.call(&JsValue::undefined(), &[event.into()], ctx)
+            });
+            if let Err(err) = result {
+                error!("Error handling an animation event: {err}");
+            }
+        }
+        ctx.run_jobs();
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use boa_engine::Source;
+
+    use super::*;
+    use crate::access::provide_world;
+
+    fn progress(seek_time: f32, completions: u32, speed: f32, finished: bool) -> Progress {
+        Progress {
+            seek_time,
+            completions,
+            speed,
+            finished,
+        }
+    }
+
+    #[test]
+    fn triggers_fire_when_clips_pass_them() {
+        let at = Trigger::At(0.5);
+        let forward = |time, completions| progress(time, completions, 1.0, false);
+        assert!(at.passed(None, forward(0.5, 0)));
+        assert!(!at.passed(None, forward(0.4, 0)));
+        assert!(at.passed(Some(forward(0.4, 0)), forward(0.6, 0)));
+        assert!(!at.passed(Some(forward(0.5, 0)), forward(0.6, 0)));
+        // Wrapping around to the start of the next lap.
+        assert!(at.passed(Some(forward(0.4, 0)), forward(0.1, 1)));
+        assert!(at.passed(Some(forward(0.9, 0)), forward(0.6, 1)));
+        assert!(!at.passed(Some(forward(0.6, 0)), forward(0.4, 1)));
+        assert!(at.passed(Some(forward(0.6, 0)), forward(0.1, 2)));
+
+        let backward = |time, completions| progress(time, completions, -1.0, false);
+        assert!(at.passed(None, backward(0.5, 0)));
+        assert!(at.passed(Some(backward(0.6, 0)), backward(0.4, 0)));
+        assert!(!at.passed(Some(backward(0.4, 0)), backward(0.3, 0)));
+
+        let finish = Trigger::Finish;
+        let done = progress(1.0, 1, 1.0, true);
+        assert!(!finish.passed(None, forward(0.5, 0)));
+        assert!(finish.passed(Some(forward(0.9, 0)), done));
+        assert!(!finish.passed(Some(done), done));
+        // A finished clip doesn't wrap around to pass points it stopped short of.
+        assert!(!at.passed(Some(forward(0.6, 0)), progress(1.0, 1, 1.0, true)));
+    }
+
+    #[test]
+    fn scripts_control_an_entitys_clips() {
+        let mut world = World::new();
+        let entity = world.spawn(AnimationPlayer::default()).id();
+        let bare = world.spawn_empty().id();
+        let mut ctx = Context::default();
+        let listeners = JsObject::from_proto_and_data(
+            None,
+            AnimationListeners {
+                next_id: 0,
+                listeners: Vec::new(),
+            },
+        );
+        let binding = animation_binding(&listeners, &mut ctx);
+        ctx.register_global_property(js_string!(ANIMATION_BINDING), binding, Attribute::all())
+            .unwrap();
+        let source = format!(
+            r#"
+            const entity = {}n;
+            const results = [];
+            animation.play(entity, 1, {{ speed: 2, weight: 0.5, seek: 0.25, repeat: true }});
+            let status = animation.status(entity, 1);
+            results.push(status.time, status.speed, status.weight, status.paused);
+            animation.pause(entity);
+            results.push(animation.status(entity, 1).paused);
+            animation.resume(entity, 1);
+            results.push(animation.status(entity, 1).paused);
+            results.push(animation.seek(entity, 1, 0.75), animation.status(entity, 1).time);
+            results.push(animation.seek(entity, 2, 0.75), animation.setSpeed(entity, 2, 1));
+            const id = animation.on(entity, 1, "finish", () => {{}});
+            results.push(animation.off(id), animation.off(id));
+            animation.stop(entity, 1);
+            results.push(animation.status(entity, 1));
+            for (const call of [
+                () => animation.play({}n, 1),
+                () => animation.play(entity, -1),
+                () => animation.on(entity, 1, "start", () => {{}}),
+                () => animation.on(entity, 1, 0, 1),
+            ]) {{
+                try {{ call(); }} catch (err) {{ results.push(err.name); }}
+            }}
+            results.join()
+            "#,
+            entity.to_bits(),
+            bare.to_bits(),
+        );
+        let result = provide_world(&mut world, || ctx.eval(Source::from_bytes(&source)))
+            .unwrap()
+            .to_string(&mut ctx)
+            .unwrap()
+            .to_std_string_escaped();
+        assert_eq!(
+            result,
+            "0.25,2,0.5,false,true,false,true,0.75,false,false,true,false,,\
+             Error,TypeError,TypeError,TypeError"
+        );
+    }
+}
@@ -0,0 +1,331 @@
+use bevy::audio::{PlaybackMode, Volume};
+use bevy::prelude::*;
+use boa_engine::object::{FunctionObjectBuilder, ObjectInitializer};
+use boa_engine::property::Attribute;
+use boa_engine::{
+    js_str, js_string, Context, Finalize, JsData, JsNativeError, JsObject, JsResult, JsValue,
+    NativeFunction, Trace,
+};
+
+use crate::access::with_world;
+use crate::bindings::arg;
+use crate::runtime::{entity_to_js_value, ScriptRuntime};
+
+/// The global the audio API is installed under.
+pub const AUDIO_BINDING: &str = "audio";
+
+/// Gives scripts `audio.play(path, { volume, speed, looped, paused })`, which plays a sound
+/// loaded through the asset server on an entity of its own, like an [`AudioBundle`], and returns
+/// a handle to control it. Volumes and speeds must be finite and not negative, or a `RangeError`
+/// is thrown. Handles have `play()`, `pause()`, `toggle()` and `stop()`, `volume`
+/// and `speed` properties, `paused` and `finished`, and the `entity` playing the sound.
+///
+/// Sounds that aren't looped despawn their entity once they finish. Changes made before a sound
+/// has loaded apply once it starts. Add it next to [`BoaScriptPlugin`](crate::BoaScriptPlugin),
+/// with bevy's `AudioPlugin` to hear anything.
+#[derive(Default)]
+pub struct ScriptAudioPlugin;
+
+impl Plugin for ScriptAudioPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, register_audio);
+    }
+}
+
+/// The entity a sound handle controls.
+#[derive(Trace, Finalize, JsData)]
+struct Sound(#[unsafe_ignore_trace] Entity);
+
+fn register_audio(world: &mut World) {
+    let Some(mut runtime) = world.get_non_send_resource_mut::<ScriptRuntime>() else {
+        warn!("Script audio needs the BoaScriptPlugin");
+        return;
+    };
+    if let Err(err) = runtime.register_global(AUDIO_BINDING, audio_binding) {
+        error!("Error registering script audio: {err}");
+    }
+}
+
+/// Build the `audio` object.
+fn audio_binding(ctx: &mut Context) -> JsResult<JsObject> {
+    let prototype = sound_prototype(ctx);
+    let play = NativeFunction::from_copy_closure_with_captures(play, prototype);
+    Ok(ObjectInitializer::new(ctx)
+        .function(play, js_string!("play"), 2)
+        .build())
+}
+
+/// `audio.play(path, options)`: spawn an entity playing a sound, and return its handle.
+fn play(
+    _: &JsValue,
+    args: &[JsValue],
+    prototype: &JsObject,
+    ctx: &mut Context,
+) -> JsResult<JsValue> {
+    let path = arg(args, 0).to_string(ctx)?.to_std_string_escaped();
+    let mut settings = PlaybackSettings::DESPAWN;
+    if let Some(options) = arg(args, 1).as_object() {
+        let volume = options.get(js_str!("volume"), ctx)?;
+        if !volume.is_undefined() {
+            settings.volume = Volume::new(level(&volume, "volume", ctx)?);
+        }
+        let speed = options.get(js_str!("speed"), ctx)?;
+        if !speed.is_undefined() {
+            settings.speed = level(&speed, "speed", ctx)?;
+        }
+        if options.get(js_str!("looped"), ctx)?.to_boolean() {
+            settings.mode = PlaybackMode::Loop;
+        }
+        settings.paused = options.get(js_str!("paused"), ctx)?.to_boolean();
+    }
+    let entity = with_world(|world| {
+        // Loading an asset type that was never initialized panics.
+        world.get_resource::<Assets<AudioSource>>()?;
+        let source = world.get_resource::<AssetServer>()?.load(path);
+        Some(world.spawn(AudioBundle { source, settings }).id())
+    })?
+    .ok_or_else(|| JsNativeError::error().with_message("Audio needs bevy's AudioPlugin"))?;
+    let sound = JsObject::from_proto_and_data(prototype.clone(), Sound(entity));
+    sound.set(js_str!("entity"), entity_to_js_value(entity), false, ctx)?;
+    Ok(sound.into())
+}
+
+/// Read a volume or speed, which must be finite and not negative: sinks and [`Volume`] don't
+/// accept anything else.
+fn level(value: &JsValue, name: &str, ctx: &mut Context) -> JsResult<f32> {
+    let level = value.to_number(ctx)? as f32;
+    if !level.is_finite() || level < 0.0 {
+        return Err(JsNativeError::range()
+            .with_message(format!(
+                "Sound {name} must be a finite number that isn't negative"
+            ))
+            .into());
+    }
+    Ok(level)
+}
+
+/// The methods and properties of sound handles.
+fn sound_prototype(ctx: &mut Context) -> JsObject {
+    let accessor = |ctx: &mut Context, name, function: NativeFunction| {
+        FunctionObjectBuilder::new(ctx.realm(), function)
+            .name(name)
+            .length(0)
+            .build()
+    };
+    let get_volume = accessor(
+        ctx,
+        js_str!("volume"),
+        NativeFunction::from_fn_ptr(|this, _, _| {
+            Ok(with_playback(this, |playback| playback.volume())?
+                .map_or(0.0, f64::from)
+                .into())
+        }),
+    );
+    let set_volume = accessor(
+        ctx,
+        js_str!("volume"),
+        NativeFunction::from_fn_ptr(|this, args, ctx| {
+            let volume = level(&arg(args, 0), "volume", ctx)?;
+            with_playback(this, |mut playback| playback.set_volume(volume))?;
+            Ok(JsValue::undefined())
+        }),
+    );
+    let get_speed = accessor(
+        ctx,
+        js_str!("speed"),
+        NativeFunction::from_fn_ptr(|this, _, _| {
+            Ok(with_playback(this, |playback| playback.speed())?
+                .map_or(0.0, f64::from)
+                .into())
+        }),
+    );
+    let set_speed = accessor(
+        ctx,
+        js_str!("speed"),
+        NativeFunction::from_fn_ptr(|this, args, ctx| {
+            let speed = level(&arg(args, 0), "speed", ctx)?;
+            with_playback(this, |mut playback| playback.set_speed(speed))?;
+            Ok(JsValue::undefined())
+        }),
+    );
+    let paused = accessor(
+        ctx,
+        js_str!("paused"),
+        NativeFunction::from_fn_ptr(|this, _, _| {
+            Ok(with_playback(this, |playback| playback.is_paused())?
+                .unwrap_or(true)
+                .into())
+        }),
+    );
+    let finished = accessor(
+        ctx,
+        js_str!("finished"),
+        NativeFunction::from_fn_ptr(|this, _, _| Ok(with_playback(this, |_| ())?.is_none().into())),
+    );
+    let play = NativeFunction::from_fn_ptr(|this, _, _| {
+        with_playback(this, |mut playback| playback.set_paused(false))?;
+        Ok(JsValue::undefined())
+    });
+    let pause = NativeFunction::from_fn_ptr(|this, _, _| {
+        with_playback(this, |mut playback| playback.set_paused(true))?;
+        Ok(JsValue::undefined())
+    });
+    let toggle = NativeFunction::from_fn_ptr(|this, _, _| {
+        with_playback(this, |mut playback| {
+            let paused = playback.is_paused();
+            playback.set_paused(!paused);
+        })?;
+        Ok(JsValue::undefined())
+    });
+    let stop = NativeFunction::from_fn_ptr(|this, _, _| {
+        let entity = sound_entity(this)?;
+        with_world(|world| {
+            if let Some(sink) = world.get::<AudioSink>(entity) {
+                sink.stop();
+            }
+            if let Some(sink) = world.get::<SpatialAudioSink>(entity) {
+                sink.stop();
+            }
+            world.despawn(entity);
+        })?;
+        Ok(JsValue::undefined())
+    });
+    let attribute = Attribute::CONFIGURABLE;
+    ObjectInitializer::new(ctx)
+        .accessor(
+            js_str!("volume"),
+            Some(get_volume),
+            Some(set_volume),
+            attribute,
+        )
+        .accessor(
+            js_str!("speed"),
+            Some(get_speed),
+            Some(set_speed),
+            attribute,
+        )
+        .accessor(js_str!("paused"), Some(paused), None, attribute)
+        .accessor(js_str!("finished"), Some(finished), None, attribute)
+        .function(play, js_string!("play"), 0)
+        .function(pause, js_string!("pause"), 0)
+        .function(toggle, js_string!("toggle"), 0)
+        .function(stop, js_string!("stop"), 0)
+        .build()
+}
+
+fn sound_entity(this: &JsValue) -> JsResult<Entity> {
+    this.as_object()
+        .and_then(|obj| obj.downcast_ref::<Sound>().map(|sound| sound.0))
+        .ok_or_else(|| {
+            JsNativeError::typ()
+                .with_message("Receiver is not a sound")
+                .into()
+        })
+}
+
+/// A sound, playing through its sink, or waiting for its source to load with the settings it
+/// will start with.
+enum Playback<'a> {
+    Playing(&'a dyn AudioSinkPlayback),
+    Queued(Mut<'a, PlaybackSettings>),
+}
+
+impl Playback<'_> {
+    fn volume(&self) -> f32 {
+        match self {
+            Self::Playing(sink) => sink.volume(),
+            Self::Queued(settings) => settings.volume.get(),
+        }
+    }
+
+    fn set_volume(&mut self, volume: f32) {
+        match self {
+            Self::Playing(sink) => sink.set_volume(volume),
+            Self::Queued(settings) => settings.volume = Volume::new(volume),
+        }
+    }
+
+    fn speed(&self) -> f32 {
+        match self {
+            Self::Playing(sink) => sink.speed(),
+            Self::Queued(settings) => settings.speed,
+        }
+    }
+
+    fn set_speed(&mut self, speed: f32) {
+        match self {
+            Self::Playing(sink) => sink.set_speed(speed),
+            Self::Queued(settings) => settings.speed = speed,
+        }
+    }
+
+    fn is_paused(&self) -> bool {
+        match self {
+            Self::Playing(sink) => sink.is_paused(),
+            Self::Queued(settings) => settings.paused,
+        }
+    }
+
+    fn set_paused(&mut self, paused: bool) {
+        match self {
+            Self::Playing(sink) if paused => sink.pause(),
+            Self::Playing(sink) => sink.play(),
+            Self::Queued(settings) => settings.paused = paused,
+        }
+    }
+}
+
+/// Run `f` with the playback of a sound handle's sound. Returns `None` if the sound finished or
+/// was stopped.
+fn with_playback<R>(this: &JsValue, f: impl FnOnce(Playback<'_>) -> R) -> JsResult<Option<R>> {
+    let entity = sound_entity(this)?;
+    with_world(|world| {
+        let mut entity = world.get_entity_mut(entity)?;
+        if let Some(sink) = entity.get::<AudioSink>() {
+            return Some(f(Playback::Playing(sink)));
+        }
+        if let Some(sink) = entity.get::<SpatialAudioSink>() {
+            return Some(f(Playback::Playing(sink)));
+        }
+        let settings = entity.get_mut::<PlaybackSettings>()?;
+        Some(f(Playback::Queued(settings)))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use boa_engine::Source;
+
+    use super::*;
+
+    fn throws(source: &str, ctx: &mut Context) -> String {
+        ctx.eval(Source::from_bytes(source))
+            .unwrap_err()
+            .to_string()
+    }
+
+    #[test]
+    fn bad_volumes_and_speeds_throw_range_errors() {
+        let mut ctx = Context::default();
+        let audio = audio_binding(&mut ctx).unwrap();
+        let sound = sound_prototype(&mut ctx);
+        ctx.register_global_property(js_string!("audio"), audio, Attribute::all())
+            .unwrap();
+        ctx.register_global_property(js_string!("sound"), sound, Attribute::all())
+            .unwrap();
+
+        for source in [
+            r#"audio.play("hit.ogg", { volume: -1 })"#,
+            r#"audio.play("hit.ogg", { volume: NaN })"#,
+            r#"audio.play("hit.ogg", { speed: Infinity })"#,
+            r#"audio.play("hit.ogg", { speed: 1e300 })"#,
+            "sound.volume = -0.5",
+            "sound.speed = NaN",
+        ] {
+            assert!(
+                throws(source, &mut ctx).starts_with("RangeError"),
+                "{source}"
+            );
+        }
+    }
+}
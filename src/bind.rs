@@ -0,0 +1,84 @@
+use std::any::TypeId;
+
+use bevy::ecs::reflect::AppTypeRegistry;
+use bevy::prelude::*;
+use bevy::reflect::{GetTypeRegistration, TypePath};
+use boa_engine::property::Attribute;
+use boa_engine::{Context, JsNativeError, JsResult, JsString};
+
+use crate::classes::reflect_class;
+use crate::into::reflect_to_js_value;
+use crate::methods::ScriptMethods;
+
+/// Register a batch of globals in a context in one block: Rust functions, constants and classes
+/// for reflected struct types, each converted as its own registration function does. Evaluates to
+/// a `JsResult<()>`, failing on the first global that can't be registered:
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_boa_reflect::js_bind;
+/// # fn distance(a: Vec3, b: Vec3) -> f32 { a.distance(b) }
+/// # let ctx = &mut boa_engine::Context::default();
+/// js_bind!(ctx, {
+///     fn spawnExplosion = |pos: Vec3, strength: f32| pos.length() * strength;
+///     fn distance = distance;
+///     const GRAVITY = 9.81_f32;
+///     type Vector = Vec3;
+/// })?;
+/// # Ok::<(), boa_engine::JsError>(())
+/// ```
+///
+/// `fn` registers anything [`register_fn`](crate::register_fn) takes, `const` a reflected value
+/// with [`register_const`], and `type` a class with [`register_class`].
+#[macro_export]
+macro_rules! js_bind {
+    ($ctx:expr, { $($items:tt)* }) => {
+        (|ctx: &mut ::boa_engine::Context| -> ::boa_engine::JsResult<()> {
+            $crate::js_bind!(@items ctx; $($items)*);
+            Ok(())
+        })($ctx)
+    };
+    (@items $ctx:ident;) => {};
+    (@items $ctx:ident; fn $name:ident = $function:expr; $($rest:tt)*) => {
+        $crate::register_fn($ctx, stringify!($name), $function)?;
+        $crate::js_bind!(@items $ctx; $($rest)*);
+    };
+    (@items $ctx:ident; const $name:ident = $value:expr; $($rest:tt)*) => {
+        $crate::register_const($ctx, stringify!($name), &$value)?;
+        $crate::js_bind!(@items $ctx; $($rest)*);
+    };
+    (@items $ctx:ident; type $name:ident = $ty:ty; $($rest:tt)*) => {
+        $crate::register_class::<$ty>($ctx, stringify!($name))?;
+        $crate::js_bind!(@items $ctx; $($rest)*);
+    };
+}
+
+/// Register a reflected value as a read-only global in the context.
+pub fn register_const(ctx: &mut Context, name: &str, value: &dyn Reflect) -> JsResult<()> {
+    let value = reflect_to_js_value(value, ctx)?;
+    ctx.register_global_property(JsString::from(name), value, Attribute::ENUMERABLE)
+}
+
+/// Register the class of a reflected struct type as a global in the context, under a name of its
+/// own rather than the type's short path. See [`reflect_class`](crate::reflect_class).
+pub fn register_class<T: Reflect + TypePath + GetTypeRegistration>(
+    ctx: &mut Context,
+    name: &str,
+) -> JsResult<()> {
+    let registry = AppTypeRegistry::default();
+    registry.write().register::<T>();
+    let class = {
+        let type_registry = registry.read();
+        let registration = type_registry
+            .get(TypeId::of::<T>())
+            .expect("the type was just registered");
+        reflect_class(registration, &registry, &ScriptMethods::default(), ctx)?
+    };
+    let class = class.ok_or_else(|| {
+        JsNativeError::typ().with_message(format!(
+            "Cannot bind {} as {name}, only structs have classes",
+            T::type_path()
+        ))
+    })?;
+    ctx.register_global_property(JsString::from(name), class, Attribute::all())
+}
@@ -0,0 +1,292 @@
+use bevy::prelude::*;
+use boa_engine::object::{FunctionObjectBuilder, IntegrityLevel};
+use boa_engine::property::PropertyDescriptor;
+use boa_engine::realm::Realm;
+use boa_engine::{
+    Context, Finalize, JsData, JsError, JsNativeError, JsObject, JsResult, JsString, JsValue,
+    NativeFunction, Trace,
+};
+
+use super::arg;
+use crate::script::ScriptAsset;
+use crate::structured_clone::structured_clone;
+
+/// The global the event bus is installed under.
+pub const BUS_BINDING: &str = "bus";
+
+/// The state behind the `bus` object: subscribed handlers and the events emitted this frame.
+#[derive(Debug, Trace, Finalize, JsData)]
+struct EventBus {
+    #[unsafe_ignore_trace]
+    current: Option<AssetId<ScriptAsset>>,
+    handlers: Vec<Handler>,
+    queue: Vec<(JsString, JsValue)>,
+}
+
+#[derive(Debug, Trace, Finalize)]
+struct Handler {
+    event: JsString,
+    /// The script that subscribed, so its handlers can be dropped when it reloads.
+    #[unsafe_ignore_trace]
+    owner: Option<AssetId<ScriptAsset>>,
+    /// The realm the handler subscribed from, which its payloads are cloned into.
+    realm: Realm,
+    function: JsObject,
+}
+
+/// The event bus every realm shares, holding the subscribed handlers and queued events. Scripts
+/// reach it through the `bus` object [`bus_binding`] builds in their realm.
+pub fn event_bus() -> JsObject {
+    JsObject::from_proto_and_data(
+        None,
+        EventBus {
+            current: None,
+            handlers: Vec::new(),
+            queue: Vec::new(),
+        },
+    )
+}
+
+/// Build the `bus` object scripts use to talk to each other without going through ECS events,
+/// in the context's current realm. `bus.emit(event, payload)` queues an event,
+/// `bus.on(event, handler)` subscribes and `bus.off(event, handler)` unsubscribes. Queued events
+/// are delivered by [`deliver_events`] in the order they were emitted. The object is frozen, so
+/// a script can't swap its methods for any other script sharing its realm.
+pub fn bus_binding(bus: &JsObject, ctx: &mut Context) -> JsResult<JsObject> {
+    let object = JsObject::with_object_proto(ctx.intrinsics());
+
+    let emit = NativeFunction::from_copy_closure_with_captures(
+        |_, args, bus, _| {
+            let event = event_name(&arg(args, 0))?;
+            with_bus(bus, |bus| bus.queue.push((event, arg(args, 1))))?;
+            Ok(JsValue::undefined())
+        },
+        bus.clone(),
+    );
+    let on = NativeFunction::from_copy_closure_with_captures(
+        |_, args, bus, ctx| {
+            let event = event_name(&arg(args, 0))?;
+            let function = handler_function(&arg(args, 1))?;
+            let realm = ctx.realm().clone();
+            with_bus(bus, |bus| {
+                bus.handlers.push(Handler {
+                    event,
+                    owner: bus.current,
+                    realm,
+                    function,
+                })
+            })?;
+            Ok(JsValue::undefined())
+        },
+        bus.clone(),
+    );
+    let off = NativeFunction::from_copy_closure_with_captures(
+        |_, args, bus, _| {
+            let event = event_name(&arg(args, 0))?;
+            let function = handler_function(&arg(args, 1))?;
+            with_bus(bus, |bus| {
+                bus.handlers.retain(|handler| {
+                    handler.event != event || !JsObject::equals(&handler.function, &function)
+                })
+            })?;
+            Ok(JsValue::undefined())
+        },
+        bus.clone(),
+    );
+
+    for (name, function, length) in [("emit", emit, 2), ("on", on, 2), ("off", off, 2)] {
+        let function = FunctionObjectBuilder::new(ctx.realm(), function)
+            .name(JsString::from(name))
+            .length(length)
+            .build();
+        object.define_property_or_throw(
+            JsString::from(name),
+            PropertyDescriptor::builder()
+                .value(function)
+                .writable(false)
+                .enumerable(true)
+                .configurable(false)
+                .build(),
+            ctx,
+        )?;
+    }
+    object.set_integrity_level(IntegrityLevel::Frozen, ctx)?;
+    Ok(object)
+}
+
+/// Deliver the events emitted since the last delivery, calling every handler subscribed to each
+/// one with its payload. Every handler gets its own copy of the payload, made with
+/// [`structured_clone`] in the realm it subscribed from, so handlers can't change what other
+/// handlers see and never hold objects from another realm. Payloads that can't be cloned, such
+/// as functions, fail delivery to each handler with the clone's error. Events emitted by
+/// handlers are queued for the next delivery. Handler errors don't stop delivery and are
+/// returned together.
+pub fn deliver_events(
+    bus: &JsObject,
+    ctx: &mut Context,
+) -> Vec<(Option<AssetId<ScriptAsset>>, JsError)> {
+    let Some(queue) = bus
+        .downcast_mut::<EventBus>()
+        .map(|mut bus| std::mem::take(&mut bus.queue))
+    else {
+        return Vec::new();
+    };
+    let mut errors = Vec::new();
+    for (event, payload) in queue {
+        let handlers = match bus.downcast_ref::<EventBus>() {
+            Some(bus) => bus
+                .handlers
+                .iter()
+                .filter(|handler| handler.event == event)
+                .map(|handler| {
+                    (
+                        handler.owner,
+                        handler.realm.clone(),
+                        handler.function.clone(),
+                    )
+                })
+                .collect::<Vec<_>>(),
+            None => Vec::new(),
+        };
+        for (owner, realm, handler) in handlers {
+            let previous = ctx.enter_realm(realm);
+            let result = structured_clone(&payload, ctx)
+                .and_then(|payload| handler.call(&JsValue::undefined(), &[payload], ctx));
+            ctx.enter_realm(previous);
+            if let Err(err) = result {
+                errors.push((owner, err));
+            }
+        }
+    }
+    errors
+}
+
+/// Set the script handlers subscribed from now on belong to.
+pub(crate) fn set_current_script(bus: &JsObject, script: Option<AssetId<ScriptAsset>>) {
+    if let Some(mut bus) = bus.downcast_mut::<EventBus>() {
+        bus.current = script;
+    }
+}
+
+/// Unsubscribe every handler a script subscribed.
+pub(crate) fn remove_script_handlers(bus: &JsObject, script: AssetId<ScriptAsset>) {
+    if let Some(mut bus) = bus.downcast_mut::<EventBus>() {
+        bus.handlers.retain(|handler| handler.owner != Some(script));
+    }
+}
+
+fn with_bus(bus: &JsObject, f: impl FnOnce(&mut EventBus)) -> JsResult<()> {
+    let mut bus = bus
+        .downcast_mut::<EventBus>()
+        .ok_or_else(|| JsNativeError::typ().with_message("The event bus is gone"))?;
+    f(&mut bus);
+    Ok(())
+}
+
+fn event_name(value: &JsValue) -> JsResult<JsString> {
+    value.as_string().cloned().ok_or_else(|| {
+        JsNativeError::typ()
+            .with_message("Event name must be a string")
+            .into()
+    })
+}
+
+fn handler_function(value: &JsValue) -> JsResult<JsObject> {
+    value.as_callable().cloned().ok_or_else(|| {
+        JsNativeError::typ()
+            .with_message("Event handler must be a function")
+            .into()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use boa_engine::{js_string, Source};
+
+    use super::*;
+
+    fn install(bus: &JsObject, ctx: &mut Context) {
+        let binding = bus_binding(bus, ctx).unwrap();
+        ctx.register_global_property(js_string!(BUS_BINDING), binding, Default::default())
+            .unwrap();
+    }
+
+    fn eval(source: &str, ctx: &mut Context) -> JsValue {
+        ctx.eval(Source::from_bytes(source)).unwrap()
+    }
+
+    #[test]
+    fn handlers_get_their_own_copy_in_their_own_realm() {
+        let mut ctx = Context::default();
+        let bus = event_bus();
+        install(&bus, &mut ctx);
+        let emitter = ctx.realm().clone();
+        let listener = ctx.create_realm().unwrap();
+        ctx.enter_realm(listener);
+        install(&bus, &mut ctx);
+        eval(
+            r#"
+            var received = [];
+            bus.on("hit", (hit) => {
+                received.push(Object.getPrototypeOf(hit) === Object.prototype);
+                hit.damage = 0;
+            });
+            bus.on("hit", (hit) => received.push(hit.damage));
+            "#,
+            &mut ctx,
+        );
+        ctx.enter_realm(emitter);
+        eval(
+            r#"var hit = { damage: 5 }; bus.emit("hit", hit);"#,
+            &mut ctx,
+        );
+
+        assert!(deliver_events(&bus, &mut ctx).is_empty());
+        assert_eq!(eval("hit.damage", &mut ctx), JsValue::new(5));
+        let listener = bus.downcast_ref::<EventBus>().unwrap().handlers[0]
+            .realm
+            .clone();
+        ctx.enter_realm(listener);
+        assert_eq!(
+            eval("received.join()", &mut ctx).display().to_string(),
+            "\"true,5\""
+        );
+    }
+
+    #[test]
+    fn reloaded_scripts_lose_their_handlers() {
+        let mut ctx = Context::default();
+        let bus = event_bus();
+        install(&bus, &mut ctx);
+        let script = AssetId::<ScriptAsset>::invalid();
+        set_current_script(&bus, Some(script));
+        eval(r#"var calls = 0; bus.on("tick", () => calls++);"#, &mut ctx);
+        set_current_script(&bus, None);
+        eval(
+            r#"var others = 0; bus.on("tick", () => others++);"#,
+            &mut ctx,
+        );
+
+        eval(r#"bus.emit("tick");"#, &mut ctx);
+        assert!(deliver_events(&bus, &mut ctx).is_empty());
+        remove_script_handlers(&bus, script);
+        eval(r#"bus.emit("tick");"#, &mut ctx);
+        assert!(deliver_events(&bus, &mut ctx).is_empty());
+
+        assert_eq!(eval("calls", &mut ctx), JsValue::new(1));
+        assert_eq!(eval("others", &mut ctx), JsValue::new(2));
+    }
+
+    #[test]
+    fn payloads_that_cannot_be_cloned_fail_delivery() {
+        let mut ctx = Context::default();
+        let bus = event_bus();
+        install(&bus, &mut ctx);
+        eval(
+            r#"var calls = 0; bus.on("go", () => calls++); bus.emit("go", () => {});"#,
+            &mut ctx,
+        );
+        assert_eq!(deliver_events(&bus, &mut ctx).len(), 1);
+        assert_eq!(eval("calls", &mut ctx), JsValue::new(0));
+    }
+}
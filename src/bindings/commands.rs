@@ -0,0 +1,298 @@
+use std::any::TypeId;
+use std::sync::{Arc, Mutex, PoisonError};
+
+use bevy::ecs::reflect::{AppTypeRegistry, ReflectBundle, ReflectComponent};
+use bevy::ecs::world::{Command, CommandQueue};
+use bevy::prelude::*;
+use bevy::reflect::{ReflectFromReflect, ReflectRef, TypeInfo, TypeRegistry};
+use boa_engine::{js_str, Context, JsNativeError, JsObject, JsResult, JsValue};
+
+use super::{arg, native_function};
+use crate::from::js_value_to_typed_reflect_all;
+use crate::runtime::js_value_to_entity;
+
+/// The global the commands API is installed under.
+pub const COMMANDS_BINDING: &str = "commands";
+
+/// World changes queued by scripts, applied after scripts have run for the frame.
+#[derive(Resource, Clone, Default)]
+pub struct ScriptCommandQueue(Arc<Mutex<CommandQueue>>);
+
+impl ScriptCommandQueue {
+    /// Queue a command to run when the queue is next applied.
+    pub fn push(&self, command: impl Command) {
+        self.0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(command);
+    }
+
+    /// Apply the queued commands to the world.
+    pub fn apply(&self, world: &mut World) {
+        let mut queued = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut queue = std::mem::take(&mut *queued);
+        drop(queued);
+        queue.apply(world);
+    }
+}
+
+/// Build the `commands` object, letting scripts queue changes to the world.
+/// `commands.insert(entity, "my_game::Health", { current: 10 })` inserts a reflected component, or
+/// every component of a reflected bundle, and `commands.despawnRecursive(entity)` despawns an
+/// entity with its children. What can be inserted is described on [`reflect_components`].
+pub fn commands_binding(
+    queue: &ScriptCommandQueue,
+    registry: &AppTypeRegistry,
+    ctx: &mut Context,
+) -> JsResult<JsObject> {
+    let commands = JsObject::with_object_proto(ctx.intrinsics());
+
+    let insert_queue = queue.clone();
+    let insert_registry = registry.clone();
+    let insert = native_function(ctx, "insert", 3, move |_, args, ctx| {
+        let entity = js_value_to_entity(&arg(args, 0))?;
+        let type_path = arg(args, 1);
+        let type_path = type_path
+            .as_string()
+            .ok_or_else(|| JsNativeError::typ().with_message("Type path must be a string"))?
+            .to_std_string_escaped();
+        let components =
+            reflect_components(&type_path, arg(args, 2), &insert_registry.read(), ctx)?;
+        insert_queue.push(move |world: &mut World| insert_components(world, entity, components));
+        Ok(JsValue::undefined())
+    });
+    commands.set(js_str!("insert"), insert, false, ctx)?;
+
+    let despawn_queue = queue.clone();
+    let despawn_recursive = native_function(ctx, "despawnRecursive", 1, move |_, args, _| {
+        let entity = js_value_to_entity(&arg(args, 0))?;
+        despawn_queue.push(move |world: &mut World| {
+            if let Some(entity_mut) = world.get_entity_mut(entity) {
+                entity_mut.despawn_recursive();
+            }
+        });
+        Ok(JsValue::undefined())
+    });
+    commands.set(js_str!("despawnRecursive"), despawn_recursive, false, ctx)?;
+
+    Ok(commands)
+}
+
+/// Convert a JS value into the components of a reflected component or bundle type, found by its
+/// full or short type path. These can be inserted:
+///
+/// - Components registered with `#[reflect(Component)]`.
+/// - Bundles registered with `#[reflect(Bundle)]`, which are inserted whole through their
+///   [`ReflectBundle`], the way `EntityCommands::insert` would.
+/// - Other reflected structs whose fields are any of these, which are walked field by field,
+///   with nested structs flattened.
+///
+/// Fields missing from the value are taken from the type's `Default` where it has one. Every
+/// field that can't be converted is reported in the error, not just the first. Bundles that
+/// don't derive `Reflect`, like bevy's own `SpriteBundle`, can't be inserted by name, and their
+/// components have to be inserted one by one.
+pub fn reflect_components(
+    type_path: &str,
+    value: JsValue,
+    registry: &TypeRegistry,
+    ctx: &mut Context,
+) -> JsResult<Vec<Box<dyn Reflect>>> {
+    let registration = registry
+        .get_with_type_path(type_path)
+        .or_else(|| registry.get_with_short_type_path(type_path))
+        .ok_or_else(|| JsNativeError::typ().with_message(format!("Unknown type {type_path}")))?;
+    let value = js_value_to_typed_reflect_all(value, registration.type_id(), registry, ctx)?;
+    let mut components = Vec::new();
+    collect_components(value, registration.type_id(), registry, &mut components)?;
+    Ok(components)
+}
+
+fn collect_components(
+    value: Box<dyn Reflect>,
+    type_id: TypeId,
+    registry: &TypeRegistry,
+    components: &mut Vec<Box<dyn Reflect>>,
+) -> JsResult<()> {
+    let Some(registration) = registry.get(type_id) else {
+        return Err(JsNativeError::typ()
+            .with_message("Bundle field type is not registered")
+            .into());
+    };
+    if registration.data::<ReflectComponent>().is_some()
+        || registration.data::<ReflectBundle>().is_some()
+    {
+        let component = registration
+            .data::<ReflectFromReflect>()
+            .and_then(|from_reflect| from_reflect.from_reflect(value.as_ref()))
+            .or_else(|| {
+                let mut component = registration.data::<ReflectDefault>()?.default();
+                component.apply(value.as_ref());
+                Some(component)
+            })
+            .ok_or_else(|| {
+                JsNativeError::typ().with_message(format!(
+                    "Missing fields for {}",
+                    registration.type_info().type_path()
+                ))
+            })?;
+        components.push(component);
+        return Ok(());
+    }
+    let TypeInfo::Struct(info) = registration.type_info() else {
+        return Err(JsNativeError::typ()
+            .with_message(format!(
+                "{} is neither a component nor a bundle",
+                registration.type_info().type_path()
+            ))
+            .into());
+    };
+
+    let bundle = match registration.data::<ReflectDefault>() {
+        Some(default) => {
+            let mut bundle = default.default();
+            bundle.apply(value.as_ref());
+            bundle
+        }
+        None => value,
+    };
+    let ReflectRef::Struct(bundle) = bundle.reflect_ref() else {
+        return Err(JsNativeError::typ()
+            .with_message(format!("Invalid value for {}", info.type_path()))
+            .into());
+    };
+    for field in info.iter() {
+        let Some(value) = bundle.field(field.name()) else {
+            return Err(JsNativeError::typ()
+                .with_message(format!(
+                    "Missing field {} for {}",
+                    field.name(),
+                    info.type_path()
+                ))
+                .into());
+        };
+        collect_components(value.clone_value(), field.type_id(), registry, components)?;
+    }
+    Ok(())
+}
+
+pub(crate) fn insert_components(
+    world: &mut World,
+    entity: Entity,
+    components: Vec<Box<dyn Reflect>>,
+) {
+    let registry = world.resource::<AppTypeRegistry>().clone();
+    let registry = registry.read();
+    let Some(mut entity_mut) = world.get_entity_mut(entity) else {
+        warn!("Script tried to insert components into {entity}, which does not exist");
+        return;
+    };
+    for component in components {
+        let reflect_component = component
+            .get_represented_type_info()
+            .and_then(|info| registry.get_type_data::<ReflectComponent>(info.type_id()));
+        match reflect_component {
+            Some(reflect_component) => {
+                reflect_component.insert(&mut entity_mut, component.as_ref(), &registry)
+            }
+            None => match component
+                .get_represented_type_info()
+                .and_then(|info| registry.get_type_data::<ReflectBundle>(info.type_id()))
+            {
+                Some(reflect_bundle) => {
+                    reflect_bundle.insert(&mut entity_mut, component.as_ref(), &registry)
+                }
+                None => warn!(
+                    "Script tried to insert {}, which is not a component",
+                    component.reflect_type_path()
+                ),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use boa_engine::{js_string, Source};
+
+    use super::*;
+
+    #[derive(Component, Reflect, Default, Debug, PartialEq)]
+    #[reflect(Component, Default)]
+    struct Health {
+        current: u32,
+    }
+
+    #[derive(Component, Reflect, Default, Debug, PartialEq)]
+    #[reflect(Component, Default)]
+    struct Speed {
+        value: f32,
+    }
+
+    #[derive(Bundle, Reflect, Default)]
+    #[reflect(Bundle, Default)]
+    struct Mover {
+        speed: Speed,
+    }
+
+    #[derive(Bundle, Reflect, Default)]
+    #[reflect(Bundle, Default)]
+    struct Unit {
+        health: Health,
+        mover: Mover,
+    }
+
+    /// Reflected, but not registered as a bundle, so it's walked field by field.
+    #[derive(Reflect, Default)]
+    #[reflect(Default)]
+    struct Loose {
+        health: Health,
+        mover: Mover,
+    }
+
+    fn insert(type_path: &str, value: &str) -> (Option<u32>, Option<f32>) {
+        let mut world = World::new();
+        let registry = AppTypeRegistry::default();
+        {
+            let mut registry = registry.write();
+            registry.register::<Unit>();
+            registry.register::<Loose>();
+        }
+        world.insert_resource(registry.clone());
+        let mut ctx = Context::default();
+        let value = ctx.eval(Source::from_bytes(value)).unwrap();
+        let components = reflect_components(type_path, value, &registry.read(), &mut ctx).unwrap();
+        let entity = world.spawn_empty().id();
+        insert_components(&mut world, entity, components);
+        let entity = world.entity(entity);
+        (
+            entity.get::<Health>().map(|health| health.current),
+            entity.get::<Speed>().map(|speed| speed.value),
+        )
+    }
+
+    #[test]
+    fn nested_bundles_are_inserted_whole() {
+        let value = "({ health: { current: 3 }, mover: { speed: { value: 1.5 } } })";
+        assert_eq!(insert("Unit", value), (Some(3), Some(1.5)));
+        assert_eq!(insert("Loose", value), (Some(3), Some(1.5)));
+        assert_eq!(
+            insert("Unit", "({ health: { current: 3 } })"),
+            (Some(3), Some(0.0))
+        );
+        assert_eq!(
+            insert("Mover", "({ speed: { value: 2 } })"),
+            (None, Some(2.0))
+        );
+    }
+
+    #[test]
+    fn types_that_are_neither_are_refused() {
+        let registry = AppTypeRegistry::default();
+        registry.write().register::<String>();
+        let mut ctx = Context::default();
+        let err = reflect_components("String", js_string!("x").into(), &registry.read(), &mut ctx)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("neither a component nor a bundle"), "{err}");
+    }
+}
@@ -0,0 +1,48 @@
+use boa_engine::object::builtins::JsFunction;
+use boa_engine::object::FunctionObjectBuilder;
+use boa_engine::realm::Realm;
+use boa_engine::{Context, JsResult, JsString, JsValue, NativeFunction};
+
+use crate::errors::attach_js_stack;
+
+pub mod bus;
+pub mod commands;
+pub mod reflect;
+pub mod storage;
+pub mod world;
+
+/// Create a JS function from a Rust closure. The closure must be `Send`, which rules out capturing
+/// garbage collected JS values that the collector would need to trace.
+pub(crate) fn native_function<F>(ctx: &Context, name: &str, length: usize, f: F) -> JsFunction
+where
+    F: Fn(&JsValue, &[JsValue], &mut Context) -> JsResult<JsValue> + Send + 'static,
+{
+    // SAFETY: `Send` closures can't hold garbage collected values, which are `!Send`.
+    let function = unsafe {
+        NativeFunction::from_closure(move |this, args, ctx| {
+            f(this, args, ctx).map_err(|err| attach_js_stack(err, ctx))
+        })
+    };
+    FunctionObjectBuilder::new(ctx.realm(), function)
+        .name(JsString::from(name))
+        .length(length)
+        .build()
+}
+
+/// Run `f` in a realm, e.g. to call back into the script that subscribed a handler with values
+/// made in that script's realm rather than the one the context is in.
+pub(crate) fn in_realm<R>(
+    realm: &Realm,
+    ctx: &mut Context,
+    f: impl FnOnce(&mut Context) -> R,
+) -> R {
+    let previous = ctx.enter_realm(realm.clone());
+    let result = f(ctx);
+    ctx.enter_realm(previous);
+    result
+}
+
+/// The argument at an index, or `undefined` if it wasn't passed.
+pub(crate) fn arg(args: &[JsValue], idx: usize) -> JsValue {
+    args.get(idx).cloned().unwrap_or_default()
+}
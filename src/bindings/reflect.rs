@@ -0,0 +1,513 @@
+use bevy::ecs::reflect::{AppTypeRegistry, ReflectComponent, ReflectResource};
+use bevy::prelude::*;
+use bevy::reflect::{
+    Access, NamedField, ParsedPath, ReflectDeserialize, ReflectSerialize, TypeInfo,
+    TypeRegistration, TypeRegistry, UnnamedField, VariantInfo,
+};
+use boa_engine::object::builtins::JsArray;
+use boa_engine::object::ObjectInitializer;
+use boa_engine::property::{Attribute, PropertyKey};
+use boa_engine::{js_str, Context, JsError, JsNativeError, JsObject, JsResult, JsString, JsValue};
+
+use super::{arg, native_function};
+use crate::classes::{instance_of, instance_value, write_instance_fields};
+use crate::from::js_value_to_typed_reflect;
+use crate::into::reflect_to_js_value;
+
+/// Read the docs of some type info as a JS string, or `null` without the `documentation` feature.
+macro_rules! docs {
+    ($info:expr) => {{
+        #[cfg(feature = "documentation")]
+        let docs = $info
+            .docs()
+            .map_or(JsValue::null(), |docs| JsString::from(docs).into());
+        #[cfg(not(feature = "documentation"))]
+        let docs = {
+            let _ = &$info;
+            JsValue::null()
+        };
+        docs
+    }};
+}
+
+/// The global the reflection API is installed under.
+pub const REFLECT_BINDING: &str = "reflect";
+
+/// Build the `reflect` object, giving scripts generic access to reflected values.
+pub fn reflect_binding(registry: &AppTypeRegistry, ctx: &mut Context) -> JsResult<JsObject> {
+    let reflect = JsObject::with_object_proto(ctx.intrinsics());
+
+    let get_registry = registry.clone();
+    let get_path = native_function(ctx, "getPath", 2, move |_, args, ctx| {
+        get_path(&arg(args, 0), &arg(args, 1), &get_registry, ctx)
+    });
+    reflect.set(js_str!("getPath"), get_path, false, ctx)?;
+
+    let set_registry = registry.clone();
+    let set_path = native_function(ctx, "setPath", 3, move |_, args, ctx| {
+        set_path(
+            &arg(args, 0),
+            &arg(args, 1),
+            arg(args, 2),
+            &set_registry,
+            ctx,
+        )?;
+        Ok(JsValue::undefined())
+    });
+    reflect.set(js_str!("setPath"), set_path, false, ctx)?;
+
+    let describe_registry = registry.clone();
+    let describe = native_function(ctx, "describe", 1, move |_, args, ctx| {
+        let type_path = arg(args, 0);
+        let type_path = type_path
+            .as_string()
+            .ok_or_else(|| JsNativeError::typ().with_message("Type path must be a string"))?
+            .to_std_string_escaped();
+        describe(&type_path, &describe_registry.read(), ctx)
+    });
+    reflect.set(js_str!("describe"), describe, false, ctx)?;
+
+    let default_registry = registry.clone();
+    let default = native_function(ctx, "default", 1, move |_, args, ctx| {
+        let type_path = arg(args, 0);
+        let type_path = type_path
+            .as_string()
+            .ok_or_else(|| JsNativeError::typ().with_message("Type path must be a string"))?
+            .to_std_string_escaped();
+        default_value(&type_path, &default_registry.read(), ctx)
+    });
+    reflect.set(js_str!("default"), default, false, ctx)?;
+
+    let types_registry = registry.clone();
+    let types = native_function(ctx, "types", 1, move |_, args, ctx| {
+        let filter = match arg(args, 0).as_object() {
+            Some(options) => options.get(js_str!("filter"), ctx)?,
+            None => JsValue::undefined(),
+        };
+        let filter = match filter {
+            JsValue::Undefined | JsValue::Null => None,
+            JsValue::String(filter) => Some(filter.to_std_string_escaped()),
+            _ => {
+                return Err(JsNativeError::typ()
+                    .with_message("Type filter must be a string")
+                    .into())
+            }
+        };
+        types(filter.as_deref(), &types_registry.read(), ctx)
+    });
+    reflect.set(js_str!("types"), types, false, ctx)?;
+
+    Ok(reflect)
+}
+
+/// Read a nested value by a path like `foo.bar[2].baz`. Instances of generated classes are read
+/// through bevy's `GetPath` on their Rust type; other objects are walked property by property.
+pub fn get_path(
+    target: &JsValue,
+    path: &JsValue,
+    registry: &AppTypeRegistry,
+    ctx: &mut Context,
+) -> JsResult<JsValue> {
+    let path = parse_path(path)?;
+    if let Some((type_id, fields)) = target.as_object().and_then(instance_of) {
+        let value = instance_value(&fields, type_id, registry, ctx)?;
+        let field = value.reflect_path(&path).map_err(path_error)?;
+        return reflect_to_js_value(field, ctx);
+    }
+
+    let mut current = target.clone();
+    for access in &path.0 {
+        let key = property_key(&current, &access.access, ctx)?;
+        current = expect_object(&current)?.get(key, ctx)?;
+    }
+    Ok(current)
+}
+
+/// Write a nested value by path. For instances of generated classes, the value is converted into
+/// the type of the field at the path, so invalid writes are rejected.
+pub fn set_path(
+    target: &JsValue,
+    path: &JsValue,
+    value: JsValue,
+    registry: &AppTypeRegistry,
+    ctx: &mut Context,
+) -> JsResult<()> {
+    let path = parse_path(path)?;
+    if let Some((type_id, fields)) = target.as_object().and_then(instance_of) {
+        let mut instance = instance_value(&fields, type_id, registry, ctx)?;
+        let field = instance.reflect_path_mut(&path).map_err(path_error)?;
+        let field_type_id = field
+            .get_represented_type_info()
+            .map(TypeInfo::type_id)
+            .ok_or_else(|| JsNativeError::typ().with_message("Field type is unknown"))?;
+        let new_value = js_value_to_typed_reflect(value, field_type_id, &registry.read(), ctx)?;
+        field.apply(new_value.as_ref());
+        return write_instance_fields(&fields, instance.as_ref(), ctx);
+    }
+
+    let Some((last, parents)) = path.0.split_last() else {
+        return Err(JsNativeError::typ().with_message("Path is empty").into());
+    };
+    let mut current = target.clone();
+    for access in parents {
+        let key = property_key(&current, &access.access, ctx)?;
+        current = expect_object(&current)?.get(key, ctx)?;
+    }
+    let key = property_key(&current, &last.access, ctx)?;
+    expect_object(&current)?.set(key, value, true, ctx)?;
+    Ok(())
+}
+
+/// Describe a registered type, found by its full or short type path: its kind, fields, enum
+/// variants and, with the `documentation` feature, its docs. Returns `null` for unknown types.
+pub fn describe(type_path: &str, registry: &TypeRegistry, ctx: &mut Context) -> JsResult<JsValue> {
+    let Some(registration) = find_registration(type_path, registry) else {
+        return Ok(JsValue::null());
+    };
+    let type_info = registration.type_info();
+    let table = type_info.type_path_table();
+    let (kind, fields, variants, extra) = match type_info {
+        TypeInfo::Struct(info) => {
+            let fields = describe_named_fields(info.iter(), ctx);
+            ("struct", Some(fields), None, Vec::new())
+        }
+        TypeInfo::TupleStruct(info) => {
+            let fields = describe_unnamed_fields(info.iter(), ctx);
+            ("tupleStruct", Some(fields), None, Vec::new())
+        }
+        TypeInfo::Tuple(info) => {
+            let fields = describe_unnamed_fields(info.iter(), ctx);
+            ("tuple", Some(fields), None, Vec::new())
+        }
+        TypeInfo::List(info) => {
+            let item = JsString::from(info.item_type_path_table().path());
+            ("list", None, None, vec![(js_str!("itemType"), item.into())])
+        }
+        TypeInfo::Array(info) => {
+            let item = JsString::from(info.item_type_path_table().path());
+            let extra = vec![
+                (js_str!("itemType"), item.into()),
+                (js_str!("length"), JsValue::from(info.capacity() as f64)),
+            ];
+            ("array", None, None, extra)
+        }
+        TypeInfo::Map(info) => {
+            let key = JsString::from(info.key_type_path_table().path());
+            let value = JsString::from(info.value_type_path_table().path());
+            let extra = vec![
+                (js_str!("keyType"), key.into()),
+                (js_str!("valueType"), value.into()),
+            ];
+            ("map", None, None, extra)
+        }
+        TypeInfo::Enum(info) => {
+            let variants = info
+                .iter()
+                .map(|variant| describe_variant(variant, ctx))
+                .collect::<Vec<_>>();
+            let variants = JsArray::from_iter(variants, ctx);
+            ("enum", None, Some(variants), Vec::new())
+        }
+        TypeInfo::Value(_) => ("value", None, None, Vec::new()),
+    };
+
+    let mut obj = ObjectInitializer::new(ctx);
+    obj.property(
+        js_str!("typePath"),
+        JsString::from(table.path()),
+        Attribute::all(),
+    )
+    .property(
+        js_str!("shortPath"),
+        JsString::from(table.short_path()),
+        Attribute::all(),
+    )
+    .property(js_str!("kind"), JsString::from(kind), Attribute::all())
+    .property(js_str!("docs"), docs!(type_info), Attribute::all());
+    if let Some(fields) = fields {
+        obj.property(js_str!("fields"), fields, Attribute::all());
+    }
+    if let Some(variants) = variants {
+        obj.property(js_str!("variants"), variants, Attribute::all());
+    }
+    for (key, value) in extra {
+        obj.property(key, value, Attribute::all());
+    }
+    Ok(obj.build().into())
+}
+
+/// Create the default value of a registered type, found by its full or short type path, and
+/// convert it to JS. Fails if the type isn't registered or doesn't reflect `Default`.
+pub fn default_value(
+    type_path: &str,
+    registry: &TypeRegistry,
+    ctx: &mut Context,
+) -> JsResult<JsValue> {
+    let registration = find_registration(type_path, registry)
+        .ok_or_else(|| JsNativeError::typ().with_message(format!("Unknown type {type_path}")))?;
+    let default = registration.data::<ReflectDefault>().ok_or_else(|| {
+        JsNativeError::typ().with_message(format!("Type {type_path} does not reflect Default"))
+    })?;
+    reflect_to_js_value(default.default().as_ref(), ctx)
+}
+
+/// A type data flag's name, and whether a registration has it.
+type TypeDataFlag = (&'static str, fn(&TypeRegistration) -> bool);
+
+/// The type data flags reported by [`types`] and accepted as its filter.
+const TYPE_DATA_FLAGS: [TypeDataFlag; 4] = [
+    ("Component", |registration| {
+        registration.data::<ReflectComponent>().is_some()
+    }),
+    ("Resource", |registration| {
+        registration.data::<ReflectResource>().is_some()
+    }),
+    ("Default", |registration| {
+        registration.data::<ReflectDefault>().is_some()
+    }),
+    ("Serialize", |registration| {
+        registration.data::<ReflectSerialize>().is_some()
+            && registration.data::<ReflectDeserialize>().is_some()
+    }),
+];
+
+/// List the registered types, sorted by type path, with flags for the type data they have:
+/// `{ typePath, shortPath, Component, Resource, Default, Serialize }`. The filter names one of the
+/// flags to only list types that have it.
+pub fn types(
+    filter: Option<&str>,
+    registry: &TypeRegistry,
+    ctx: &mut Context,
+) -> JsResult<JsValue> {
+    let filter = filter
+        .map(|filter| {
+            TYPE_DATA_FLAGS
+                .iter()
+                .find(|(name, _)| *name == filter)
+                .map(|(_, has)| has)
+                .ok_or_else(|| {
+                    JsNativeError::typ().with_message(format!("Unknown type filter {filter}"))
+                })
+        })
+        .transpose()?;
+
+    let mut registrations = registry
+        .iter()
+        .filter(|registration| filter.is_none_or(|has| has(registration)))
+        .collect::<Vec<_>>();
+    registrations.sort_by_key(|registration| registration.type_info().type_path());
+
+    let types = registrations
+        .into_iter()
+        .map(|registration| {
+            let table = registration.type_info().type_path_table();
+            let mut obj = ObjectInitializer::new(ctx);
+            obj.property(
+                js_str!("typePath"),
+                JsString::from(table.path()),
+                Attribute::all(),
+            )
+            .property(
+                js_str!("shortPath"),
+                JsString::from(table.short_path()),
+                Attribute::all(),
+            );
+            for (name, has) in TYPE_DATA_FLAGS {
+                obj.property(JsString::from(name), has(registration), Attribute::all());
+            }
+            obj.build().into()
+        })
+        .collect::<Vec<JsValue>>();
+    Ok(JsArray::from_iter(types, ctx).into())
+}
+
+fn describe_named_fields<'a>(
+    fields: impl Iterator<Item = &'a NamedField>,
+    ctx: &mut Context,
+) -> JsArray {
+    let fields = fields
+        .map(|field| {
+            ObjectInitializer::new(ctx)
+                .property(
+                    js_str!("name"),
+                    JsString::from(field.name()),
+                    Attribute::all(),
+                )
+                .property(
+                    js_str!("type"),
+                    JsString::from(field.type_path()),
+                    Attribute::all(),
+                )
+                .property(js_str!("docs"), docs!(field), Attribute::all())
+                .build()
+                .into()
+        })
+        .collect::<Vec<JsValue>>();
+    JsArray::from_iter(fields, ctx)
+}
+
+fn describe_unnamed_fields<'a>(
+    fields: impl Iterator<Item = &'a UnnamedField>,
+    ctx: &mut Context,
+) -> JsArray {
+    let fields = fields
+        .map(|field| {
+            ObjectInitializer::new(ctx)
+                .property(js_str!("index"), field.index() as f64, Attribute::all())
+                .property(
+                    js_str!("type"),
+                    JsString::from(field.type_path()),
+                    Attribute::all(),
+                )
+                .property(js_str!("docs"), docs!(field), Attribute::all())
+                .build()
+                .into()
+        })
+        .collect::<Vec<JsValue>>();
+    JsArray::from_iter(fields, ctx)
+}
+
+fn describe_variant(variant: &VariantInfo, ctx: &mut Context) -> JsValue {
+    let (kind, fields) = match variant {
+        VariantInfo::Struct(info) => ("struct", Some(describe_named_fields(info.iter(), ctx))),
+        VariantInfo::Tuple(info) => ("tuple", Some(describe_unnamed_fields(info.iter(), ctx))),
+        VariantInfo::Unit(_) => ("unit", None),
+    };
+    let mut obj = ObjectInitializer::new(ctx);
+    obj.property(
+        js_str!("name"),
+        JsString::from(variant.name()),
+        Attribute::all(),
+    )
+    .property(js_str!("kind"), JsString::from(kind), Attribute::all())
+    .property(js_str!("docs"), docs!(variant), Attribute::all());
+    if let Some(fields) = fields {
+        obj.property(js_str!("fields"), fields, Attribute::all());
+    }
+    obj.build().into()
+}
+
+/// Look a type up by its full type path, falling back to its short path.
+pub(crate) fn find_registration<'a>(
+    type_path: &str,
+    registry: &'a TypeRegistry,
+) -> Option<&'a TypeRegistration> {
+    registry
+        .get_with_type_path(type_path)
+        .or_else(|| registry.get_with_short_type_path(type_path))
+}
+
+fn parse_path(path: &JsValue) -> JsResult<ParsedPath> {
+    let path = path
+        .as_string()
+        .ok_or_else(|| JsNativeError::typ().with_message("Path must be a string"))?;
+    ParsedPath::parse(&path.to_std_string_escaped()).map_err(path_error)
+}
+
+fn property_key(value: &JsValue, access: &Access, ctx: &mut Context) -> JsResult<PropertyKey> {
+    Ok(match access {
+        Access::Field(name) => JsString::from(name.as_ref()).into(),
+        Access::TupleIndex(idx) | Access::ListIndex(idx) => (*idx).into(),
+        Access::FieldIndex(idx) => expect_object(value)?
+            .own_property_keys(ctx)?
+            .into_iter()
+            .nth(*idx)
+            .ok_or_else(|| {
+                JsNativeError::range().with_message(format!("No field at index {idx}"))
+            })?,
+    })
+}
+
+fn expect_object(value: &JsValue) -> JsResult<&JsObject> {
+    value.as_object().ok_or_else(|| {
+        JsNativeError::typ()
+            .with_message("Path does not lead to an object")
+            .into()
+    })
+}
+
+pub(crate) fn path_error(err: impl std::fmt::Display) -> JsError {
+    JsNativeError::typ().with_message(err.to_string()).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use boa_engine::{js_string, Source};
+
+    use super::*;
+    use crate::classes::reflect_class;
+    use crate::methods::ScriptMethods;
+
+    #[derive(Reflect, Default)]
+    #[reflect(Default)]
+    struct Health {
+        current: u32,
+    }
+
+    #[derive(Component, Reflect, Default)]
+    #[reflect(Component, Default)]
+    struct Stats {
+        health: Health,
+        tags: Vec<String>,
+    }
+
+    fn run(source: &str) -> String {
+        let registry = AppTypeRegistry::default();
+        registry.write().register::<Stats>();
+        let mut ctx = Context::default();
+        let registration = registry
+            .read()
+            .get(std::any::TypeId::of::<Stats>())
+            .cloned();
+        let class = reflect_class(
+            &registration.unwrap(),
+            &registry,
+            &ScriptMethods::default(),
+            &mut ctx,
+        )
+        .unwrap()
+        .unwrap();
+        ctx.register_global_property(js_string!("Stats"), class, Attribute::all())
+            .unwrap();
+        let reflect = reflect_binding(&registry, &mut ctx).unwrap();
+        ctx.register_global_property(js_string!("reflect"), reflect, Attribute::all())
+            .unwrap();
+        match ctx.eval(Source::from_bytes(source)) {
+            Ok(value) => value.to_string(&mut ctx).unwrap().to_std_string_escaped(),
+            Err(err) => err.to_string(),
+        }
+    }
+
+    #[test]
+    fn paths_read_and_write_class_instances() {
+        let result = run(
+            r#"const stats = new Stats({ health: { current: 5 }, tags: ["a", "b"] });
+            reflect.setPath(stats, "health.current", 7);
+            reflect.setPath(stats, "tags[0]", "c");
+            [
+                reflect.getPath(stats, "health.current"),
+                reflect.getPath(stats, "tags[0]"),
+                reflect.getPath(stats, "tags[1]"),
+                stats.health.current,
+            ].join()"#,
+        );
+        assert_eq!(result, "7,c,b,7");
+    }
+
+    #[test]
+    fn invalid_writes_to_class_instances_are_rejected() {
+        let result = run(r#"const stats = new Stats({});
+            reflect.setPath(stats, "health.current", "lots")"#);
+        assert!(result.starts_with("TypeError"), "{result}");
+        let result = run(r#"reflect.getPath(new Stats({}), "health.missing")"#);
+        assert!(result.contains("missing"), "{result}");
+    }
+
+    #[test]
+    fn paths_walk_plain_objects() {
+        let result = run(r#"const plain = { list: [{ name: "a" }] };
+            reflect.setPath(plain, "list[0].name", "b");
+            reflect.getPath(plain, "list[0].name")"#);
+        assert_eq!(result, "b");
+    }
+}
@@ -0,0 +1,316 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, PoisonError, RwLock};
+
+use bevy::prelude::*;
+use boa_engine::object::builtins::JsArray;
+use boa_engine::{js_str, Context, JsNativeError, JsObject, JsResult, JsString, JsValue};
+use ron::ser::PrettyConfig;
+use serde_json::Value;
+
+use super::{arg, native_function};
+use crate::json::{js_value_to_json, json_to_js_value};
+
+/// The global the storage API is installed under.
+pub const STORAGE_BINDING: &str = "storage";
+
+/// The format the storage file is written in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StorageFormat {
+    #[default]
+    Ron,
+    Json,
+}
+
+impl StorageFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            Self::Ron => "ron",
+            Self::Json => "json",
+        }
+    }
+}
+
+/// Where [`ScriptStorage`] is kept on disk, and in what format.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StorageSettings {
+    pub path: PathBuf,
+    pub format: StorageFormat,
+}
+
+impl StorageSettings {
+    /// Keep storage in the platform's data directory, under a directory named for the app:
+    /// `%APPDATA%` on Windows, `~/Library/Application Support` on macOS, and `$XDG_DATA_HOME`
+    /// or `~/.local/share` elsewhere. Falls back to the working directory when the platform's
+    /// isn't known.
+    pub fn for_app(app_name: &str, format: StorageFormat) -> Self {
+        let file = format!("script_storage.{}", format.extension());
+        Self {
+            path: data_dir().join(app_name).join(file),
+            format,
+        }
+    }
+}
+
+fn data_dir() -> PathBuf {
+    let env = |name| std::env::var_os(name).filter(|value| !value.is_empty());
+    let home = || env("HOME").map(PathBuf::from);
+    let dir = if cfg!(windows) {
+        env("APPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        home().map(|home| home.join("Library/Application Support"))
+    } else {
+        env("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .or_else(|| home().map(|home| home.join(".local/share")))
+    };
+    dir.unwrap_or_default()
+}
+
+/// A key-value store scripts keep settings and progress in, like the web's `localStorage`, kept
+/// in a file that is loaded when the app starts and written back after scripts change it.
+/// Values are anything JSON can hold, rather than only strings.
+#[derive(Resource, Clone)]
+pub struct ScriptStorage(Arc<RwLock<StorageData>>);
+
+struct StorageData {
+    settings: StorageSettings,
+    entries: BTreeMap<String, Value>,
+    /// Whether entries changed since they were last saved.
+    dirty: bool,
+}
+
+impl ScriptStorage {
+    /// Load storage from the file the settings point to, or start empty if there isn't one yet.
+    pub fn load(settings: StorageSettings) -> std::io::Result<Self> {
+        let entries = match std::fs::read_to_string(&settings.path) {
+            Ok(contents) => parse(&contents, settings.format)?,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(err) => return Err(err),
+        };
+        Ok(Self(Arc::new(RwLock::new(StorageData {
+            settings,
+            entries,
+            dirty: false,
+        }))))
+    }
+
+    /// Where storage is saved.
+    pub fn path(&self) -> PathBuf {
+        self.read(|data| data.settings.path.clone())
+    }
+
+    pub fn get(&self, key: &str) -> Option<Value> {
+        self.read(|data| data.entries.get(key).cloned())
+    }
+
+    pub fn set(&self, key: &str, value: Value) {
+        self.write(|data| {
+            if data.entries.get(key) != Some(&value) {
+                data.entries.insert(key.to_owned(), value);
+                data.dirty = true;
+            }
+        });
+    }
+
+    pub fn remove(&self, key: &str) -> Option<Value> {
+        self.write(|data| {
+            let removed = data.entries.remove(key);
+            data.dirty |= removed.is_some();
+            removed
+        })
+    }
+
+    pub fn clear(&self) {
+        self.write(|data| {
+            data.dirty |= !data.entries.is_empty();
+            data.entries.clear();
+        });
+    }
+
+    /// The stored keys, in order.
+    pub fn keys(&self) -> Vec<String> {
+        self.read(|data| data.entries.keys().cloned().collect())
+    }
+
+    /// Whether there are changes that haven't been saved.
+    pub fn is_dirty(&self) -> bool {
+        self.read(|data| data.dirty)
+    }
+
+    /// Write storage to its file, replacing the file only once it's fully written. Changes that
+    /// fail to save aren't retried until storage changes again.
+    pub fn save(&self) -> std::io::Result<()> {
+        let (path, contents) = self.write(|data| {
+            data.dirty = false;
+            let contents = match data.settings.format {
+                StorageFormat::Ron => {
+                    ron::ser::to_string_pretty(&data.entries, PrettyConfig::default())
+                        .map_err(std::io::Error::other)
+                }
+                StorageFormat::Json => {
+                    serde_json::to_string_pretty(&data.entries).map_err(std::io::Error::other)
+                }
+            };
+            (data.settings.path.clone(), contents)
+        });
+        write_atomically(&path, contents?.as_bytes())
+    }
+
+    fn read<R>(&self, f: impl FnOnce(&StorageData) -> R) -> R {
+        f(&self.0.read().unwrap_or_else(PoisonError::into_inner))
+    }
+
+    fn write<R>(&self, f: impl FnOnce(&mut StorageData) -> R) -> R {
+        f(&mut self.0.write().unwrap_or_else(PoisonError::into_inner))
+    }
+}
+
+fn parse(contents: &str, format: StorageFormat) -> std::io::Result<BTreeMap<String, Value>> {
+    let invalid = |err: String| std::io::Error::new(std::io::ErrorKind::InvalidData, err);
+    match format {
+        StorageFormat::Ron => ron::from_str(contents).map_err(|err| invalid(err.to_string())),
+        StorageFormat::Json => {
+            serde_json::from_str(contents).map_err(|err| invalid(err.to_string()))
+        }
+    }
+}
+
+fn write_atomically(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let mut temp = path.as_os_str().to_owned();
+    temp.push(".tmp");
+    std::fs::write(&temp, contents)?;
+    std::fs::rename(&temp, path)
+}
+
+/// Save storage at the end of frames scripts changed it in.
+pub(crate) fn save_script_storage(storage: Res<ScriptStorage>) {
+    if !storage.is_dirty() {
+        return;
+    }
+    if let Err(err) = storage.save() {
+        error!(
+            "Error saving script storage to {}: {err}",
+            storage.path().display()
+        );
+    }
+}
+
+/// Build the `storage` object, giving scripts a place to keep values between runs.
+/// `storage.get(key)` returns the value stored under a key, or `null`, `storage.set(key, value)`
+/// stores a copy of a value, and `storage.remove(key)` removes it. `storage.keys()` lists the
+/// stored keys and `storage.clear()` removes them all. Values go through JSON, so functions and
+/// `undefined` properties are dropped.
+pub fn storage_binding(storage: &ScriptStorage, ctx: &mut Context) -> JsResult<JsObject> {
+    let object = JsObject::with_object_proto(ctx.intrinsics());
+
+    let get_storage = storage.clone();
+    let get = native_function(ctx, "get", 1, move |_, args, ctx| {
+        let key = storage_key(&arg(args, 0))?;
+        Ok(get_storage
+            .get(&key)
+            .map_or(JsValue::null(), |value| json_to_js_value(&value, ctx)))
+    });
+    object.set(js_str!("get"), get, false, ctx)?;
+
+    let set_storage = storage.clone();
+    let set = native_function(ctx, "set", 2, move |_, args, ctx| {
+        let key = storage_key(&arg(args, 0))?;
+        set_storage.set(&key, js_value_to_json(&arg(args, 1), ctx)?);
+        Ok(JsValue::undefined())
+    });
+    object.set(js_str!("set"), set, false, ctx)?;
+
+    let remove_storage = storage.clone();
+    let remove = native_function(ctx, "remove", 1, move |_, args, _| {
+        let key = storage_key(&arg(args, 0))?;
+        Ok(remove_storage.remove(&key).is_some().into())
+    });
+    object.set(js_str!("remove"), remove, false, ctx)?;
+
+    let keys_storage = storage.clone();
+    let keys = native_function(ctx, "keys", 0, move |_, _, ctx| {
+        let keys = keys_storage.keys().into_iter().map(JsString::from);
+        Ok(JsArray::from_iter(keys.map(JsValue::from), ctx).into())
+    });
+    object.set(js_str!("keys"), keys, false, ctx)?;
+
+    let clear_storage = storage.clone();
+    let clear = native_function(ctx, "clear", 0, move |_, _, _| {
+        clear_storage.clear();
+        Ok(JsValue::undefined())
+    });
+    object.set(js_str!("clear"), clear, false, ctx)?;
+
+    Ok(object)
+}
+
+fn storage_key(value: &JsValue) -> JsResult<String> {
+    value
+        .as_string()
+        .map(JsString::to_std_string_escaped)
+        .ok_or_else(|| {
+            JsNativeError::typ()
+                .with_message("Storage key must be a string")
+                .into()
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use boa_engine::{js_string, Source};
+
+    use super::*;
+
+    fn run(storage: &ScriptStorage, source: &str) -> String {
+        let mut ctx = Context::default();
+        let binding = storage_binding(storage, &mut ctx).unwrap();
+        ctx.register_global_property(js_string!(STORAGE_BINDING), binding, Default::default())
+            .unwrap();
+        let result = ctx.eval(Source::from_bytes(source)).unwrap();
+        result.to_string(&mut ctx).unwrap().to_std_string_escaped()
+    }
+
+    fn round_trip(format: StorageFormat) {
+        let dir = std::env::temp_dir().join(format!("bevy_boa_reflect_{}", std::process::id()));
+        let settings = StorageSettings {
+            path: dir.join(format!("storage.{}", format.extension())),
+            format,
+        };
+        let storage = ScriptStorage::load(settings.clone()).unwrap();
+        assert!(storage.keys().is_empty());
+        run(
+            &storage,
+            r#"storage.set("settings", { volume: 0.5, keys: ["w", "a"], name: null });
+            storage.set("level", 3);
+            storage.set("gone", true);
+            storage.remove("gone");"#,
+        );
+        assert!(storage.is_dirty());
+        storage.save().unwrap();
+        assert!(!storage.is_dirty());
+
+        let loaded = ScriptStorage::load(settings.clone()).unwrap();
+        let result = run(
+            &loaded,
+            r#"const settings = storage.get("settings");
+            [storage.keys().join("+"), settings.volume, settings.keys.join("+"),
+             settings.name, storage.get("level"), storage.get("gone")].join()"#,
+        );
+        assert_eq!(result, "level+settings,0.5,w+a,,3,");
+        std::fs::remove_file(&settings.path).unwrap();
+    }
+
+    #[test]
+    fn storage_round_trips_through_ron() {
+        round_trip(StorageFormat::Ron);
+    }
+
+    #[test]
+    fn storage_round_trips_through_json() {
+        round_trip(StorageFormat::Json);
+    }
+}
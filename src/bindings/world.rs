@@ -0,0 +1,278 @@
+use std::any::TypeId;
+use std::sync::{Arc, PoisonError, RwLock};
+
+use bevy::ecs::component::{ComponentId, ComponentTicks};
+use bevy::ecs::event::ManualEventReader;
+use bevy::ecs::reflect::{AppTypeRegistry, ReflectComponent};
+use bevy::ecs::removal_detection::RemovedComponentEntity;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use boa_engine::object::builtins::JsArray;
+use boa_engine::{js_str, Context, JsNativeError, JsObject, JsResult, JsValue};
+
+use super::{arg, native_function};
+use crate::access::{current_instance, last_run, with_world};
+use crate::proxies::entity_proxy;
+use crate::runtime::{entity_to_js_value, js_value_to_entity};
+use crate::script::Script;
+
+/// The global the world API is installed under.
+pub const WORLD_BINDING: &str = "world";
+
+/// An index of entities by their [`Name`], kept up to date by the plugin so scripts can look
+/// entities up without scanning the world.
+#[derive(Resource, Clone, Default)]
+pub struct EntityNameIndex(Arc<RwLock<NameIndex>>);
+
+#[derive(Default)]
+struct NameIndex {
+    entities: HashMap<String, Vec<Entity>>,
+    names: HashMap<Entity, String>,
+}
+
+impl EntityNameIndex {
+    /// The entities with the given name, in the order they were named.
+    pub fn get(&self, name: &str) -> Vec<Entity> {
+        let index = self.0.read().unwrap_or_else(PoisonError::into_inner);
+        index.entities.get(name).cloned().unwrap_or_default()
+    }
+
+    /// Record an entity's current name, replacing any name it had before.
+    pub fn insert(&self, entity: Entity, name: &str) {
+        let mut index = self.0.write().unwrap_or_else(PoisonError::into_inner);
+        index.remove(entity);
+        index
+            .entities
+            .entry(name.to_owned())
+            .or_default()
+            .push(entity);
+        index.names.insert(entity, name.to_owned());
+    }
+
+    /// Forget an entity, after it lost its name or was despawned.
+    pub fn remove(&self, entity: Entity) {
+        self.0
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(entity);
+    }
+}
+
+impl NameIndex {
+    fn remove(&mut self, entity: Entity) {
+        let Some(name) = self.names.remove(&entity) else {
+            return;
+        };
+        if let Some(entities) = self.entities.get_mut(&name) {
+            entities.retain(|named| *named != entity);
+            if entities.is_empty() {
+                self.entities.remove(&name);
+            }
+        }
+    }
+}
+
+/// Build the `world` object, giving scripts access to the world.
+/// `world.getEntityByName("Player")` returns the first entity with that [`Name`], or `null`, and
+/// `world.getEntitiesByName("Enemy")` returns all of them. `world.entity(entity)` returns the
+/// entity's proxy, with `clone()` and `despawnRecursive()` methods, or `null` if it doesn't exist.
+/// `world.cloneEntity(entity)` spawns a copy of an entity and returns it. `world.isChanged(entity, "Transform")` and
+/// `world.isAdded(entity, "Transform")` tell whether a component changed or was added since the
+/// script last ran for the entity.
+pub fn world_binding(names: &EntityNameIndex, ctx: &mut Context) -> JsResult<JsObject> {
+    let world = JsObject::with_object_proto(ctx.intrinsics());
+
+    let by_name_index = names.clone();
+    let get_entity_by_name = native_function(ctx, "getEntityByName", 1, move |_, args, _| {
+        let name = entity_name(&arg(args, 0))?;
+        Ok(by_name_index
+            .get(&name)
+            .first()
+            .copied()
+            .map_or(JsValue::null(), entity_to_js_value))
+    });
+    world.set(js_str!("getEntityByName"), get_entity_by_name, false, ctx)?;
+
+    let all_by_name_index = names.clone();
+    let get_entities_by_name = native_function(ctx, "getEntitiesByName", 1, move |_, args, ctx| {
+        let name = entity_name(&arg(args, 0))?;
+        let entities = all_by_name_index
+            .get(&name)
+            .into_iter()
+            .map(entity_to_js_value);
+        Ok(JsArray::from_iter(entities, ctx).into())
+    });
+    world.set(
+        js_str!("getEntitiesByName"),
+        get_entities_by_name,
+        false,
+        ctx,
+    )?;
+
+    let entity = native_function(ctx, "entity", 1, |_, args, ctx| {
+        let entity = js_value_to_entity(&arg(args, 0))?;
+        let proxy = with_world(|world| entity_proxy(world, entity, &[], ctx))??;
+        Ok(proxy.map_or(JsValue::null(), JsValue::from))
+    });
+    world.set(js_str!("entity"), entity, false, ctx)?;
+
+    let clone_entity = native_function(ctx, "cloneEntity", 1, |_, args, _| {
+        let entity = js_value_to_entity(&arg(args, 0))?;
+        with_world(|world| clone_entity(world, entity))?
+            .map(entity_to_js_value)
+            .ok_or_else(|| {
+                JsNativeError::typ()
+                    .with_message(format!("Entity {entity} does not exist"))
+                    .into()
+            })
+    });
+    world.set(js_str!("cloneEntity"), clone_entity, false, ctx)?;
+
+    let is_changed = native_function(ctx, "isChanged", 2, |_, args, _| {
+        let entity = js_value_to_entity(&arg(args, 0))?;
+        let type_path = type_path(&arg(args, 1))?;
+        let ticks = with_world(|world| component_ticks(world, entity, &type_path))??;
+        let this_run = with_world(|world| world.read_change_tick())?;
+        Ok(ticks
+            .is_some_and(|ticks| ticks.is_changed(last_run(), this_run))
+            .into())
+    });
+    world.set(js_str!("isChanged"), is_changed, false, ctx)?;
+
+    let is_added = native_function(ctx, "isAdded", 2, |_, args, _| {
+        let entity = js_value_to_entity(&arg(args, 0))?;
+        let type_path = type_path(&arg(args, 1))?;
+        let ticks = with_world(|world| component_ticks(world, entity, &type_path))??;
+        let this_run = with_world(|world| world.read_change_tick())?;
+        Ok(ticks
+            .is_some_and(|ticks| ticks.is_added(last_run(), this_run))
+            .into())
+    });
+    world.set(js_str!("isAdded"), is_added, false, ctx)?;
+
+    let removed = native_function(ctx, "removed", 1, |_, args, ctx| {
+        let type_path = type_path(&arg(args, 0))?;
+        let instance = current_instance().ok_or_else(|| {
+            JsNativeError::error()
+                .with_message("world.removed can only be called while running for an entity")
+        })?;
+        let entities = with_world(|world| removed_entities(world, instance, &type_path))??;
+        Ok(JsArray::from_iter(entities.into_iter().map(entity_to_js_value), ctx).into())
+    });
+    world.set(js_str!("removed"), removed, false, ctx)?;
+
+    Ok(world)
+}
+
+/// Spawn a copy of an entity with clones of its reflected components and its [`Script`]. The
+/// copy is added to the original's parent rather than taking its children, so the hierarchy stays
+/// consistent. Returns `None` if the entity doesn't exist.
+pub fn clone_entity(world: &mut World, entity: Entity) -> Option<Entity> {
+    let registry = world.resource::<AppTypeRegistry>().clone();
+    let registry = registry.read();
+    let original = world.get_entity(entity)?;
+    let components = original
+        .archetype()
+        .components()
+        .filter_map(|id| world.components().get_info(id)?.type_id())
+        .filter(|type_id| {
+            *type_id != TypeId::of::<Parent>() && *type_id != TypeId::of::<Children>()
+        })
+        .filter_map(|type_id| {
+            let reflect_component = registry.get_type_data::<ReflectComponent>(type_id)?;
+            let value = reflect_component.reflect(original)?.clone_value();
+            Some((reflect_component.clone(), value))
+        })
+        .collect::<Vec<_>>();
+    let script = original.get::<Script>().cloned();
+    let parent = original.get::<Parent>().map(Parent::get);
+
+    let mut copy = world.spawn_empty();
+    for (reflect_component, value) in components {
+        reflect_component.insert(&mut copy, value.as_ref(), &registry);
+    }
+    if let Some(script) = script {
+        copy.insert(script);
+    }
+    let copy = copy.id();
+    if let Some(mut parent) = parent.and_then(|parent| world.get_entity_mut(parent)) {
+        parent.add_child(copy);
+    }
+    Some(copy)
+}
+
+/// Each script instance's cursor into the removed component events of each component.
+#[derive(Resource, Default)]
+pub(crate) struct RemovedCursors(
+    HashMap<(Entity, ComponentId), ManualEventReader<RemovedComponentEntity>>,
+);
+
+impl RemovedCursors {
+    /// Drop the cursors of a script instance.
+    pub(crate) fn remove_instance(&mut self, instance: Entity) {
+        self.0.retain(|(entity, _), _| *entity != instance);
+    }
+}
+
+/// The entities that lost a component, found by type path, since the script instance last asked.
+fn removed_entities(world: &mut World, instance: Entity, type_path: &str) -> JsResult<Vec<Entity>> {
+    let Some(component_id) = component_id(world, type_path)? else {
+        return Ok(Vec::new());
+    };
+    world.init_resource::<RemovedCursors>();
+    world.resource_scope(|world, mut cursors: Mut<RemovedCursors>| {
+        let cursor = cursors.0.entry((instance, component_id)).or_default();
+        let Some(events) = world.removed_components().get(component_id) else {
+            return Ok(Vec::new());
+        };
+        Ok(cursor.read(events).cloned().map(Entity::from).collect())
+    })
+}
+
+/// The change ticks of an entity's component, found by type path. Returns `None` if the entity
+/// doesn't have the component.
+fn component_ticks(
+    world: &World,
+    entity: Entity,
+    type_path: &str,
+) -> JsResult<Option<ComponentTicks>> {
+    let Some(component_id) = component_id(world, type_path)? else {
+        return Ok(None);
+    };
+    Ok(world
+        .get_entity(entity)
+        .and_then(|entity| entity.get_change_ticks_by_id(component_id)))
+}
+
+/// The id of a component, found by type path. Returns `None` if the component has never been used
+/// in the world.
+fn component_id(world: &World, type_path: &str) -> JsResult<Option<ComponentId>> {
+    let registry = world.resource::<AppTypeRegistry>().read();
+    let registration = registry
+        .get_with_type_path(type_path)
+        .or_else(|| registry.get_with_short_type_path(type_path))
+        .ok_or_else(|| JsNativeError::typ().with_message(format!("Unknown type {type_path}")))?;
+    Ok(world.components().get_id(registration.type_id()))
+}
+
+fn type_path(value: &JsValue) -> JsResult<String> {
+    value
+        .as_string()
+        .map(|type_path| type_path.to_std_string_escaped())
+        .ok_or_else(|| {
+            JsNativeError::typ()
+                .with_message("Type path must be a string")
+                .into()
+        })
+}
+
+fn entity_name(value: &JsValue) -> JsResult<String> {
+    value
+        .as_string()
+        .map(|name| name.to_std_string_escaped())
+        .ok_or_else(|| {
+            JsNativeError::typ()
+                .with_message("Entity name must be a string")
+                .into()
+        })
+}
@@ -0,0 +1,84 @@
+#[cfg(feature = "bevy")]
+use bevy::ecs::reflect::AppTypeRegistry;
+use bevy_reflect::{FromReflect, GetTypeRegistration, TypePath, TypeRegistry};
+use boa_engine::{Context, JsResult, JsValue};
+
+#[cfg(feature = "bevy")]
+use crate::access::with_world;
+use crate::errors::ConversionError;
+use crate::from::js_value_to_typed;
+
+/// Implement Boa's own conversion traits, [`TryFromJs`](boa_engine::value::TryFromJs) and
+/// [`TryIntoJsResult`](boa_engine::TryIntoJsResult), for reflected types, so they work with
+/// [`JsValue::try_js_into`], as fields of types deriving Boa's `TryFromJs`, and anywhere else Boa
+/// converts values itself. Boa's traits are foreign to both crates, so they can't be implemented
+/// for every reflected type at once:
+///
+/// ```no_run
+/// # use bevy_boa_reflect::impl_boa_conversions;
+/// # use bevy_reflect::Reflect;
+/// # #[derive(Reflect)]
+/// # struct Health { current: f32 }
+/// # #[derive(Reflect)]
+/// # struct Inventory { slots: Vec<u32> }
+/// impl_boa_conversions!(Health, Inventory);
+///
+/// # let ctx = &mut boa_engine::Context::default();
+/// # let value = boa_engine::JsValue::undefined();
+/// let health: Health = value.try_js_into(ctx)?;
+/// # Ok::<(), boa_engine::JsError>(())
+/// ```
+///
+/// Types are converted with the world's type registry while scripts run, and with a registry
+/// of the type and the types of its fields otherwise, or always without the `bevy` feature.
+#[macro_export]
+macro_rules! impl_boa_conversions {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl ::boa_engine::value::TryFromJs for $ty {
+                fn try_from_js(
+                    value: &::boa_engine::JsValue,
+                    context: &mut ::boa_engine::Context,
+                ) -> ::boa_engine::JsResult<Self> {
+                    $crate::reflect_try_from_js(value, context)
+                }
+            }
+
+            impl ::boa_engine::TryIntoJsResult for $ty {
+                fn try_into_js_result(
+                    self,
+                    context: &mut ::boa_engine::Context,
+                ) -> ::boa_engine::JsResult<::boa_engine::JsValue> {
+                    $crate::reflect_to_js_value(&self, context)
+                }
+            }
+        )*
+    };
+}
+
+/// Convert a JS value into a reflected type, for [`impl_boa_conversions!`]. Uses the world's
+/// type registry while scripts run, and otherwise registers the type and the types of its fields
+/// in a registry of their own.
+pub fn reflect_try_from_js<T: FromReflect + TypePath + GetTypeRegistration>(
+    value: &JsValue,
+    ctx: &mut Context,
+) -> JsResult<T> {
+    Ok(typed_from_js(value, ctx)?)
+}
+
+/// Like [`reflect_try_from_js`], returning a [`ConversionError`] for Rust callers.
+pub(crate) fn typed_from_js<T: FromReflect + TypePath + GetTypeRegistration>(
+    value: &JsValue,
+    ctx: &mut Context,
+) -> Result<T, ConversionError> {
+    #[cfg(feature = "bevy")]
+    if let Some(registry) = with_world(|world| world.get_resource::<AppTypeRegistry>().cloned())
+        .ok()
+        .flatten()
+    {
+        return js_value_to_typed(value.clone(), &registry.read(), ctx);
+    }
+    let mut registry = TypeRegistry::new();
+    registry.register::<T>();
+    js_value_to_typed(value.clone(), &registry, ctx)
+}
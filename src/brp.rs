@@ -0,0 +1,114 @@
+use bevy::ecs::reflect::{AppTypeRegistry, ReflectComponent};
+use bevy::prelude::*;
+use bevy::reflect::serde::{TypedReflectDeserializer, TypedReflectSerializer};
+use bevy::reflect::{FromReflect, ReflectFromReflect, TypePath, TypeRegistry};
+use boa_engine::object::ObjectInitializer;
+use boa_engine::property::Attribute;
+use boa_engine::{Context, JsError, JsNativeError, JsString, JsValue};
+use serde::de::DeserializeSeed;
+
+use crate::errors::{ConversionError, FieldPath};
+use crate::json::{js_value_to_json, json_to_js_value};
+
+/// Convert a reflected value to the shape the Bevy Remote Protocol gives it, so data scripts get
+/// from this crate and data tools get over the protocol can be mixed without translating one to
+/// the other. The value goes through bevy's serde representation, as the protocol's does:
+/// structs become objects, enums are keyed by their variant, `Option`s are `null` or their value,
+/// maps are objects, and types registered with `ReflectSerialize`, like `Entity`, use their own
+/// serde impls.
+///
+/// The JSON is read with [`json_to_js_value`], so integers past an `i32` become `BigInt`s.
+pub fn reflect_to_brp_value(
+    value: &dyn Reflect,
+    registry: &TypeRegistry,
+    ctx: &mut Context,
+) -> Result<JsValue, ConversionError> {
+    let json = serde_json::to_value(TypedReflectSerializer::new(value, registry))
+        .map_err(|err| brp_error(value.reflect_type_path(), err))?;
+    Ok(json_to_js_value(&json, ctx))
+}
+
+/// Convert a value in the Bevy Remote Protocol's shape into the registered type at `type_path`,
+/// reading what [`reflect_to_brp_value`] writes, or what the protocol sends, back.
+pub fn brp_value_to_reflect(
+    value: &JsValue,
+    type_path: &str,
+    registry: &TypeRegistry,
+    ctx: &mut Context,
+) -> Result<Box<dyn Reflect>, ConversionError> {
+    let registration =
+        registry
+            .get_with_type_path(type_path)
+            .ok_or_else(|| ConversionError::Unregistered {
+                type_path: type_path.to_owned(),
+                path: FieldPath::default(),
+            })?;
+    let json = js_value_to_json(value, ctx)?;
+    let value = TypedReflectDeserializer::new(registration, registry)
+        .deserialize(json)
+        .map_err(|err| brp_error(type_path, err))?;
+    // Deserialized values are dynamic, unless the type has serde impls of its own.
+    let concrete = registration
+        .data::<ReflectFromReflect>()
+        .and_then(|from_reflect| from_reflect.from_reflect(value.as_ref()));
+    Ok(concrete.unwrap_or(value))
+}
+
+/// Convert a value in the Bevy Remote Protocol's shape into a registered type. See
+/// [`brp_value_to_reflect`].
+pub fn brp_value_to_typed<T: FromReflect + TypePath>(
+    value: &JsValue,
+    registry: &TypeRegistry,
+    ctx: &mut Context,
+) -> Result<T, ConversionError> {
+    let value = brp_value_to_reflect(value, T::type_path(), registry, ctx)?;
+    T::from_reflect(value.as_ref()).ok_or_else(|| ConversionError::FromReflect {
+        type_path: T::type_path().to_owned(),
+        path: FieldPath::default(),
+    })
+}
+
+/// An entity's reflected components as the Bevy Remote Protocol's `bevy/get` gives them, an
+/// object of each component's value under its full type path. Components that can't be
+/// serialized are left out, as the protocol reports them apart from the others.
+pub fn entity_components_to_brp(
+    world: &World,
+    entity: Entity,
+    ctx: &mut Context,
+) -> Result<JsValue, ConversionError> {
+    let entity_ref = world.get_entity(entity).ok_or_else(|| {
+        JsError::from(JsNativeError::typ().with_message(format!("Entity {entity} does not exist")))
+    })?;
+    let registry = world.resource::<AppTypeRegistry>().read();
+    let mut components = Vec::new();
+    for type_id in entity_ref
+        .archetype()
+        .components()
+        .filter_map(|id| world.components().get_info(id)?.type_id())
+    {
+        let Some(registration) = registry.get(type_id) else {
+            continue;
+        };
+        let Some(component) = registration
+            .data::<ReflectComponent>()
+            .and_then(|component| component.reflect(entity_ref))
+        else {
+            continue;
+        };
+        if let Ok(value) = reflect_to_brp_value(component, &registry, ctx) {
+            components.push((JsString::from(registration.type_info().type_path()), value));
+        }
+    }
+    let mut obj = ObjectInitializer::new(ctx);
+    for (type_path, value) in components {
+        obj.property(type_path, value, Attribute::all());
+    }
+    Ok(obj.build().into())
+}
+
+fn brp_error(type_path: &str, err: impl std::fmt::Display) -> ConversionError {
+    JsError::from(
+        JsNativeError::typ().with_message(format!("Cannot convert {type_path} as BRP: {err}")),
+    )
+    .into()
+}
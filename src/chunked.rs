@@ -0,0 +1,200 @@
+use std::any::TypeId;
+
+use bevy_reflect::prelude::*;
+use bevy_reflect::TypeRegistry;
+use boa_engine::object::builtins::JsArray;
+use boa_engine::{Context, JsValue};
+
+use crate::errors::ConversionError;
+use crate::from::{js_array, JsValueConverter};
+
+/// How far a [`ChunkedConversion`] has got.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConversionProgress {
+    pub converted: usize,
+    pub total: usize,
+}
+
+impl ConversionProgress {
+    /// The share of the items converted so far, from 0 to 1.
+    pub fn fraction(&self) -> f32 {
+        if self.total == 0 {
+            1.0
+        } else {
+            self.converted as f32 / self.total as f32
+        }
+    }
+}
+
+/// Converts a JS array into a registered type a chunk of items at a time, so importing a huge
+/// array can be spread over frames instead of freezing the one it arrives in. Call
+/// [`step`](Self::step) once a frame until it returns the converted items:
+///
+/// ```no_run
+/// # use std::any::TypeId;
+/// # use bevy_boa_reflect::ChunkedConversion;
+/// # use bevy_reflect::TypeRegistry;
+/// # let registry = TypeRegistry::new();
+/// # let ctx = &mut boa_engine::Context::default();
+/// # let value = boa_engine::JsValue::undefined();
+/// # let mut import = ChunkedConversion::new(&value, TypeId::of::<f32>(), &registry, ctx)?;
+/// if let Some(items) = import.step(&registry, ctx)? {
+///     // All done.
+/// #   let _ = items;
+/// }
+/// # Ok::<(), bevy_boa_reflect::ConversionError>(())
+/// ```
+///
+/// Items are read from the array as they are converted, so changes scripts make to the array in
+/// the meantime show up in the items not converted yet.
+pub struct ChunkedConversion {
+    items: JsArray,
+    item_type: TypeId,
+    total: usize,
+    /// The index of the next item to convert.
+    next: usize,
+    chunk_size: usize,
+    converted: Vec<Box<dyn Reflect>>,
+    on_progress: Option<Box<dyn FnMut(ConversionProgress)>>,
+}
+
+impl ChunkedConversion {
+    /// Items converted per step unless [`with_chunk_size`](Self::with_chunk_size) says otherwise.
+    pub const DEFAULT_CHUNK_SIZE: usize = 10_000;
+
+    /// Start converting each item of a JS array into the registered type `item_type`. Fails if
+    /// the value isn't an array.
+    pub fn new(
+        value: &JsValue,
+        item_type: TypeId,
+        registry: &TypeRegistry,
+        ctx: &mut Context,
+    ) -> Result<Self, ConversionError> {
+        let type_path = registry
+            .get(item_type)
+            .map_or("", |registration| registration.type_info().type_path());
+        let items = js_array(value, type_path)?;
+        let total = items.length(ctx)? as usize;
+        Ok(Self {
+            items,
+            item_type,
+            total,
+            next: 0,
+            chunk_size: Self::DEFAULT_CHUNK_SIZE,
+            converted: Vec::with_capacity(total),
+            on_progress: None,
+        })
+    }
+
+    /// Convert `chunk_size` items per step.
+    pub fn with_chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = chunk_size.max(1);
+        self
+    }
+
+    /// Call `on_progress` after each step, e.g. to update a loading bar.
+    pub fn with_progress(mut self, on_progress: impl FnMut(ConversionProgress) + 'static) -> Self {
+        self.on_progress = Some(Box::new(on_progress));
+        self
+    }
+
+    pub fn progress(&self) -> ConversionProgress {
+        ConversionProgress {
+            converted: self.next,
+            total: self.total,
+        }
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.next >= self.total
+    }
+
+    /// Convert the next chunk of items, returning all the converted items once the last chunk is
+    /// done, and `None` before that. Steps after that return no items. Errors name the index of
+    /// the item that failed, which the next step tries again.
+    pub fn step(
+        &mut self,
+        registry: &TypeRegistry,
+        ctx: &mut Context,
+    ) -> Result<Option<Vec<Box<dyn Reflect>>>, ConversionError> {
+        let end = (self.next + self.chunk_size).min(self.total);
+        let mut converter = JsValueConverter::new(registry);
+        while self.next < end {
+            let item = self.items.get(self.next as u64, ctx)?;
+            let value = converter.convert_item(self.next, item, self.item_type, ctx)?;
+            self.converted.push(value);
+            self.next += 1;
+        }
+        let progress = self.progress();
+        if let Some(on_progress) = &mut self.on_progress {
+            on_progress(progress);
+        }
+        Ok(self.is_done().then(|| std::mem::take(&mut self.converted)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use boa_engine::Source;
+
+    use super::*;
+
+    fn numbers(items: Vec<Box<dyn Reflect>>) -> Vec<u32> {
+        items
+            .into_iter()
+            .map(|item| *item.downcast_ref::<u32>().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn chunks_report_progress_until_done() {
+        let registry = TypeRegistry::default();
+        let mut ctx = Context::default();
+        let array = ctx.eval(Source::from_bytes("[1, 2, 3, 4, 5]")).unwrap();
+        let progress = Rc::new(RefCell::new(Vec::new()));
+        let seen = progress.clone();
+        let mut import = ChunkedConversion::new(&array, TypeId::of::<u32>(), &registry, &mut ctx)
+            .unwrap()
+            .with_chunk_size(2)
+            .with_progress(move |progress| seen.borrow_mut().push(progress.converted));
+
+        assert!(import.step(&registry, &mut ctx).unwrap().is_none());
+        assert!(import.step(&registry, &mut ctx).unwrap().is_none());
+        assert_eq!(import.progress().fraction(), 0.8);
+        let items = import.step(&registry, &mut ctx).unwrap().unwrap();
+        assert_eq!(numbers(items), [1, 2, 3, 4, 5]);
+        assert!(import.is_done());
+        assert_eq!(*progress.borrow(), [2, 4, 5]);
+        assert!(import
+            .step(&registry, &mut ctx)
+            .unwrap()
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn failed_items_are_tried_again() {
+        let registry = TypeRegistry::default();
+        let mut ctx = Context::default();
+        let array = ctx
+            .eval(Source::from_bytes(r#"var items = [1, "two", 3]; items"#))
+            .unwrap();
+        let mut import = ChunkedConversion::new(&array, TypeId::of::<u32>(), &registry, &mut ctx)
+            .unwrap()
+            .with_chunk_size(2);
+
+        let err = import.step(&registry, &mut ctx).unwrap_err();
+        assert!(err.to_string().contains("[1]"), "{err}");
+        assert_eq!(import.progress().converted, 1);
+        ctx.eval(Source::from_bytes("items[1] = 2")).unwrap();
+        let items = import.step(&registry, &mut ctx).unwrap().unwrap();
+        assert_eq!(numbers(items), [1, 2, 3]);
+
+        let err =
+            ChunkedConversion::new(&JsValue::new(1), TypeId::of::<u32>(), &registry, &mut ctx);
+        assert!(err.is_err());
+    }
+}
@@ -0,0 +1,324 @@
+use std::any::TypeId;
+
+use bevy::ecs::reflect::{AppTypeRegistry, ReflectComponent, ReflectResource};
+use bevy::prelude::*;
+use bevy::reflect::{ReflectFromReflect, StructInfo, TypeInfo, TypeRegistration};
+use boa_engine::object::builtins::JsFunction;
+use boa_engine::object::FunctionObjectBuilder;
+use boa_engine::property::PropertyDescriptor;
+use boa_engine::{
+    js_str, Context, Finalize, JsData, JsNativeError, JsObject, JsResult, JsString, JsValue,
+    NativeFunction, Trace,
+};
+
+use crate::errors::attach_js_stack;
+use crate::from::js_value_to_typed_reflect;
+use crate::into::reflect_to_js_value;
+use crate::methods::ScriptMethods;
+use crate::runtime::ScriptRuntime;
+
+/// The field values of an instance of a generated class, read and written through the
+/// accessors on the class prototype.
+#[derive(Debug, Trace, Finalize, JsData)]
+struct ReflectInstance {
+    #[unsafe_ignore_trace]
+    type_id: TypeId,
+    fields: JsObject,
+}
+
+/// Register a class for every reflected struct component and resource, and every type with
+/// [`ScriptMethods`], as a script global named after the type's short path, e.g.
+/// `new Transform({ translation: ... })`.
+pub fn register_type_classes(
+    runtime: &mut ScriptRuntime,
+    registry: &AppTypeRegistry,
+    methods: &ScriptMethods,
+) -> JsResult<()> {
+    let mut classes = Vec::new();
+    {
+        let type_registry = registry.read();
+        for registration in type_registry.iter() {
+            if registration.data::<ReflectComponent>().is_none()
+                && registration.data::<ReflectResource>().is_none()
+                && methods.get(registration.type_id()).is_empty()
+            {
+                continue;
+            }
+            let name = registration.type_info().type_path_table().short_path();
+            if !is_identifier(name) || classes.iter().any(|(class, _)| *class == name) {
+                continue;
+            }
+            if let TypeInfo::Struct(_) = registration.type_info() {
+                classes.push((name, registration.clone()));
+            }
+        }
+    }
+    for (name, registration) in classes {
+        let registry = registry.clone();
+        let methods = methods.clone();
+        runtime.register_global(name, move |ctx| {
+            let class = reflect_class(&registration, &registry, &methods, ctx)?;
+            Ok(class.map_or_else(JsValue::undefined, JsValue::from))
+        })?;
+    }
+    Ok(())
+}
+
+/// Build a JS class for a reflected struct type. The constructor takes an object of field values,
+/// fills missing fields from the type's `Default` where it has one, and validates the result
+/// against the type. Each field becomes an accessor on the prototype that validates writes, and
+/// the constructor has a static `typePath`. Methods registered for the type are added to the
+/// prototype. Returns `None` for types that aren't structs.
+pub fn reflect_class(
+    registration: &TypeRegistration,
+    registry: &AppTypeRegistry,
+    methods: &ScriptMethods,
+    ctx: &mut Context,
+) -> JsResult<Option<JsFunction>> {
+    let TypeInfo::Struct(info) = registration.type_info() else {
+        return Ok(None);
+    };
+    let prototype = JsObject::with_object_proto(ctx.intrinsics());
+    let type_id = registration.type_id();
+    let type_path = info.type_path();
+
+    let constructor_registry = registry.clone();
+    // SAFETY: the closure only captures the type registry, which holds no garbage collected values.
+    let constructor = unsafe {
+        NativeFunction::from_closure_with_captures(
+            move |this, args, prototype, ctx| {
+                let new_target = this
+                    .as_object()
+                    .filter(|obj| obj.is_constructor())
+                    .ok_or_else(|| {
+                        JsNativeError::typ()
+                            .with_message(format!("Class {type_path} must be called with new"))
+                    })?;
+                let prototype = new_target
+                    .get(js_str!("prototype"), ctx)?
+                    .as_object()
+                    .cloned()
+                    .unwrap_or_else(|| prototype.clone());
+                let input = args.first().cloned().unwrap_or_default();
+                let fields = construct_fields(type_id, info, &input, &constructor_registry, ctx)
+                    .map_err(|err| attach_js_stack(err, ctx))?;
+                Ok(
+                    JsObject::from_proto_and_data(prototype, ReflectInstance { type_id, fields })
+                        .into(),
+                )
+            },
+            prototype.clone(),
+        )
+    };
+    let constructor = FunctionObjectBuilder::new(ctx.realm(), constructor)
+        .name(JsString::from(info.type_path_table().short_path()))
+        .length(1)
+        .constructor(true)
+        .build();
+
+    constructor.define_property_or_throw(
+        js_str!("prototype"),
+        PropertyDescriptor::builder()
+            .value(prototype.clone())
+            .writable(false)
+            .enumerable(false)
+            .configurable(false),
+        ctx,
+    )?;
+    constructor.define_property_or_throw(
+        js_str!("typePath"),
+        PropertyDescriptor::builder()
+            .value(JsString::from(type_path))
+            .writable(false)
+            .enumerable(false)
+            .configurable(false),
+        ctx,
+    )?;
+    prototype.define_property_or_throw(
+        js_str!("constructor"),
+        PropertyDescriptor::builder()
+            .value(constructor.clone())
+            .writable(true)
+            .enumerable(false)
+            .configurable(true),
+        ctx,
+    )?;
+
+    for field in info.iter() {
+        let name = field.name();
+        let getter = NativeFunction::from_copy_closure(move |this, _, ctx| {
+            let fields = instance_fields(this, type_path)?;
+            fields.get(JsString::from(name), ctx)
+        });
+        let field_type_id = field.type_id();
+        let setter_registry = registry.clone();
+        // SAFETY: the closure only captures the type registry, which holds no garbage collected
+        // values.
+        let setter = unsafe {
+            NativeFunction::from_closure(move |this, args, ctx| {
+                let fields = instance_fields(this, type_path)?;
+                let value = args.first().cloned().unwrap_or_default();
+                js_value_to_typed_reflect(
+                    value.clone(),
+                    field_type_id,
+                    &setter_registry.read(),
+                    ctx,
+                )
+                .map_err(|err| attach_js_stack(err.into(), ctx))?;
+                fields.set(JsString::from(name), value, true, ctx)?;
+                Ok(JsValue::undefined())
+            })
+        };
+        prototype.define_property_or_throw(
+            JsString::from(name),
+            PropertyDescriptor::builder()
+                .get(getter.to_js_function(ctx.realm()))
+                .set(setter.to_js_function(ctx.realm()))
+                .enumerable(true)
+                .configurable(true),
+            ctx,
+        )?;
+    }
+
+    for (name, method) in methods.get(type_id) {
+        let method = method.clone();
+        let method_registry = registry.clone();
+        // SAFETY: the closure only captures the type registry and the method, neither of which
+        // hold garbage collected values.
+        let function = unsafe {
+            NativeFunction::from_closure(move |this, args, ctx| {
+                let fields = instance_fields(this, type_path)?;
+                let mut value = instance_value(&fields, type_id, &method_registry, ctx)
+                    .map_err(|err| attach_js_stack(err, ctx))?;
+                let result = method(value.as_mut(), args, ctx)?;
+                write_instance_fields(&fields, value.as_ref(), ctx)?;
+                Ok(result)
+            })
+        };
+        prototype.define_property_or_throw(
+            JsString::from(*name),
+            PropertyDescriptor::builder()
+                .value(
+                    FunctionObjectBuilder::new(ctx.realm(), function)
+                        .name(JsString::from(*name))
+                        .build(),
+                )
+                .writable(true)
+                .enumerable(false)
+                .configurable(true),
+            ctx,
+        )?;
+    }
+
+    Ok(Some(constructor))
+}
+
+/// The type and fields of an instance of a generated class.
+pub(crate) fn instance_of(obj: &JsObject) -> Option<(TypeId, JsObject)> {
+    obj.downcast_ref::<ReflectInstance>()
+        .map(|instance| (instance.type_id, instance.fields.clone()))
+}
+
+/// A new instance of the same class as another, with the given fields.
+pub(crate) fn new_instance(like: &JsObject, type_id: TypeId, fields: JsObject) -> JsObject {
+    JsObject::from_proto_and_data(like.prototype(), ReflectInstance { type_id, fields })
+}
+
+/// Overwrite an instance's fields with those of a reflected value.
+pub(crate) fn write_instance_fields(
+    fields: &JsObject,
+    value: &dyn Reflect,
+    ctx: &mut Context,
+) -> JsResult<()> {
+    let updated = reflect_to_js_value(value, ctx)?;
+    if let Some(updated) = updated.as_object() {
+        for key in updated.own_property_keys(ctx)? {
+            let value = updated.get(key.clone(), ctx)?;
+            fields.set(key, value, true, ctx)?;
+        }
+    }
+    Ok(())
+}
+
+/// Convert an instance's fields into a value of its concrete Rust type.
+pub(crate) fn instance_value(
+    fields: &JsObject,
+    type_id: TypeId,
+    registry: &AppTypeRegistry,
+    ctx: &mut Context,
+) -> JsResult<Box<dyn Reflect>> {
+    let type_registry = registry.read();
+    let reflect_value =
+        js_value_to_typed_reflect(fields.clone().into(), type_id, &type_registry, ctx)?;
+    type_registry
+        .get_type_data::<ReflectFromReflect>(type_id)
+        .and_then(|from_reflect| from_reflect.from_reflect(reflect_value.as_ref()))
+        .ok_or_else(|| {
+            JsNativeError::typ()
+                .with_message("Could not convert instance to its Rust type")
+                .into()
+        })
+}
+
+/// Merge the constructor's argument over the type's default and validate the result.
+fn construct_fields(
+    type_id: TypeId,
+    info: &StructInfo,
+    input: &JsValue,
+    registry: &AppTypeRegistry,
+    ctx: &mut Context,
+) -> JsResult<JsObject> {
+    let type_registry = registry.read();
+    let registration = type_registry
+        .get(type_id)
+        .ok_or_else(|| JsNativeError::typ().with_message("Type is not registered"))?;
+
+    let fields = match registration.data::<ReflectDefault>() {
+        Some(default) => reflect_to_js_value(default.default().as_ref(), ctx)?
+            .as_object()
+            .cloned()
+            .unwrap_or_else(|| JsObject::with_object_proto(ctx.intrinsics())),
+        None => JsObject::with_object_proto(ctx.intrinsics()),
+    };
+    if let Some(input) = input.as_object() {
+        for field in info.iter() {
+            let name = JsString::from(field.name());
+            if input.has_property(name.clone(), ctx)? {
+                let value = input.get(name.clone(), ctx)?;
+                fields.set(name, value, true, ctx)?;
+            }
+        }
+    }
+
+    let reflect_value =
+        js_value_to_typed_reflect(fields.clone().into(), type_id, &type_registry, ctx)?;
+    if let Some(from_reflect) = registration.data::<ReflectFromReflect>() {
+        if from_reflect.from_reflect(reflect_value.as_ref()).is_none() {
+            return Err(JsNativeError::typ()
+                .with_message(format!("Missing fields for {}", info.type_path()))
+                .into());
+        }
+    }
+    Ok(fields)
+}
+
+fn instance_fields(this: &JsValue, type_path: &str) -> JsResult<JsObject> {
+    this.as_object()
+        .and_then(|obj| {
+            obj.downcast_ref::<ReflectInstance>()
+                .map(|instance| instance.fields.clone())
+        })
+        .ok_or_else(|| {
+            JsNativeError::typ()
+                .with_message(format!("Receiver is not an instance of {type_path}"))
+                .into()
+        })
+}
+
+pub(crate) fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+}
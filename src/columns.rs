@@ -0,0 +1,309 @@
+use std::any::TypeId;
+
+use bevy::ecs::query::QueryBuilder;
+use bevy::ecs::reflect::AppTypeRegistry;
+use bevy::prelude::*;
+use bevy::reflect::{ReflectRef, TypeInfo, TypeRegistry};
+use boa_engine::object::builtins::{
+    JsArray, JsFloat32Array, JsFloat64Array, JsInt16Array, JsInt32Array, JsInt8Array,
+    JsUint16Array, JsUint32Array, JsUint8Array,
+};
+use boa_engine::{js_str, Context, JsObject, JsResult, JsString, JsValue};
+
+use crate::errors::ConversionError;
+use crate::hooks::ConversionHooks;
+use crate::into::try_reflect_slice_to_js_array;
+use crate::memo::reflect_component;
+use crate::runtime::entity_to_js_value;
+
+/// Convert the components of every entity that has all of them as columns rather than an object
+/// per entity, which for data heavy scripts cuts the number of objects created to a handful.
+/// Each column is a property named as given, and `entities` holds the entities in the same order:
+///
+/// ```js
+/// { entities: [1n, 2n], translations: Float32Array [0, 1, 2, 3, 4, 5], ... }
+/// ```
+///
+/// Components made only of one kind of number, like `Vec3` or a health `f32`, become typed arrays
+/// of their numbers in field order, so a `Vec3` column has three numbers per entity. Other
+/// components become arrays of the objects they usually convert to, as do all of them in a
+/// context with [`ConversionHooks`], so hooks see every field.
+pub fn query_to_js_columns(
+    world: &mut World,
+    columns: &[(&str, TypeId)],
+    ctx: &mut Context,
+) -> Result<JsObject, ConversionError> {
+    // A component that was never added to the world has no entities to convert.
+    let component_ids = columns
+        .iter()
+        .map(|(_, type_id)| world.components().get_id(*type_id))
+        .collect::<Option<Vec<_>>>();
+    let registry = world.resource::<AppTypeRegistry>().clone();
+    let registry = registry.read();
+    let reflect_components = columns
+        .iter()
+        .map(|(_, type_id)| reflect_component(&registry, *type_id))
+        .collect::<Result<Vec<_>, _>>()?;
+    let entities: Vec<EntityRef> = match component_ids {
+        Some(component_ids) => {
+            let mut builder = QueryBuilder::<EntityRef>::new(world);
+            for component_id in component_ids {
+                builder.with_id(component_id);
+            }
+            let mut query = builder.build();
+            query.iter(world).collect()
+        }
+        None => Vec::new(),
+    };
+
+    let hooked = ConversionHooks::has_post_hooks(ctx);
+    let obj = JsObject::with_object_proto(ctx.intrinsics());
+    let entity_values = entities
+        .iter()
+        .map(|entity| entity_to_js_value(entity.id()));
+    let entity_values = JsArray::from_iter(entity_values, ctx);
+    obj.set(js_str!("entities"), entity_values, false, ctx)?;
+    for ((name, type_id), reflect_component) in columns.iter().zip(reflect_components) {
+        let values = entities
+            .iter()
+            .filter_map(|entity| reflect_component.reflect(*entity))
+            .collect::<Vec<_>>();
+        let column = match Numeric::leaves_of(*type_id, &registry) {
+            Some(numeric) if !hooked => numeric.typed_array(&values, ctx)?,
+            _ => try_reflect_slice_to_js_array(&values, ctx)?.into(),
+        };
+        obj.set(JsString::from(*name), column, false, ctx)?;
+    }
+    Ok(obj)
+}
+
+/// The kinds of numbers with a typed array to hold them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Numeric {
+    F32,
+    F64,
+    I8,
+    U8,
+    I16,
+    U16,
+    I32,
+    U32,
+}
+
+impl Numeric {
+    fn of(type_id: TypeId) -> Option<Self> {
+        [
+            (TypeId::of::<f32>(), Self::F32),
+            (TypeId::of::<f64>(), Self::F64),
+            (TypeId::of::<i8>(), Self::I8),
+            (TypeId::of::<u8>(), Self::U8),
+            (TypeId::of::<i16>(), Self::I16),
+            (TypeId::of::<u16>(), Self::U16),
+            (TypeId::of::<i32>(), Self::I32),
+            (TypeId::of::<u32>(), Self::U32),
+        ]
+        .into_iter()
+        .find_map(|(id, numeric)| (id == type_id).then_some(numeric))
+    }
+
+    /// The kind of number every leaf of a type is, if they are all the same kind. Only types with
+    /// a fixed number of leaves count, so structs, tuples and fixed size arrays of numbers.
+    fn leaves_of(type_id: TypeId, registry: &TypeRegistry) -> Option<Self> {
+        if let Some(numeric) = Self::of(type_id) {
+            return Some(numeric);
+        }
+        let fields: Vec<TypeId> = match registry.get_type_info(type_id)? {
+            TypeInfo::Struct(info) => info.iter().map(|field| field.type_id()).collect(),
+            TypeInfo::TupleStruct(info) => info.iter().map(|field| field.type_id()).collect(),
+            TypeInfo::Tuple(info) => info.iter().map(|field| field.type_id()).collect(),
+            TypeInfo::Array(info) if info.capacity() > 0 => vec![info.item_type_id()],
+            _ => return None,
+        };
+        let mut leaves = None;
+        for field in fields {
+            let numeric = Self::leaves_of(field, registry)?;
+            if leaves.is_some_and(|leaves| leaves != numeric) {
+                return None;
+            }
+            leaves = Some(numeric);
+        }
+        leaves
+    }
+
+    fn typed_array(self, values: &[&dyn Reflect], ctx: &mut Context) -> JsResult<JsValue> {
+        Ok(match self {
+            Self::F32 => JsFloat32Array::from_iter(leaves::<f32>(values), ctx)?.into(),
+            Self::F64 => JsFloat64Array::from_iter(leaves::<f64>(values), ctx)?.into(),
+            Self::I8 => JsInt8Array::from_iter(leaves::<i8>(values), ctx)?.into(),
+            Self::U8 => JsUint8Array::from_iter(leaves::<u8>(values), ctx)?.into(),
+            Self::I16 => JsInt16Array::from_iter(leaves::<i16>(values), ctx)?.into(),
+            Self::U16 => JsUint16Array::from_iter(leaves::<u16>(values), ctx)?.into(),
+            Self::I32 => JsInt32Array::from_iter(leaves::<i32>(values), ctx)?.into(),
+            Self::U32 => JsUint32Array::from_iter(leaves::<u32>(values), ctx)?.into(),
+        })
+    }
+}
+
+/// The numbers in values, in field order.
+fn leaves<T: Reflect + Copy>(values: &[&dyn Reflect]) -> Vec<T> {
+    let mut leaves = Vec::new();
+    let mut stack = Vec::new();
+    for value in values {
+        stack.push(*value);
+        while let Some(value) = stack.pop() {
+            let start = stack.len();
+            match value.reflect_ref() {
+                ReflectRef::Struct(value) => stack.extend(value.iter_fields()),
+                ReflectRef::TupleStruct(value) => stack.extend(value.iter_fields()),
+                ReflectRef::Tuple(value) => stack.extend(value.iter_fields()),
+                ReflectRef::Array(value) => stack.extend(value.iter()),
+                _ => leaves.extend(value.downcast_ref::<T>().copied()),
+            }
+            stack[start..].reverse();
+        }
+    }
+    leaves
+}
+
+#[cfg(test)]
+mod tests {
+    use boa_engine::object::builtins::JsTypedArray;
+
+    use crate::errors::{FieldPath, PathSegment};
+    use crate::hooks::HookAction;
+
+    use super::*;
+
+    #[derive(Component, Reflect)]
+    #[reflect(Component)]
+    struct Pin {
+        code: u32,
+    }
+
+    #[derive(Component, Reflect)]
+    #[reflect(Component)]
+    struct Velocity {
+        value: Vec3,
+    }
+
+    #[derive(Component, Reflect)]
+    #[reflect(Component)]
+    struct Label {
+        text: String,
+    }
+
+    #[derive(Component, Reflect)]
+    #[reflect(Component)]
+    struct Unused;
+
+    fn column(columns: &JsObject, name: &str, ctx: &mut Context) -> String {
+        let value = columns.get(JsString::from(name), ctx).unwrap();
+        let value = value.as_object().unwrap();
+        let kind = value
+            .get(js_str!("constructor"), ctx)
+            .unwrap()
+            .as_object()
+            .unwrap()
+            .get(js_str!("name"), ctx)
+            .unwrap()
+            .to_string(ctx)
+            .unwrap()
+            .to_std_string_escaped();
+        let items = JsValue::from(value.clone()).to_string(ctx).unwrap();
+        format!("{kind} {}", items.to_std_string_escaped())
+    }
+
+    #[test]
+    fn columns_hold_entities_in_the_same_order() {
+        let mut world = World::new();
+        world.init_resource::<AppTypeRegistry>();
+        {
+            let registry = world.resource::<AppTypeRegistry>();
+            let mut registry = registry.write();
+            registry.register::<Velocity>();
+            registry.register::<Label>();
+            registry.register::<Unused>();
+        }
+        let spawned = ["a", "b"]
+            .into_iter()
+            .enumerate()
+            .map(|(idx, text)| {
+                let value = Vec3::splat(idx as f32);
+                world
+                    .spawn((Velocity { value }, Label { text: text.into() }))
+                    .id()
+            })
+            .collect::<Vec<_>>();
+        world.spawn(Velocity { value: Vec3::X });
+        let mut ctx = Context::default();
+
+        let columns = [
+            ("velocities", TypeId::of::<Velocity>()),
+            ("labels", TypeId::of::<Label>()),
+        ];
+        let converted = query_to_js_columns(&mut world, &columns, &mut ctx).unwrap();
+        assert_eq!(
+            column(&converted, "velocities", &mut ctx),
+            "Float32Array 0,0,0,1,1,1"
+        );
+        assert_eq!(
+            column(&converted, "labels", &mut ctx),
+            "Array [object Object],[object Object]"
+        );
+        let entities = converted.get(js_str!("entities"), &mut ctx).unwrap();
+        let entities = JsArray::from_object(entities.as_object().unwrap().clone()).unwrap();
+        assert_eq!(entities.length(&mut ctx).unwrap(), 2);
+        let labels = converted.get(js_str!("labels"), &mut ctx).unwrap();
+        let labels = JsArray::from_object(labels.as_object().unwrap().clone()).unwrap();
+        for (idx, (entity, text)) in spawned.into_iter().zip(["a", "b"]).enumerate() {
+            let label = labels.get(idx as u32, &mut ctx).unwrap();
+            let label = label.as_object().unwrap().get(js_str!("text"), &mut ctx);
+            assert_eq!(label.unwrap(), JsValue::from(JsString::from(text)));
+            let converted = entities.get(idx as u32, &mut ctx).unwrap();
+            assert_eq!(converted, entity_to_js_value(entity));
+        }
+
+        let columns = [
+            ("velocities", TypeId::of::<Velocity>()),
+            ("unused", TypeId::of::<Unused>()),
+        ];
+        let converted = query_to_js_columns(&mut world, &columns, &mut ctx).unwrap();
+        assert_eq!(column(&converted, "velocities", &mut ctx), "Float32Array ");
+    }
+
+    fn redact(_: &str, path: &FieldPath, _: &JsValue) -> HookAction {
+        match path.segments.last() {
+            Some(PathSegment::Field(field)) if field == "code" => {
+                HookAction::Replace(JsValue::null())
+            }
+            _ => HookAction::Keep,
+        }
+    }
+
+    fn pins(world: &mut World, ctx: &mut Context) -> JsValue {
+        let columns = query_to_js_columns(world, &[("pins", TypeId::of::<Pin>())], ctx).unwrap();
+        columns.get(js_str!("pins"), ctx).unwrap()
+    }
+
+    #[test]
+    fn numeric_columns_run_hooks() {
+        let mut world = World::new();
+        world.init_resource::<AppTypeRegistry>();
+        world
+            .resource::<AppTypeRegistry>()
+            .write()
+            .register::<Pin>();
+        world.spawn(Pin { code: 1234 });
+        let mut ctx = Context::default();
+
+        let pins_value = pins(&mut world, &mut ctx);
+        let typed = JsTypedArray::from_object(pins_value.as_object().unwrap().clone()).unwrap();
+        assert_eq!(typed.at(0, &mut ctx).unwrap(), JsValue::from(1234));
+
+        ConversionHooks::register_post(&mut ctx, redact);
+        let pins_value = pins(&mut world, &mut ctx);
+        let pin = pins_value.as_object().unwrap().get(0, &mut ctx).unwrap();
+        let code = pin.as_object().unwrap().get(js_str!("code"), &mut ctx);
+        assert!(code.unwrap().is_null());
+    }
+}
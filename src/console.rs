@@ -0,0 +1,61 @@
+use std::io::BufRead;
+use std::sync::mpsc::{channel, Receiver};
+use std::sync::{Mutex, PoisonError};
+
+use bevy::prelude::*;
+
+use crate::plugin::with_runtime;
+
+/// A REPL on stdin for debugging a running game. Each line is evaluated in the script context,
+/// with the world bindings available, and the result is printed. Add it next to
+/// [`BoaScriptPlugin`](crate::BoaScriptPlugin).
+#[derive(Default)]
+pub struct ScriptConsolePlugin;
+
+/// The lines read from stdin, waiting to be evaluated.
+#[derive(Resource)]
+struct ConsoleInput(Mutex<Receiver<String>>);
+
+impl Plugin for ScriptConsolePlugin {
+    fn build(&self, app: &mut App) {
+        if cfg!(target_arch = "wasm32") {
+            warn!("The script console reads stdin, which isn't available on the web");
+            return;
+        }
+        let (sender, receiver) = channel();
+        std::thread::spawn(move || {
+            for line in std::io::stdin().lock().lines() {
+                let Ok(line) = line else {
+                    break;
+                };
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        app.insert_resource(ConsoleInput(Mutex::new(receiver)))
+            .add_systems(Update, evaluate_console_input);
+    }
+}
+
+fn evaluate_console_input(world: &mut World) {
+    let lines = world
+        .resource::<ConsoleInput>()
+        .0
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .try_iter()
+        .filter(|line| !line.trim().is_empty())
+        .collect::<Vec<_>>();
+    if lines.is_empty() {
+        return;
+    }
+    with_runtime(world, |runtime| {
+        for line in lines {
+            match runtime.eval(&line) {
+                Ok(value) => println!("{}", value.display()),
+                Err(err) => eprintln!("Uncaught {err}"),
+            }
+        }
+    });
+}
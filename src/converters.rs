@@ -0,0 +1,57 @@
+use std::any::TypeId;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use bevy_reflect::prelude::*;
+use bevy_utils::TypeIdMap;
+use boa_engine::{Context, Finalize, JsData, JsResult, JsValue, Trace};
+
+/// Converts a value of one type to JS, in place of the conversion its kind would get.
+pub type JsConverter = fn(&dyn Reflect, &mut Context) -> JsResult<JsValue>;
+
+/// Conversions to JS registered for individual types in a context, looked up by type before the
+/// built in primitive conversions and the match on a value's kind. Types the crate doesn't know,
+/// like a newtype around a number, can be given a primitive of their own, and any type can be
+/// given a shape that suits scripts better, like `Vec3` as an array.
+///
+/// Converters only apply when converting to JS. Values of these types read back from JS follow
+/// their usual shape.
+#[derive(Debug, Default, Trace, Finalize, JsData)]
+pub struct JsConverters {
+    /// Replaced rather than changed in place, so a conversion can hold on to the converters it
+    /// started with while converters run.
+    #[unsafe_ignore_trace]
+    converters: RefCell<Rc<TypeIdMap<JsConverter>>>,
+}
+
+impl JsConverters {
+    /// Convert values of type `T` with `converter` in a context.
+    pub fn register<T: Reflect>(ctx: &mut Context, converter: JsConverter) {
+        Self::update(ctx, |converters| {
+            converters.insert(TypeId::of::<T>(), converter);
+        });
+    }
+
+    /// Go back to converting values of type `T` as usual in a context.
+    pub fn unregister<T: Reflect>(ctx: &mut Context) {
+        Self::update(ctx, |converters| {
+            converters.remove(&TypeId::of::<T>());
+        });
+    }
+
+    fn update(ctx: &mut Context, f: impl FnOnce(&mut TypeIdMap<JsConverter>)) {
+        if !ctx.has_data::<Self>() {
+            ctx.insert_data(Self::default());
+        }
+        if let Some(registered) = ctx.get_data::<Self>() {
+            let mut converters = registered.converters.borrow_mut();
+            f(Rc::make_mut(&mut converters));
+        }
+    }
+
+    /// The converters registered in a context, or `None` if there are none.
+    pub(crate) fn of(ctx: &Context) -> Option<Rc<TypeIdMap<JsConverter>>> {
+        let converters = ctx.get_data::<Self>()?.converters.borrow().clone();
+        (!converters.is_empty()).then_some(converters)
+    }
+}
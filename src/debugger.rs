@@ -0,0 +1,278 @@
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Mutex, PoisonError};
+
+use bevy::prelude::*;
+use boa_engine::object::builtins::JsArray;
+use boa_engine::property::PropertyKey;
+use boa_engine::{JsObject, JsResult, JsValue};
+use serde_json::{json, Value};
+
+use crate::classes::instance_of;
+use crate::plugin::with_runtime;
+use crate::runtime::ScriptRuntime;
+
+/// A [Debug Adapter Protocol](https://microsoft.github.io/debug-adapter-protocol/) server editors
+/// can attach to, to evaluate expressions against the running game and inspect the results,
+/// including converted component values, as variables.
+///
+/// Boa 0.19 has no debugger hooks, so breakpoints are reported as unverified and execution can't
+/// be paused or stepped. The server answers the rest of the protocol so editors stay attached.
+pub struct ScriptDebugPlugin {
+    /// The address the server listens on, `127.0.0.1:4711` by default.
+    pub address: SocketAddr,
+}
+
+impl Default for ScriptDebugPlugin {
+    fn default() -> Self {
+        Self {
+            address: SocketAddr::from(([127, 0, 0, 1], 4711)),
+        }
+    }
+}
+
+/// A request from the editor, with the channel its responses and events go back on.
+type DebugRequest = (Value, Sender<Vec<Value>>);
+
+#[derive(Resource)]
+struct DebugRequests(Mutex<Receiver<DebugRequest>>);
+
+/// The objects handed to the editor as variables, indexed by their variables reference minus one.
+#[derive(Default)]
+struct DebugVariables(Vec<JsObject>);
+
+impl Plugin for ScriptDebugPlugin {
+    fn build(&self, app: &mut App) {
+        if cfg!(target_arch = "wasm32") {
+            warn!("The script debugger listens on a socket, which isn't available on the web");
+            return;
+        }
+        let listener = match TcpListener::bind(self.address) {
+            Ok(listener) => listener,
+            Err(err) => {
+                error!("Could not start script debugger on {}: {err}", self.address);
+                return;
+            }
+        };
+        let (sender, receiver) = channel();
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                if let Err(err) = serve(stream, &sender) {
+                    warn!("Script debugger connection closed: {err}");
+                }
+            }
+        });
+        app.insert_resource(DebugRequests(Mutex::new(receiver)))
+            .add_systems(Update, handle_debug_requests);
+    }
+}
+
+/// Forward an editor's requests to the app, one at a time, writing back what it answers.
+fn serve(stream: TcpStream, requests: &Sender<DebugRequest>) -> std::io::Result<()> {
+    let mut writer = stream.try_clone()?;
+    let mut reader = BufReader::new(stream);
+    loop {
+        let mut length = None;
+        loop {
+            let mut header = String::new();
+            if reader.read_line(&mut header)? == 0 {
+                return Ok(());
+            }
+            let header = header.trim();
+            if header.is_empty() {
+                break;
+            }
+            if let Some(value) = header.strip_prefix("Content-Length:") {
+                length = value.trim().parse::<usize>().ok();
+            }
+        }
+        let Some(length) = length else {
+            continue;
+        };
+        let mut body = vec![0; length];
+        reader.read_exact(&mut body)?;
+        let Ok(request) = serde_json::from_slice::<Value>(&body) else {
+            continue;
+        };
+        let disconnect = request["command"] == "disconnect";
+        let (sender, receiver) = channel();
+        if requests.send((request, sender)).is_err() {
+            return Ok(());
+        }
+        for message in receiver.recv().unwrap_or_default() {
+            let body = message.to_string();
+            write!(writer, "Content-Length: {}\r\n\r\n{body}", body.len())?;
+        }
+        writer.flush()?;
+        if disconnect {
+            return Ok(());
+        }
+    }
+}
+
+fn handle_debug_requests(world: &mut World) {
+    let requests = world
+        .resource::<DebugRequests>()
+        .0
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .try_iter()
+        .collect::<Vec<_>>();
+    if requests.is_empty() {
+        return;
+    }
+    let mut variables = world
+        .remove_non_send_resource::<DebugVariables>()
+        .unwrap_or_default();
+    with_runtime(world, |runtime| {
+        for (request, reply) in requests {
+            let messages = handle_request(&request, runtime, &mut variables);
+            let _ = reply.send(messages);
+        }
+    });
+    world.insert_non_send_resource(variables);
+}
+
+fn handle_request(
+    request: &Value,
+    runtime: &mut ScriptRuntime,
+    variables: &mut DebugVariables,
+) -> Vec<Value> {
+    let command = request["command"].as_str().unwrap_or_default();
+    let arguments = &request["arguments"];
+    let result = match command {
+        "initialize" => {
+            let capabilities = json!({
+                "supportsConfigurationDoneRequest": true,
+                "supportsEvaluateForHovers": true,
+            });
+            return vec![
+                response(request, Ok(capabilities)),
+                json!({ "type": "event", "seq": 0, "event": "initialized" }),
+            ];
+        }
+        "attach" | "launch" | "configurationDone" => Ok(Value::Null),
+        "disconnect" => {
+            variables.0.clear();
+            Ok(Value::Null)
+        }
+        "threads" => Ok(json!({ "threads": [{ "id": 1, "name": "scripts" }] })),
+        "stackTrace" => Ok(json!({ "stackFrames": [], "totalFrames": 0 })),
+        "scopes" => Ok(json!({ "scopes": [] })),
+        "setBreakpoints" => {
+            let breakpoints = arguments["breakpoints"]
+                .as_array()
+                .map(|breakpoints| {
+                    breakpoints
+                        .iter()
+                        .map(|breakpoint| {
+                            json!({
+                                "verified": false,
+                                "line": breakpoint["line"],
+                                "message": "Breakpoints are not supported by the script engine",
+                            })
+                        })
+                        .collect::<Vec<_>>()
+                })
+                .unwrap_or_default();
+            Ok(json!({ "breakpoints": breakpoints }))
+        }
+        "evaluate" => {
+            let expression = arguments["expression"].as_str().unwrap_or_default();
+            runtime
+                .eval(expression)
+                .and_then(|value| variable(None, &value, runtime, variables))
+                .map(|variable| {
+                    json!({
+                        "result": variable["value"],
+                        "variablesReference": variable["variablesReference"],
+                    })
+                })
+                .map_err(|err| err.to_string())
+        }
+        "variables" => {
+            let reference = arguments["variablesReference"].as_u64().unwrap_or_default();
+            match (reference as usize)
+                .checked_sub(1)
+                .and_then(|idx| variables.0.get(idx).cloned())
+            {
+                Some(obj) => children(&obj, runtime, variables)
+                    .map(|children| json!({ "variables": children }))
+                    .map_err(|err| err.to_string()),
+                None => Err(format!("Unknown variables reference {reference}")),
+            }
+        }
+        _ => Err(format!("Unsupported request {command}")),
+    };
+    vec![response(request, result)]
+}
+
+fn response(request: &Value, result: Result<Value, String>) -> Value {
+    let mut response = json!({
+        "type": "response",
+        "seq": 0,
+        "request_seq": request["seq"],
+        "command": request["command"],
+        "success": result.is_ok(),
+    });
+    match result {
+        Ok(body) => response["body"] = body,
+        Err(message) => response["message"] = Value::String(message),
+    }
+    response
+}
+
+/// Describe a value as a DAP variable, giving objects a reference their properties can be
+/// listed by.
+fn variable(
+    name: Option<String>,
+    value: &JsValue,
+    runtime: &mut ScriptRuntime,
+    variables: &mut DebugVariables,
+) -> JsResult<Value> {
+    let reference = match value.as_object() {
+        Some(obj) if !obj.is_callable() => {
+            variables.0.push(obj.clone());
+            variables.0.len()
+        }
+        _ => 0,
+    };
+    let display = match value.as_object() {
+        Some(obj) if obj.is_array() => {
+            let length = JsArray::from_object(obj.clone())?.length(runtime.context())?;
+            format!("Array({length})")
+        }
+        Some(obj) if obj.is_callable() => "function".to_owned(),
+        Some(_) => "Object".to_owned(),
+        None => value.display().to_string(),
+    };
+    Ok(json!({
+        "name": name.unwrap_or_default(),
+        "value": display,
+        "variablesReference": reference,
+    }))
+}
+
+fn children(
+    obj: &JsObject,
+    runtime: &mut ScriptRuntime,
+    variables: &mut DebugVariables,
+) -> JsResult<Vec<Value>> {
+    let obj = match instance_of(obj) {
+        Some((_, fields)) => fields,
+        None => obj.clone(),
+    };
+    let keys = obj.own_property_keys(runtime.context())?;
+    let mut children = Vec::new();
+    for key in keys {
+        let name = match &key {
+            PropertyKey::String(name) => name.to_std_string_escaped(),
+            PropertyKey::Index(idx) => idx.get().to_string(),
+            PropertyKey::Symbol(_) => continue,
+        };
+        let value = obj.get(key, runtime.context())?;
+        children.push(variable(Some(name), &value, runtime, variables)?);
+    }
+    Ok(children)
+}
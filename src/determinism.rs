@@ -0,0 +1,123 @@
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+
+use bevy::prelude::*;
+use boa_engine::property::Attribute;
+use boa_engine::{js_str, Context, JsObject, JsResult, JsValue};
+
+use crate::bindings::native_function;
+
+/// Makes scripts deterministic, for lockstep multiplayer and replays: `Math.random` is seeded, and
+/// `Date.now` and `performance.now` follow virtual game time instead of the wall clock.
+///
+/// `Date.now` counts milliseconds as though the game started at the Unix epoch, or at the time
+/// given to [`ScriptDeterminism::with_epoch`]. `new Date()` still reads the wall clock, so
+/// deterministic scripts should construct dates from `Date.now()`.
+#[derive(Resource, Clone)]
+pub struct ScriptDeterminism(Arc<Mutex<DeterministicState>>);
+
+struct DeterministicState {
+    rng: u64,
+    elapsed: Duration,
+    epoch: Duration,
+}
+
+impl ScriptDeterminism {
+    pub fn new(seed: u64) -> Self {
+        Self(Arc::new(Mutex::new(DeterministicState {
+            rng: seed,
+            elapsed: Duration::ZERO,
+            epoch: Duration::ZERO,
+        })))
+    }
+
+    /// Start `Date.now` at a point after the Unix epoch, rather than at the epoch itself.
+    pub fn with_epoch(self, epoch: Duration) -> Self {
+        self.lock().epoch = epoch;
+        self
+    }
+
+    /// Restart `Math.random`'s sequence, e.g. when a replay is rewound.
+    pub fn reseed(&self, seed: u64) {
+        self.lock().rng = seed;
+    }
+
+    /// Set the game time scripts see, which the plugin keeps in step with [`Time<Virtual>`].
+    pub fn set_elapsed(&self, elapsed: Duration) {
+        self.lock().elapsed = elapsed;
+    }
+
+    /// The next number in `Math.random`'s sequence, in `[0, 1)`.
+    pub fn random(&self) -> f64 {
+        // SplitMix64, which is small and good enough for gameplay randomness.
+        let mut state = self.lock();
+        state.rng = state.rng.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = state.rng;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^= z >> 31;
+        (z >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    fn elapsed_ms(&self) -> f64 {
+        self.lock().elapsed.as_secs_f64() * 1000.0
+    }
+
+    fn now_ms(&self) -> f64 {
+        let state = self.lock();
+        (state.epoch + state.elapsed).as_millis() as f64
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, DeterministicState> {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Replace `Math.random`, `Date.now` and `performance.now` in the context's current realm.
+pub(crate) fn install_determinism(
+    determinism: &ScriptDeterminism,
+    ctx: &mut Context,
+) -> JsResult<()> {
+    let global = ctx.global_object();
+
+    let math = global.get(js_str!("Math"), ctx)?;
+    if let Some(math) = math.as_object() {
+        let state = determinism.clone();
+        let random = native_function(ctx, "random", 0, move |_, _, _| {
+            Ok(JsValue::from(state.random()))
+        });
+        math.set(js_str!("random"), random, true, ctx)?;
+    }
+
+    let date = global.get(js_str!("Date"), ctx)?;
+    if let Some(date) = date.as_object() {
+        let state = determinism.clone();
+        let now = native_function(ctx, "now", 0, move |_, _, _| {
+            Ok(JsValue::from(state.now_ms()))
+        });
+        date.set(js_str!("now"), now, true, ctx)?;
+    }
+
+    let performance = match global.get(js_str!("performance"), ctx)?.as_object() {
+        Some(performance) => performance.clone(),
+        None => {
+            let performance = JsObject::with_object_proto(ctx.intrinsics());
+            ctx.register_global_property(
+                js_str!("performance"),
+                performance.clone(),
+                Attribute::all(),
+            )?;
+            performance
+        }
+    };
+    let state = determinism.clone();
+    let now = native_function(ctx, "now", 0, move |_, _, _| {
+        Ok(JsValue::from(state.elapsed_ms()))
+    });
+    performance.set(js_str!("now"), now, true, ctx)?;
+    Ok(())
+}
+
+pub(crate) fn advance_script_clock(time: Res<Time<Virtual>>, determinism: Res<ScriptDeterminism>) {
+    determinism.set_elapsed(time.elapsed());
+}
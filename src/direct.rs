@@ -0,0 +1,301 @@
+use std::any::TypeId;
+use std::borrow::Cow;
+use std::convert::Infallible;
+
+use bevy_reflect::TypePath;
+use boa_engine::object::builtins::JsArray;
+use boa_engine::{js_str, Context, JsValue};
+
+use crate::engine::{variant_to_js_object, Primitive};
+use crate::errors::{ConversionError, PathSegment};
+use crate::from::{js_array_items, js_value_to_float, js_value_to_int, js_value_to_string};
+use crate::templates::ObjectTemplates;
+
+pub use bevy_boa_reflect_derive::{FromJs, IntoJs};
+
+/// Conversion of a type into a `JsValue` field by field, without going through reflection, for
+/// hot types where looking up how to convert each value adds up. Derived values have the shape
+/// reflection gives them, so scripts can't tell which way they were converted:
+///
+/// ```no_run
+/// # use bevy_boa_reflect::{FromJs, IntoJs};
+/// # use bevy_reflect::Reflect;
+/// # #[derive(Reflect)]
+/// # struct Color { hue: f32 }
+/// # let ctx = &mut boa_engine::Context::default();
+/// #[derive(Reflect, IntoJs, FromJs)]
+/// struct Particle {
+///     position: Vec<f32>,
+///     age: u32,
+///     // Converted through reflection, for fields without `IntoJs` and `FromJs`.
+///     #[js(reflect)]
+///     color: Color,
+/// }
+///
+/// # let particle = Particle { position: vec![0.0; 3], age: 0, color: Color { hue: 0.0 } };
+/// let value = particle.to_js(ctx)?;
+/// # Ok::<(), bevy_boa_reflect::ConversionError>(())
+/// ```
+///
+/// Direct conversions use the default [`ConversionSettings`](crate::ConversionSettings), and
+/// neither [`JsConverters`](crate::JsConverters) nor [`ConversionHooks`](crate::ConversionHooks)
+/// run on them.
+pub trait IntoJs {
+    fn to_js(&self, ctx: &mut Context) -> Result<JsValue, ConversionError>;
+}
+
+/// Conversion of a `JsValue` into a type field by field, without going through reflection. See
+/// [`IntoJs`].
+pub trait FromJs: Sized {
+    fn from_js(value: &JsValue, ctx: &mut Context) -> Result<Self, ConversionError>;
+}
+
+macro_rules! impl_primitive {
+    ($($ty:ty => $read:expr),* $(,)?) => {
+        $(
+            impl IntoJs for $ty {
+                fn to_js(&self, _: &mut Context) -> Result<JsValue, ConversionError> {
+                    Ok(Primitive::from(*self).into_js_value())
+                }
+            }
+
+            impl FromJs for $ty {
+                fn from_js(value: &JsValue, _: &mut Context) -> Result<Self, ConversionError> {
+                    $read(value, <$ty>::type_path())
+                }
+            }
+        )*
+    };
+}
+
+impl_primitive! {
+    i8 => js_value_to_int,
+    i16 => js_value_to_int,
+    i32 => js_value_to_int,
+    i64 => js_value_to_int,
+    i128 => js_value_to_int,
+    isize => js_value_to_int,
+    u8 => js_value_to_int,
+    u16 => js_value_to_int,
+    u32 => js_value_to_int,
+    u64 => js_value_to_int,
+    u128 => js_value_to_int,
+    usize => js_value_to_int,
+    f32 => |value, type_path| js_value_to_float(value, type_path).map(|f| f as f32),
+    f64 => js_value_to_float,
+    bool => |value: &JsValue, _| Ok(value.to_boolean()),
+}
+
+impl IntoJs for String {
+    fn to_js(&self, _: &mut Context) -> Result<JsValue, ConversionError> {
+        Ok(Primitive::String(Cow::Borrowed(self)).into_js_value())
+    }
+}
+
+impl FromJs for String {
+    fn from_js(value: &JsValue, _: &mut Context) -> Result<Self, ConversionError> {
+        js_value_to_string(value, Self::type_path())
+    }
+}
+
+/// `Some` is converted as reflection converts any other variant.
+impl<T: IntoJs> IntoJs for Option<T> {
+    fn to_js(&self, ctx: &mut Context) -> Result<JsValue, ConversionError> {
+        Ok(match self {
+            Some(value) => {
+                let value = value
+                    .to_js(ctx)
+                    .map_err(|err| err.at(PathSegment::Index(0)))?;
+                __private::variant_to_js("Some", &[None], vec![value], ctx)
+            }
+            None => __private::variant_to_js("None", &[], Vec::new(), ctx),
+        })
+    }
+}
+
+/// Read from `null` or `undefined`, a tagged variant, or the bare inner value.
+impl<T: FromJs> FromJs for Option<T> {
+    fn from_js(value: &JsValue, ctx: &mut Context) -> Result<Self, ConversionError> {
+        match value {
+            JsValue::Null | JsValue::Undefined => return Ok(None),
+            JsValue::Object(obj) if obj.has_property(js_str!("__variant"), ctx)? => {
+                let type_path = std::any::type_name::<Self>();
+                let (variant, obj) = __private::read_variant(value, type_path, ctx)?;
+                return match variant.as_str() {
+                    "None" => Ok(None),
+                    "Some" => {
+                        let obj = __private::variant_fields(obj, type_path, &variant)?;
+                        let inner = __private::get(&obj, "0", ctx)?;
+                        __private::at_index(T::from_js(&inner, ctx), 0).map(Some)
+                    }
+                    _ => Err(__private::unknown_variant(type_path, variant)),
+                };
+            }
+            _ => {}
+        }
+        T::from_js(value, ctx).map(Some)
+    }
+}
+
+impl<T: IntoJs> IntoJs for Vec<T> {
+    fn to_js(&self, ctx: &mut Context) -> Result<JsValue, ConversionError> {
+        let values = self
+            .iter()
+            .enumerate()
+            .map(|(idx, item)| __private::at_index(item.to_js(ctx), idx))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(__private::tuple_to_js(values, ctx))
+    }
+}
+
+impl<T: FromJs> FromJs for Vec<T> {
+    fn from_js(value: &JsValue, ctx: &mut Context) -> Result<Self, ConversionError> {
+        let items = js_array_items(value, std::any::type_name::<Self>(), ctx)?;
+        items
+            .iter()
+            .enumerate()
+            .map(|(idx, item)| __private::at_index(T::from_js(item, ctx), idx))
+            .collect()
+    }
+}
+
+/// What derived conversions call. Not part of the crate's API.
+#[doc(hidden)]
+pub mod __private {
+    use bevy_reflect::{FromReflect, GetTypeRegistration, Reflect, TypePath};
+    use boa_engine::JsObject;
+
+    use super::*;
+    use crate::boa_conversions::typed_from_js;
+    use crate::errors::FieldPath;
+    use crate::into::try_reflect_to_js_value;
+
+    pub use boa_engine::{Context, JsValue};
+
+    pub fn at_field<T>(
+        result: Result<T, ConversionError>,
+        name: &str,
+    ) -> Result<T, ConversionError> {
+        result.map_err(|err| err.at(PathSegment::Field(name.to_owned())))
+    }
+
+    pub fn at_index<T>(
+        result: Result<T, ConversionError>,
+        idx: usize,
+    ) -> Result<T, ConversionError> {
+        result.map_err(|err| err.at(PathSegment::Index(idx)))
+    }
+
+    /// A struct's fields, with the same template reflection creates the type's objects with.
+    pub fn struct_to_js<T: 'static>(
+        names: &[&str],
+        values: Vec<JsValue>,
+        ctx: &mut Context,
+    ) -> JsValue {
+        let template = ObjectTemplates::get(ctx, TypeId::of::<T>(), || {
+            Ok::<_, Infallible>(names.to_vec())
+        });
+        match template {
+            Ok(template) => template.create(values, ctx).into(),
+            Err(never) => match never {},
+        }
+    }
+
+    pub fn tuple_to_js(values: Vec<JsValue>, ctx: &mut Context) -> JsValue {
+        JsArray::from_iter(values, ctx).into()
+    }
+
+    pub fn variant_to_js(
+        variant: &str,
+        names: &[Option<&str>],
+        values: Vec<JsValue>,
+        ctx: &mut Context,
+    ) -> JsValue {
+        let variant = ObjectTemplates::of(ctx).intern(variant);
+        variant_to_js_object(variant, names.iter().copied(), values, ctx)
+    }
+
+    pub fn reflect_to_js(
+        value: &dyn Reflect,
+        ctx: &mut Context,
+    ) -> Result<JsValue, ConversionError> {
+        try_reflect_to_js_value(value, ctx)
+    }
+
+    pub fn reflect_from_js<T: FromReflect + TypePath + GetTypeRegistration>(
+        value: &JsValue,
+        ctx: &mut Context,
+    ) -> Result<T, ConversionError> {
+        typed_from_js(value, ctx)
+    }
+
+    pub fn expect_object(value: &JsValue, type_path: &str) -> Result<JsObject, ConversionError> {
+        value
+            .as_object()
+            .cloned()
+            .ok_or_else(|| ConversionError::type_mismatch("an object", type_path, value))
+    }
+
+    pub fn expect_array(
+        value: &JsValue,
+        type_path: &str,
+        ctx: &mut Context,
+    ) -> Result<Vec<JsValue>, ConversionError> {
+        js_array_items(value, type_path, ctx)
+    }
+
+    pub fn item(items: &[JsValue], idx: usize) -> JsValue {
+        items.get(idx).cloned().unwrap_or_default()
+    }
+
+    pub fn get(obj: &JsObject, key: &str, ctx: &mut Context) -> Result<JsValue, ConversionError> {
+        let key = ObjectTemplates::of(ctx).intern(key);
+        Ok(obj.get(key, ctx)?)
+    }
+
+    /// The name of the variant a value holds, and the object of its fields if it has one. Unit
+    /// variants may be given as a plain string, as with reflection.
+    pub fn read_variant(
+        value: &JsValue,
+        type_path: &str,
+        ctx: &mut Context,
+    ) -> Result<(String, Option<JsObject>), ConversionError> {
+        match value {
+            JsValue::String(s) => Ok((s.to_std_string_escaped(), None)),
+            JsValue::Object(obj) if obj.has_property(js_str!("__variant"), ctx)? => {
+                let variant = obj.get(js_str!("__variant"), ctx)?;
+                let name = variant.as_string().ok_or_else(|| {
+                    ConversionError::type_mismatch("a string", type_path, &variant)
+                        .at(PathSegment::Field("__variant".to_owned()))
+                })?;
+                Ok((name.to_std_string_escaped(), Some(obj.clone())))
+            }
+            _ => Err(ConversionError::type_mismatch(
+                "an enum value",
+                type_path,
+                value,
+            )),
+        }
+    }
+
+    /// The fields of a variant that has them, which can't be given by name alone.
+    pub fn variant_fields(
+        obj: Option<JsObject>,
+        type_path: &str,
+        variant: &str,
+    ) -> Result<JsObject, ConversionError> {
+        obj.ok_or_else(|| ConversionError::MissingVariantFields {
+            type_path: type_path.to_owned(),
+            path: FieldPath::default(),
+            variant: variant.to_owned(),
+        })
+    }
+
+    pub fn unknown_variant(type_path: &str, variant: String) -> ConversionError {
+        ConversionError::UnknownVariant {
+            type_path: type_path.to_owned(),
+            path: FieldPath::default(),
+            variant,
+        }
+    }
+}
@@ -0,0 +1,366 @@
+use boa_engine::class::{Class, ClassBuilder};
+use boa_engine::object::builtins::{JsArrayBuffer, JsTypedArray, JsUint8Array};
+use boa_engine::object::ObjectInitializer;
+use boa_engine::property::Attribute;
+use boa_engine::{
+    js_str, js_string, Context, Finalize, JsData, JsNativeError, JsObject, JsResult, JsString,
+    JsValue, NativeFunction, Trace,
+};
+
+use crate::from::js_array_items;
+
+/// Labels the WHATWG encoding standard gives UTF-8, the only encoding the decoder reads.
+const UTF8_LABELS: [&str; 6] = [
+    "utf-8",
+    "utf8",
+    "unicode-1-1-utf-8",
+    "unicode11utf8",
+    "unicode20utf8",
+    "x-unicode20utf8",
+];
+
+/// Register the `TextEncoder` and `TextDecoder` classes in the context's current realm, so
+/// scripts can turn strings into UTF-8 `Uint8Array`s and back. The script runtime registers them
+/// in every realm it creates.
+///
+/// Decoders read UTF-8 only. Besides typed arrays and `ArrayBuffer`s, `decode` takes arrays of
+/// numbers, which is how `Vec<u8>` fields reach scripts, and `Uint8Array`s convert back into
+/// `Vec<u8>` like arrays do.
+pub fn register_text_encoding(ctx: &mut Context) -> JsResult<()> {
+    ctx.register_global_class::<TextEncoder>()?;
+    ctx.register_global_class::<TextDecoder>()
+}
+
+#[derive(Debug, Trace, Finalize, JsData)]
+struct TextEncoder;
+
+impl Class for TextEncoder {
+    const NAME: &'static str = "TextEncoder";
+
+    fn init(class: &mut ClassBuilder<'_>) -> JsResult<()> {
+        class
+            .property(
+                js_str!("encoding"),
+                js_str!("utf-8"),
+                Attribute::CONFIGURABLE,
+            )
+            .method(
+                js_string!("encode"),
+                0,
+                NativeFunction::from_fn_ptr(|_, args, ctx| {
+                    let string = match args.first() {
+                        None | Some(JsValue::Undefined) => String::new(),
+                        Some(value) => well_formed(&value.to_string(ctx)?),
+                    };
+                    Ok(JsUint8Array::from_iter(string.into_bytes(), ctx)?.into())
+                }),
+            )
+            .method(
+                js_string!("encodeInto"),
+                2,
+                NativeFunction::from_fn_ptr(|_, args, ctx| {
+                    let source = args.first().cloned().unwrap_or_default().to_string(ctx)?;
+                    let destination = args
+                        .get(1)
+                        .and_then(JsValue::as_object)
+                        .and_then(|obj| JsUint8Array::from_object(obj.clone()).ok())
+                        .ok_or_else(|| {
+                            JsNativeError::typ().with_message("Destination must be a Uint8Array")
+                        })?;
+                    encode_into(&source, &destination, ctx)
+                }),
+            );
+        Ok(())
+    }
+
+    fn data_constructor(_: &JsValue, _: &[JsValue], _: &mut Context) -> JsResult<Self> {
+        Ok(Self)
+    }
+}
+
+/// Encode as much of a string as fits into a `Uint8Array`, whole characters at a time, and
+/// report how much was read and written.
+fn encode_into(
+    source: &JsString,
+    destination: &JsUint8Array,
+    ctx: &mut Context,
+) -> JsResult<JsValue> {
+    let capacity = destination.length(ctx)?;
+    let (mut read, mut written) = (0, 0);
+    let mut buf = [0; 4];
+    for c in char::decode_utf16(source.iter()) {
+        let c = c.unwrap_or(char::REPLACEMENT_CHARACTER);
+        let bytes = c.encode_utf8(&mut buf).as_bytes();
+        if written + bytes.len() > capacity {
+            break;
+        }
+        for byte in bytes {
+            destination.set(written, *byte, true, ctx)?;
+            written += 1;
+        }
+        read += c.len_utf16();
+    }
+    let result = ObjectInitializer::new(ctx)
+        .property(js_str!("read"), read, Attribute::all())
+        .property(js_str!("written"), written, Attribute::all())
+        .build();
+    Ok(result.into())
+}
+
+/// A string with lone surrogates replaced, as the encoder writes them.
+fn well_formed(string: &JsString) -> String {
+    char::decode_utf16(string.iter())
+        .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+        .collect()
+}
+
+#[derive(Debug, Trace, Finalize, JsData)]
+struct TextDecoder {
+    fatal: bool,
+    ignore_bom: bool,
+    /// The bytes of a character split across `decode` calls in a stream.
+    #[unsafe_ignore_trace]
+    pending: Vec<u8>,
+    /// Whether the stream's byte order mark has been dealt with.
+    bom_seen: bool,
+}
+
+impl Class for TextDecoder {
+    const NAME: &'static str = "TextDecoder";
+
+    fn init(class: &mut ClassBuilder<'_>) -> JsResult<()> {
+        class.method(
+            js_string!("decode"),
+            0,
+            NativeFunction::from_fn_ptr(|this, args, ctx| {
+                let input = args.first().cloned().unwrap_or_default();
+                let bytes = if input.is_undefined() {
+                    Vec::new()
+                } else {
+                    buffer_bytes(&input, ctx)?
+                };
+                let stream = match args.get(1).and_then(JsValue::as_object) {
+                    Some(options) => options.get(js_str!("stream"), ctx)?.to_boolean(),
+                    None => false,
+                };
+                let mut decoder = this
+                    .as_object()
+                    .and_then(|obj| obj.downcast_mut::<TextDecoder>())
+                    .ok_or_else(|| {
+                        JsNativeError::typ().with_message("Receiver is not a TextDecoder")
+                    })?;
+                Ok(JsString::from(decoder.decode(bytes, stream)?).into())
+            }),
+        );
+        Ok(())
+    }
+
+    fn data_constructor(_: &JsValue, args: &[JsValue], ctx: &mut Context) -> JsResult<Self> {
+        if let Some(label) = args.first().filter(|label| !label.is_undefined()) {
+            let label = label.to_string(ctx)?.to_std_string_escaped();
+            if !UTF8_LABELS.contains(&label.trim().to_ascii_lowercase().as_str()) {
+                return Err(JsNativeError::range()
+                    .with_message(format!("Unsupported encoding {label:?}, only UTF-8 is"))
+                    .into());
+            }
+        }
+        let mut decoder = Self {
+            fatal: false,
+            ignore_bom: false,
+            pending: Vec::new(),
+            bom_seen: false,
+        };
+        if let Some(options) = args.get(1).and_then(JsValue::as_object) {
+            decoder.fatal = options.get(js_str!("fatal"), ctx)?.to_boolean();
+            decoder.ignore_bom = options.get(js_str!("ignoreBOM"), ctx)?.to_boolean();
+        }
+        Ok(decoder)
+    }
+
+    fn object_constructor(instance: &JsObject, _: &[JsValue], ctx: &mut Context) -> JsResult<()> {
+        let (fatal, ignore_bom) = instance
+            .downcast_ref::<TextDecoder>()
+            .map(|decoder| (decoder.fatal, decoder.ignore_bom))
+            .unwrap_or_default();
+        for (name, value) in [
+            (js_str!("encoding"), js_str!("utf-8").into()),
+            (js_str!("fatal"), fatal.into()),
+            (js_str!("ignoreBOM"), ignore_bom.into()),
+        ] {
+            instance.define_property_or_throw(
+                name,
+                boa_engine::property::PropertyDescriptor::builder()
+                    .value::<JsValue>(value)
+                    .writable(false)
+                    .enumerable(true)
+                    .configurable(true),
+                ctx,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl TextDecoder {
+    fn decode(&mut self, bytes: Vec<u8>, stream: bool) -> JsResult<String> {
+        let mut bytes = std::mem::take(&mut self.pending)
+            .into_iter()
+            .chain(bytes)
+            .collect::<Vec<_>>();
+        if stream {
+            // Keep an unfinished character at the end for the next call, even after invalid
+            // bytes earlier on.
+            let tail = incomplete_tail(&bytes);
+            self.pending = bytes.split_off(bytes.len() - tail);
+        }
+        let mut string = match String::from_utf8(bytes) {
+            Ok(string) => string,
+            Err(_) if self.fatal => {
+                self.bom_seen = false;
+                return Err(JsNativeError::typ()
+                    .with_message("The encoded data was not valid UTF-8")
+                    .into());
+            }
+            Err(err) => String::from_utf8_lossy(err.as_bytes()).into_owned(),
+        };
+        if !self.bom_seen && !string.is_empty() {
+            if !self.ignore_bom && string.starts_with('\u{feff}') {
+                string.remove(0);
+            }
+            self.bom_seen = true;
+        }
+        if !stream {
+            self.bom_seen = false;
+        }
+        Ok(string)
+    }
+}
+
+/// The length of the character the bytes end in the middle of, if they do. Characters are at most
+/// 4 bytes long, so only the last 3 bytes are looked at.
+fn incomplete_tail(bytes: &[u8]) -> usize {
+    for len in 1..=bytes.len().min(3) {
+        let tail = &bytes[bytes.len() - len..];
+        // Continuation bytes can't start a character, so look further back.
+        if (0x80..0xc0).contains(&tail[0]) {
+            continue;
+        }
+        return match std::str::from_utf8(tail) {
+            Err(err) if err.valid_up_to() == 0 && err.error_len().is_none() => len,
+            _ => 0,
+        };
+    }
+    0
+}
+
+/// Whether an object holds bytes [`buffer_bytes`] reads as they are: a typed array, an
+/// `ArrayBuffer` or a `DataView`.
+#[cfg(feature = "fetch")]
+pub(crate) fn is_buffer_source(obj: &JsObject) -> bool {
+    obj.is::<boa_engine::builtins::typed_array::TypedArray>()
+        || JsArrayBuffer::from_object(obj.clone()).is_ok()
+        || boa_engine::object::builtins::JsDataView::from_object(obj.clone()).is_ok()
+}
+
+/// The bytes of a typed array, `ArrayBuffer` or `DataView`, or of an array of numbers.
+pub(crate) fn buffer_bytes(value: &JsValue, ctx: &mut Context) -> JsResult<Vec<u8>> {
+    let invalid = || {
+        JsNativeError::typ()
+            .with_message("Expected an ArrayBuffer, a typed array or an array of bytes")
+    };
+    let obj = value.as_object().ok_or_else(invalid)?;
+    if let Ok(buffer) = JsArrayBuffer::from_object(obj.clone()) {
+        return Ok(buffer.data().map(|data| data.to_vec()).unwrap_or_default());
+    }
+    let view = if let Ok(typed) = JsTypedArray::from_object(obj.clone()) {
+        Some((
+            typed.buffer(ctx)?,
+            typed.byte_offset(ctx)?,
+            typed.byte_length(ctx)?,
+        ))
+    } else if let Ok(view) = boa_engine::object::builtins::JsDataView::from_object(obj.clone()) {
+        Some((
+            view.buffer(ctx)?,
+            view.byte_offset(ctx)? as usize,
+            view.byte_length(ctx)? as usize,
+        ))
+    } else {
+        None
+    };
+    if let Some((buffer, offset, len)) = view {
+        let buffer = buffer
+            .as_object()
+            .and_then(|buffer| JsArrayBuffer::from_object(buffer.clone()).ok())
+            .ok_or_else(invalid)?;
+        let data = buffer.data();
+        return Ok(data
+            .as_deref()
+            .and_then(|data| data.get(offset..offset + len))
+            .map(<[u8]>::to_vec)
+            .unwrap_or_default());
+    }
+    let items = js_array_items(value, "Uint8Array", ctx).map_err(|_| invalid())?;
+    items
+        .iter()
+        .map(|item| Ok(item.to_number(ctx)? as u8))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use boa_engine::Source;
+
+    use super::*;
+
+    fn run(source: &str) -> String {
+        let mut ctx = Context::default();
+        register_text_encoding(&mut ctx).unwrap();
+        match ctx.eval(Source::from_bytes(source)) {
+            Ok(value) => value.to_string(&mut ctx).unwrap().to_std_string_escaped(),
+            Err(err) => err.to_string(),
+        }
+    }
+
+    #[test]
+    fn strings_round_trip() {
+        let result = run("const bytes = new TextEncoder().encode('héllo 🐉'); \
+             [bytes.length, new TextDecoder().decode(bytes)].join()");
+        assert_eq!(result, "11,héllo 🐉");
+    }
+
+    #[test]
+    fn streams_keep_split_characters_for_the_next_call() {
+        // The dragon's 4 bytes are split across calls, after an invalid byte.
+        let result = run("const decoder = new TextDecoder(); \
+             decoder.decode(new Uint8Array([0x61, 0xff, 0xf0, 0x9f]), { stream: true }) + '|' + \
+             decoder.decode(new Uint8Array([0x90, 0x89]), { stream: true }) + '|' + \
+             decoder.decode(new Uint8Array([0xe2, 0x82]), { stream: true }) + '|' + \
+             decoder.decode()");
+        assert_eq!(result, "a\u{fffd}|🐉||\u{fffd}");
+    }
+
+    #[test]
+    fn byte_order_marks_are_dropped_once_per_stream() {
+        let result = run("const bom = [0xef, 0xbb, 0xbf]; \
+             const decoder = new TextDecoder(); \
+             [decoder.decode(new Uint8Array([...bom, 0x61]), { stream: true }), \
+              decoder.decode(new Uint8Array([...bom, 0x62])), \
+              decoder.decode(new Uint8Array([...bom, 0x63])), \
+              new TextDecoder('utf-8', { ignoreBOM: true }).decode(new Uint8Array(bom)).length] \
+             .join()");
+        assert_eq!(result, "a,\u{feff}b,c,1");
+    }
+
+    #[test]
+    fn fatal_decoders_throw_on_invalid_bytes() {
+        let result =
+            run("new TextDecoder('utf-8', { fatal: true }).decode(new Uint8Array([0xff]))");
+        assert!(result.starts_with("TypeError"), "{result}");
+        let result = run(
+            "const decoder = new TextDecoder('utf-8', { fatal: true }); \
+             decoder.decode(new Uint8Array([0xe2, 0x82]), { stream: true }) + '|' + \
+             decoder.decode(new Uint8Array([0xac]))",
+        );
+        assert_eq!(result, "|€");
+    }
+}
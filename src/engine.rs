@@ -0,0 +1,907 @@
+use std::any::{Any, TypeId};
+use std::borrow::Cow;
+use std::fmt;
+use std::rc::Rc;
+use std::sync::OnceLock;
+use std::vec::Drain;
+
+use bevy_reflect::{Enum, Reflect, Struct, StructInfo};
+use bevy_utils::TypeIdMap;
+use boa_engine::builtins::map::ordered_map::OrderedMap;
+use boa_engine::builtins::set::ordered_set::OrderedSet;
+use boa_engine::object::builtins::{JsArray, JsMap, JsSet, JsTypedArray};
+use boa_engine::object::ObjectInitializer;
+use boa_engine::property::{Attribute, PropertyKey};
+use boa_engine::{js_str, Context, JsObject, JsResult, JsString, JsValue};
+
+use crate::converters::{JsConverter, JsConverters};
+use crate::errors::{ConversionError, FieldPath, JsValueKind};
+use crate::hooks::{ConversionHooks, Hooks};
+use crate::templates::{ObjectTemplate, ObjectTemplates};
+
+/// A JS engine that reflected values are converted to and from. Conversions walk values the same
+/// way whatever the engine, with the same settings and into the same shapes, and only go through
+/// this trait to make the engine's values and to read them, so an embedding of another engine,
+/// like QuickJS or V8, converts by implementing it. [`BoaEngine`] is the implementation the
+/// crate's own conversions use, see [`reflect_to_engine_value`](crate::reflect_to_engine_value)
+/// and [`engine_value_to_typed_reflect`](crate::engine_value_to_typed_reflect) to convert with
+/// another.
+///
+/// Values are made bottom up: the values inside an object, array or map are made first, and
+/// handed over in order, drained from the end of the values made so far.
+pub trait JsEngine {
+    /// A value of the engine, where `Default` is `undefined`.
+    type Value: Clone + Default;
+
+    /// Make a primitive value.
+    fn primitive(&mut self, primitive: Primitive<'_>) -> Self::Value;
+
+    /// Make the string naming an enum value's variant.
+    fn variant_name(&mut self, enum_value: &dyn Enum) -> Self::Value {
+        let name = enum_value.variant_name();
+        self.primitive(Primitive::String(Cow::Borrowed(name)))
+    }
+
+    /// Make an object with a property for each key, holding the value in the same place.
+    fn object(
+        &mut self,
+        keys: ObjectKeys<'_>,
+        values: Drain<'_, Self::Value>,
+    ) -> Result<Self::Value, ConversionError>;
+
+    /// Make an array of items.
+    fn array(&mut self, items: Drain<'_, Self::Value>) -> Result<Self::Value, ConversionError>;
+
+    /// Make a `Map` of keys and values, taken in turn.
+    fn map(&mut self, entries: Drain<'_, Self::Value>) -> Result<Self::Value, ConversionError>;
+
+    /// Read a value, or just its kind if it is an object or a symbol.
+    fn read(&self, value: &Self::Value) -> Read;
+
+    /// The kind of a value, for engines where that's cheaper than reading it whole.
+    fn kind(&self, value: &Self::Value) -> JsValueKind {
+        self.read(value).kind()
+    }
+
+    /// Read the properties of an object under keys, `undefined` for those it doesn't have.
+    /// Arrays are read by index too, as the fields of externally represented tuple variants.
+    fn properties(
+        &mut self,
+        object: &Self::Value,
+        keys: ObjectKeys<'_>,
+    ) -> Result<Vec<Self::Value>, ConversionError>;
+
+    /// Whether an object has a property, of its own or through its prototypes.
+    fn has_property(&mut self, object: &Self::Value, key: Key<'_>)
+        -> Result<bool, ConversionError>;
+
+    /// The keys of an object's own properties, other than symbols, in order.
+    fn own_keys(&mut self, object: &Self::Value) -> Result<Vec<Key<'static>>, ConversionError>;
+
+    /// Read the items of an array or a `Set`, or the entries of a `Map`.
+    fn collection(
+        &mut self,
+        value: &Self::Value,
+    ) -> Result<Collection<Self::Value>, ConversionError>;
+
+    /// Convert a value the engine has a conversion of its own for, in place of the one its kind
+    /// would get, or return `None` to convert it as usual.
+    fn convert(&mut self, _value: &dyn Reflect) -> Option<Result<Self::Value, ConversionError>> {
+        None
+    }
+
+    /// Whether to run [`post_hook`](Self::post_hook) on the values made. Paths are only tracked
+    /// for hooks while this is true.
+    fn has_post_hooks(&self) -> bool {
+        false
+    }
+
+    /// Look at a value made for a type, at a path within the value converted.
+    fn post_hook(
+        &mut self,
+        _type_path: &str,
+        _path: &FieldPath,
+        _value: &mut Self::Value,
+    ) -> Result<(), ConversionError> {
+        Ok(())
+    }
+
+    /// Whether to run [`pre_hook`](Self::pre_hook) on the values read.
+    fn has_pre_hooks(&self) -> bool {
+        false
+    }
+
+    /// Look at a value about to be read into a type, at a path within the value converted.
+    fn pre_hook(
+        &mut self,
+        _type_path: &str,
+        _path: &FieldPath,
+        _value: &mut Self::Value,
+    ) -> Result<(), ConversionError> {
+        Ok(())
+    }
+
+    /// A short description of a value for conversion logs.
+    fn summary(&self, value: &Self::Value) -> String {
+        self.kind(value).to_string()
+    }
+}
+
+/// The key of a property.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Key<'a> {
+    Name(Cow<'a, str>),
+    /// An array index, which is how tuple variant fields are keyed.
+    Index(usize),
+}
+
+impl fmt::Display for Key<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Name(name) => f.write_str(name),
+            Self::Index(idx) => write!(f, "{idx}"),
+        }
+    }
+}
+
+/// The keys of an object's properties, in order.
+#[derive(Clone, Copy)]
+pub enum ObjectKeys<'a> {
+    /// The fields of a struct value, by their names.
+    Struct(&'a dyn Struct),
+    /// The fields of a struct type, by their names.
+    StructInfo(&'a StructInfo),
+    /// Keys given one by one, like renamed fields or an enum variant's.
+    Keys(&'a [Key<'a>]),
+}
+
+/// A value read out of an engine.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Read {
+    Undefined,
+    Null,
+    Boolean(bool),
+    Number(f64),
+    /// A `BigInt`, in decimal.
+    BigInt(String),
+    String(String),
+    /// A symbol, or an object of this kind, read further through the engine.
+    Other(JsValueKind),
+}
+
+/// Values display as JS displays them, with strings in quotes.
+impl fmt::Display for Read {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Undefined => f.write_str("undefined"),
+            Self::Null => f.write_str("null"),
+            Self::Boolean(b) => write!(f, "{b}"),
+            Self::Number(n) => write!(f, "{n}"),
+            Self::BigInt(b) => write!(f, "{b}n"),
+            Self::String(s) => write!(f, "\"{s}\""),
+            Self::Other(kind) => write!(f, "{kind}"),
+        }
+    }
+}
+
+impl Read {
+    pub fn kind(&self) -> JsValueKind {
+        match self {
+            Self::Undefined => JsValueKind::Undefined,
+            Self::Null => JsValueKind::Null,
+            Self::Boolean(_) => JsValueKind::Boolean,
+            Self::Number(_) => JsValueKind::Number,
+            Self::BigInt(_) => JsValueKind::BigInt,
+            Self::String(_) => JsValueKind::String,
+            Self::Other(kind) => *kind,
+        }
+    }
+
+    /// Whether the value is truthy, as JS converts it to a boolean.
+    pub fn to_boolean(&self) -> bool {
+        match self {
+            Self::Undefined | Self::Null => false,
+            Self::Boolean(b) => *b,
+            Self::Number(n) => *n != 0.0 && !n.is_nan(),
+            Self::BigInt(b) => b != "0",
+            Self::String(s) => !s.is_empty(),
+            Self::Other(_) => true,
+        }
+    }
+}
+
+/// What a collection read out of an engine holds.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Collection<V> {
+    /// The items of an array or a typed array.
+    Array(Vec<V>),
+    Set(Vec<V>),
+    Map(Vec<(V, V)>),
+    /// The value isn't a collection.
+    None,
+}
+
+/// A primitive read out of a reflected value. Reading it needs no context, so it can be done on
+/// other threads, leaving only making the JS value to the context's thread. Strings are borrowed
+/// from the value, so they're copied once, straight into the engine's string.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Primitive<'a> {
+    Null,
+    Boolean(bool),
+    Integer(i32),
+    BigInt(i128),
+    BigUint(u128),
+    Rational(f64),
+    String(Cow<'a, str>),
+}
+
+type ReadPrimitive = for<'a> fn(&'a dyn Any) -> Option<Primitive<'a>>;
+
+/// How to read each primitive type, found by the value's type in one lookup rather than trying
+/// each type in turn.
+static PRIMITIVES: OnceLock<TypeIdMap<ReadPrimitive>> = OnceLock::new();
+
+fn read<T: Any + Copy + Into<Primitive<'static>>>(value: &dyn Any) -> Option<Primitive<'_>> {
+    value.downcast_ref::<T>().copied().map(Into::into)
+}
+
+fn read_string(value: &dyn Any) -> Option<Primitive<'_>> {
+    let value = value.downcast_ref::<String>()?;
+    Some(Primitive::String(Cow::Borrowed(value)))
+}
+
+fn read_str(value: &dyn Any) -> Option<Primitive<'_>> {
+    let value = value.downcast_ref::<&str>()?;
+    Some(Primitive::String(Cow::Borrowed(value)))
+}
+
+fn read_char(value: &dyn Any) -> Option<Primitive<'_>> {
+    let value = value.downcast_ref::<char>()?;
+    Some(Primitive::String(Cow::Owned(value.to_string())))
+}
+
+impl<'a> Primitive<'a> {
+    /// Read a primitive of one of the common primitive types, or `None` for any other value.
+    /// Checked before matching on a value's kind, which spares the primitives that make up most
+    /// fields that walk.
+    pub(crate) fn lookup(value: &'a dyn Reflect) -> Option<Self> {
+        let primitives = PRIMITIVES.get_or_init(|| {
+            let entries: [(TypeId, ReadPrimitive); 18] = [
+                (TypeId::of::<bool>(), read::<bool>),
+                (TypeId::of::<i8>(), read::<i8>),
+                (TypeId::of::<i16>(), read::<i16>),
+                (TypeId::of::<i32>(), read::<i32>),
+                (TypeId::of::<i64>(), read::<i64>),
+                (TypeId::of::<i128>(), read::<i128>),
+                (TypeId::of::<isize>(), read::<isize>),
+                (TypeId::of::<u8>(), read::<u8>),
+                (TypeId::of::<u16>(), read::<u16>),
+                (TypeId::of::<u32>(), read::<u32>),
+                (TypeId::of::<u64>(), read::<u64>),
+                (TypeId::of::<u128>(), read::<u128>),
+                (TypeId::of::<usize>(), read::<usize>),
+                (TypeId::of::<f32>(), read::<f32>),
+                (TypeId::of::<f64>(), read::<f64>),
+                (TypeId::of::<String>(), read_string),
+                (TypeId::of::<&'static str>(), read_str),
+                (TypeId::of::<char>(), read_char),
+            ];
+            entries.into_iter().collect()
+        });
+        let value = value.as_any();
+        primitives.get(&value.type_id())?(value)
+    }
+
+    /// The primitive with its string, if any, copied out of the value it was read from.
+    pub(crate) fn into_owned(self) -> Primitive<'static> {
+        match self {
+            Self::Null => Primitive::Null,
+            Self::Boolean(v) => Primitive::Boolean(v),
+            Self::Integer(v) => Primitive::Integer(v),
+            Self::BigInt(v) => Primitive::BigInt(v),
+            Self::BigUint(v) => Primitive::BigUint(v),
+            Self::Rational(v) => Primitive::Rational(v),
+            Self::String(v) => Primitive::String(Cow::Owned(v.into_owned())),
+        }
+    }
+
+    /// The primitive with 64 bit integers as plain numbers, for
+    /// [`NumberPolicy::Lossy`](crate::NumberPolicy::Lossy).
+    pub(crate) fn into_number(self) -> Self {
+        match self {
+            Self::BigInt(v) => Self::Rational(v as f64),
+            Self::BigUint(v) => Self::Rational(v as f64),
+            primitive => primitive,
+        }
+    }
+
+    /// The bytes of string the primitive holds.
+    pub(crate) fn string_len(&self) -> usize {
+        match self {
+            Self::String(s) => s.len(),
+            _ => 0,
+        }
+    }
+
+    pub(crate) fn into_js_value(self) -> JsValue {
+        match self {
+            Self::Null => JsValue::Null,
+            Self::Boolean(v) => JsValue::Boolean(v),
+            Self::Integer(v) => JsValue::Integer(v),
+            Self::BigInt(v) => JsValue::BigInt(v.into()),
+            Self::BigUint(v) => JsValue::BigInt(v.into()),
+            Self::Rational(v) => JsValue::Rational(v),
+            Self::String(v) => JsValue::String(JsString::from(&*v)),
+        }
+    }
+}
+
+macro_rules! primitive_from {
+    ($($ty:ty => $variant:ident as $as:ty),* $(,)?) => {
+        $(
+            impl From<$ty> for Primitive<'_> {
+                fn from(value: $ty) -> Self {
+                    Self::$variant(value as $as)
+                }
+            }
+        )*
+    };
+}
+
+primitive_from! {
+    i8 => Integer as i32,
+    i16 => Integer as i32,
+    i32 => Integer as i32,
+    u8 => Integer as i32,
+    u16 => Integer as i32,
+    i64 => BigInt as i128,
+    i128 => BigInt as i128,
+    isize => BigInt as i128,
+    u32 => BigUint as u128,
+    u64 => BigUint as u128,
+    u128 => BigUint as u128,
+    usize => BigUint as u128,
+    f32 => Rational as f64,
+    f64 => Rational as f64,
+}
+
+impl From<bool> for Primitive<'_> {
+    fn from(value: bool) -> Self {
+        Self::Boolean(value)
+    }
+}
+
+/// Converts in a Boa context, with the context's cached object templates, and the
+/// [`JsConverters`] and [`ConversionHooks`] registered in it.
+pub struct BoaEngine<'a> {
+    ctx: &'a mut Context,
+    templates: ObjectTemplates,
+    converters: Option<Rc<TypeIdMap<JsConverter>>>,
+    hooks: Option<Rc<Hooks>>,
+}
+
+impl<'a> BoaEngine<'a> {
+    pub fn new(ctx: &'a mut Context) -> Self {
+        let converters = JsConverters::of(ctx);
+        let hooks = ConversionHooks::of(ctx);
+        Self {
+            templates: ObjectTemplates::of(ctx),
+            ctx,
+            converters,
+            hooks,
+        }
+    }
+
+    /// An engine for reading values without the context's converters and hooks, for helpers
+    /// that read a collection or two rather than converting a value.
+    pub(crate) fn reader(ctx: &'a mut Context) -> Self {
+        Self {
+            templates: ObjectTemplates::of(ctx),
+            ctx,
+            converters: None,
+            hooks: None,
+        }
+    }
+
+    pub fn context(&mut self) -> &mut Context {
+        self.ctx
+    }
+
+    /// Read a value, which needs no context.
+    pub(crate) fn read_value(value: &JsValue) -> Read {
+        match value {
+            JsValue::Undefined => Read::Undefined,
+            JsValue::Null => Read::Null,
+            JsValue::Boolean(b) => Read::Boolean(*b),
+            JsValue::Integer(i) => Read::Number(f64::from(*i)),
+            JsValue::Rational(f) => Read::Number(*f),
+            JsValue::BigInt(b) => Read::BigInt(b.to_string()),
+            JsValue::String(s) => Read::String(s.to_std_string_escaped()),
+            value => Read::Other(JsValueKind::of(value)),
+        }
+    }
+
+    fn property_key(&self, key: &Key) -> PropertyKey {
+        match key {
+            Key::Name(name) => self.templates.intern(name).into(),
+            Key::Index(idx) => self.templates.index(*idx).into(),
+        }
+    }
+
+    fn template(
+        &mut self,
+        keys: ObjectKeys,
+    ) -> Result<Option<Rc<ObjectTemplate>>, ConversionError> {
+        Ok(Some(match keys {
+            ObjectKeys::Struct(s) if s.is_dynamic() => {
+                Rc::new(ObjectTemplate::new(field_names(s)?))
+            }
+            ObjectKeys::Struct(s) => {
+                ObjectTemplates::get(self.ctx, s.as_any().type_id(), || field_names(s))?
+            }
+            ObjectKeys::StructInfo(info) => ObjectTemplates::struct_info(self.ctx, info),
+            ObjectKeys::Keys(_) => return Ok(None),
+        }))
+    }
+}
+
+fn expect_object(value: &JsValue) -> Result<&JsObject, ConversionError> {
+    value
+        .as_object()
+        .ok_or_else(|| ConversionError::type_mismatch("an object", "", value))
+}
+
+pub(crate) fn field_names(reflect_struct: &dyn Struct) -> Result<Vec<&str>, ConversionError> {
+    (0..reflect_struct.field_len())
+        .map(|idx| {
+            reflect_struct
+                .name_at(idx)
+                .ok_or_else(|| ConversionError::UnnamedField {
+                    type_path: reflect_struct.reflect_type_path().to_owned(),
+                    path: FieldPath::default(),
+                    index: idx,
+                })
+        })
+        .collect()
+}
+
+impl JsEngine for BoaEngine<'_> {
+    type Value = JsValue;
+
+    fn primitive(&mut self, primitive: Primitive<'_>) -> JsValue {
+        primitive.into_js_value()
+    }
+
+    // Names are made once per enum type, so enum heavy data like state machines converts without
+    // allocating its variant names again and again.
+    fn variant_name(&mut self, enum_value: &dyn Enum) -> JsValue {
+        JsValue::String(self.templates.variant_name(enum_value))
+    }
+
+    fn object(
+        &mut self,
+        keys: ObjectKeys<'_>,
+        values: Drain<'_, JsValue>,
+    ) -> Result<JsValue, ConversionError> {
+        if let Some(template) = self.template(keys)? {
+            return Ok(template.create(values, self.ctx).into());
+        }
+        let ObjectKeys::Keys(keys) = keys else {
+            unreachable!("only keys given one by one have no template");
+        };
+        let keys = keys
+            .iter()
+            .map(|key| self.property_key(key))
+            .collect::<Vec<_>>();
+        let mut obj = ObjectInitializer::new(self.ctx);
+        for (key, value) in keys.into_iter().zip(values) {
+            obj.property(key, value, Attribute::all());
+        }
+        Ok(obj.build().into())
+    }
+
+    // Built from the items at once, rather than going through `push` per item.
+    fn array(&mut self, items: Drain<'_, JsValue>) -> Result<JsValue, ConversionError> {
+        Ok(JsArray::from_iter(items, self.ctx).into())
+    }
+
+    fn map(&mut self, entries: Drain<'_, JsValue>) -> Result<JsValue, ConversionError> {
+        Ok(entries_to_js_map(entries, self.ctx)?.into())
+    }
+
+    fn read(&self, value: &JsValue) -> Read {
+        Self::read_value(value)
+    }
+
+    fn kind(&self, value: &JsValue) -> JsValueKind {
+        JsValueKind::of(value)
+    }
+
+    fn properties(
+        &mut self,
+        object: &JsValue,
+        keys: ObjectKeys<'_>,
+    ) -> Result<Vec<JsValue>, ConversionError> {
+        let obj = expect_object(object)?;
+        match self.template(keys)? {
+            Some(template) => template
+                .keys()
+                .iter()
+                .map(|key| Ok(obj.get(key.clone(), self.ctx)?))
+                .collect(),
+            None => {
+                let ObjectKeys::Keys(keys) = keys else {
+                    unreachable!("only keys given one by one have no template");
+                };
+                keys.iter()
+                    .map(|key| Ok(obj.get(self.property_key(key), self.ctx)?))
+                    .collect()
+            }
+        }
+    }
+
+    fn has_property(&mut self, object: &JsValue, key: Key<'_>) -> Result<bool, ConversionError> {
+        let key = self.property_key(&key);
+        Ok(expect_object(object)?.has_property(key, self.ctx)?)
+    }
+
+    fn own_keys(&mut self, object: &JsValue) -> Result<Vec<Key<'static>>, ConversionError> {
+        let keys = expect_object(object)?.own_property_keys(self.ctx)?;
+        Ok(keys
+            .into_iter()
+            .filter_map(|key| match key {
+                PropertyKey::String(name) => Some(Key::Name(name.to_std_string_escaped().into())),
+                PropertyKey::Index(idx) => Some(Key::Index(idx.get() as usize)),
+                PropertyKey::Symbol(_) => None,
+            })
+            .collect())
+    }
+
+    fn collection(&mut self, value: &JsValue) -> Result<Collection<JsValue>, ConversionError> {
+        let Some(obj) = value.as_object() else {
+            return Ok(Collection::None);
+        };
+        let ctx = &mut *self.ctx;
+        // Typed arrays, like the `Uint8Array`s `TextEncoder` makes, read like arrays of numbers.
+        if let Ok(typed) = JsTypedArray::from_object(obj.clone()) {
+            let items = (0..typed.length(ctx)?)
+                .map(|i| typed.at(i as i64, ctx))
+                .collect::<JsResult<_>>()?;
+            return Ok(Collection::Array(items));
+        }
+        if obj.is_array() {
+            let array = JsArray::from_object(obj.clone())?;
+            let items = (0..array.length(ctx)?)
+                .map(|i| array.get(i, ctx))
+                .collect::<JsResult<_>>()?;
+            return Ok(Collection::Array(items));
+        }
+        if obj.is::<OrderedMap<JsValue>>() {
+            let iterator = JsMap::from_object(obj.clone())?.entries(ctx)?;
+            let mut entries = Vec::new();
+            loop {
+                let result = iterator.next(ctx)?.to_object(ctx)?;
+                if result.get(js_str!("done"), ctx)?.to_boolean() {
+                    break;
+                }
+                let entry = result.get(js_str!("value"), ctx)?.to_object(ctx)?;
+                let entry = JsArray::from_object(entry)?;
+                entries.push((entry.get(0, ctx)?, entry.get(1, ctx)?));
+            }
+            return Ok(Collection::Map(entries));
+        }
+        if obj.is::<OrderedSet>() {
+            let values = JsSet::from_object(obj.clone())?.values(ctx)?;
+            let mut items = Vec::new();
+            loop {
+                let result = values.next(ctx)?.to_object(ctx)?;
+                if result.get(js_str!("done"), ctx)?.to_boolean() {
+                    break;
+                }
+                items.push(result.get(js_str!("value"), ctx)?);
+            }
+            return Ok(Collection::Set(items));
+        }
+        Ok(Collection::None)
+    }
+
+    fn convert(&mut self, value: &dyn Reflect) -> Option<Result<JsValue, ConversionError>> {
+        let convert = self.converters.as_ref()?.get(&value.as_any().type_id())?;
+        Some(convert(value, self.ctx).map_err(ConversionError::from))
+    }
+
+    fn has_post_hooks(&self) -> bool {
+        self.hooks.as_ref().is_some_and(|hooks| hooks.has_post())
+    }
+
+    fn post_hook(
+        &mut self,
+        type_path: &str,
+        path: &FieldPath,
+        value: &mut JsValue,
+    ) -> Result<(), ConversionError> {
+        match &self.hooks {
+            Some(hooks) => hooks.post(type_path, path, value),
+            None => Ok(()),
+        }
+    }
+
+    fn has_pre_hooks(&self) -> bool {
+        self.hooks.as_ref().is_some_and(|hooks| hooks.has_pre())
+    }
+
+    fn pre_hook(
+        &mut self,
+        type_path: &str,
+        path: &FieldPath,
+        value: &mut JsValue,
+    ) -> Result<(), ConversionError> {
+        match &self.hooks {
+            Some(hooks) => hooks.pre(type_path, path, value),
+            None => Ok(()),
+        }
+    }
+
+    #[cfg(feature = "verbose")]
+    fn summary(&self, value: &JsValue) -> String {
+        crate::verbose::js_summary(value)
+    }
+}
+
+/// The object for an enum variant, with its fields keyed by their names, or their indices for
+/// tuple variants.
+pub(crate) fn variant_to_js_object<'a>(
+    variant: JsString,
+    names: impl IntoIterator<Item = Option<&'a str>>,
+    values: impl IntoIterator<Item = JsValue>,
+    context: &mut Context,
+) -> JsValue {
+    let templates = ObjectTemplates::of(context);
+    let mut obj = ObjectInitializer::new(context);
+    // Tuple variant fields are keyed by index, which is how they are read back.
+    for (idx, (name, value)) in names.into_iter().zip(values).enumerate() {
+        let key = match name {
+            Some(name) => templates.intern(name),
+            None => templates.index(idx),
+        };
+        obj.property(key, value, Attribute::all());
+    }
+    obj.property(
+        js_str!("__variant"),
+        JsValue::String(variant),
+        Attribute::all(),
+    );
+    obj.build().into()
+}
+
+/// A `Map` of keys and values, taken in turn.
+pub(crate) fn entries_to_js_map(
+    mut entries: impl Iterator<Item = JsValue>,
+    ctx: &mut Context,
+) -> JsResult<JsMap> {
+    let js_map = JsMap::new(ctx);
+    while let (Some(key), Some(value)) = (entries.next(), entries.next()) {
+        js_map.set(key, value, ctx)?;
+    }
+    Ok(js_map)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use bevy_reflect::{FromReflect, TypeRegistry};
+
+    use crate::from::engine_value_to_typed_reflect;
+    use crate::into::reflect_to_engine_value;
+    use crate::settings::{ConversionSettings, EnumRepresentation};
+
+    use super::*;
+
+    /// The values of an engine that keeps them as plain Rust data, standing in for another
+    /// engine than Boa.
+    #[derive(Debug, Clone, Default, PartialEq)]
+    enum Plain {
+        #[default]
+        Undefined,
+        Null,
+        Boolean(bool),
+        Number(f64),
+        BigInt(String),
+        String(String),
+        Array(Vec<Plain>),
+        Object(Vec<(String, Plain)>),
+        Map(Vec<(Plain, Plain)>),
+    }
+
+    struct PlainEngine;
+
+    fn names(keys: ObjectKeys) -> Vec<String> {
+        match keys {
+            ObjectKeys::Struct(s) => field_names(s)
+                .unwrap()
+                .into_iter()
+                .map(str::to_owned)
+                .collect(),
+            ObjectKeys::StructInfo(info) => {
+                info.iter().map(|field| field.name().to_owned()).collect()
+            }
+            ObjectKeys::Keys(keys) => keys.iter().map(Key::to_string).collect(),
+        }
+    }
+
+    impl JsEngine for PlainEngine {
+        type Value = Plain;
+
+        fn primitive(&mut self, primitive: Primitive<'_>) -> Plain {
+            match primitive {
+                Primitive::Null => Plain::Null,
+                Primitive::Boolean(b) => Plain::Boolean(b),
+                Primitive::Integer(i) => Plain::Number(i.into()),
+                Primitive::BigInt(i) => Plain::BigInt(i.to_string()),
+                Primitive::BigUint(i) => Plain::BigInt(i.to_string()),
+                Primitive::Rational(f) => Plain::Number(f),
+                Primitive::String(s) => Plain::String(s.into_owned()),
+            }
+        }
+
+        fn object(
+            &mut self,
+            keys: ObjectKeys<'_>,
+            values: Drain<'_, Plain>,
+        ) -> Result<Plain, ConversionError> {
+            Ok(Plain::Object(names(keys).into_iter().zip(values).collect()))
+        }
+
+        fn array(&mut self, items: Drain<'_, Plain>) -> Result<Plain, ConversionError> {
+            Ok(Plain::Array(items.collect()))
+        }
+
+        fn map(&mut self, mut entries: Drain<'_, Plain>) -> Result<Plain, ConversionError> {
+            let mut map = Vec::new();
+            while let (Some(key), Some(value)) = (entries.next(), entries.next()) {
+                map.push((key, value));
+            }
+            Ok(Plain::Map(map))
+        }
+
+        fn read(&self, value: &Plain) -> Read {
+            match value {
+                Plain::Undefined => Read::Undefined,
+                Plain::Null => Read::Null,
+                Plain::Boolean(b) => Read::Boolean(*b),
+                Plain::Number(n) => Read::Number(*n),
+                Plain::BigInt(b) => Read::BigInt(b.clone()),
+                Plain::String(s) => Read::String(s.clone()),
+                Plain::Array(_) => Read::Other(JsValueKind::Array),
+                Plain::Object(_) | Plain::Map(_) => Read::Other(JsValueKind::Object),
+            }
+        }
+
+        fn properties(
+            &mut self,
+            object: &Plain,
+            keys: ObjectKeys<'_>,
+        ) -> Result<Vec<Plain>, ConversionError> {
+            let find = |name: &String| {
+                let property = match object {
+                    Plain::Object(properties) => properties
+                        .iter()
+                        .find(|(key, _)| key == name)
+                        .map(|(_, value)| value),
+                    Plain::Array(items) => name.parse().ok().and_then(|idx: usize| items.get(idx)),
+                    _ => None,
+                };
+                property.cloned().unwrap_or_default()
+            };
+            Ok(names(keys).iter().map(find).collect())
+        }
+
+        fn has_property(&mut self, object: &Plain, key: Key<'_>) -> Result<bool, ConversionError> {
+            let key = key.to_string();
+            Ok(
+                matches!(object, Plain::Object(properties) if properties.iter().any(|(name, _)| *name == key)),
+            )
+        }
+
+        fn own_keys(&mut self, object: &Plain) -> Result<Vec<Key<'static>>, ConversionError> {
+            let Plain::Object(properties) = object else {
+                return Ok(Vec::new());
+            };
+            Ok(properties
+                .iter()
+                .map(|(name, _)| Key::Name(Cow::Owned(name.clone())))
+                .collect())
+        }
+
+        fn collection(&mut self, value: &Plain) -> Result<Collection<Plain>, ConversionError> {
+            Ok(match value {
+                Plain::Array(items) => Collection::Array(items.clone()),
+                Plain::Map(entries) => Collection::Map(entries.clone()),
+                _ => Collection::None,
+            })
+        }
+    }
+
+    #[derive(Reflect, Debug, Clone, PartialEq)]
+    enum Mode {
+        Idle,
+        Walk(f32, bool),
+        Move { x: f32, y: f32 },
+    }
+
+    #[derive(Reflect, Debug, Clone, PartialEq)]
+    struct Unit {
+        name: String,
+        health: u32,
+        id: u64,
+        position: (f32, f32),
+        modes: Vec<Mode>,
+        tags: BTreeMap<String, i32>,
+        target: Option<u8>,
+    }
+
+    fn unit() -> Unit {
+        Unit {
+            name: "scout".into(),
+            health: 40,
+            id: u64::MAX,
+            position: (1.5, -2.0),
+            modes: vec![
+                Mode::Idle,
+                Mode::Walk(0.5, true),
+                Mode::Move { x: 1.0, y: 2.0 },
+            ],
+            tags: BTreeMap::from([("fast".into(), 1), ("quiet".into(), -1)]),
+            target: Some(3),
+        }
+    }
+
+    #[test]
+    fn other_engines_round_trip_every_kind() {
+        let mut registry = TypeRegistry::default();
+        registry.register::<Unit>();
+        let external = ConversionSettings::DEFAULT.with_enums(EnumRepresentation::External);
+        for settings in [&ConversionSettings::DEFAULT, &external] {
+            let value = reflect_to_engine_value(&unit(), settings, &mut PlainEngine).unwrap();
+            let back = engine_value_to_typed_reflect(
+                value,
+                TypeId::of::<Unit>(),
+                &registry,
+                settings,
+                &mut PlainEngine,
+            )
+            .unwrap();
+            assert_eq!(Unit::from_reflect(back.as_ref()), Some(unit()));
+        }
+    }
+
+    #[test]
+    fn other_engines_get_the_shapes_boa_does() {
+        let string = |s: &str| Plain::String(s.to_owned());
+        let tagged = reflect_to_engine_value(
+            &Mode::Move { x: 1.0, y: 2.0 },
+            &ConversionSettings::DEFAULT,
+            &mut PlainEngine,
+        )
+        .unwrap();
+        assert_eq!(
+            tagged,
+            Plain::Object(vec![
+                ("x".into(), Plain::Number(1.0)),
+                ("y".into(), Plain::Number(2.0)),
+                ("__variant".into(), string("Move")),
+            ])
+        );
+        let external = ConversionSettings::DEFAULT.with_enums(EnumRepresentation::External);
+        let walk =
+            reflect_to_engine_value(&Mode::Walk(0.5, true), &external, &mut PlainEngine).unwrap();
+        assert_eq!(
+            walk,
+            Plain::Object(vec![(
+                "Walk".into(),
+                Plain::Array(vec![Plain::Number(0.5), Plain::Boolean(true)])
+            )])
+        );
+    }
+}
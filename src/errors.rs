@@ -0,0 +1,974 @@
+use std::fmt;
+
+#[cfg(feature = "bevy")]
+use bevy::prelude::*;
+#[cfg(feature = "bevy")]
+use boa_engine::{js_str, Context, JsString};
+use boa_engine::{JsError, JsNativeError, JsValue};
+use serde::Deserialize;
+
+#[cfg(feature = "bevy")]
+use crate::script::ScriptAsset;
+
+/// Sent whenever a script throws, whether evaluating, running a hook or handling a bus event, so
+/// games and tooling can react, e.g. by showing a toast or disabling a mod.
+#[cfg(feature = "bevy")]
+#[derive(Event, Debug, Clone)]
+pub struct ScriptError {
+    /// The script that threw, if it is known.
+    pub script: Option<AssetId<ScriptAsset>>,
+    /// The entity the script was running for, if any.
+    pub entity: Option<Entity>,
+    pub message: String,
+    /// The `stack` of the thrown error, if it has one. Errors thrown by bindings, such as failed
+    /// conversions, are given the script call stack they were thrown from.
+    pub stack: Option<String>,
+    pub location: Option<ScriptErrorLocation>,
+}
+
+#[cfg(feature = "bevy")]
+impl ScriptError {
+    pub(crate) fn new(
+        script: Option<AssetId<ScriptAsset>>,
+        entity: Option<Entity>,
+        err: &JsError,
+        location: Option<ScriptErrorLocation>,
+        ctx: &mut Context,
+    ) -> Self {
+        let stack = err
+            .as_opaque()
+            .and_then(|value| value.as_object())
+            .and_then(|obj| obj.get(js_str!("stack"), ctx).ok())
+            .and_then(|stack| Some(stack.as_string()?.to_std_string_escaped()));
+        Self {
+            script,
+            entity,
+            message: err.to_string(),
+            stack,
+            location,
+        }
+    }
+}
+
+#[cfg(feature = "bevy")]
+impl fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.location, self.entity) {
+            (Some(location), Some(entity)) => {
+                write!(f, "{location} for {entity}: {}", self.message)
+            }
+            (Some(location), None) => write!(f, "{location}: {}", self.message),
+            (None, Some(entity)) => write!(f, "for {entity}: {}", self.message),
+            (None, None) => write!(f, "{}", self.message),
+        }
+    }
+}
+
+/// Lets a `ScriptError` be returned from a system with `?`, e.g. into an `anyhow::Error`. Bevy 0.14
+/// has no fallible systems, so such systems are piped into a handler with
+/// `update.map(bevy_utils::error)`.
+#[cfg(feature = "bevy")]
+impl std::error::Error for ScriptError {}
+
+/// For errors thrown outside a script's hooks, without the script, entity or `stack` the runtime
+/// attaches to the errors it sends.
+#[cfg(feature = "bevy")]
+impl From<JsError> for ScriptError {
+    fn from(err: JsError) -> Self {
+        Self {
+            script: None,
+            entity: None,
+            message: err.to_string(),
+            stack: None,
+            location: None,
+        }
+    }
+}
+
+/// The message is the one the error throws to scripts with, ending in its code.
+#[cfg(feature = "bevy")]
+impl From<ConversionError> for ScriptError {
+    fn from(err: ConversionError) -> Self {
+        JsError::from(err).into()
+    }
+}
+
+#[cfg(feature = "bevy")]
+impl From<ConversionErrors> for ScriptError {
+    fn from(errors: ConversionErrors) -> Self {
+        JsError::from(errors).into()
+    }
+}
+
+/// Adds context to script and conversion errors, turning them into an `anyhow::Error`. They hold
+/// engine values that aren't `Send`, so anyhow's own `Context` can't take them directly.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_boa_reflect::{js_value_to_typed, ScriptResultExt, ScriptRuntime};
+/// # use boa_engine::Source;
+/// # #[derive(Reflect)]
+/// # struct Settings { volume: f32 }
+/// fn load_settings(world: &mut World) -> anyhow::Result<()> {
+///     # let registry = world.resource::<AppTypeRegistry>().clone();
+///     # let registry = registry.read();
+///     # let mut runtime = world.non_send_resource_mut::<ScriptRuntime>();
+///     # let ctx = runtime.context();
+///     # let value = ctx.eval(Source::from_bytes("settings")).script_context("")?;
+///     let settings: Settings =
+///         js_value_to_typed(value, &registry, ctx).script_context("reading settings")?;
+///     # let _ = settings;
+///     Ok(())
+/// }
+///
+/// # let mut app = App::new();
+/// app.add_systems(Update, load_settings.map(bevy_utils::error));
+/// ```
+#[cfg(feature = "bevy")]
+pub trait ScriptResultExt<T> {
+    fn script_context<C>(self, context: C) -> anyhow::Result<T>
+    where
+        C: fmt::Display + Send + Sync + 'static;
+
+    /// Like [`script_context`](Self::script_context), building the context only on error.
+    fn with_script_context<C, F>(self, f: F) -> anyhow::Result<T>
+    where
+        C: fmt::Display + Send + Sync + 'static,
+        F: FnOnce() -> C;
+}
+
+#[cfg(feature = "bevy")]
+impl<T, E: Into<ScriptError>> ScriptResultExt<T> for Result<T, E> {
+    fn script_context<C>(self, context: C) -> anyhow::Result<T>
+    where
+        C: fmt::Display + Send + Sync + 'static,
+    {
+        self.map_err(|err| anyhow::Error::new(err.into()).context(context))
+    }
+
+    fn with_script_context<C, F>(self, f: F) -> anyhow::Result<T>
+    where
+        C: fmt::Display + Send + Sync + 'static,
+        F: FnOnce() -> C,
+    {
+        self.map_err(|err| anyhow::Error::new(err.into()).context(f()))
+    }
+}
+
+/// Where in a script an error happened. Boa only reports positions for syntax errors, so runtime
+/// errors are located by script alone.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScriptErrorLocation {
+    /// The asset path of the script, or the original source file if the script has a source map.
+    pub path: String,
+    pub line: Option<u32>,
+    pub column: Option<u32>,
+}
+
+impl ScriptErrorLocation {
+    /// Locate an error in a script, mapping its position back to the original source if the
+    /// script has a source map.
+    pub fn new(path: &str, source_map: Option<&SourceMap>, err: &JsError) -> Self {
+        let position = error_position(err);
+        let mapped = position.and_then(|(line, column)| source_map?.lookup(line, column));
+        match (mapped, position) {
+            (Some((source, line, column)), _) => Self {
+                path: source.to_owned(),
+                line: Some(line),
+                column: Some(column),
+            },
+            (None, Some((line, column))) => Self {
+                path: path.to_owned(),
+                line: Some(line),
+                column: Some(column),
+            },
+            (None, None) => Self {
+                path: path.to_owned(),
+                line: None,
+                column: None,
+            },
+        }
+    }
+}
+
+impl fmt::Display for ScriptErrorLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.path)?;
+        if let Some(line) = self.line {
+            write!(f, ":{line}")?;
+            if let Some(column) = self.column {
+                write!(f, ":{column}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The kind of a JS value, for reporting what a conversion got instead of what it expected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JsValueKind {
+    Undefined,
+    Null,
+    Boolean,
+    Number,
+    String,
+    BigInt,
+    Symbol,
+    Array,
+    Function,
+    Object,
+}
+
+impl JsValueKind {
+    pub fn of(value: &JsValue) -> Self {
+        match value {
+            JsValue::Undefined => Self::Undefined,
+            JsValue::Null => Self::Null,
+            JsValue::Boolean(_) => Self::Boolean,
+            JsValue::Integer(_) | JsValue::Rational(_) => Self::Number,
+            JsValue::String(_) => Self::String,
+            JsValue::BigInt(_) => Self::BigInt,
+            JsValue::Symbol(_) => Self::Symbol,
+            JsValue::Object(obj) if obj.is_array() => Self::Array,
+            JsValue::Object(obj) if obj.is_callable() => Self::Function,
+            JsValue::Object(_) => Self::Object,
+        }
+    }
+
+    /// Whether the value is an object of any kind.
+    pub(crate) fn is_object(self) -> bool {
+        matches!(self, Self::Array | Self::Function | Self::Object)
+    }
+}
+
+impl fmt::Display for JsValueKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Undefined => "undefined",
+            Self::Null => "null",
+            Self::Boolean => "a boolean",
+            Self::Number => "a number",
+            Self::String => "a string",
+            Self::BigInt => "a BigInt",
+            Self::Symbol => "a symbol",
+            Self::Array => "an array",
+            Self::Function => "a function",
+            Self::Object => "an object",
+        })
+    }
+}
+
+/// One step into a converted value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PathSegment {
+    Field(String),
+    Index(usize),
+    /// A map entry, by its key as scripts would display it.
+    Key(String),
+}
+
+/// Where in a value a conversion failed, starting from the short path of the type being
+/// converted, e.g. `Player.inventory[3].name`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FieldPath {
+    pub root: Option<String>,
+    pub segments: Vec<PathSegment>,
+}
+
+impl FieldPath {
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none() && self.segments.is_empty()
+    }
+}
+
+impl fmt::Display for FieldPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(root) = &self.root {
+            f.write_str(root)?;
+        }
+        for (idx, segment) in self.segments.iter().enumerate() {
+            match segment {
+                PathSegment::Field(name) if idx == 0 && self.root.is_none() => f.write_str(name)?,
+                PathSegment::Field(name) => write!(f, ".{name}")?,
+                PathSegment::Index(idx) => write!(f, "[{idx}]")?,
+                PathSegment::Key(key) => write!(f, "[{key}]")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Why a value couldn't be converted between JS and Rust, with the path to the offending field.
+/// Converts into a `TypeError`, or a `RangeError` for numbers and lengths out of range, so it can
+/// be thrown back to scripts.
+#[derive(Debug, Clone)]
+pub enum ConversionError {
+    /// The value was the wrong kind for the type, e.g. a string where a number was expected.
+    TypeMismatch {
+        type_path: String,
+        path: FieldPath,
+        expected: &'static str,
+        found: JsValueKind,
+    },
+    /// A number didn't fit in the integer type.
+    OutOfRange {
+        type_path: String,
+        path: FieldPath,
+        value: String,
+    },
+    /// An array had the wrong number of elements for a fixed size array.
+    WrongLength {
+        type_path: String,
+        path: FieldPath,
+        expected: usize,
+        found: usize,
+    },
+    UnknownVariant {
+        type_path: String,
+        path: FieldPath,
+        variant: String,
+    },
+    /// A variant with fields was given by name alone.
+    MissingVariantFields {
+        type_path: String,
+        path: FieldPath,
+        variant: String,
+    },
+    /// A field had no name to convert it under.
+    UnnamedField {
+        type_path: String,
+        path: FieldPath,
+        index: usize,
+    },
+    /// The type isn't in the type registry.
+    Unregistered { type_path: String, path: FieldPath },
+    /// The type is a value type conversions don't know about.
+    Unsupported { type_path: String, path: FieldPath },
+    /// The converted value couldn't be turned into the concrete type.
+    FromReflect { type_path: String, path: FieldPath },
+    /// The engine threw while reading the value, e.g. from a getter.
+    Engine { path: FieldPath, error: JsError },
+    /// A property the type has no field for, in a strict conversion.
+    UnknownField {
+        type_path: String,
+        path: FieldPath,
+        name: String,
+    },
+    /// The value was nested deeper than the conversion's settings allow.
+    TooDeep {
+        type_path: String,
+        path: FieldPath,
+        max_depth: usize,
+    },
+    /// A [`ConversionHook`](crate::ConversionHook) refused the value.
+    Vetoed {
+        type_path: String,
+        path: FieldPath,
+        reason: String,
+    },
+}
+
+/// The class of a [`ConversionError`], with a stable code that is part of the message of errors
+/// thrown to scripts, so tests and tooling can check for a failure without matching on its text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ConversionErrorKind {
+    TypeMismatch,
+    OutOfRange,
+    WrongLength,
+    UnknownVariant,
+    MissingVariantFields,
+    UnnamedField,
+    Unregistered,
+    Unsupported,
+    FromReflect,
+    Engine,
+    UnknownField,
+    TooDeep,
+    Vetoed,
+}
+
+impl ConversionErrorKind {
+    const ALL: [Self; 13] = [
+        Self::TypeMismatch,
+        Self::OutOfRange,
+        Self::WrongLength,
+        Self::UnknownVariant,
+        Self::MissingVariantFields,
+        Self::UnnamedField,
+        Self::Unregistered,
+        Self::Unsupported,
+        Self::FromReflect,
+        Self::Engine,
+        Self::UnknownField,
+        Self::TooDeep,
+        Self::Vetoed,
+    ];
+
+    pub fn code(self) -> &'static str {
+        match self {
+            Self::TypeMismatch => "E_TYPE_MISMATCH",
+            Self::OutOfRange => "E_NUM_OVERFLOW",
+            Self::WrongLength => "E_ARRAY_LENGTH",
+            Self::UnknownVariant => "E_ENUM_VARIANT_UNKNOWN",
+            Self::MissingVariantFields => "E_ENUM_VARIANT_FIELDS",
+            Self::UnnamedField => "E_FIELD_UNNAMED",
+            Self::Unregistered => "E_TYPE_UNREGISTERED",
+            Self::Unsupported => "E_TYPE_UNSUPPORTED",
+            Self::FromReflect => "E_FROM_REFLECT",
+            Self::Engine => "E_ENGINE",
+            Self::UnknownField => "E_FIELD_UNKNOWN",
+            Self::TooDeep => "E_DEPTH_LIMIT",
+            Self::Vetoed => "E_VETOED",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.code() == code)
+    }
+
+    /// The kind of conversion error a script error was thrown for, read from the code at the end
+    /// of its message, e.g. `Health.current: expected a number for f32, got a string
+    /// (E_TYPE_MISMATCH)`. For errors listing several failures, this is the first one's kind.
+    pub fn from_message(message: &str) -> Option<Self> {
+        message.lines().find_map(|line| {
+            let (_, code) = line.trim_end().strip_suffix(')')?.rsplit_once('(')?;
+            Self::from_code(code)
+        })
+    }
+
+    /// The kind of conversion error a [`JsError`] was thrown for, if it was thrown for one.
+    pub fn from_js_error(err: &JsError) -> Option<Self> {
+        Self::from_message(&err.to_string())
+    }
+}
+
+impl fmt::Display for ConversionErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+impl ConversionError {
+    pub(crate) fn type_mismatch(expected: &'static str, type_path: &str, value: &JsValue) -> Self {
+        Self::mismatch(expected, type_path, JsValueKind::of(value))
+    }
+
+    pub(crate) fn mismatch(expected: &'static str, type_path: &str, found: JsValueKind) -> Self {
+        Self::TypeMismatch {
+            type_path: type_path.to_owned(),
+            path: FieldPath::default(),
+            expected,
+            found,
+        }
+    }
+
+    pub fn kind(&self) -> ConversionErrorKind {
+        match self {
+            Self::TypeMismatch { .. } => ConversionErrorKind::TypeMismatch,
+            Self::OutOfRange { .. } => ConversionErrorKind::OutOfRange,
+            Self::WrongLength { .. } => ConversionErrorKind::WrongLength,
+            Self::UnknownVariant { .. } => ConversionErrorKind::UnknownVariant,
+            Self::MissingVariantFields { .. } => ConversionErrorKind::MissingVariantFields,
+            Self::UnnamedField { .. } => ConversionErrorKind::UnnamedField,
+            Self::Unregistered { .. } => ConversionErrorKind::Unregistered,
+            Self::Unsupported { .. } => ConversionErrorKind::Unsupported,
+            Self::FromReflect { .. } => ConversionErrorKind::FromReflect,
+            Self::Engine { .. } => ConversionErrorKind::Engine,
+            Self::UnknownField { .. } => ConversionErrorKind::UnknownField,
+            Self::TooDeep { .. } => ConversionErrorKind::TooDeep,
+            Self::Vetoed { .. } => ConversionErrorKind::Vetoed,
+        }
+    }
+
+    /// The stable code of the error's kind, e.g. `E_NUM_OVERFLOW`.
+    pub fn code(&self) -> &'static str {
+        self.kind().code()
+    }
+
+    /// Where in the converted value the conversion failed.
+    pub fn path(&self) -> &FieldPath {
+        match self {
+            Self::TypeMismatch { path, .. }
+            | Self::OutOfRange { path, .. }
+            | Self::WrongLength { path, .. }
+            | Self::UnknownVariant { path, .. }
+            | Self::MissingVariantFields { path, .. }
+            | Self::UnnamedField { path, .. }
+            | Self::Unregistered { path, .. }
+            | Self::Unsupported { path, .. }
+            | Self::FromReflect { path, .. }
+            | Self::Engine { path, .. }
+            | Self::UnknownField { path, .. }
+            | Self::TooDeep { path, .. }
+            | Self::Vetoed { path, .. } => path,
+        }
+    }
+
+    fn path_mut(&mut self) -> &mut FieldPath {
+        match self {
+            Self::TypeMismatch { path, .. }
+            | Self::OutOfRange { path, .. }
+            | Self::WrongLength { path, .. }
+            | Self::UnknownVariant { path, .. }
+            | Self::MissingVariantFields { path, .. }
+            | Self::UnnamedField { path, .. }
+            | Self::Unregistered { path, .. }
+            | Self::Unsupported { path, .. }
+            | Self::FromReflect { path, .. }
+            | Self::Engine { path, .. }
+            | Self::UnknownField { path, .. }
+            | Self::TooDeep { path, .. }
+            | Self::Vetoed { path, .. } => path,
+        }
+    }
+
+    /// Record that the error happened inside a field, as it propagates out of the field.
+    pub(crate) fn at(mut self, segment: PathSegment) -> Self {
+        self.path_mut().segments.insert(0, segment);
+        self
+    }
+
+    /// Place the error at a path, in front of any path it already has within the value.
+    pub(crate) fn with_path(mut self, mut path: FieldPath) -> Self {
+        let own = self.path_mut();
+        path.segments.append(&mut own.segments);
+        *own = path;
+        self
+    }
+
+    fn message(&self) -> String {
+        match self {
+            Self::TypeMismatch {
+                type_path,
+                expected,
+                found,
+                ..
+            } => format!("expected {expected} for {type_path}, got {found}"),
+            Self::OutOfRange {
+                type_path, value, ..
+            } => format!("{value} is out of range for {type_path}"),
+            Self::WrongLength {
+                type_path,
+                expected,
+                found,
+                ..
+            } => format!("expected {expected} elements for {type_path}, got {found}"),
+            Self::UnknownVariant {
+                type_path, variant, ..
+            } => format!("unknown variant {variant} for {type_path}"),
+            Self::MissingVariantFields {
+                type_path, variant, ..
+            } => format!("variant {variant} of {type_path} has fields"),
+            Self::UnnamedField {
+                type_path, index, ..
+            } => format!("field {index} of {type_path} has no name"),
+            Self::Unregistered { type_path, .. } => format!("{type_path} is not registered"),
+            Self::Unsupported { type_path, .. } => {
+                format!("conversion to {type_path} is not supported")
+            }
+            Self::FromReflect { type_path, .. } => {
+                format!("could not convert value to {type_path}")
+            }
+            Self::Engine { error, .. } => error.to_string(),
+            Self::UnknownField {
+                type_path, name, ..
+            } => format!("{type_path} has no field {name}"),
+            Self::TooDeep {
+                type_path,
+                max_depth,
+                ..
+            } => format!("{type_path} is nested deeper than {max_depth} levels"),
+            Self::Vetoed {
+                type_path, reason, ..
+            } => format!("{type_path} was refused: {reason}"),
+        }
+    }
+}
+
+impl fmt::Display for ConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = self.message();
+        if self.path().is_empty() {
+            let mut chars = message.chars();
+            if let Some(first) = chars.next() {
+                write!(f, "{}{}", first.to_uppercase(), chars.as_str())?;
+            }
+            return Ok(());
+        }
+        write!(f, "{}: {message}", self.path())
+    }
+}
+
+impl std::error::Error for ConversionError {}
+
+impl From<JsError> for ConversionError {
+    fn from(error: JsError) -> Self {
+        Self::Engine {
+            path: FieldPath::default(),
+            error,
+        }
+    }
+}
+
+/// The message is followed by the error's code, which
+/// [`ConversionErrorKind::from_js_error`] reads back.
+impl From<ConversionError> for JsError {
+    fn from(err: ConversionError) -> Self {
+        let message = format!("{err} ({})", err.code());
+        match err {
+            ConversionError::Engine { path, error } if path.is_empty() => error,
+            ConversionError::OutOfRange { .. }
+            | ConversionError::WrongLength { .. }
+            | ConversionError::TooDeep { .. } => {
+                JsNativeError::range().with_message(message).into()
+            }
+            _ => JsNativeError::typ().with_message(message).into(),
+        }
+    }
+}
+
+/// A problem [`can_convert`](crate::can_convert) found with a value.
+#[derive(Debug, Clone)]
+pub enum ConversionIssue {
+    /// The value can't be converted.
+    Error(ConversionError),
+    /// A property the type has no field for. Conversion ignores these, but they are usually a
+    /// misspelt field name.
+    UnknownField { path: FieldPath, name: String },
+}
+
+impl ConversionIssue {
+    /// Where in the value the issue is. For unknown fields, this is the object holding them.
+    pub fn path(&self) -> &FieldPath {
+        match self {
+            Self::Error(err) => err.path(),
+            Self::UnknownField { path, .. } => path,
+        }
+    }
+
+    /// Whether the value would fail to convert, rather than convert with a warning.
+    pub fn is_error(&self) -> bool {
+        matches!(self, Self::Error(_))
+    }
+
+    /// The error's code, or `E_FIELD_UNKNOWN` for unknown fields.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Error(err) => err.code(),
+            Self::UnknownField { .. } => "E_FIELD_UNKNOWN",
+        }
+    }
+}
+
+impl fmt::Display for ConversionIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Error(err) => write!(f, "{err}"),
+            Self::UnknownField { path, name } if path.is_empty() => {
+                write!(f, "Unknown field {name}")
+            }
+            Self::UnknownField { path, name } => write!(f, "{path}: unknown field {name}"),
+        }
+    }
+}
+
+/// Every problem found converting a value, from the accumulating conversions like
+/// [`js_value_to_typed_reflect_all`](crate::js_value_to_typed_reflect_all), so a large value can
+/// be fixed in one pass instead of one error per run.
+#[derive(Debug, Clone, Default)]
+pub struct ConversionErrors(Vec<ConversionError>);
+
+impl ConversionErrors {
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, ConversionError> {
+        self.0.iter()
+    }
+
+    pub fn into_vec(self) -> Vec<ConversionError> {
+        self.0
+    }
+}
+
+impl fmt::Display for ConversionErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0.as_slice() {
+            [err] => write!(f, "{err}"),
+            errors => {
+                write!(f, "{} conversion errors:", errors.len())?;
+                for err in errors {
+                    write!(f, "\n  {err}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConversionErrors {}
+
+impl From<ConversionError> for ConversionErrors {
+    fn from(err: ConversionError) -> Self {
+        Self(vec![err])
+    }
+}
+
+impl From<JsError> for ConversionErrors {
+    fn from(error: JsError) -> Self {
+        ConversionError::from(error).into()
+    }
+}
+
+impl FromIterator<ConversionError> for ConversionErrors {
+    fn from_iter<I: IntoIterator<Item = ConversionError>>(iter: I) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl IntoIterator for ConversionErrors {
+    type Item = ConversionError;
+    type IntoIter = std::vec::IntoIter<ConversionError>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a ConversionErrors {
+    type Item = &'a ConversionError;
+    type IntoIter = std::slice::Iter<'a, ConversionError>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+/// A single error throws as it would on its own, and several throw as one `TypeError` listing
+/// them all.
+impl From<ConversionErrors> for JsError {
+    fn from(errors: ConversionErrors) -> Self {
+        let mut errors = errors.0;
+        if errors.len() == 1 {
+            return errors.remove(0).into();
+        }
+        let mut message = format!("{} conversion errors:", errors.len());
+        for err in &errors {
+            message.push_str(&format!("\n  {err} ({})", err.code()));
+        }
+        JsNativeError::typ().with_message(message).into()
+    }
+}
+
+/// Give an error thrown from Rust the script's current call stack as its `stack`, so the
+/// [`ScriptError`] it ends up in has the script's stack alongside the message, which for a failed
+/// conversion holds the type and field path. Boa only gives errors a stack when scripts create
+/// them. Errors thrown outside of any script are returned as they are.
+#[cfg(feature = "bevy")]
+pub(crate) fn attach_js_stack(err: JsError, ctx: &mut Context) -> JsError {
+    if err.as_native().is_none() {
+        return err;
+    }
+    let mut frames = ctx
+        .stack_trace()
+        .map(|frame| frame.code_block().name().to_std_string_escaped())
+        .peekable();
+    if frames.peek().is_none() {
+        return err;
+    }
+    let mut stack = err.to_string();
+    for name in frames {
+        match name.as_str() {
+            "" => stack.push_str("\n    at <anonymous>"),
+            name => stack.push_str(&format!("\n    at {name}")),
+        }
+    }
+    let error = err.to_opaque(ctx);
+    if let Some(obj) = error.as_object() {
+        // Error objects are extensible, so this only fails for errors that aren't.
+        let _ = obj.set(js_str!("stack"), JsString::from(stack), false, ctx);
+    }
+    JsError::from_opaque(error)
+}
+
+/// Read the 1-based line and column Boa's parser appends to syntax errors, as
+/// `... at line 3, col 14`.
+fn error_position(err: &JsError) -> Option<(u32, u32)> {
+    let message = err.to_string();
+    let (_, position) = message.rsplit_once(" at line ")?;
+    let (line, column) = position.split_once(", col ")?;
+    let column = column
+        .split(|c: char| !c.is_ascii_digit())
+        .next()
+        .unwrap_or_default();
+    Some((line.trim().parse().ok()?, column.parse().ok()?))
+}
+
+/// A decoded [source map](https://sourcemaps.info/spec.html), mapping positions in a transpiled
+/// or bundled script back to its original sources.
+#[derive(Debug, Clone, Default)]
+pub struct SourceMap {
+    sources: Vec<String>,
+    /// For each generated line, the segments sorted by generated column.
+    lines: Vec<Vec<Segment>>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Segment {
+    column: u32,
+    source: u32,
+    line: u32,
+    source_column: u32,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawSourceMap {
+    #[serde(default)]
+    source_root: Option<String>,
+    sources: Vec<Option<String>>,
+    mappings: String,
+}
+
+impl SourceMap {
+    /// Parse a source map from its JSON. Returns `None` if it is invalid.
+    pub fn parse(json: &[u8]) -> Option<Self> {
+        let raw = serde_json::from_slice::<RawSourceMap>(json).ok()?;
+        let root = raw.source_root.unwrap_or_default();
+        let sources = raw
+            .sources
+            .into_iter()
+            .map(|source| {
+                let source = source.unwrap_or_default();
+                if root.is_empty() {
+                    source
+                } else {
+                    format!("{}/{source}", root.trim_end_matches('/'))
+                }
+            })
+            .collect();
+
+        let mut lines = Vec::new();
+        let (mut source, mut line, mut source_column) = (0i64, 0i64, 0i64);
+        for generated in raw.mappings.split(';') {
+            let mut segments = Vec::new();
+            let mut column = 0i64;
+            for segment in generated.split(',').filter(|segment| !segment.is_empty()) {
+                let fields = decode_vlq(segment)?;
+                column += *fields.first()?;
+                if fields.len() < 4 {
+                    continue;
+                }
+                source += fields[1];
+                line += fields[2];
+                source_column += fields[3];
+                segments.push(Segment {
+                    column: u32::try_from(column).ok()?,
+                    source: u32::try_from(source).ok()?,
+                    line: u32::try_from(line).ok()?,
+                    source_column: u32::try_from(source_column).ok()?,
+                });
+            }
+            segments.sort_by_key(|segment| segment.column);
+            lines.push(segments);
+        }
+        Some(Self { sources, lines })
+    }
+
+    /// Map a 1-based line and column in the generated script to the original source, returning
+    /// its path and 1-based line and column.
+    pub fn lookup(&self, line: u32, column: u32) -> Option<(&str, u32, u32)> {
+        let segments = self.lines.get(line.checked_sub(1)? as usize)?;
+        let column = column.saturating_sub(1);
+        let idx = segments.partition_point(|segment| segment.column <= column);
+        let segment = segments.get(idx.checked_sub(1)?)?;
+        let source = self.sources.get(segment.source as usize)?;
+        Some((source, segment.line + 1, segment.source_column + 1))
+    }
+}
+
+/// Find the source map a script points to with a `//# sourceMappingURL=` comment. Inline
+/// `data:` URLs are decoded, and other URLs are returned to be loaded relative to the script.
+#[cfg(feature = "bevy")]
+pub(crate) fn source_mapping_url(source: &str) -> Option<SourceMappingUrl> {
+    let url = source
+        .lines()
+        .rev()
+        .find_map(|line| {
+            line.trim()
+                .strip_prefix("//# sourceMappingURL=")
+                .or_else(|| line.trim().strip_prefix("//@ sourceMappingURL="))
+        })?
+        .trim();
+    match url.strip_prefix("data:") {
+        Some(data) => {
+            let (_, encoded) = data.split_once(";base64,")?;
+            Some(SourceMappingUrl::Inline(decode_base64(encoded)?))
+        }
+        None => Some(SourceMappingUrl::Path(url.to_owned())),
+    }
+}
+
+#[cfg(feature = "bevy")]
+pub(crate) enum SourceMappingUrl {
+    Inline(Vec<u8>),
+    Path(String),
+}
+
+fn base64_value(c: u8) -> Option<u8> {
+    match c {
+        b'A'..=b'Z' => Some(c - b'A'),
+        b'a'..=b'z' => Some(c - b'a' + 26),
+        b'0'..=b'9' => Some(c - b'0' + 52),
+        b'+' => Some(62),
+        b'/' => Some(63),
+        _ => None,
+    }
+}
+
+#[cfg(feature = "bevy")]
+fn decode_base64(encoded: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(encoded.len() * 3 / 4);
+    let (mut buffer, mut bits) = (0u32, 0u32);
+    for c in encoded
+        .bytes()
+        .filter(|c| *c != b'=' && !c.is_ascii_whitespace())
+    {
+        // Only the bits not yet written out are kept, which never exceed 14.
+        buffer = ((buffer << 6) | u32::from(base64_value(c)?)) & 0x3fff;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+        }
+    }
+    Some(bytes)
+}
+
+/// Decode the base64 VLQ fields of a mapping segment.
+fn decode_vlq(segment: &str) -> Option<Vec<i64>> {
+    let mut fields = Vec::new();
+    let (mut value, mut shift) = (0i64, 0u32);
+    for c in segment.bytes() {
+        let digit = i64::from(base64_value(c)?);
+        value += (digit & 0b11111) << shift;
+        if digit & 0b100000 != 0 {
+            shift += 5;
+            if shift > 60 {
+                return None;
+            }
+            continue;
+        }
+        let negative = value & 1 == 1;
+        value >>= 1;
+        fields.push(if negative { -value } else { value });
+        value = 0;
+        shift = 0;
+    }
+    Some(fields)
+}
@@ -0,0 +1,250 @@
+use std::any::TypeId;
+use std::cell::{Cell, RefCell};
+
+use bevy::ecs::reflect::{AppTypeRegistry, ReflectComponent};
+use bevy::ecs::system::EntityCommands;
+use bevy::prelude::*;
+use bevy::reflect::{FromReflect, TypePath};
+use bevy::utils::HashMap;
+use boa_engine::{js_string, Context, JsObject, JsResult, JsString, JsValue, Source};
+
+use crate::access::provide_world;
+use crate::bindings::commands::{
+    commands_binding, insert_components, reflect_components, ScriptCommandQueue, COMMANDS_BINDING,
+};
+use crate::bindings::reflect::{reflect_binding, REFLECT_BINDING};
+use crate::bindings::world::{world_binding, EntityNameIndex, WORLD_BINDING};
+use crate::errors::ConversionError;
+use crate::from::{js_value_to_typed, js_value_to_typed_reflect};
+use crate::memo::{component_to_js_value, reflect_component};
+use crate::plugin::with_runtime;
+
+/// Conversions of an entity's components, looking up the component's type in the world's type
+/// registry and reading or writing it through its `ReflectComponent` in one call:
+///
+/// ```no_run
+/// # use std::any::TypeId;
+/// # use bevy::prelude::*;
+/// # use bevy_boa_reflect::WorldJsExt;
+/// # #[derive(Component, Reflect)]
+/// # #[reflect(Component)]
+/// # struct Health { current: f32 }
+/// # let mut world = World::new();
+/// # let player = world.spawn(Health { current: 1.0 }).id();
+/// # let ctx = &mut boa_engine::Context::default();
+/// if let Some(value) = world.component_to_js(player, TypeId::of::<Health>(), ctx)? {
+///     world.apply_js_to_component(player, TypeId::of::<Health>(), value, ctx)?;
+/// }
+/// # Ok::<(), bevy_boa_reflect::ConversionError>(())
+/// ```
+pub trait WorldJsExt {
+    /// Convert a component of an entity to JS, as [`component_to_js_value`] does. Returns `None`
+    /// if the entity doesn't have the component.
+    fn component_to_js(
+        &self,
+        entity: Entity,
+        type_id: TypeId,
+        ctx: &mut Context,
+    ) -> Result<Option<JsValue>, ConversionError>;
+
+    /// Convert a JS value into a component and apply it to an entity, inserting the component if
+    /// the entity doesn't have it yet. Returns `false` if the entity doesn't exist.
+    fn apply_js_to_component(
+        &mut self,
+        entity: Entity,
+        type_id: TypeId,
+        value: JsValue,
+        ctx: &mut Context,
+    ) -> Result<bool, ConversionError>;
+}
+
+impl WorldJsExt for World {
+    fn component_to_js(
+        &self,
+        entity: Entity,
+        type_id: TypeId,
+        ctx: &mut Context,
+    ) -> Result<Option<JsValue>, ConversionError> {
+        component_to_js_value(self, entity, type_id, ctx)
+    }
+
+    fn apply_js_to_component(
+        &mut self,
+        entity: Entity,
+        type_id: TypeId,
+        value: JsValue,
+        ctx: &mut Context,
+    ) -> Result<bool, ConversionError> {
+        let registry = self.resource::<AppTypeRegistry>().clone();
+        let registry = registry.read();
+        let reflect_component = reflect_component(&registry, type_id)?;
+        let value = js_value_to_typed_reflect(value, type_id, &registry, ctx)?;
+        let Some(mut entity) = self.get_entity_mut(entity) else {
+            return Ok(false);
+        };
+        reflect_component.apply_or_insert(&mut entity, value.as_ref(), &registry);
+        Ok(true)
+    }
+}
+
+/// Evaluation of scripts against a world from a plain [`Context`], without the plugin or a
+/// [`ScriptRuntime`](crate::ScriptRuntime), for integration tests and tools:
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_boa_reflect::ContextWorldExt;
+/// # use boa_engine::Context;
+/// let mut world = World::new();
+/// let count: f64 = Context::default().eval_with_world(&mut world, "reflect.types().length")?;
+/// # Ok::<(), boa_engine::JsError>(())
+/// ```
+pub trait ContextWorldExt {
+    /// Evaluate `src` with the `reflect`, `commands` and `world` bindings installed, converting
+    /// the result into `T`. Commands the script queued are applied before returning, and the
+    /// context's own globals of the same names are put back afterwards.
+    fn eval_with_world<T: FromReflect + TypePath>(
+        &mut self,
+        world: &mut World,
+        src: &str,
+    ) -> JsResult<T>;
+}
+
+impl ContextWorldExt for Context {
+    fn eval_with_world<T: FromReflect + TypePath>(
+        &mut self,
+        world: &mut World,
+        src: &str,
+    ) -> JsResult<T> {
+        let registry = world
+            .get_resource_or_insert_with(AppTypeRegistry::default)
+            .clone();
+        let names = world
+            .get_resource_or_insert_with(EntityNameIndex::default)
+            .clone();
+        // Queued separately from the world's own queue, so only this script's commands are
+        // applied here.
+        let commands = ScriptCommandQueue::default();
+        let bindings = [
+            (REFLECT_BINDING, reflect_binding(&registry, self)?),
+            (
+                COMMANDS_BINDING,
+                commands_binding(&commands, &registry, self)?,
+            ),
+            (WORLD_BINDING, world_binding(&names, self)?),
+        ];
+
+        let global = self.global_object();
+        let mut previous = Vec::new();
+        for (name, binding) in bindings {
+            let key = js_string!(name);
+            let value = if global.has_own_property(key.clone(), self)? {
+                Some(global.get(key.clone(), self)?)
+            } else {
+                None
+            };
+            previous.push((key.clone(), value));
+            global.set(key, binding, true, self)?;
+        }
+
+        let result = provide_world(world, || self.eval(Source::from_bytes(src)));
+        let restored = restore_globals(&global, previous, self);
+        let value = result?;
+        restored?;
+        commands.apply(world);
+        let converted = js_value_to_typed(value, &registry.read(), self)?;
+        Ok(converted)
+    }
+}
+
+/// Put back the globals bindings replaced, deleting those that weren't there before.
+fn restore_globals(
+    global: &JsObject,
+    previous: Vec<(JsString, Option<JsValue>)>,
+    ctx: &mut Context,
+) -> JsResult<()> {
+    for (key, value) in previous {
+        match value {
+            Some(value) => global.set(key, value, true, ctx)?,
+            None => global.delete_property_or_throw(key, ctx)?,
+        };
+    }
+    Ok(())
+}
+
+/// Inserting components converted from JS values, for systems passing on what a script produced
+/// without a [`Context`] to convert it with. The value is converted with the script runtime's
+/// context when the command is applied:
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_boa_reflect::EntityCommandsJsExt;
+/// # fn system(mut commands: Commands, enemy: Entity, value: boa_engine::JsValue) {
+/// commands.entity(enemy).insert_js("my_game::Health", value);
+/// # }
+/// ```
+///
+/// `JsValue`s can't be sent to other threads, so values are held on the thread that queued them
+/// until the commands are applied, which Bevy does on the main thread. Failures are logged, as
+/// they are for commands scripts queue.
+pub trait EntityCommandsJsExt {
+    /// Insert the component with this type path, converted from `value`.
+    fn insert_js(&mut self, type_path: impl Into<String>, value: JsValue) -> &mut Self;
+
+    /// Insert every component of the bundle with this type path, converted from `value` as
+    /// `commands.insert` does for scripts.
+    fn insert_js_bundle(&mut self, type_path: impl Into<String>, value: JsValue) -> &mut Self;
+}
+
+impl EntityCommandsJsExt for EntityCommands<'_> {
+    fn insert_js(&mut self, type_path: impl Into<String>, value: JsValue) -> &mut Self {
+        queue_insert(self, type_path.into(), value, false)
+    }
+
+    fn insert_js_bundle(&mut self, type_path: impl Into<String>, value: JsValue) -> &mut Self {
+        queue_insert(self, type_path.into(), value, true)
+    }
+}
+
+thread_local! {
+    /// Values of queued inserts, by the id their command carries.
+    static PENDING: RefCell<HashMap<u64, JsValue>> = RefCell::default();
+    static NEXT_PENDING: Cell<u64> = const { Cell::new(0) };
+}
+
+fn queue_insert<'a, 'w>(
+    commands: &'a mut EntityCommands<'w>,
+    type_path: String,
+    value: JsValue,
+    bundle: bool,
+) -> &'a mut EntityCommands<'w> {
+    let id = NEXT_PENDING.get();
+    NEXT_PENDING.set(id.wrapping_add(1));
+    PENDING.with_borrow_mut(|pending| pending.insert(id, value));
+    commands.add(move |entity: Entity, world: &mut World| {
+        let Some(value) = PENDING.with_borrow_mut(|pending| pending.remove(&id)) else {
+            warn!("Inserting {type_path} from JS on a thread other than the one it was queued on");
+            return;
+        };
+        let registry = world
+            .get_resource_or_insert_with(AppTypeRegistry::default)
+            .clone();
+        let registry = registry.read();
+        if !bundle
+            && registry
+                .get_with_type_path(&type_path)
+                .is_some_and(|registration| registration.data::<ReflectComponent>().is_none())
+        {
+            warn!("Tried to insert {type_path} from JS, which is not a component");
+            return;
+        }
+        let components = with_runtime(world, |runtime| {
+            reflect_components(&type_path, value, &registry, runtime.context())
+        });
+        drop(registry);
+        match components {
+            Some(Ok(components)) => insert_components(world, entity, components),
+            Some(Err(err)) => warn!("Could not convert {type_path} to insert from JS: {err}"),
+            None => warn!("Tried to insert {type_path} from JS without a script runtime"),
+        }
+    })
+}
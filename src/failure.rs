@@ -0,0 +1,59 @@
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use bevy_utils::tracing::error;
+use boa_engine::JsResult;
+
+/// What [`IntoJsValue::into_js_value`](crate::IntoJsValue::into_js_value) and
+/// [`FromJsValue::from_js_value`](crate::FromJsValue::from_js_value) do when a conversion fails.
+/// Panicking catches mismatches early in development; shipping builds can log them instead, so
+/// bad script data never crashes the game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[repr(u8)]
+pub enum ConversionFailurePolicy {
+    #[default]
+    Panic,
+    /// Log an error and return `null`, or the conversion of `null` when converting from JS.
+    Log,
+    /// Panic in debug builds, and log like [`Log`](Self::Log) in release builds.
+    DebugAssert,
+}
+
+static POLICY: AtomicU8 = AtomicU8::new(ConversionFailurePolicy::Panic as u8);
+
+impl ConversionFailurePolicy {
+    /// The policy in effect for the whole process.
+    pub fn global() -> Self {
+        match POLICY.load(Ordering::Relaxed) {
+            1 => Self::Log,
+            2 => Self::DebugAssert,
+            _ => Self::Panic,
+        }
+    }
+
+    /// Set the policy for the whole process, usually once at startup.
+    pub fn set_global(self) {
+        POLICY.store(self as u8, Ordering::Relaxed);
+    }
+
+    fn panics(self) -> bool {
+        match self {
+            Self::Panic => true,
+            Self::Log => false,
+            Self::DebugAssert => cfg!(debug_assertions),
+        }
+    }
+}
+
+/// Unwrap the result of a conversion, handling a failure as the global policy says.
+pub(crate) fn recover<T>(result: JsResult<T>, fallback: impl FnOnce() -> T) -> T {
+    match result {
+        Ok(value) => value,
+        Err(err) if ConversionFailurePolicy::global().panics() => {
+            panic!("Conversion failed: {err}")
+        }
+        Err(err) => {
+            error!("Conversion failed, using a fallback value: {err}");
+            fallback()
+        }
+    }
+}
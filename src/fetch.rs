@@ -0,0 +1,649 @@
+use std::io::Read;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::time::{Duration, Instant};
+
+use bevy::prelude::*;
+use boa_engine::object::builtins::{JsArrayBuffer, JsFunction, JsPromise};
+use boa_engine::object::ObjectInitializer;
+use boa_engine::property::Attribute;
+use boa_engine::realm::Realm;
+use boa_engine::{
+    js_str, js_string, Context, Finalize, JsData, JsNativeError, JsObject, JsResult, JsString,
+    JsValue, NativeFunction, Trace,
+};
+use url::Url;
+
+use crate::bindings::{arg, in_realm};
+use crate::encoding::{buffer_bytes, is_buffer_source};
+use crate::json::json_to_js_value;
+use crate::plugin::with_runtime;
+use crate::runtime::ScriptRuntime;
+
+/// The global `fetch` is installed under.
+pub const FETCH_BINDING: &str = "fetch";
+
+/// Redirects followed before a fetch fails, as browsers do.
+const MAX_REDIRECTS: usize = 20;
+
+/// Gives scripts `fetch(url, { method, headers, body })`, for pulling remote config or
+/// leaderboards. Bodies may be strings, typed arrays, `ArrayBuffer`s or `DataView`s, and other
+/// values are sent as strings. Requests are sent with `ureq`, each on a thread of its own, and
+/// at most [`max_in_flight`](Self::max_in_flight) at once. The promises
+/// `fetch` returns are settled each frame with a response that has `status`, `statusText`, `ok`,
+/// `url`, `headers`, and `text()`, `json()` and `arrayBuffer()`. Add it next to
+/// [`BoaScriptPlugin`](crate::BoaScriptPlugin).
+///
+/// The plugin also runs the context's promise jobs each frame, so `then` callbacks and `await`s
+/// continue. Both `http` and `https` URLs can be fetched, over rustls.
+pub struct ScriptFetchPlugin {
+    /// How long a request may take in all, from connecting through following redirects to
+    /// reading the whole body, before it fails.
+    pub timeout: Duration,
+    /// The most bytes of body a response may have. Larger responses fail rather than being read
+    /// into memory.
+    pub max_body_size: usize,
+    /// The most requests waiting on a response at once, across every script. Each has a thread
+    /// of its own, so further requests are rejected with a `TypeError` until one finishes.
+    pub max_in_flight: usize,
+}
+
+impl Default for ScriptFetchPlugin {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(30),
+            max_body_size: 16 * 1024 * 1024,
+            max_in_flight: 32,
+        }
+    }
+}
+
+impl Plugin for ScriptFetchPlugin {
+    fn build(&self, app: &mut App) {
+        if cfg!(target_arch = "wasm32") {
+            warn!("Script fetch opens sockets, which isn't possible on the web");
+            return;
+        }
+        let limits = FetchLimits {
+            timeout: self.timeout,
+            max_body_size: self.max_body_size,
+            max_in_flight: self.max_in_flight,
+        };
+        app.add_systems(Startup, move |world: &mut World| {
+            register_fetch(world, limits)
+        })
+        .add_systems(Update, settle_fetches);
+    }
+}
+
+/// The queue behind `fetch`, kept outside the runtime so it can be settled each frame.
+struct ScriptFetches(JsObject);
+
+/// How long a request may take, how much it may read and how many may be sent at once, from
+/// [`ScriptFetchPlugin`].
+#[derive(Clone, Copy)]
+struct FetchLimits {
+    timeout: Duration,
+    max_body_size: usize,
+    max_in_flight: usize,
+}
+
+/// Requests sent by scripts and waiting on a response.
+#[derive(Trace, Finalize, JsData)]
+struct FetchQueue {
+    #[unsafe_ignore_trace]
+    limits: FetchLimits,
+    requests: Vec<PendingFetch>,
+}
+
+#[derive(Trace, Finalize)]
+struct PendingFetch {
+    #[unsafe_ignore_trace]
+    response: Receiver<Result<FetchResponse, String>>,
+    resolve: JsFunction,
+    reject: JsFunction,
+    /// The realm of the script that sent the request, which its response is made in.
+    realm: Realm,
+    response_prototype: JsObject,
+}
+
+/// The body behind a response object, read by its methods.
+#[derive(Trace, Finalize, JsData)]
+struct ResponseBody(#[unsafe_ignore_trace] Vec<u8>);
+
+fn register_fetch(world: &mut World, limits: FetchLimits) {
+    let Some(mut runtime) = world.get_non_send_resource_mut::<ScriptRuntime>() else {
+        warn!("Script fetch needs the BoaScriptPlugin");
+        return;
+    };
+    let queue = JsObject::from_proto_and_data(
+        None,
+        FetchQueue {
+            limits,
+            requests: Vec::new(),
+        },
+    );
+    let fetch_queue = queue.clone();
+    let result = runtime.register_global(FETCH_BINDING, move |ctx| {
+        Ok(fetch_function(&fetch_queue, ctx))
+    });
+    if let Err(err) = result {
+        error!("Error registering script fetch: {err}");
+        return;
+    }
+    world.insert_non_send_resource(ScriptFetches(queue));
+}
+
+/// Build the `fetch` function in the context's current realm, queueing requests in `queue`.
+fn fetch_function(queue: &JsObject, ctx: &mut Context) -> JsFunction {
+    let captures = (queue.clone(), response_prototype(ctx));
+    NativeFunction::from_copy_closure_with_captures(fetch, captures).to_js_function(ctx.realm())
+}
+
+/// `fetch(url, options)`: send the request on a thread of its own and return a promise of its
+/// response. `ureq` blocks while it waits, so requests don't hold up a task pool's threads, and
+/// the number of threads is capped by rejecting requests while too many are in flight.
+fn fetch(
+    _: &JsValue,
+    args: &[JsValue],
+    (queue, response_prototype): &(JsObject, JsObject),
+    ctx: &mut Context,
+) -> JsResult<JsValue> {
+    let request = FetchRequest::from_js(&arg(args, 0), &arg(args, 1), ctx)?;
+    let mut queue = queue
+        .downcast_mut::<FetchQueue>()
+        .ok_or_else(|| JsNativeError::typ().with_message("fetch has no request queue"))?;
+    let limits = queue.limits;
+    if queue.requests.len() >= limits.max_in_flight {
+        let err = JsNativeError::typ().with_message(format!(
+            "Failed to fetch {}: too many requests in flight",
+            request.url
+        ));
+        return Ok(JsPromise::reject(err, ctx).into());
+    }
+    let (sender, response) = mpsc::channel();
+    std::thread::Builder::new()
+        .name("script fetch".to_owned())
+        .spawn(move || {
+            // The script's context may be gone by the time the response is, which is fine.
+            let _ = sender.send(request.send(limits));
+        })
+        .map_err(|err| JsNativeError::error().with_message(format!("Failed to fetch: {err}")))?;
+    let (promise, resolvers) = JsPromise::new_pending(ctx);
+    queue.requests.push(PendingFetch {
+        response,
+        resolve: resolvers.resolve,
+        reject: resolvers.reject,
+        realm: ctx.realm().clone(),
+        response_prototype: response_prototype.clone(),
+    });
+    Ok(promise.into())
+}
+
+/// Settle the promises of requests that have finished, then run the promise jobs queued in the
+/// context, including the callbacks waiting on them.
+fn settle_fetches(world: &mut World) {
+    let Some(queue) = world
+        .get_non_send_resource::<ScriptFetches>()
+        .map(|fetches| fetches.0.clone())
+    else {
+        return;
+    };
+    with_runtime(world, |runtime| {
+        let ctx = runtime.context();
+        for (result, request) in finished_requests(&queue) {
+            let result = in_realm(&request.realm, ctx, |ctx| {
+                let result = result
+                    .map_err(|err| JsNativeError::typ().with_message(err).into())
+                    .and_then(|response| response.into_js_value(&request.response_prototype, ctx));
+                match result {
+                    Ok(response) => request
+                        .resolve
+                        .call(&JsValue::undefined(), &[response], ctx),
+                    Err(err) => {
+                        let err = err.to_opaque(ctx);
+                        request.reject.call(&JsValue::undefined(), &[err], ctx)
+                    }
+                }
+            });
+            if let Err(err) = result {
+                error!("Error settling script fetch: {err}");
+            }
+        }
+        ctx.run_jobs();
+    });
+}
+
+/// Take the requests that have finished out of the queue, with their results. The queue isn't
+/// borrowed while they're settled, as callbacks may fetch again.
+fn finished_requests(queue: &JsObject) -> Vec<(Result<FetchResponse, String>, PendingFetch)> {
+    let Some(mut queue) = queue.downcast_mut::<FetchQueue>() else {
+        return Vec::new();
+    };
+    let (finished, pending) = std::mem::take(&mut queue.requests)
+        .into_iter()
+        .map(|request| match request.response.try_recv() {
+            Ok(result) => (Some(result), request),
+            Err(TryRecvError::Empty) => (None, request),
+            Err(TryRecvError::Disconnected) => {
+                (Some(Err("The request was dropped".to_owned())), request)
+            }
+        })
+        .partition::<Vec<_>, _>(|(result, _)| result.is_some());
+    queue.requests = pending.into_iter().map(|(_, request)| request).collect();
+    finished
+        .into_iter()
+        .filter_map(|(result, request)| Some((result?, request)))
+        .collect()
+}
+
+/// The methods every response has, reading its body.
+fn response_prototype(ctx: &mut Context) -> JsObject {
+    let text = NativeFunction::from_fn_ptr(|this, _, ctx| {
+        let text = with_body(this, |body| String::from_utf8_lossy(body).into_owned())?;
+        Ok(JsPromise::resolve(JsString::from(text), ctx).into())
+    });
+    let json = NativeFunction::from_fn_ptr(|this, _, ctx| {
+        let promise = match with_body(this, |body| serde_json::from_slice(body))? {
+            Ok(json) => JsPromise::resolve(json_to_js_value(&json, ctx), ctx),
+            Err(err) => JsPromise::reject(
+                JsNativeError::syntax().with_message(format!("Invalid JSON response: {err}")),
+                ctx,
+            ),
+        };
+        Ok(promise.into())
+    });
+    let array_buffer = NativeFunction::from_fn_ptr(|this, _, ctx| {
+        let bytes = with_body(this, <[u8]>::to_vec)?;
+        let buffer = JsArrayBuffer::from_byte_block(bytes, ctx)?;
+        Ok(JsPromise::resolve(buffer, ctx).into())
+    });
+    ObjectInitializer::new(ctx)
+        .function(text, js_string!("text"), 0)
+        .function(json, js_string!("json"), 0)
+        .function(array_buffer, js_string!("arrayBuffer"), 0)
+        .build()
+}
+
+fn with_body<R>(this: &JsValue, f: impl FnOnce(&[u8]) -> R) -> JsResult<R> {
+    let body = this
+        .as_object()
+        .and_then(|obj| obj.downcast_ref::<ResponseBody>())
+        .ok_or_else(|| JsNativeError::typ().with_message("Receiver is not a fetch response"))?;
+    Ok(f(&body.0))
+}
+
+/// A request read from the arguments to `fetch`.
+struct FetchRequest {
+    url: Url,
+    method: String,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+/// A response read in full, before it's made into a JS object.
+struct FetchResponse {
+    url: String,
+    status: u16,
+    status_text: String,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+impl FetchRequest {
+    fn from_js(url: &JsValue, options: &JsValue, ctx: &mut Context) -> JsResult<Self> {
+        let url = url.to_string(ctx)?.to_std_string_escaped();
+        let url = Url::parse(&url)
+            .ok()
+            .filter(|url| matches!(url.scheme(), "http" | "https"))
+            .ok_or_else(|| {
+                JsNativeError::typ().with_message(format!("Can't fetch {url:?}, only http(s) URLs"))
+            })?;
+        let mut request = Self {
+            url,
+            method: "GET".to_owned(),
+            headers: Vec::new(),
+            body: Vec::new(),
+        };
+        let Some(options) = options.as_object() else {
+            return Ok(request);
+        };
+        let method = options.get(js_str!("method"), ctx)?;
+        if !method.is_undefined() {
+            request.method = method
+                .to_string(ctx)?
+                .to_std_string_escaped()
+                .to_uppercase();
+            if !is_token(&request.method) {
+                return Err(JsNativeError::typ()
+                    .with_message(format!("Invalid method {:?}", request.method))
+                    .into());
+            }
+        }
+        if let Some(headers) = options.get(js_str!("headers"), ctx)?.as_object() {
+            for key in headers.own_property_keys(ctx)? {
+                let name = key.to_string();
+                let value = headers
+                    .get(key, ctx)?
+                    .to_string(ctx)?
+                    .to_std_string_escaped();
+                if !is_token(&name) {
+                    return Err(JsNativeError::typ()
+                        .with_message(format!("Invalid header name {name:?}"))
+                        .into());
+                }
+                // Line breaks would end the header, letting a value smuggle in headers or a
+                // request of its own.
+                if value.contains(['\r', '\n', '\0']) {
+                    return Err(JsNativeError::typ()
+                        .with_message(format!("Invalid value for header {name:?}"))
+                        .into());
+                }
+                request.headers.push((name, value.trim().to_owned()));
+            }
+        }
+        let body = options.get(js_str!("body"), ctx)?;
+        request.body = match body.as_object() {
+            Some(obj) if is_buffer_source(obj) => buffer_bytes(&body, ctx)?,
+            _ if body.is_null_or_undefined() => Vec::new(),
+            _ => body.to_string(ctx)?.to_std_string_escaped().into_bytes(),
+        };
+        Ok(request)
+    }
+
+    /// Send the request, following redirects. Errors are messages for the `TypeError` the
+    /// promise is rejected with.
+    fn send(mut self, limits: FetchLimits) -> Result<FetchResponse, String> {
+        // Redirects are followed here rather than by `ureq`, to drop credentials on the way.
+        let agent = ureq::AgentBuilder::new().redirects(0).build();
+        let deadline = Instant::now() + limits.timeout;
+        for _ in 0..=MAX_REDIRECTS {
+            let response = self
+                .send_once(&agent, deadline, limits.max_body_size)
+                .map_err(|err| format!("Failed to fetch {}: {err}", self.url))?;
+            let location = match response.status {
+                301 | 302 | 303 | 307 | 308 => header(&response.headers, "location"),
+                _ => None,
+            };
+            let Some(location) = location else {
+                return Ok(response);
+            };
+            self.redirect(response.status, location)?;
+        }
+        Err(format!("Too many redirects fetching {}", self.url))
+    }
+
+    /// Point the request where a redirect with a status and `Location` says to.
+    fn redirect(&mut self, status: u16, location: &str) -> Result<(), String> {
+        let url = self
+            .url
+            .join(location)
+            .map_err(|err| format!("Invalid redirect to {location:?}: {err}"))?;
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(format!(
+                "Can't follow a redirect to {url}, only http(s) URLs"
+            ));
+        }
+        // Credentials are only sent to the origin they were given for, as a redirect elsewhere
+        // would leak them.
+        if url.origin() != self.url.origin() {
+            self.headers
+                .retain(|(name, _)| !is_credential(&name.to_ascii_lowercase()));
+        }
+        self.url = url;
+        let keeps_method = matches!(status, 307 | 308) || (status != 303 && self.method != "POST");
+        if !keeps_method && self.method != "HEAD" {
+            self.method = "GET".to_owned();
+            self.body.clear();
+        }
+        Ok(())
+    }
+
+    fn send_once(
+        &self,
+        agent: &ureq::Agent,
+        deadline: Instant,
+        max_body_size: usize,
+    ) -> Result<FetchResponse, String> {
+        let timeout = deadline.saturating_duration_since(Instant::now());
+        if timeout.is_zero() {
+            return Err("timed out".to_owned());
+        }
+        let mut request = agent.request_url(&self.method, &self.url).timeout(timeout);
+        for (name, value) in &self.headers {
+            let lowercase = name.to_ascii_lowercase();
+            if !matches!(lowercase.as_str(), "host" | "connection" | "content-length") {
+                request = request.set(name, value);
+            }
+        }
+        let result = if self.body.is_empty() && matches!(self.method.as_str(), "GET" | "HEAD") {
+            request.call()
+        } else {
+            request.send_bytes(&self.body)
+        };
+        // Error statuses are responses like any other to scripts, which check `ok`.
+        let response = match result {
+            Ok(response) | Err(ureq::Error::Status(_, response)) => response,
+            Err(err) => return Err(err.to_string()),
+        };
+        FetchResponse::read(response, max_body_size)
+    }
+}
+
+impl FetchResponse {
+    fn read(response: ureq::Response, max_body_size: usize) -> Result<Self, String> {
+        let too_large = || format!("the response body is over {max_body_size} bytes");
+        let mut headers: Vec<(String, String)> = Vec::new();
+        for name in response.headers_names() {
+            let name = name.to_ascii_lowercase();
+            // Repeated headers are combined, as the `Headers` class does.
+            if header(&headers, &name).is_none() {
+                let value = response.all(&name).join(", ");
+                headers.push((name, value));
+            }
+        }
+        let len = header(&headers, "content-length").and_then(|len| len.parse::<u64>().ok());
+        if len.is_some_and(|len| len > max_body_size as u64) {
+            return Err(too_large());
+        }
+        let url = response.get_url().to_owned();
+        let status = response.status();
+        let status_text = response.status_text().to_owned();
+        let mut body = Vec::new();
+        response
+            .into_reader()
+            .take(max_body_size as u64 + 1)
+            .read_to_end(&mut body)
+            .map_err(|err| err.to_string())?;
+        if body.len() > max_body_size {
+            return Err(too_large());
+        }
+        Ok(Self {
+            url,
+            status,
+            status_text,
+            headers,
+            body,
+        })
+    }
+
+    fn into_js_value(self, prototype: &JsObject, ctx: &mut Context) -> JsResult<JsValue> {
+        let mut headers = ObjectInitializer::new(ctx);
+        for (name, value) in &self.headers {
+            headers.property(
+                JsString::from(name.as_str()),
+                JsString::from(value.as_str()),
+                Attribute::all(),
+            );
+        }
+        let headers = headers.build();
+        let response = JsObject::from_proto_and_data(prototype.clone(), ResponseBody(self.body));
+        response.create_data_property_or_throw(js_str!("url"), JsString::from(self.url), ctx)?;
+        response.create_data_property_or_throw(js_str!("status"), self.status, ctx)?;
+        response.create_data_property_or_throw(
+            js_str!("statusText"),
+            JsString::from(self.status_text),
+            ctx,
+        )?;
+        let ok = (200..300).contains(&self.status);
+        response.create_data_property_or_throw(js_str!("ok"), ok, ctx)?;
+        response.create_data_property_or_throw(js_str!("headers"), headers, ctx)?;
+        Ok(response.into())
+    }
+}
+
+/// Whether a method or header name is a token, as RFC 7230 defines it.
+fn is_token(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b))
+}
+
+/// Headers that carry credentials, dropped when a redirect leaves the origin they were given
+/// for.
+fn is_credential(name: &str) -> bool {
+    matches!(name, "authorization" | "cookie" | "proxy-authorization")
+}
+
+fn header<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|(header, _)| header == name)
+        .map(|(_, value)| value.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(url: &str, headers: &[(&str, &str)]) -> FetchRequest {
+        FetchRequest {
+            url: Url::parse(url).unwrap(),
+            method: "POST".to_owned(),
+            headers: headers
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect(),
+            body: b"score=10".to_vec(),
+        }
+    }
+
+    fn fetch_context(max_in_flight: usize) -> Context {
+        let mut ctx = Context::default();
+        let queue = JsObject::from_proto_and_data(
+            None,
+            FetchQueue {
+                limits: FetchLimits {
+                    timeout: Duration::from_secs(1),
+                    max_body_size: 1024,
+                    max_in_flight,
+                },
+                requests: Vec::new(),
+            },
+        );
+        let fetch = fetch_function(&queue, &mut ctx);
+        ctx.register_global_property(js_str!("fetch"), fetch, Attribute::all())
+            .unwrap();
+        ctx
+    }
+
+    fn fetch_error(source: &str) -> String {
+        let err = fetch_context(1)
+            .eval(boa_engine::Source::from_bytes(source))
+            .unwrap_err();
+        err.to_string()
+    }
+
+    fn body(source: &str) -> Vec<u8> {
+        let mut ctx = Context::default();
+        let body = ctx.eval(boa_engine::Source::from_bytes(source)).unwrap();
+        let options = JsObject::with_object_proto(ctx.intrinsics());
+        options.set(js_str!("body"), body, false, &mut ctx).unwrap();
+        FetchRequest::from_js(
+            &js_string!("http://a.test/").into(),
+            &options.into(),
+            &mut ctx,
+        )
+        .unwrap()
+        .body
+    }
+
+    #[test]
+    fn bodies_are_read_as_bytes_or_strings() {
+        assert_eq!(body("new Uint8Array([1, 2, 300])"), [1, 2, 44]);
+        assert_eq!(
+            body("new Uint16Array([0x0201, 0x0403]).subarray(1)"),
+            [3, 4]
+        );
+        assert_eq!(body("new Uint8Array([5, 6, 7]).buffer"), [5, 6, 7]);
+        assert_eq!(
+            body("new DataView(new Uint8Array([5, 6, 7]).buffer, 1)"),
+            [6, 7]
+        );
+        assert_eq!(body(r#""score=10""#), b"score=10");
+        assert_eq!(body("[1, 2]"), b"1,2");
+        assert!(body("undefined").is_empty());
+    }
+
+    #[test]
+    fn requests_beyond_the_cap_are_rejected() {
+        let mut ctx = fetch_context(0);
+        let promise = ctx
+            .eval(boa_engine::Source::from_bytes(r#"fetch("http://a.test/")"#))
+            .unwrap();
+        let promise = JsPromise::from_object(promise.as_object().unwrap().clone()).unwrap();
+        let boa_engine::builtins::promise::PromiseState::Rejected(err) = promise.state() else {
+            panic!("the request wasn't rejected");
+        };
+        let err = boa_engine::JsError::from_opaque(err).to_string();
+        assert!(err.contains("too many requests in flight"), "{err}");
+    }
+
+    #[test]
+    fn line_breaks_and_invalid_names_are_refused() {
+        let err = fetch_error(r#"fetch("http://a.test/", { headers: { "x-a": "1\r\nHost: b" } })"#);
+        assert!(err.contains("Invalid value for header"), "{err}");
+        let err = fetch_error(r#"fetch("http://a.test/", { headers: { "x a\r\n": "1" } })"#);
+        assert!(err.contains("Invalid header name"), "{err}");
+        let err = fetch_error(r#"fetch("http://a.test/", { method: "GET / HTTP/1.1\r\n" })"#);
+        assert!(err.contains("Invalid method"), "{err}");
+    }
+
+    #[test]
+    fn credentials_only_follow_redirects_within_their_authority() {
+        let headers = [
+            ("Authorization", "Bearer t"),
+            ("Cookie", "a=1"),
+            ("Accept", "*/*"),
+        ];
+        let mut same = request("http://a.test/scores", &headers);
+        same.redirect(307, "/v2/scores").unwrap();
+        assert_eq!(same.url.as_str(), "http://a.test/v2/scores");
+        assert_eq!(same.headers.len(), 3);
+
+        let mut elsewhere = request("http://a.test/scores", &headers);
+        elsewhere.redirect(307, "https://b.test/scores").unwrap();
+        let names = elsewhere
+            .headers
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["Accept"]);
+        assert_eq!(elsewhere.method, "POST");
+
+        let mut other_port = request("http://a.test/scores", &headers);
+        other_port
+            .redirect(303, "http://a.test:8080/scores")
+            .unwrap();
+        assert_eq!(other_port.headers.len(), 1);
+        assert_eq!(other_port.method, "GET");
+        assert!(other_port.body.is_empty());
+
+        let mut other_scheme = request("https://a.test/scores", &headers);
+        other_scheme.redirect(302, "http://a.test/scores").unwrap();
+        assert_eq!(other_scheme.headers.len(), 1);
+        assert!(request("http://a.test/", &[])
+            .redirect(302, "file:///etc")
+            .is_err());
+    }
+}
@@ -1,88 +1,1716 @@
-use bevy::prelude::*;
-use bevy::reflect::{DynamicList, DynamicMap, DynamicStruct, Map, Reflect};
-use boa_engine::builtins::map::ordered_map::OrderedMap;
-use boa_engine::builtins::set::ordered_set::OrderedSet;
-use boa_engine::object::builtins::{JsArray, JsMap, JsSet};
-use boa_engine::{js_str, Context, JsError, JsObject, JsResult, JsValue};
+use std::any::TypeId;
+use std::borrow::Cow;
+use std::str::FromStr;
 
+use bevy_reflect::prelude::*;
+use bevy_reflect::{
+    DynamicArray, DynamicEnum, DynamicList, DynamicMap, DynamicStruct, DynamicTuple,
+    DynamicTupleStruct, DynamicVariant, EnumInfo, Map, Reflect, TypeInfo, TypeRegistry,
+    VariantInfo,
+};
+#[cfg(feature = "verbose")]
+use bevy_utils::tracing::trace;
+use bevy_utils::tracing::warn;
+use boa_engine::object::builtins::JsArray;
+use boa_engine::{Context, JsError, JsNativeError, JsResult, JsValue};
+
+use crate::engine::{BoaEngine, Collection, JsEngine, Key, ObjectKeys, Primitive, Read};
+use crate::errors::{
+    ConversionError, ConversionErrors, ConversionIssue, FieldPath, JsValueKind, PathSegment,
+};
+use crate::report::{type_kind, ConversionReport};
+use crate::settings::{ConversionSettings, EnumRepresentation, NumberPolicy};
+
+// Conversions walk values with an explicit stack of steps rather than recursing, so deeply nested
+// data can't overflow the native stack. A value's children are pushed after the step that
+// assembles them, and their results collect on a second stack until that step runs.
+
+/// The type path untyped conversions report in errors, having no type to convert to.
+const UNTYPED: &str = "dyn Reflect";
+
+/// Convert a `JsValue` into a dynamic value shaped like it, without a type to convert to. Numbers
+/// become `f32`s, arrays and sets lists, `Map`s maps and other objects structs.
 pub fn js_value_to_reflect(value: JsValue, ctx: &mut Context) -> JsResult<Box<dyn Reflect>> {
-    match value {
-        JsValue::Null | JsValue::Undefined => Ok(Box::new(()) as Box<dyn Reflect>),
-        JsValue::Boolean(b) => Ok(Box::new(b)),
-        JsValue::Integer(i) => Ok(Box::new(i as f32)),
-        JsValue::Rational(f) => Ok(Box::new(f as f32)),
-        JsValue::String(s) => Ok(Box::new(s.to_std_string_escaped())),
-        JsValue::Object(obj) => {
-            if obj.is_array() {
-                return js_array_to_reflect(&JsArray::from_object(obj)?, ctx);
-            }
-            if obj.is::<OrderedMap<JsValue>>() {
-                return js_map_to_reflect(&JsMap::from_object(obj)?, ctx);
-            }
-            if obj.is::<OrderedSet>() {
-                return js_set_to_reflect(&JsSet::from_object(obj)?, ctx);
-            }
-            js_object_to_reflect(&obj, ctx)
-        }
-        JsValue::Symbol(_) => Err(JsError::from_opaque(
-            js_str!("Symbol conversion not supported").into(),
+    Ok(try_js_value_to_reflect(value, ctx)?)
+}
+
+/// Like [`js_value_to_reflect`], returning a [`ConversionError`] for Rust callers rather than an
+/// error to throw.
+pub fn try_js_value_to_reflect(
+    value: JsValue,
+    ctx: &mut Context,
+) -> Result<Box<dyn Reflect>, ConversionError> {
+    #[cfg(feature = "trace")]
+    let span = bevy_utils::tracing::info_span!(
+        "js_value_to_reflect",
+        elements = bevy_utils::tracing::field::Empty
+    )
+    .entered();
+    let result = to_reflect(value, &ConversionSettings::DEFAULT, ctx);
+    #[cfg(feature = "trace")]
+    if let Ok(value) = &result {
+        span.record("elements", crate::trace::element_count(value.as_ref()));
+    }
+    result
+}
+
+/// Like [`js_value_to_reflect`], converting as `settings` say rather than by default. Untyped
+/// conversions have no types to number, enum or field settings to, so only the depth limit
+/// applies.
+pub fn js_value_to_reflect_with_settings(
+    value: JsValue,
+    settings: &ConversionSettings,
+    ctx: &mut Context,
+) -> JsResult<Box<dyn Reflect>> {
+    Ok(try_js_value_to_reflect_with_settings(value, settings, ctx)?)
+}
+
+/// Like [`js_value_to_reflect_with_settings`], returning a [`ConversionError`] for Rust callers.
+pub fn try_js_value_to_reflect_with_settings(
+    value: JsValue,
+    settings: &ConversionSettings,
+    ctx: &mut Context,
+) -> Result<Box<dyn Reflect>, ConversionError> {
+    to_reflect(value, settings, ctx)
+}
+
+/// Convert a value of any [`JsEngine`] into a dynamic value shaped like it, as
+/// [`js_value_to_reflect_with_settings`] converts Boa's.
+pub fn engine_value_to_reflect<E: JsEngine>(
+    value: E::Value,
+    settings: &ConversionSettings,
+    engine: &mut E,
+) -> Result<Box<dyn Reflect>, ConversionError> {
+    to_engine_reflect(value, settings, engine)
+}
+
+enum UntypedStep<V> {
+    /// Convert a value, nested at a depth.
+    Convert(V, usize),
+    /// Collect the last `len` converted values into a list.
+    List(usize),
+    /// Collect the last `len` converted keys and values into a map.
+    Map(usize),
+    /// Collect the last converted values into a struct with these fields.
+    Struct(Vec<String>),
+}
+
+fn to_reflect(
+    value: JsValue,
+    settings: &ConversionSettings,
+    ctx: &mut Context,
+) -> Result<Box<dyn Reflect>, ConversionError> {
+    to_engine_reflect(value, settings, &mut BoaEngine::new(ctx))
+}
+
+fn to_engine_reflect<E: JsEngine>(
+    value: E::Value,
+    settings: &ConversionSettings,
+    engine: &mut E,
+) -> Result<Box<dyn Reflect>, ConversionError> {
+    let mut steps = vec![UntypedStep::Convert(value, 1)];
+    let mut converted: Vec<Box<dyn Reflect>> = Vec::new();
+    while let Some(step) = steps.pop() {
+        let value: Box<dyn Reflect> = match step {
+            UntypedStep::Convert(value, depth) => match engine.read(&value) {
+                Read::Null | Read::Undefined => Box::new(()),
+                Read::Boolean(b) => Box::new(b),
+                Read::Number(n) => Box::new(n as f32),
+                Read::String(s) => Box::new(s),
+                Read::BigInt(b) => Box::new(b),
+                Read::Other(JsValueKind::Symbol) => {
+                    return Err(ConversionError::mismatch(
+                        "a value other than a symbol",
+                        UNTYPED,
+                        JsValueKind::Symbol,
+                    ))
+                }
+                Read::Other(_) => {
+                    settings.check_depth(depth, || UNTYPED)?;
+                    let (step, children) = object_children(&value, engine)?;
+                    steps.push(step);
+                    let children = children
+                        .into_iter()
+                        .map(|child| UntypedStep::Convert(child, depth + 1));
+                    push_children(&mut steps, children);
+                    continue;
+                }
+            },
+            UntypedStep::List(len) => {
+                let mut dynamic_list = DynamicList::default();
+                for value in take_last(&mut converted, len) {
+                    dynamic_list.push_box(value);
+                }
+                Box::new(dynamic_list)
+            }
+            UntypedStep::Map(len) => {
+                let mut dynamic_map = DynamicMap::default();
+                let mut entries = take_last(&mut converted, len * 2).into_iter();
+                while let (Some(key), Some(value)) = (entries.next(), entries.next()) {
+                    dynamic_map.insert_boxed(key, value);
+                }
+                Box::new(dynamic_map)
+            }
+            UntypedStep::Struct(names) => {
+                let mut dynamic_struct = DynamicStruct::default();
+                let values = take_last(&mut converted, names.len());
+                for (name, value) in names.into_iter().zip(values) {
+                    dynamic_struct.insert_boxed(name, value);
+                }
+                Box::new(dynamic_struct)
+            }
+        };
+        converted.push(value);
+    }
+    converted.pop().ok_or_else(|| ConversionError::FromReflect {
+        type_path: UNTYPED.to_owned(),
+        path: FieldPath::default(),
+    })
+}
+
+/// The step that assembles a value, and the values it is assembled from.
+type Children<V> = (UntypedStep<V>, Vec<V>);
+
+/// The step that assembles an object, and the values inside it. Arrays and sets become lists,
+/// `Map`s become maps, and other objects become structs of their own properties.
+fn object_children<E: JsEngine>(
+    value: &E::Value,
+    engine: &mut E,
+) -> Result<Children<E::Value>, ConversionError> {
+    match engine.collection(value)? {
+        Collection::Array(items) | Collection::Set(items) => {
+            return Ok((UntypedStep::List(items.len()), items))
+        }
+        Collection::Map(entries) => {
+            let len = entries.len();
+            let children = entries
+                .into_iter()
+                .flat_map(|(key, value)| [key, value])
+                .collect();
+            return Ok((UntypedStep::Map(len), children));
+        }
+        Collection::None => {}
+    }
+
+    let variant = engine.properties(value, ObjectKeys::Keys(&[variant_key()]))?;
+    if !matches!(
+        variant.first().map(|variant| engine.kind(variant)),
+        None | Some(JsValueKind::Null | JsValueKind::Undefined)
+    ) {
+        // We can't handle enums right now... it's a bit complicated
+        return Err(
+            JsError::from(JsNativeError::typ().with_message("Enums are not supported")).into(),
+        );
+    }
+    let keys = engine.own_keys(value)?;
+    let values = engine.properties(value, ObjectKeys::Keys(&keys))?;
+    let names = keys.iter().map(Key::to_string).collect();
+    Ok((UntypedStep::Struct(names), values))
+}
+
+/// The key enum values are tagged with their variant's name under.
+fn variant_key() -> Key<'static> {
+    Key::Name(Cow::Borrowed("__variant"))
+}
+
+/// Push the steps for a value's children so they run in order.
+fn push_children<T>(steps: &mut Vec<T>, children: impl IntoIterator<Item = T>) {
+    let start = steps.len();
+    steps.extend(children);
+    steps[start..].reverse();
+}
+
+/// Take the results of a value's children off the end of the converted values.
+fn take_last<T>(converted: &mut Vec<T>, len: usize) -> Vec<T> {
+    converted.split_off(converted.len().saturating_sub(len))
+}
+
+/// Convert a `JsValue` into the shape of a registered type, so the result can be turned into
+/// the concrete type with `FromReflect`. The registry is used to look up nested field types.
+/// Stops at the first field that can't be converted.
+pub fn js_value_to_typed_reflect(
+    value: JsValue,
+    type_id: TypeId,
+    registry: &TypeRegistry,
+    ctx: &mut Context,
+) -> Result<Box<dyn Reflect>, ConversionError> {
+    js_value_to_typed_reflect_with_settings(
+        value,
+        type_id,
+        registry,
+        &ConversionSettings::DEFAULT,
+        ctx,
+    )
+}
+
+/// Like [`js_value_to_typed_reflect`], converting as `settings` say rather than by default.
+pub fn js_value_to_typed_reflect_with_settings(
+    value: JsValue,
+    type_id: TypeId,
+    registry: &TypeRegistry,
+    settings: &ConversionSettings,
+    ctx: &mut Context,
+) -> Result<Box<dyn Reflect>, ConversionError> {
+    TypedConversion::new(registry, settings, type_id, ConversionMode::FirstError)
+        .run(value, type_id, &mut BoaEngine::new(ctx))?
+        .into_value()
+}
+
+/// Convert a value of any [`JsEngine`] into the shape of a registered type, as
+/// [`js_value_to_typed_reflect_with_settings`] converts Boa's.
+pub fn engine_value_to_typed_reflect<E: JsEngine>(
+    value: E::Value,
+    type_id: TypeId,
+    registry: &TypeRegistry,
+    settings: &ConversionSettings,
+    engine: &mut E,
+) -> Result<Box<dyn Reflect>, ConversionError> {
+    TypedConversion::new(registry, settings, type_id, ConversionMode::FirstError)
+        .run(value, type_id, engine)?
+        .into_value()
+}
+
+/// Like [`js_value_to_typed_reflect`], also reporting what the conversion cost.
+pub fn js_value_to_typed_reflect_with_report(
+    value: JsValue,
+    type_id: TypeId,
+    registry: &TypeRegistry,
+    ctx: &mut Context,
+) -> Result<(Box<dyn Reflect>, ConversionReport), ConversionError> {
+    let mut conversion = TypedConversion::new(
+        registry,
+        &ConversionSettings::DEFAULT,
+        type_id,
+        ConversionMode::FirstError,
+    );
+    conversion.report = Some(ConversionReport::default());
+    let mut converted = conversion.run(value, type_id, &mut BoaEngine::new(ctx))?;
+    let report = converted.report.take().unwrap_or_default();
+    Ok((converted.into_value()?, report))
+}
+
+/// Like [`js_value_to_typed_reflect`], but keeps converting past fields that fail, returning
+/// every error along with its path.
+pub fn js_value_to_typed_reflect_all(
+    value: JsValue,
+    type_id: TypeId,
+    registry: &TypeRegistry,
+    ctx: &mut Context,
+) -> Result<Box<dyn Reflect>, ConversionErrors> {
+    let mut converted = TypedConversion::new(
+        registry,
+        &ConversionSettings::DEFAULT,
+        type_id,
+        ConversionMode::AllErrors,
+    )
+    .run(value, type_id, &mut BoaEngine::new(ctx))?;
+    let errors = std::mem::take(&mut converted.issues)
+        .into_iter()
+        .filter_map(|issue| match issue {
+            ConversionIssue::Error(err) => Some(err),
+            ConversionIssue::UnknownField { .. } => None,
+        })
+        .collect::<ConversionErrors>();
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    Ok(converted.into_value()?)
+}
+
+/// Like [`js_value_to_typed_reflect`], but skips struct fields that can't be converted, logging a
+/// warning with their path, rather than failing the whole value. Useful for third-party types
+/// with fields scripts can't represent. Skipped fields are left out of the result, so applying
+/// it leaves them unchanged, and `FromReflect` needs them to have a `#[reflect(default)]`.
+pub fn js_value_to_typed_reflect_lenient(
+    value: JsValue,
+    type_id: TypeId,
+    registry: &TypeRegistry,
+    ctx: &mut Context,
+) -> Result<Box<dyn Reflect>, ConversionError> {
+    let mut converted = TypedConversion::new(
+        registry,
+        &ConversionSettings::DEFAULT,
+        type_id,
+        ConversionMode::Lenient,
+    )
+    .run(value, type_id, &mut BoaEngine::new(ctx))?;
+    // Without a struct to skip it from, a failure leaves nothing to return.
+    if converted.value.is_none() {
+        if let Some(ConversionIssue::Error(err)) = converted.issues.drain(..).next() {
+            return Err(err);
+        }
+    }
+    converted.into_value()
+}
+
+/// Convert each item of a JS array into the shape of a registered type, e.g. to apply values a
+/// script produced for many entities at once. Errors are reported with the index of the item, as
+/// in `[3].translation`.
+pub fn js_array_to_typed_reflect_vec(
+    value: JsValue,
+    type_id: TypeId,
+    registry: &TypeRegistry,
+    ctx: &mut Context,
+) -> Result<Vec<Box<dyn Reflect>>, ConversionError> {
+    #[cfg(feature = "trace")]
+    let _span = bevy_utils::tracing::info_span!("js_array_to_typed_reflect_vec").entered();
+    js_array_to_typed_reflect_vec_with_settings(
+        value,
+        type_id,
+        registry,
+        &ConversionSettings::DEFAULT,
+        ctx,
+    )
+}
+
+/// Like [`js_array_to_typed_reflect_vec`], converting as `settings` say rather than by default.
+pub fn js_array_to_typed_reflect_vec_with_settings(
+    value: JsValue,
+    type_id: TypeId,
+    registry: &TypeRegistry,
+    settings: &ConversionSettings,
+    ctx: &mut Context,
+) -> Result<Vec<Box<dyn Reflect>>, ConversionError> {
+    let type_path = registry
+        .get(type_id)
+        .map_or("", |registration| registration.type_info().type_path());
+    let items = js_array_items(&value, type_path, ctx)?;
+    let mut converter = JsValueConverter::with_settings(registry, settings);
+    items
+        .into_iter()
+        .enumerate()
+        .map(|(idx, item)| converter.convert_item(idx, item, type_id, ctx))
+        .collect()
+}
+
+/// Like [`js_array_to_typed_reflect_vec`], converting the items into a concrete type.
+pub fn js_array_to_typed_vec<T: FromReflect + TypePath>(
+    value: JsValue,
+    registry: &TypeRegistry,
+    ctx: &mut Context,
+) -> Result<Vec<T>, ConversionError> {
+    js_array_to_typed_reflect_vec(value, TypeId::of::<T>(), registry, ctx)?
+        .iter()
+        .map(|value| from_typed_reflect(value.as_ref()))
+        .collect()
+}
+
+/// Converts values from JS into registered types one after another, reusing the buffers a
+/// conversion works in rather than allocating them for every value, for bulk conversions like
+/// importing a scene's components. Bevy's dynamic types box each of their fields, so those
+/// allocations remain.
+pub struct JsValueConverter<'a> {
+    conversion: TypedConversion<'a, JsValue>,
+}
+
+impl<'a> JsValueConverter<'a> {
+    pub fn new(registry: &'a TypeRegistry) -> Self {
+        Self::with_settings(registry, &ConversionSettings::DEFAULT)
+    }
+
+    /// Convert values as `settings` say rather than by default.
+    pub fn with_settings(registry: &'a TypeRegistry, settings: &'a ConversionSettings) -> Self {
+        Self {
+            conversion: TypedConversion::new(
+                registry,
+                settings,
+                TypeId::of::<()>(),
+                ConversionMode::FirstError,
+            ),
+        }
+    }
+
+    /// Convert a value into the shape of a registered type, as
+    /// [`js_value_to_typed_reflect`] does.
+    pub fn convert(
+        &mut self,
+        value: JsValue,
+        type_id: TypeId,
+        ctx: &mut Context,
+    ) -> Result<Box<dyn Reflect>, ConversionError> {
+        self.conversion.reset(type_id);
+        self.conversion
+            .run(value, type_id, &mut BoaEngine::new(ctx))?
+            .into_value()
+    }
+
+    /// Convert an item of an array, reporting errors with the item's index, as in
+    /// `[3].translation`.
+    pub(crate) fn convert_item(
+        &mut self,
+        idx: usize,
+        value: JsValue,
+        type_id: TypeId,
+        ctx: &mut Context,
+    ) -> Result<Box<dyn Reflect>, ConversionError> {
+        self.conversion.reset(type_id);
+        self.conversion.root = None;
+        self.conversion.path.push(PathSegment::Index(idx));
+        self.conversion
+            .run(value, type_id, &mut BoaEngine::new(ctx))?
+            .into_value()
+    }
+
+    /// Convert a value into a concrete type, as [`js_value_to_typed`] does.
+    pub fn convert_typed<T: FromReflect + TypePath>(
+        &mut self,
+        value: JsValue,
+        ctx: &mut Context,
+    ) -> Result<T, ConversionError> {
+        let value = self.convert(value, TypeId::of::<T>(), ctx)?;
+        from_typed_reflect(value.as_ref())
+    }
+}
+
+/// Convert a `JsValue` into a concrete type, using the registry to interpret nested fields.
+pub fn js_value_to_typed<T: FromReflect + TypePath>(
+    value: JsValue,
+    registry: &TypeRegistry,
+    ctx: &mut Context,
+) -> Result<T, ConversionError> {
+    js_value_to_typed_with_settings(value, registry, &ConversionSettings::DEFAULT, ctx)
+}
+
+/// Like [`js_value_to_typed`], converting as `settings` say rather than by default.
+pub fn js_value_to_typed_with_settings<T: FromReflect + TypePath>(
+    value: JsValue,
+    registry: &TypeRegistry,
+    settings: &ConversionSettings,
+    ctx: &mut Context,
+) -> Result<T, ConversionError> {
+    let type_id = TypeId::of::<T>();
+    let reflect_value =
+        js_value_to_typed_reflect_with_settings(value, type_id, registry, settings, ctx)?;
+    from_typed_reflect(reflect_value.as_ref())
+}
+
+/// Like [`js_value_to_typed`], but returns every error in the value rather than the first.
+pub fn js_value_to_typed_all<T: FromReflect + TypePath>(
+    value: JsValue,
+    registry: &TypeRegistry,
+    ctx: &mut Context,
+) -> Result<T, ConversionErrors> {
+    let reflect_value = js_value_to_typed_reflect_all(value, TypeId::of::<T>(), registry, ctx)?;
+    Ok(from_typed_reflect(reflect_value.as_ref())?)
+}
+
+/// Like [`js_value_to_typed`], skipping fields that can't be converted as
+/// [`js_value_to_typed_reflect_lenient`] does.
+pub fn js_value_to_typed_lenient<T: FromReflect + TypePath>(
+    value: JsValue,
+    registry: &TypeRegistry,
+    ctx: &mut Context,
+) -> Result<T, ConversionError> {
+    let reflect_value = js_value_to_typed_reflect_lenient(value, TypeId::of::<T>(), registry, ctx)?;
+    from_typed_reflect(reflect_value.as_ref())
+}
+
+fn from_typed_reflect<T: FromReflect + TypePath>(
+    value: &dyn Reflect,
+) -> Result<T, ConversionError> {
+    T::from_reflect(value).ok_or_else(|| ConversionError::FromReflect {
+        type_path: T::type_path().to_owned(),
+        path: FieldPath {
+            root: Some(T::short_type_path().to_owned()),
+            segments: Vec::new(),
+        },
+    })
+}
+
+/// Check whether a `JsValue` could be converted to a registered type, without building anything,
+/// e.g. to validate what a user typed into an editor before applying it. Returns every issue
+/// found, including properties the type has no field for, which conversion would silently
+/// ignore. An empty result means the value converts.
+pub fn can_convert(
+    value: &JsValue,
+    type_id: TypeId,
+    registry: &TypeRegistry,
+    ctx: &mut Context,
+) -> Vec<ConversionIssue> {
+    can_convert_with_settings(value, type_id, registry, &ConversionSettings::DEFAULT, ctx)
+}
+
+/// Like [`can_convert`], checking the value as `settings` say it would be converted. Strict
+/// settings report unknown properties as errors.
+pub fn can_convert_with_settings(
+    value: &JsValue,
+    type_id: TypeId,
+    registry: &TypeRegistry,
+    settings: &ConversionSettings,
+    ctx: &mut Context,
+) -> Vec<ConversionIssue> {
+    let mut conversion = TypedConversion::new(registry, settings, type_id, ConversionMode::Check);
+    match conversion.run(value.clone(), type_id, &mut BoaEngine::new(ctx)) {
+        Ok(converted) => converted.issues,
+        Err(err) => vec![ConversionIssue::Error(err)],
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ConversionMode {
+    /// Stop at the first value that can't be converted.
+    FirstError,
+    /// Keep converting past values that fail, collecting their errors.
+    AllErrors,
+    /// Collect errors and unknown fields without assembling anything.
+    Check,
+    /// Warn about struct fields that fail and leave them out.
+    Lenient,
+}
+
+struct TypedConversion<'a, V> {
+    registry: &'a TypeRegistry,
+    settings: &'a ConversionSettings,
+    mode: ConversionMode,
+    /// The short path of the type being converted to, which issue paths start from.
+    root: Option<&'static str>,
+    /// The path to the value being converted.
+    path: Vec<PathSegment>,
+    issues: Vec<ConversionIssue>,
+    report: Option<ConversionReport>,
+    /// The steps left to run and the values converted so far, kept between runs so converting
+    /// many values reuses their allocations.
+    steps: Vec<TypedStep<V>>,
+    /// Values that failed to convert are `None`, and so is everything in check mode.
+    converted: Vec<Option<Box<dyn Reflect>>>,
+}
+
+/// The outcome of a [`TypedConversion`] that didn't stop early.
+struct Converted {
+    value: Option<Box<dyn Reflect>>,
+    root: Option<&'static str>,
+    issues: Vec<ConversionIssue>,
+    report: Option<ConversionReport>,
+}
+
+impl Converted {
+    fn into_value(self) -> Result<Box<dyn Reflect>, ConversionError> {
+        // Values are only missing after errors, which were already reported.
+        self.value.ok_or_else(|| ConversionError::FromReflect {
+            type_path: self.root.unwrap_or_default().to_owned(),
+            path: FieldPath {
+                root: self.root.map(str::to_owned),
+                segments: Vec::new(),
+            },
+        })
+    }
+}
+
+enum TypedStep<V> {
+    Convert {
+        value: V,
+        type_id: TypeId,
+        /// Where the value is within its parent, or `None` for the root and `Option` contents.
+        segment: Option<PathSegment>,
+        depth: usize,
+    },
+    /// Assemble the last `len` converted values, then leave the value's path segment.
+    Assemble {
+        shape: Shape,
+        len: usize,
+        entered: bool,
+    },
+}
+
+/// What to assemble from a value's converted children.
+enum Shape {
+    Struct {
+        type_info: &'static TypeInfo,
+        /// The fields the value had, in the order they were converted.
+        names: Vec<&'static str>,
+    },
+    TupleStruct(&'static TypeInfo),
+    Tuple(&'static TypeInfo),
+    List(&'static TypeInfo),
+    Array(&'static TypeInfo),
+    /// Keys and values alternate.
+    Map(&'static TypeInfo),
+    Some(&'static TypeInfo),
+    StructVariant {
+        type_info: &'static TypeInfo,
+        variant: String,
+        names: Vec<&'static str>,
+    },
+    TupleVariant {
+        type_info: &'static TypeInfo,
+        variant: String,
+    },
+}
+
+impl Shape {
+    /// Leave out the fields of a struct that failed to convert, so the rest can be assembled.
+    fn skip_failed(&mut self, children: &mut Vec<Option<Box<dyn Reflect>>>) {
+        if let Shape::Struct { names, .. } | Shape::StructVariant { names, .. } = self {
+            let mut converted = children.iter().map(Option::is_some);
+            names.retain(|_| converted.next().unwrap_or(false));
+            children.retain(Option::is_some);
+        }
+    }
+
+    fn assemble(self, children: Vec<Box<dyn Reflect>>) -> Box<dyn Reflect> {
+        match self {
+            Shape::Struct { type_info, names } => {
+                let mut dynamic_struct = DynamicStruct::default();
+                dynamic_struct.set_represented_type(Some(type_info));
+                for (name, value) in names.into_iter().zip(children) {
+                    dynamic_struct.insert_boxed(name, value);
+                }
+                Box::new(dynamic_struct)
+            }
+            Shape::TupleStruct(type_info) => {
+                let mut dynamic_tuple_struct = DynamicTupleStruct::default();
+                dynamic_tuple_struct.set_represented_type(Some(type_info));
+                for value in children {
+                    dynamic_tuple_struct.insert_boxed(value);
+                }
+                Box::new(dynamic_tuple_struct)
+            }
+            Shape::Tuple(type_info) => {
+                let mut dynamic_tuple = DynamicTuple::default();
+                dynamic_tuple.set_represented_type(Some(type_info));
+                for value in children {
+                    dynamic_tuple.insert_boxed(value);
+                }
+                Box::new(dynamic_tuple)
+            }
+            Shape::List(type_info) => {
+                let mut dynamic_list = DynamicList::default();
+                dynamic_list.set_represented_type(Some(type_info));
+                for value in children {
+                    dynamic_list.push_box(value);
+                }
+                Box::new(dynamic_list)
+            }
+            Shape::Array(type_info) => {
+                let mut dynamic_array = DynamicArray::new(children.into_boxed_slice());
+                dynamic_array.set_represented_type(Some(type_info));
+                Box::new(dynamic_array)
+            }
+            Shape::Map(type_info) => {
+                let mut dynamic_map = DynamicMap::default();
+                dynamic_map.set_represented_type(Some(type_info));
+                let mut entries = children.into_iter();
+                while let (Some(key), Some(value)) = (entries.next(), entries.next()) {
+                    dynamic_map.insert_boxed(key, value);
+                }
+                Box::new(dynamic_map)
+            }
+            Shape::Some(type_info) => {
+                let mut dynamic_tuple = DynamicTuple::default();
+                for value in children {
+                    dynamic_tuple.insert_boxed(value);
+                }
+                let mut dynamic_enum = DynamicEnum::new("Some", dynamic_tuple);
+                dynamic_enum.set_represented_type(Some(type_info));
+                Box::new(dynamic_enum)
+            }
+            Shape::StructVariant {
+                type_info,
+                variant,
+                names,
+            } => {
+                let mut dynamic_struct = DynamicStruct::default();
+                for (name, value) in names.into_iter().zip(children) {
+                    dynamic_struct.insert_boxed(name, value);
+                }
+                let mut dynamic_enum =
+                    DynamicEnum::new(variant, DynamicVariant::Struct(dynamic_struct));
+                dynamic_enum.set_represented_type(Some(type_info));
+                Box::new(dynamic_enum)
+            }
+            Shape::TupleVariant { type_info, variant } => {
+                let mut dynamic_tuple = DynamicTuple::default();
+                for value in children {
+                    dynamic_tuple.insert_boxed(value);
+                }
+                let mut dynamic_enum =
+                    DynamicEnum::new(variant, DynamicVariant::Tuple(dynamic_tuple));
+                dynamic_enum.set_represented_type(Some(type_info));
+                Box::new(dynamic_enum)
+            }
+        }
+    }
+}
+
+/// The short path of a type, which issue paths start from.
+fn root_of(registry: &TypeRegistry, type_id: TypeId) -> Option<&'static str> {
+    registry
+        .get(type_id)
+        .map(|registration| registration.type_info().type_path_table().short_path())
+}
+
+/// A value converted on its own, or the children it needs converted first.
+enum Expanded<V> {
+    Value(Box<dyn Reflect>),
+    Children(Shape, Vec<TypedStep<V>>),
+}
+
+impl<'a, V: Clone + Default> TypedConversion<'a, V> {
+    fn new(
+        registry: &'a TypeRegistry,
+        settings: &'a ConversionSettings,
+        type_id: TypeId,
+        mode: ConversionMode,
+    ) -> Self {
+        Self {
+            registry,
+            settings,
+            mode,
+            root: root_of(registry, type_id),
+            path: Vec::new(),
+            issues: Vec::new(),
+            report: None,
+            steps: Vec::new(),
+            converted: Vec::new(),
+        }
+    }
+
+    /// Start over for converting another value into a type, keeping the buffers.
+    fn reset(&mut self, type_id: TypeId) {
+        self.root = root_of(self.registry, type_id);
+        self.path.clear();
+        self.issues.clear();
+    }
+
+    /// Convert a value, returning the first error in [`ConversionMode::FirstError`], and
+    /// collecting them otherwise.
+    fn run<E: JsEngine<Value = V>>(
+        &mut self,
+        value: V,
+        type_id: TypeId,
+        engine: &mut E,
+    ) -> Result<Converted, ConversionError> {
+        #[cfg(feature = "trace")]
+        let span = bevy_utils::tracing::info_span!(
+            "js_value_to_typed_reflect",
+            type_path = self
+                .registry
+                .get(type_id)
+                .map_or("", |registration| registration.type_info().type_path()),
+            elements = bevy_utils::tracing::field::Empty,
+        )
+        .entered();
+        let hooked = engine.has_pre_hooks();
+        let mut steps = std::mem::take(&mut self.steps);
+        let mut converted = std::mem::take(&mut self.converted);
+        steps.clear();
+        converted.clear();
+        steps.push(TypedStep::Convert {
+            value,
+            type_id,
+            segment: None,
+            depth: 1,
+        });
+        while let Some(step) = steps.pop() {
+            match step {
+                TypedStep::Convert {
+                    mut value,
+                    type_id,
+                    segment,
+                    depth,
+                } => {
+                    let entered = segment.is_some();
+                    self.path.extend(segment);
+                    #[cfg(feature = "verbose")]
+                    let source = engine.summary(&value);
+                    let expanded = match hooked {
+                        true => self.run_pre_hooks(&mut value, type_id, engine),
+                        false => Ok(()),
+                    }
+                    .and_then(|()| self.expand(value, type_id, depth, engine));
+                    #[cfg(feature = "verbose")]
+                    self.log_step(&source, type_id, &expanded);
+                    match expanded {
+                        Ok(Expanded::Children(shape, children)) => {
+                            steps.push(TypedStep::Assemble {
+                                shape,
+                                len: children.len(),
+                                entered,
+                            });
+                            push_children(&mut steps, children);
+                            continue;
+                        }
+                        Ok(Expanded::Value(value)) => {
+                            converted.push((self.mode != ConversionMode::Check).then_some(value));
+                        }
+                        Err(err) => {
+                            self.fail(err)?;
+                            converted.push(None);
+                        }
+                    }
+                    if entered {
+                        self.path.pop();
+                    }
+                }
+                TypedStep::Assemble {
+                    mut shape,
+                    len,
+                    entered,
+                } => {
+                    let mut children = take_last(&mut converted, len);
+                    if self.mode == ConversionMode::Lenient {
+                        shape.skip_failed(&mut children);
+                    }
+                    let children = children.into_iter().collect::<Option<Vec<_>>>();
+                    if let (Some(report), Some(_)) = (&mut self.report, &children) {
+                        report.object();
+                    }
+                    converted.push(children.map(|children| shape.assemble(children)));
+                    if entered {
+                        self.path.pop();
+                    }
+                }
+            }
+        }
+        #[cfg(feature = "trace")]
+        if let Some(Some(value)) = converted.last() {
+            span.record("elements", crate::trace::element_count(value.as_ref()));
+        }
+        let value = converted.pop().flatten();
+        self.steps = steps;
+        self.converted = converted;
+        Ok(Converted {
+            value,
+            root: self.root,
+            issues: std::mem::take(&mut self.issues),
+            report: self.report.take(),
+        })
+    }
+
+    fn field_path(&self) -> FieldPath {
+        FieldPath {
+            root: self.root.map(str::to_owned),
+            segments: self.path.clone(),
+        }
+    }
+
+    /// Log what the value at the current path converted to.
+    #[cfg(feature = "verbose")]
+    fn log_step(
+        &self,
+        source: &str,
+        type_id: TypeId,
+        expanded: &Result<Expanded<V>, ConversionError>,
+    ) {
+        let path = self.field_path();
+        let type_path = self
+            .registry
+            .get(type_id)
+            .map_or("<unregistered>", |registration| {
+                registration.type_info().type_path()
+            });
+        match expanded {
+            Ok(Expanded::Value(value)) => trace!(
+                target: "bevy_boa_reflect::conversions",
+                "{path}: {source} -> {type_path} {}",
+                crate::verbose::reflect_summary(value.as_ref()),
+            ),
+            Ok(Expanded::Children(_, children)) => trace!(
+                target: "bevy_boa_reflect::conversions",
+                "{path}: {source} -> {type_path} with {} children",
+                children.len(),
+            ),
+            Err(err) => trace!(
+                target: "bevy_boa_reflect::conversions",
+                "{path}: {source} -> {type_path} failed: {err}",
+            ),
+        }
+    }
+
+    /// Run the pre hooks on a value about to be converted into a type.
+    fn run_pre_hooks<E: JsEngine<Value = V>>(
+        &self,
+        value: &mut V,
+        type_id: TypeId,
+        engine: &mut E,
+    ) -> Result<(), ConversionError> {
+        let type_path = self
+            .registry
+            .get(type_id)
+            .map_or("", |registration| registration.type_info().type_path());
+        engine.pre_hook(type_path, &self.field_path(), value)
+    }
+
+    /// Report an error at the current path, returning it unless collecting errors or skipping
+    /// the field it is in.
+    fn fail(&mut self, err: ConversionError) -> Result<(), ConversionError> {
+        let err = err.with_path(self.field_path());
+        match self.mode {
+            ConversionMode::FirstError => return Err(err),
+            ConversionMode::Lenient if self.path.is_empty() => return Err(err),
+            ConversionMode::Lenient => warn!("Skipping a field that can't be converted: {err}"),
+            ConversionMode::AllErrors | ConversionMode::Check => {}
+        }
+        self.issues.push(ConversionIssue::Error(err));
+        Ok(())
+    }
+
+    fn expand<E: JsEngine<Value = V>>(
+        &mut self,
+        value: V,
+        type_id: TypeId,
+        depth: usize,
+        engine: &mut E,
+    ) -> Result<Expanded<V>, ConversionError> {
+        let registration =
+            self.registry
+                .get(type_id)
+                .ok_or_else(|| ConversionError::Unregistered {
+                    type_path: format!("{type_id:?}"),
+                    path: FieldPath::default(),
+                })?;
+        let type_info = registration.type_info();
+        if let Some(report) = &mut self.report {
+            report.value(type_kind(type_info), depth);
+        }
+        self.settings.check_depth(depth, || type_info.type_path())?;
+        let convert = |value, type_id, segment| TypedStep::Convert {
+            value,
+            type_id,
+            segment: Some(segment),
+            depth: depth + 1,
+        };
+        Ok(match type_info {
+            TypeInfo::Struct(info) => {
+                expect_object(engine, &value, info.type_path())?;
+                let renamed = self.renamed_keys(info.iter().map(|field| field.name()));
+                let keys = match &renamed {
+                    Some(keys) => ObjectKeys::Keys(keys),
+                    None => ObjectKeys::StructInfo(info),
+                };
+                let values = engine.properties(&value, keys)?;
+                let mut names = Vec::new();
+                let mut children = Vec::new();
+                for (field, value) in info.iter().zip(values) {
+                    if engine.kind(&value) == JsValueKind::Undefined {
+                        continue;
+                    }
+                    names.push(field.name());
+                    let segment = PathSegment::Field(field.name().to_owned());
+                    children.push(convert(value, field.type_id(), segment));
+                }
+                let fields = info.iter().map(|field| field.name());
+                self.unknown_fields(&value, info.type_path(), fields, engine)?;
+                Expanded::Children(Shape::Struct { type_info, names }, children)
+            }
+            TypeInfo::TupleStruct(info) => {
+                let items = array_items(engine, &value, info.type_path())?;
+                self.check_length(info.type_path(), info.field_len(), items.len())?;
+                let children = info
+                    .iter()
+                    .zip(items)
+                    .enumerate()
+                    .map(|(idx, (field, value))| {
+                        convert(value, field.type_id(), PathSegment::Index(idx))
+                    })
+                    .collect();
+                Expanded::Children(Shape::TupleStruct(type_info), children)
+            }
+            TypeInfo::Tuple(info) => {
+                let items = array_items(engine, &value, info.type_path())?;
+                self.check_length(info.type_path(), info.field_len(), items.len())?;
+                let children = info
+                    .iter()
+                    .zip(items)
+                    .enumerate()
+                    .map(|(idx, (field, value))| {
+                        convert(value, field.type_id(), PathSegment::Index(idx))
+                    })
+                    .collect();
+                Expanded::Children(Shape::Tuple(type_info), children)
+            }
+            TypeInfo::List(info) => {
+                let items = array_items(engine, &value, info.type_path())?;
+                let children = items
+                    .into_iter()
+                    .enumerate()
+                    .map(|(idx, value)| {
+                        convert(value, info.item_type_id(), PathSegment::Index(idx))
+                    })
+                    .collect();
+                Expanded::Children(Shape::List(type_info), children)
+            }
+            TypeInfo::Array(info) => {
+                let items = array_items(engine, &value, info.type_path())?;
+                self.check_length(info.type_path(), info.capacity(), items.len())?;
+                let children = items
+                    .into_iter()
+                    .enumerate()
+                    .map(|(idx, value)| {
+                        convert(value, info.item_type_id(), PathSegment::Index(idx))
+                    })
+                    .collect();
+                Expanded::Children(Shape::Array(type_info), children)
+            }
+            TypeInfo::Map(info) => {
+                let mut children = Vec::new();
+                for (key, value) in map_entries(engine, &value, info.type_path())? {
+                    let segment = PathSegment::Key(engine.read(&key).to_string());
+                    children.push(convert(key, info.key_type_id(), segment.clone()));
+                    children.push(convert(value, info.value_type_id(), segment));
+                }
+                Expanded::Children(Shape::Map(type_info), children)
+            }
+            TypeInfo::Enum(info) => self.expand_enum(value, info, type_info, depth, engine)?,
+            TypeInfo::Value(info) => {
+                let read = engine.read(&value);
+                let value = read_primitive(read, info.type_id(), info.type_path(), self.settings)?;
+                if let (Some(report), Some(s)) = (&mut self.report, value.downcast_ref::<String>())
+                {
+                    report.string(s.len());
+                }
+                Expanded::Value(value)
+            }
+        })
+    }
+
+    /// Fail on an array, tuple or tuple struct given the wrong number of items. Items past the
+    /// end are still converted when collecting errors, so theirs are reported too.
+    fn check_length(
+        &mut self,
+        type_path: &str,
+        expected: usize,
+        found: usize,
+    ) -> Result<(), ConversionError> {
+        if expected == found {
+            return Ok(());
+        }
+        let wrong_length = ConversionError::WrongLength {
+            type_path: type_path.to_owned(),
+            path: FieldPath::default(),
+            expected,
+            found,
+        };
+        // A lenient conversion can't keep a value of the wrong length either, as applying it
+        // would panic.
+        if matches!(
+            self.mode,
+            ConversionMode::FirstError | ConversionMode::Lenient
+        ) {
+            return Err(wrong_length);
+        }
+        self.fail(wrong_length)
+    }
+
+    /// Enums are read from the shape `reflect_to_js_value` gives them: an object with the
+    /// variant's fields and a `__variant` name. Unit variants may also be given as a plain
+    /// string, and `Option`s as `null` or the bare inner value.
+    fn expand_enum<E: JsEngine<Value = V>>(
+        &mut self,
+        value: V,
+        info: &EnumInfo,
+        type_info: &'static TypeInfo,
+        depth: usize,
+        engine: &mut E,
+    ) -> Result<Expanded<V>, ConversionError> {
+        // The type of the value inside `Some`, if this enum is an `Option`.
+        let option_inner = match info.variant("Some") {
+            Some(VariantInfo::Tuple(some))
+                if info.type_path_table().module_path() == Some("core::option")
+                    && info.type_path_table().ident() == Some("Option") =>
+            {
+                some.field_at(0).map(|field| field.type_id())
+            }
+            _ => None,
+        };
+
+        let read = engine.read(&value);
+        let is_object = read.kind().is_object();
+        let external = match (is_object, self.settings.enums) {
+            (true, EnumRepresentation::External) => external_variant(engine, &value, info)?,
+            _ => None,
+        };
+        let tag = match is_object && self.settings.enums == EnumRepresentation::Tagged {
+            true if engine.has_property(&value, variant_key())? => {
+                let tag = engine.properties(&value, ObjectKeys::Keys(&[variant_key()]))?;
+                tag.first().map(|tag| engine.read(tag))
+            }
+            _ => None,
+        };
+        // The bare value inside an `Option` may be an enum tagged with a variant of its own.
+        let tag = tag.filter(|tag| {
+            option_inner.is_none()
+                || matches!(tag, Read::String(name) if name == "Some" || name == "None")
+        });
+        let (variant_name, obj) = match (read, option_inner, external, tag) {
+            (_, _, Some(external), _) => external,
+            (Read::Null | Read::Undefined, Some(_), _, _) => ("None".to_string(), None),
+            // Externally represented, `None` is a unit variant, given by its name.
+            (Read::String(s), Some(_), _, _)
+                if self.settings.enums == EnumRepresentation::External && s == "None" =>
+            {
+                ("None".to_string(), None)
+            }
+            (Read::String(s), None, _, _) => (s, None),
+            (Read::Other(_), _, _, Some(variant)) => {
+                let Read::String(name) = variant else {
+                    return Err(ConversionError::mismatch(
+                        "a string",
+                        info.type_path(),
+                        variant.kind(),
+                    )
+                    .at(PathSegment::Field("__variant".to_owned())));
+                };
+                (name, Some(value))
+            }
+            (_, Some(inner), _, _) => {
+                let child = TypedStep::Convert {
+                    value,
+                    type_id: inner,
+                    segment: None,
+                    depth: depth + 1,
+                };
+                return Ok(Expanded::Children(Shape::Some(type_info), vec![child]));
+            }
+            (read, ..) => {
+                return Err(ConversionError::mismatch(
+                    "an enum value",
+                    info.type_path(),
+                    read.kind(),
+                ))
+            }
+        };
+
+        let variant =
+            info.variant(&variant_name)
+                .ok_or_else(|| ConversionError::UnknownVariant {
+                    type_path: info.type_path().to_owned(),
+                    path: FieldPath::default(),
+                    variant: variant_name.clone(),
+                })?;
+        let missing_fields = || ConversionError::MissingVariantFields {
+            type_path: info.type_path().to_owned(),
+            path: FieldPath::default(),
+            variant: variant_name.clone(),
+        };
+        Ok(match variant {
+            VariantInfo::Unit(_) => {
+                let mut dynamic_enum = DynamicEnum::new(variant_name, DynamicVariant::Unit);
+                dynamic_enum.set_represented_type(Some(type_info));
+                if let Some(report) = &mut self.report {
+                    report.object();
+                }
+                Expanded::Value(Box::new(dynamic_enum))
+            }
+            VariantInfo::Struct(variant) => {
+                let obj = obj.ok_or_else(missing_fields)?;
+                let keys = variant
+                    .iter()
+                    .map(|field| Key::Name(self.settings.rename.apply(field.name())))
+                    .collect::<Vec<_>>();
+                let values = engine.properties(&obj, ObjectKeys::Keys(&keys))?;
+                let mut names = Vec::new();
+                let mut children = Vec::new();
+                for (field, value) in variant.iter().zip(values) {
+                    names.push(field.name());
+                    children.push(TypedStep::Convert {
+                        value,
+                        type_id: field.type_id(),
+                        segment: Some(PathSegment::Field(field.name().to_owned())),
+                        depth: depth + 1,
+                    });
+                }
+                let fields = variant.iter().map(|field| field.name());
+                self.unknown_fields(&obj, info.type_path(), fields, engine)?;
+                let shape = Shape::StructVariant {
+                    type_info,
+                    variant: variant_name,
+                    names,
+                };
+                Expanded::Children(shape, children)
+            }
+            VariantInfo::Tuple(variant) => {
+                let obj = obj.ok_or_else(missing_fields)?;
+                let keys = variant
+                    .iter()
+                    .map(|field| Key::Index(field.index()))
+                    .collect::<Vec<_>>();
+                let values = engine.properties(&obj, ObjectKeys::Keys(&keys))?;
+                let children = variant
+                    .iter()
+                    .zip(values)
+                    .map(|(field, value)| TypedStep::Convert {
+                        value,
+                        type_id: field.type_id(),
+                        segment: Some(PathSegment::Index(field.index())),
+                        depth: depth + 1,
+                    })
+                    .collect();
+                let shape = Shape::TupleVariant {
+                    type_info,
+                    variant: variant_name,
+                };
+                Expanded::Children(shape, children)
+            }
+        })
+    }
+
+    /// The keys of fields renamed as the settings say, or `None` if they keep their names.
+    fn renamed_keys(&self, names: impl Iterator<Item = &'static str>) -> Option<Vec<Key<'static>>> {
+        if !self.settings.renames() {
+            return None;
+        }
+        let keys = names
+            .map(|name| Key::Name(Cow::Owned(self.settings.rename.apply(name).into_owned())))
+            .collect();
+        Some(keys)
+    }
+
+    /// Report the properties of an object the type has no field for, when checking a value, and
+    /// fail on them in strict conversions.
+    fn unknown_fields<E: JsEngine<Value = V>>(
+        &mut self,
+        obj: &V,
+        type_path: &str,
+        fields: impl Iterator<Item = &'static str>,
+        engine: &mut E,
+    ) -> Result<(), ConversionError> {
+        if self.mode != ConversionMode::Check && !self.settings.strict {
+            return Ok(());
+        }
+        let fields = fields
+            .map(|name| self.settings.rename.apply(name))
+            .collect::<Vec<_>>();
+        for key in engine.own_keys(obj)? {
+            let Key::Name(name) = key else {
+                continue;
+            };
+            if name == "__variant" || fields.contains(&name) {
+                continue;
+            }
+            let name = name.into_owned();
+            if self.mode == ConversionMode::Check && !self.settings.strict {
+                self.issues.push(ConversionIssue::UnknownField {
+                    path: self.field_path(),
+                    name,
+                });
+            } else {
+                self.fail(ConversionError::UnknownField {
+                    type_path: type_path.to_owned(),
+                    path: FieldPath::default(),
+                    name,
+                })?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The name of a variant, and the object holding its fields if it was given any.
+type Variant<V> = (String, Option<V>);
+
+/// The name and fields of a variant given as an object with its fields under its name, or `None`
+/// if the object isn't shaped like that.
+fn external_variant<E: JsEngine>(
+    engine: &mut E,
+    obj: &E::Value,
+    info: &EnumInfo,
+) -> Result<Option<Variant<E::Value>>, ConversionError> {
+    let keys = engine.own_keys(obj)?;
+    let [Key::Name(name)] = keys.as_slice() else {
+        return Ok(None);
+    };
+    if info.variant(name).is_none() {
+        return Ok(None);
+    }
+    let fields = engine.properties(obj, ObjectKeys::Keys(&keys))?.pop();
+    let fields = fields.filter(|fields| engine.kind(fields).is_object());
+    Ok(Some((name.to_string(), fields)))
+}
+
+fn read_primitive(
+    read: Read,
+    type_id: TypeId,
+    type_path: &str,
+    settings: &ConversionSettings,
+) -> Result<Box<dyn Reflect>, ConversionError> {
+    let float = type_id == TypeId::of::<f32>() || type_id == TypeId::of::<f64>();
+    if settings.strict {
+        let coerced = match &read {
+            Read::Boolean(_) => false,
+            Read::BigInt(_) => float,
+            _ => type_id == TypeId::of::<bool>(),
+        };
+        if coerced {
+            let expected = if type_id == TypeId::of::<bool>() {
+                "a boolean"
+            } else {
+                "a number"
+            };
+            return Err(ConversionError::mismatch(expected, type_path, read.kind()));
+        }
+    }
+    // Integers are read from the whole part of fractional numbers.
+    let read = match read {
+        Read::Number(f) if settings.numbers == NumberPolicy::Lossy && !float => {
+            Read::Number(f.trunc())
+        }
+        read => read,
+    };
+    Ok(match type_id {
+        t if t == TypeId::of::<bool>() => Box::new(read.to_boolean()),
+        t if t == TypeId::of::<i8>() => Box::new(read_int::<i8>(&read, type_path)?),
+        t if t == TypeId::of::<i16>() => Box::new(read_int::<i16>(&read, type_path)?),
+        t if t == TypeId::of::<i32>() => Box::new(read_int::<i32>(&read, type_path)?),
+        t if t == TypeId::of::<i64>() => Box::new(read_int::<i64>(&read, type_path)?),
+        t if t == TypeId::of::<i128>() => Box::new(read_int::<i128>(&read, type_path)?),
+        t if t == TypeId::of::<isize>() => Box::new(read_int::<isize>(&read, type_path)?),
+        t if t == TypeId::of::<u8>() => Box::new(read_int::<u8>(&read, type_path)?),
+        t if t == TypeId::of::<u16>() => Box::new(read_int::<u16>(&read, type_path)?),
+        t if t == TypeId::of::<u32>() => Box::new(read_int::<u32>(&read, type_path)?),
+        t if t == TypeId::of::<u64>() => Box::new(read_int::<u64>(&read, type_path)?),
+        t if t == TypeId::of::<u128>() => Box::new(read_int::<u128>(&read, type_path)?),
+        t if t == TypeId::of::<usize>() => Box::new(read_int::<usize>(&read, type_path)?),
+        t if t == TypeId::of::<f32>() => Box::new(read_float(&read, type_path)? as f32),
+        t if t == TypeId::of::<f64>() => Box::new(read_float(&read, type_path)?),
+        t if t == TypeId::of::<String>() => Box::new(read_string(read, type_path)?),
+        t if t == TypeId::of::<char>() => {
+            let s = read_string(read, type_path)?;
+            let mut chars = s.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => Box::new(c),
+                _ => {
+                    return Err(ConversionError::mismatch(
+                        "a single character string",
+                        type_path,
+                        JsValueKind::String,
+                    ))
+                }
+            }
+        }
+        t if t == TypeId::of::<()>() => Box::new(()),
+        _ => {
+            return Err(ConversionError::Unsupported {
+                type_path: type_path.to_owned(),
+                path: FieldPath::default(),
+            })
+        }
+    })
+}
+
+fn read_int<T: TryFrom<i128> + FromStr>(
+    read: &Read,
+    type_path: &str,
+) -> Result<T, ConversionError> {
+    let int = match read {
+        Read::Number(f) if f.is_finite() && f.fract() == 0.0 => *f as i128,
+        // Parsed straight into the type, as `u128`s can be past the range of an `i128`.
+        Read::BigInt(int) => {
+            return int.parse().map_err(|_| ConversionError::OutOfRange {
+                type_path: type_path.to_owned(),
+                path: FieldPath::default(),
+                value: int.clone(),
+            });
+        }
+        read => {
+            return Err(ConversionError::mismatch(
+                "an integer",
+                type_path,
+                read.kind(),
+            ))
+        }
+    };
+    T::try_from(int).map_err(|_| ConversionError::OutOfRange {
+        type_path: type_path.to_owned(),
+        path: FieldPath::default(),
+        value: int.to_string(),
+    })
+}
+
+fn read_float(read: &Read, type_path: &str) -> Result<f64, ConversionError> {
+    match read {
+        Read::Number(f) => Ok(*f),
+        Read::BigInt(int) => Ok(int.parse().unwrap_or(f64::NAN)),
+        read => Err(ConversionError::mismatch(
+            "a number",
+            type_path,
+            read.kind(),
         )),
-        JsValue::BigInt(b) => Ok(Box::new(b.to_string())),
     }
 }
 
-fn js_array_to_reflect(array: &JsArray, ctx: &mut Context) -> JsResult<Box<dyn Reflect>> {
-    let mut dynamic_list = DynamicList::default();
-    for i in 0..array.length(ctx)? {
-        let value = array.get(i, ctx)?;
-        let reflect_value = js_value_to_reflect(value, ctx)?;
-        dynamic_list.push_box(reflect_value);
+fn read_string(read: Read, type_path: &str) -> Result<String, ConversionError> {
+    match read {
+        Read::String(s) => Ok(s),
+        read => Err(ConversionError::mismatch(
+            "a string",
+            type_path,
+            read.kind(),
+        )),
     }
-    Ok(Box::new(dynamic_list))
 }
 
-fn js_map_to_reflect(map: &JsMap, ctx: &mut Context) -> JsResult<Box<dyn Reflect>> {
-    let mut dynamic_map = DynamicMap::default();
-    let entries = map.entries(ctx)?;
-    while let entry = entries.next(ctx)? {
-        let entry = entry.to_object(ctx)?;
-        let entry = JsArray::from_object(entry)?;
+pub(crate) fn js_value_to_int<T: TryFrom<i128> + FromStr>(
+    value: &JsValue,
+    type_path: &str,
+) -> Result<T, ConversionError> {
+    read_int(&BoaEngine::read_value(value), type_path)
+}
+
+pub(crate) fn js_value_to_float(value: &JsValue, type_path: &str) -> Result<f64, ConversionError> {
+    read_float(&BoaEngine::read_value(value), type_path)
+}
+
+pub(crate) fn js_value_to_string(
+    value: &JsValue,
+    type_path: &str,
+) -> Result<String, ConversionError> {
+    read_string(BoaEngine::read_value(value), type_path)
+}
 
-        let key = entry.get(0, ctx)?;
-        let value = entry.get(1, ctx)?;
-        let reflect_key = js_value_to_reflect(key, ctx)?;
-        let reflect_value = js_value_to_reflect(value, ctx)?;
-        dynamic_map.insert_boxed(reflect_key, reflect_value);
+fn expect_object<E: JsEngine>(
+    engine: &E,
+    value: &E::Value,
+    type_path: &str,
+) -> Result<(), ConversionError> {
+    match engine.kind(value) {
+        kind if kind.is_object() => Ok(()),
+        kind => Err(ConversionError::mismatch("an object", type_path, kind)),
     }
-    Ok(Box::new(dynamic_map))
 }
 
-fn js_set_to_reflect(set: &JsSet, ctx: &mut Context) -> JsResult<Box<dyn Reflect>> {
-    let mut dynamic_list = DynamicList::default();
-    let values = set.values(ctx)?;
-    while let value = values.next(ctx)? {
-        let reflect_value = js_value_to_reflect(value, ctx)?;
-        dynamic_list.push_box(reflect_value);
+/// Read the items of an array or a typed array.
+fn array_items<E: JsEngine>(
+    engine: &mut E,
+    value: &E::Value,
+    type_path: &str,
+) -> Result<Vec<E::Value>, ConversionError> {
+    match engine.collection(value)? {
+        Collection::Array(items) => Ok(items),
+        _ => Err(ConversionError::mismatch(
+            "an array",
+            type_path,
+            engine.kind(value),
+        )),
     }
-    Ok(Box::new(dynamic_list))
 }
 
-fn js_object_to_reflect(obj: &JsObject, ctx: &mut Context) -> JsResult<Box<dyn Reflect>> {
-    let mut dynamic_struct = DynamicStruct::default();
-    for key in obj.own_property_keys(ctx)? {
-        let value = obj.get(key.clone(), ctx)?;
-        let reflect_value = js_value_to_reflect(value, ctx)?;
-        dynamic_struct.insert_boxed(key.to_string(), reflect_value);
+pub(crate) fn js_array_items(
+    value: &JsValue,
+    type_path: &str,
+    ctx: &mut Context,
+) -> Result<Vec<JsValue>, ConversionError> {
+    array_items(&mut BoaEngine::reader(ctx), value, type_path)
+}
+
+/// A value as an array, to be converted into a type.
+pub(crate) fn js_array(value: &JsValue, type_path: &str) -> Result<JsArray, ConversionError> {
+    let obj = value
+        .as_object()
+        .filter(|obj| obj.is_array())
+        .ok_or_else(|| ConversionError::type_mismatch("an array", type_path, value))?;
+    Ok(JsArray::from_object(obj.clone())?)
+}
+
+/// The keys and values of a map, in order.
+type Entries<V> = Vec<(V, V)>;
+
+/// Read the entries of a `Map` or an array of entries, or the own properties of a plain object.
+fn map_entries<E: JsEngine>(
+    engine: &mut E,
+    value: &E::Value,
+    type_path: &str,
+) -> Result<Entries<E::Value>, ConversionError> {
+    expect_object(engine, value, type_path)?;
+    match engine.collection(value)? {
+        Collection::Map(entries) => Ok(entries),
+        // Arrays of `[key, value]` entries, as maps are written to JSON.
+        Collection::Array(items) => items
+            .into_iter()
+            .enumerate()
+            .map(|(idx, entry)| {
+                let entry = array_items(engine, &entry, type_path)
+                    .map_err(|err| err.at(PathSegment::Index(idx)))?;
+                let mut entry = entry.into_iter();
+                let key = entry.next().unwrap_or_default();
+                Ok((key, entry.next().unwrap_or_default()))
+            })
+            .collect(),
+        Collection::Set(_) | Collection::None => {
+            let keys = engine.own_keys(value)?;
+            let values = engine.properties(value, ObjectKeys::Keys(&keys))?;
+            Ok(keys
+                .iter()
+                .map(|key| engine.primitive(Primitive::String(Cow::Owned(key.to_string()))))
+                .zip(values)
+                .collect())
+        }
     }
+}
+
+/// Read the entries of a `Map` or an array of entries, or the own properties of a plain object.
+pub(crate) fn js_map_entries(
+    value: &JsValue,
+    type_path: &str,
+    ctx: &mut Context,
+) -> Result<Vec<(JsValue, JsValue)>, ConversionError> {
+    map_entries(&mut BoaEngine::reader(ctx), value, type_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::errors::ConversionErrorKind;
+    use crate::into::reflect_to_js_value;
+
+    use super::*;
+
+    #[derive(Reflect, Debug, Default, PartialEq)]
+    struct Stats {
+        health: u32,
+        speed: f32,
+        #[reflect(default)]
+        name: String,
+    }
+
+    #[derive(Reflect, Debug, PartialEq)]
+    struct Pair(u8, u8);
 
-    if let Ok(variant) = obj.get(js_str!("__variant"), ctx) {
-        if !variant.is_null_or_undefined() {
-            // We can't handle enums right now... it's a bit complicated
-            return Err(JsError::from_opaque(
-                js_str!("Enums are not supported").into(),
+    #[derive(Reflect, Debug, PartialEq)]
+    enum Mode {
+        Idle,
+        Walk(f32),
+        Attack { target: u32 },
+    }
+
+    fn registry() -> TypeRegistry {
+        let mut registry = TypeRegistry::default();
+        registry.register::<Stats>();
+        registry.register::<Pair>();
+        registry.register::<(u8, u8)>();
+        registry.register::<Mode>();
+        registry.register::<Option<Mode>>();
+        registry
+    }
+
+    fn eval(source: &str, ctx: &mut Context) -> JsValue {
+        ctx.eval(boa_engine::Source::from_bytes(source)).unwrap()
+    }
+
+    #[test]
+    fn first_error_stops_at_the_first_bad_field() {
+        let registry = registry();
+        let mut ctx = Context::default();
+        let value = eval("({ health: 10, speed: 1.5, name: 'orc' })", &mut ctx);
+        let stats = js_value_to_typed::<Stats>(value, &registry, &mut ctx).unwrap();
+        assert_eq!(
+            stats,
+            Stats {
+                health: 10,
+                speed: 1.5,
+                name: "orc".into()
+            }
+        );
+
+        let value = eval("({ health: 'full', speed: 'fast', name: 'orc' })", &mut ctx);
+        let err = js_value_to_typed::<Stats>(value, &registry, &mut ctx).unwrap_err();
+        assert_eq!(err.kind(), ConversionErrorKind::TypeMismatch);
+        assert_eq!(err.path().to_string(), "Stats.health");
+    }
+
+    #[test]
+    fn all_errors_collects_every_bad_field() {
+        let registry = registry();
+        let mut ctx = Context::default();
+        let value = eval("({ health: 'full', speed: 'fast', name: 'orc' })", &mut ctx);
+        let errors = js_value_to_typed_all::<Stats>(value, &registry, &mut ctx).unwrap_err();
+        let paths = errors
+            .iter()
+            .map(|err| err.path().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(paths, ["Stats.health", "Stats.speed"]);
+    }
+
+    #[test]
+    fn check_reports_errors_and_unknown_fields() {
+        let registry = registry();
+        let mut ctx = Context::default();
+        let value = eval(
+            "({ health: 10, speed: 1.5, name: 'orc', sped: 2 })",
+            &mut ctx,
+        );
+        let issues = can_convert(&value, TypeId::of::<Stats>(), &registry, &mut ctx);
+        assert!(matches!(
+            &issues[..],
+            [ConversionIssue::UnknownField { name, .. }] if name == "sped"
+        ));
+
+        let value = eval("({ health: -1, speed: 'fast', name: 'orc' })", &mut ctx);
+        let issues = can_convert(&value, TypeId::of::<Stats>(), &registry, &mut ctx);
+        assert_eq!(issues.len(), 2);
+        assert!(issues
+            .iter()
+            .all(|issue| matches!(issue, ConversionIssue::Error(_))));
+    }
+
+    #[test]
+    fn lenient_skips_bad_fields_with_defaults() {
+        let registry = registry();
+        let mut ctx = Context::default();
+        let value = eval("({ health: 10, speed: 1.5, name: 7 })", &mut ctx);
+        let stats = js_value_to_typed_lenient::<Stats>(value, &registry, &mut ctx).unwrap();
+        assert_eq!(
+            stats,
+            Stats {
+                health: 10,
+                speed: 1.5,
+                name: String::new()
+            }
+        );
+    }
+
+    #[test]
+    fn tuples_of_the_wrong_length_fail_in_every_mode() {
+        let registry = registry();
+        let mut ctx = Context::default();
+        for source in ["[1]", "[1, 2, 3]"] {
+            let value = eval(source, &mut ctx);
+            let err = js_value_to_typed::<Pair>(value.clone(), &registry, &mut ctx).unwrap_err();
+            assert_eq!(err.kind(), ConversionErrorKind::WrongLength);
+            let err =
+                js_value_to_typed::<(u8, u8)>(value.clone(), &registry, &mut ctx).unwrap_err();
+            assert_eq!(err.kind(), ConversionErrorKind::WrongLength);
+            let err = js_value_to_typed_lenient::<(u8, u8)>(value.clone(), &registry, &mut ctx)
+                .unwrap_err();
+            assert_eq!(err.kind(), ConversionErrorKind::WrongLength);
+            let errors =
+                js_value_to_typed_all::<Pair>(value.clone(), &registry, &mut ctx).unwrap_err();
+            assert!(errors
+                .iter()
+                .any(|err| err.kind() == ConversionErrorKind::WrongLength));
+            let issues = can_convert(&value, TypeId::of::<(u8, u8)>(), &registry, &mut ctx);
+            assert!(matches!(
+                &issues[..],
+                [ConversionIssue::Error(err)] if err.kind() == ConversionErrorKind::WrongLength
             ));
         }
+        let value = eval("[1, 2]", &mut ctx);
+        let pair = js_value_to_typed::<Pair>(value, &registry, &mut ctx).unwrap();
+        assert_eq!(pair, Pair(1, 2));
     }
 
-    Ok(Box::new(dynamic_struct))
+    #[test]
+    fn options_read_the_bare_tagged_enum_inside() {
+        let registry = registry();
+        let mut ctx = Context::default();
+        for mode in [
+            Some(Mode::Idle),
+            Some(Mode::Walk(2.5)),
+            Some(Mode::Attack { target: 3 }),
+            None,
+        ] {
+            let value = reflect_to_js_value(&mode, &mut ctx).unwrap();
+            let back = js_value_to_typed::<Option<Mode>>(value, &registry, &mut ctx).unwrap();
+            assert_eq!(back, mode);
+        }
+
+        let value = eval("({ __variant: 'Attack', target: 4 })", &mut ctx);
+        let mode = js_value_to_typed::<Option<Mode>>(value, &registry, &mut ctx).unwrap();
+        assert_eq!(mode, Some(Mode::Attack { target: 4 }));
+        let value = eval("'Idle'", &mut ctx);
+        let mode = js_value_to_typed::<Option<Mode>>(value, &registry, &mut ctx).unwrap();
+        assert_eq!(mode, Some(Mode::Idle));
+        let value = eval("null", &mut ctx);
+        let mode = js_value_to_typed::<Option<Mode>>(value, &registry, &mut ctx).unwrap();
+        assert_eq!(mode, None);
+    }
 }
@@ -0,0 +1,221 @@
+use std::any::TypeId;
+use std::borrow::Cow;
+use std::sync::Arc;
+
+use bevy::ecs::reflect::AppTypeRegistry;
+use bevy::prelude::*;
+use bevy::reflect::{GetTypeRegistration, TypePath, TypeRegistry};
+use bevy::utils::Instant;
+use boa_engine::object::builtins::JsFunction;
+use boa_engine::object::FunctionObjectBuilder;
+use boa_engine::property::Attribute;
+use boa_engine::{Context, JsError, JsNativeError, JsResult, JsString, JsValue, NativeFunction};
+
+use crate::errors::attach_js_stack;
+use crate::from::js_value_to_typed_reflect;
+use crate::into::reflect_to_js_value;
+use crate::profiling::ScriptProfiler;
+
+type ErasedFunction =
+    dyn Fn(Vec<Box<dyn Reflect>>) -> JsResult<Option<Box<dyn Reflect>>> + Send + Sync;
+
+/// A type-erased Rust function that scripts can call. bevy_reflect 0.14 has no `DynamicFunction`,
+/// so this fills its role: the argument types are recorded so JS arguments can be converted into
+/// their shape before the call, and the return value is converted back through
+/// `reflect_to_js_value`.
+#[derive(Clone)]
+pub struct ReflectFunction {
+    name: Cow<'static, str>,
+    args: Vec<TypeId>,
+    function: Arc<ErasedFunction>,
+    register_types: Option<fn(&mut TypeRegistry)>,
+    profiler: Option<ScriptProfiler>,
+}
+
+impl ReflectFunction {
+    /// Wrap a function taking reflected arguments of the given types. Arguments are passed in the
+    /// shape of their type, which may be a dynamic value, so use `FromReflect` to get concrete
+    /// values out of them.
+    pub fn new<F>(name: impl Into<Cow<'static, str>>, args: Vec<TypeId>, function: F) -> Self
+    where
+        F: Fn(Vec<Box<dyn Reflect>>) -> JsResult<Option<Box<dyn Reflect>>> + Send + Sync + 'static,
+    {
+        Self {
+            name: name.into(),
+            args,
+            function: Arc::new(function),
+            register_types: None,
+            profiler: None,
+        }
+    }
+
+    /// Set a function registering the argument types, called before the function is installed so
+    /// its arguments can be converted even if the app never registered their types.
+    pub fn with_type_registrations(mut self, register_types: fn(&mut TypeRegistry)) -> Self {
+        self.register_types = Some(register_types);
+        self
+    }
+
+    /// Record each call's time with a profiler, under `fn/<name>`.
+    pub fn with_profiler(mut self, profiler: ScriptProfiler) -> Self {
+        self.profiler = Some(profiler);
+        self
+    }
+
+    /// Register the argument types with the registry, if the function knows how.
+    pub fn register_types(&self, registry: &mut TypeRegistry) {
+        if let Some(register_types) = self.register_types {
+            register_types(registry);
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The types of the function's arguments.
+    pub fn args(&self) -> &[TypeId] {
+        &self.args
+    }
+
+    /// Call the function with JS arguments, converting them with the registry. Missing arguments
+    /// are passed as `undefined`.
+    pub fn call(
+        &self,
+        args: &[JsValue],
+        registry: &TypeRegistry,
+        ctx: &mut Context,
+    ) -> JsResult<JsValue> {
+        let args = self
+            .args
+            .iter()
+            .enumerate()
+            .map(|(idx, type_id)| {
+                let value = args.get(idx).cloned().unwrap_or_default();
+                js_value_to_typed_reflect(value, *type_id, registry, ctx).map_err(JsError::from)
+            })
+            .collect::<JsResult<Vec<_>>>()?;
+        let start = Instant::now();
+        let result = (self.function)(args);
+        if let Some(profiler) = &self.profiler {
+            profiler.record(&format!("fn/{}", self.name), start.elapsed());
+        }
+        match result? {
+            Some(value) => reflect_to_js_value(value.as_ref(), ctx),
+            None => Ok(JsValue::undefined()),
+        }
+    }
+
+    /// Create a JS function that calls this function, converting its arguments with the registry.
+    pub fn to_js_function(&self, registry: &AppTypeRegistry, ctx: &mut Context) -> JsFunction {
+        let function = self.clone();
+        let registry = registry.clone();
+        // SAFETY: the closure only captures the function and the type registry, neither of which
+        // hold garbage collected values.
+        let native = unsafe {
+            NativeFunction::from_closure(move |_, args, ctx| {
+                function
+                    .call(args, &registry.read(), ctx)
+                    .map_err(|err| attach_js_stack(err, ctx))
+            })
+        };
+        FunctionObjectBuilder::new(ctx.realm(), native)
+            .name(JsString::from(self.name()))
+            .length(self.args.len())
+            .build()
+    }
+}
+
+/// Functions installed as script globals when the plugin starts.
+#[derive(Resource, Default, Clone)]
+pub struct ScriptFunctions {
+    functions: Vec<ReflectFunction>,
+}
+
+impl ScriptFunctions {
+    /// Add a function, replacing any previously registered function with the same name.
+    pub fn add(&mut self, function: ReflectFunction) -> &mut Self {
+        self.functions
+            .retain(|existing| existing.name() != function.name());
+        self.functions.push(function);
+        self
+    }
+
+    /// Add a Rust closure, converting its arguments from JS and its return value back.
+    pub fn register<Marker>(
+        &mut self,
+        name: &'static str,
+        function: impl IntoReflectFunction<Marker>,
+    ) -> &mut Self {
+        self.add(function.into_reflect_function(name))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &ReflectFunction> {
+        self.functions.iter()
+    }
+}
+
+/// Register a Rust closure as a global function in the context, converting its arguments from JS
+/// and its return value back, e.g.
+/// `register_fn(ctx, "spawnExplosion", |pos: Vec3, strength: f32| { ... })`.
+pub fn register_fn<Marker>(
+    ctx: &mut Context,
+    name: &'static str,
+    function: impl IntoReflectFunction<Marker>,
+) -> JsResult<()> {
+    let function = function.into_reflect_function(name);
+    let registry = AppTypeRegistry::default();
+    function.register_types(&mut registry.write());
+    let js_function = function.to_js_function(&registry, ctx);
+    ctx.register_global_property(JsString::from(name), js_function, Attribute::all())
+}
+
+/// A Rust closure that can be called from scripts. Implemented for closures of up to six
+/// arguments that implement `FromReflect`, returning any reflected value. Returning `()` gives
+/// `undefined` in JS.
+pub trait IntoReflectFunction<Marker>: Send + Sync + 'static {
+    fn into_reflect_function(self, name: &'static str) -> ReflectFunction;
+}
+
+macro_rules! impl_into_reflect_function {
+    ($($arg:ident),*) => {
+        impl<F, R, $($arg,)*> IntoReflectFunction<fn($($arg,)*) -> R> for F
+        where
+            F: Fn($($arg,)*) -> R + Send + Sync + 'static,
+            R: Reflect,
+            $($arg: FromReflect + TypePath + GetTypeRegistration,)*
+        {
+            #[allow(non_snake_case, unused_mut, unused_variables)]
+            fn into_reflect_function(self, name: &'static str) -> ReflectFunction {
+                let args = vec![$(TypeId::of::<$arg>(),)*];
+                ReflectFunction::new(name, args, move |args| {
+                    let mut args = args.into_iter().enumerate();
+                    $(
+                        let $arg = args
+                            .next()
+                            .and_then(|(_, arg)| $arg::from_reflect(arg.as_ref()))
+                            .ok_or_else(|| {
+                                JsNativeError::typ().with_message(format!(
+                                        "Invalid {} argument for {name}",
+                                        $arg::type_path()
+                                    ))
+                            })?;
+                    )*
+                    let result: Box<dyn Reflect> = Box::new((self)($($arg,)*));
+                    Ok((!result.is::<()>()).then_some(result))
+                })
+                .with_type_registrations(|registry| {
+                    $(registry.register::<$arg>();)*
+                })
+            }
+        }
+    };
+}
+
+impl_into_reflect_function!();
+impl_into_reflect_function!(A);
+impl_into_reflect_function!(A, B);
+impl_into_reflect_function!(A, B, C);
+impl_into_reflect_function!(A, B, C, D);
+impl_into_reflect_function!(A, B, C, D, E);
+impl_into_reflect_function!(A, B, C, D, E, G);
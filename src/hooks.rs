@@ -0,0 +1,156 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use boa_engine::{Context, Finalize, JsData, JsValue, Trace};
+
+use crate::errors::{ConversionError, FieldPath};
+
+/// Looks at a value passing through a conversion, given the path of the type it is converted
+/// from or to, and where it is in the converted value.
+pub type ConversionHook = fn(&str, &FieldPath, &JsValue) -> HookAction;
+
+/// What a [`ConversionHook`] does with a value.
+#[derive(Debug, Clone)]
+pub enum HookAction {
+    /// Convert the value as it is.
+    Keep,
+    /// Convert this value in its place, e.g. a distance in other units.
+    Replace(JsValue),
+    /// Fail the conversion of the value with a reason, as any other value that can't be
+    /// converted fails.
+    Veto(String),
+}
+
+/// Hooks run on every value of the typed conversions in a context, for policies that cut across
+/// types, like converting units or redacting sensitive fields:
+///
+/// ```no_run
+/// # use bevy_boa_reflect::{ConversionHooks, FieldPath, HookAction, PathSegment};
+/// # use boa_engine::JsValue;
+/// # let ctx = &mut boa_engine::Context::default();
+/// fn redact(_: &str, path: &FieldPath, _: &JsValue) -> HookAction {
+///     let password = PathSegment::Field("password".to_owned());
+///     if path.segments.last() == Some(&password) {
+///         return HookAction::Replace(JsValue::Null);
+///     }
+///     HookAction::Keep
+/// }
+///
+/// ConversionHooks::register_post(ctx, redact);
+/// ```
+///
+/// Pre hooks run on values read from JS before they are converted into a type, and post hooks on
+/// values made for JS once they are converted, after converters registered with
+/// [`JsConverters`](crate::JsConverters). Hooks run in the order they were registered, each on
+/// what the one before it returned, and the first veto wins. Paths are only tracked while a
+/// context has hooks, so conversions without them don't pay for it.
+///
+/// Post hooks see every value converted for JS. While a context has any, the parallel
+/// conversions convert on the calling thread, columns of numbers become arrays of objects rather
+/// than typed arrays, and a cached component that changed is converted again whole rather than
+/// patched field by field.
+#[derive(Debug, Default, Trace, Finalize, JsData)]
+pub struct ConversionHooks {
+    /// Replaced rather than changed in place, so a conversion can hold on to the hooks it
+    /// started with while hooks run.
+    #[unsafe_ignore_trace]
+    hooks: RefCell<Rc<Hooks>>,
+}
+
+#[derive(Debug, Default, Clone)]
+pub(crate) struct Hooks {
+    pre: Vec<ConversionHook>,
+    post: Vec<ConversionHook>,
+}
+
+impl ConversionHooks {
+    /// Run `hook` on values read from JS, before they are converted.
+    pub fn register_pre(ctx: &mut Context, hook: ConversionHook) {
+        Self::update(ctx, |hooks| hooks.pre.push(hook));
+    }
+
+    /// Run `hook` on values made for JS, after they are converted.
+    pub fn register_post(ctx: &mut Context, hook: ConversionHook) {
+        Self::update(ctx, |hooks| hooks.post.push(hook));
+    }
+
+    /// Remove every hook from a context.
+    pub fn clear(ctx: &mut Context) {
+        Self::update(ctx, |hooks| *hooks = Hooks::default());
+    }
+
+    fn update(ctx: &mut Context, f: impl FnOnce(&mut Hooks)) {
+        if !ctx.has_data::<Self>() {
+            ctx.insert_data(Self::default());
+        }
+        if let Some(registered) = ctx.get_data::<Self>() {
+            let mut hooks = registered.hooks.borrow_mut();
+            f(Rc::make_mut(&mut hooks));
+        }
+    }
+
+    /// Whether a context has hooks to run on values made for JS. Conversions with shortcuts
+    /// around the usual walk, like parallel, column and cached conversions, take the usual walk
+    /// instead while it does, so no value gets past the hooks.
+    pub(crate) fn has_post_hooks(ctx: &Context) -> bool {
+        Self::of(ctx).is_some_and(|hooks| hooks.has_post())
+    }
+
+    /// The hooks registered in a context, or `None` if there are none.
+    pub(crate) fn of(ctx: &Context) -> Option<Rc<Hooks>> {
+        let hooks = ctx.get_data::<Self>()?.hooks.borrow().clone();
+        (!hooks.pre.is_empty() || !hooks.post.is_empty()).then_some(hooks)
+    }
+}
+
+impl Hooks {
+    pub(crate) fn has_pre(&self) -> bool {
+        !self.pre.is_empty()
+    }
+
+    pub(crate) fn has_post(&self) -> bool {
+        !self.post.is_empty()
+    }
+
+    /// Run the pre hooks on a value read from JS.
+    pub(crate) fn pre(
+        &self,
+        type_path: &str,
+        path: &FieldPath,
+        value: &mut JsValue,
+    ) -> Result<(), ConversionError> {
+        run(&self.pre, type_path, path, value)
+    }
+
+    /// Run the post hooks on a value made for JS.
+    pub(crate) fn post(
+        &self,
+        type_path: &str,
+        path: &FieldPath,
+        value: &mut JsValue,
+    ) -> Result<(), ConversionError> {
+        run(&self.post, type_path, path, value)
+    }
+}
+
+fn run(
+    hooks: &[ConversionHook],
+    type_path: &str,
+    path: &FieldPath,
+    value: &mut JsValue,
+) -> Result<(), ConversionError> {
+    for hook in hooks {
+        match hook(type_path, path, value) {
+            HookAction::Keep => {}
+            HookAction::Replace(replaced) => *value = replaced,
+            HookAction::Veto(reason) => {
+                return Err(ConversionError::Vetoed {
+                    type_path: type_path.to_owned(),
+                    path: FieldPath::default(),
+                    reason,
+                })
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,83 @@
+use bevy::ecs::reflect::{AppTypeRegistry, ReflectComponent};
+use bevy::prelude::*;
+use bevy::reflect::{FromReflect, TypePath, TypeRegistry};
+use boa_engine::{Context, JsNativeError, JsResult, JsValue, Source};
+
+use crate::classes::is_identifier;
+use crate::from::js_value_to_typed;
+use crate::into::reflect_to_js_value;
+use crate::plugin::with_runtime;
+use crate::runtime::entity_to_js_value;
+
+/// Evaluate an expression with an entity's reflected components bound as locals named after their
+/// short type paths, and `entity` bound to the entity, converting the result into `T`. This is the
+/// building block for editor filters and watch windows, e.g.
+/// `eval_on_entity::<bool>(world, entity, "Health.current < 10 && Transform.translation.y > 0")`.
+pub fn eval_on_entity<T: FromReflect + TypePath>(
+    world: &mut World,
+    entity: Entity,
+    expression: &str,
+) -> JsResult<T> {
+    eval_on_entity_with(world, entity, expression, |result, registry, ctx| {
+        Ok(js_value_to_typed(result, registry, ctx)?)
+    })
+}
+
+/// Evaluate an expression like [`eval_on_entity`], handing its result to `f` with the runtime's
+/// context still available.
+pub(crate) fn eval_on_entity_with<R>(
+    world: &mut World,
+    entity: Entity,
+    expression: &str,
+    f: impl FnOnce(JsValue, &TypeRegistry, &mut Context) -> JsResult<R>,
+) -> JsResult<R> {
+    let registry = world.resource::<AppTypeRegistry>().clone();
+    let components = {
+        let registry = registry.read();
+        let entity_ref = world.get_entity(entity).ok_or_else(|| {
+            JsNativeError::typ().with_message(format!("Entity {entity} does not exist"))
+        })?;
+        entity_ref
+            .archetype()
+            .components()
+            .filter_map(|id| world.components().get_info(id)?.type_id())
+            .filter_map(|type_id| {
+                let registration = registry.get(type_id)?;
+                let name = registration.type_info().type_path_table().short_path();
+                if !is_identifier(name) {
+                    return None;
+                }
+                let component = registration
+                    .data::<ReflectComponent>()?
+                    .reflect(entity_ref)?
+                    .clone_value();
+                Some((name, component))
+            })
+            .collect::<Vec<_>>()
+    };
+
+    with_runtime(world, |runtime| {
+        let ctx = runtime.context();
+        let mut names = vec!["entity"];
+        let mut values = vec![entity_to_js_value(entity)];
+        for (name, component) in &components {
+            names.push(name);
+            values.push(reflect_to_js_value(component.as_ref(), ctx)?);
+        }
+        let source = format!(
+            "(function({}) {{ return ({expression}); }})",
+            names.join(", ")
+        );
+        let function = ctx.eval(Source::from_bytes(&source))?;
+        let function = function.as_callable().ok_or_else(|| {
+            JsNativeError::error().with_message("Expression did not compile to a function")
+        })?;
+        let result = function.call(&JsValue::undefined(), &values, ctx)?;
+        f(result, &registry.read(), ctx)
+    })
+    .unwrap_or_else(|| {
+        Err(JsNativeError::error()
+            .with_message("The script runtime is not available")
+            .into())
+    })
+}
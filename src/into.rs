@@ -1,158 +1,709 @@
-use anyhow::Context as AnyhowContext;
-use bevy::prelude::*;
-use bevy::reflect::{Array, Enum, List, Map, Reflect, ReflectRef, Tuple};
-use boa_engine::object::builtins::{JsArray, JsMap, JsSet};
-use boa_engine::property::Attribute;
-use boa_engine::{
-    js_str, object::ObjectInitializer, Context, JsError, JsResult, JsString, JsValue,
-};
+use std::borrow::Cow;
+use std::vec::Drain;
 
+use bevy_reflect::prelude::*;
+use bevy_reflect::{Enum, Reflect, ReflectRef, VariantType};
+#[cfg(feature = "verbose")]
+use bevy_utils::tracing::trace;
+use bevy_utils::HashMap;
+use boa_engine::object::builtins::JsArray;
+use boa_engine::{Context, JsResult, JsValue};
+
+use crate::engine::{field_names, BoaEngine, JsEngine, Key, ObjectKeys, Primitive};
+use crate::errors::{ConversionError, FieldPath, PathSegment};
+use crate::report::ConversionReport;
+use crate::settings::{ConversionSettings, EnumRepresentation, NumberPolicy};
+
+/// Convert a reflected value into a `JsValue`. Structs and enums become objects, lists, arrays and
+/// tuples become arrays, and maps become `Map`s.
 pub fn reflect_to_js_value(value: &dyn Reflect, ctx: &mut Context) -> JsResult<JsValue> {
-    match value.reflect_ref() {
-        ReflectRef::Struct(s) => reflect_to_js_object(s, ctx),
-        ReflectRef::TupleStruct(t) => reflect_tuple_struct_to_js_array(t, ctx),
-        ReflectRef::Tuple(t) => reflect_tuple_to_js_array(t, ctx),
-        ReflectRef::List(l) => reflect_list_to_js_array(l, ctx),
-        ReflectRef::Array(a) => reflect_array_to_js_array(a, ctx),
-        ReflectRef::Map(m) => reflect_map_to_js_map(m, ctx),
-        ReflectRef::Enum(e) => reflect_enum_to_js_value(e, ctx),
-        ReflectRef::Value(v) => primitive_to_js_value(v, ctx),
-    }
-}
-
-fn reflect_to_js_object(reflect_struct: &dyn Struct, ctx: &mut Context) -> JsResult<JsValue> {
-    let mut obj = reflect_struct
-        .iter_fields()
-        .enumerate()
-        .map(|(idx, field)| {
-            let js_value = match field.reflect_ref() {
-                ReflectRef::Struct(s) => reflect_to_js_object(s, ctx)?,
-                ReflectRef::TupleStruct(t) => reflect_tuple_struct_to_js_array(t, ctx)?,
-                ReflectRef::Tuple(t) => reflect_tuple_to_js_array(t, ctx)?,
-                ReflectRef::List(l) => reflect_list_to_js_array(l, ctx)?,
-                ReflectRef::Array(a) => reflect_array_to_js_array(a, ctx)?,
-                ReflectRef::Map(m) => reflect_map_to_js_map(m, ctx)?,
-                ReflectRef::Enum(e) => reflect_enum_to_js_value(e, ctx)?,
-                ReflectRef::Value(v) => primitive_to_js_value(v, ctx)?,
-            };
-            let field_name = reflect_struct
-                .name_at(idx)
-                .ok_or_else(|| JsError::from_opaque(js_str!("Could not read field").into()))?;
-            Ok((JsString::from(field_name), js_value))
-        })
-        .collect::<JsResult<Vec<(JsString, JsValue)>>>()?
-        .into_iter()
-        .fold(ObjectInitializer::new(ctx), |mut obj, (k, v)| {
-            obj.property(k, v, Attribute::all());
-            obj
-        });
+    Ok(try_reflect_to_js_value(value, ctx)?)
+}
 
-    Ok(obj.build().into())
+/// Like [`reflect_to_js_value`], returning a [`ConversionError`] for Rust callers rather than an
+/// error to throw.
+pub fn try_reflect_to_js_value(
+    value: &dyn Reflect,
+    ctx: &mut Context,
+) -> Result<JsValue, ConversionError> {
+    #[cfg(feature = "trace")]
+    let _span = bevy_utils::tracing::info_span!(
+        "reflect_to_js_value",
+        type_path = value.reflect_type_path(),
+        elements = crate::trace::element_count(value),
+    )
+    .entered();
+    to_js_value(value, &ConversionSettings::DEFAULT, ctx, None)
 }
 
-fn reflect_tuple_struct_to_js_array(
-    tuple: &dyn TupleStruct,
-    context: &mut Context,
+/// Like [`reflect_to_js_value`], converting as `settings` say rather than by default.
+pub fn reflect_to_js_value_with_settings(
+    value: &dyn Reflect,
+    settings: &ConversionSettings,
+    ctx: &mut Context,
 ) -> JsResult<JsValue> {
-    let array = JsArray::new(context);
-    for field in tuple.iter_fields() {
-        let js_value = reflect_to_js_value(field, context)?;
-        array.push(js_value, context)?;
+    Ok(try_reflect_to_js_value_with_settings(value, settings, ctx)?)
+}
+
+/// Like [`reflect_to_js_value_with_settings`], returning a [`ConversionError`] for Rust callers.
+pub fn try_reflect_to_js_value_with_settings(
+    value: &dyn Reflect,
+    settings: &ConversionSettings,
+    ctx: &mut Context,
+) -> Result<JsValue, ConversionError> {
+    to_js_value(value, settings, ctx, None)
+}
+
+/// Like [`reflect_to_js_value`], also reporting what the conversion cost.
+pub fn reflect_to_js_value_with_report(
+    value: &dyn Reflect,
+    ctx: &mut Context,
+) -> JsResult<(JsValue, ConversionReport)> {
+    Ok(try_reflect_to_js_value_with_report(value, ctx)?)
+}
+
+/// Like [`reflect_to_js_value_with_report`], returning a [`ConversionError`] for Rust callers.
+pub fn try_reflect_to_js_value_with_report(
+    value: &dyn Reflect,
+    ctx: &mut Context,
+) -> Result<(JsValue, ConversionReport), ConversionError> {
+    let mut report = ConversionReport::default();
+    let value = to_js_value(value, &ConversionSettings::DEFAULT, ctx, Some(&mut report))?;
+    Ok((value, report))
+}
+
+/// Convert many values into one array, e.g. the components of a query's results. The values are
+/// converted in a single walk sharing the context's cached object templates, and the array is
+/// built from them at once, which is much faster than converting them one at a time.
+pub fn reflect_slice_to_js_array(values: &[&dyn Reflect], ctx: &mut Context) -> JsResult<JsArray> {
+    Ok(try_reflect_slice_to_js_array(values, ctx)?)
+}
+
+/// Like [`reflect_slice_to_js_array`], returning a [`ConversionError`] for Rust callers.
+pub fn try_reflect_slice_to_js_array(
+    values: &[&dyn Reflect],
+    ctx: &mut Context,
+) -> Result<JsArray, ConversionError> {
+    #[cfg(feature = "trace")]
+    let _span =
+        bevy_utils::tracing::info_span!("reflect_slice_to_js_array", elements = values.len())
+            .entered();
+    slice_to_js_array(values, &ConversionSettings::DEFAULT, ctx)
+}
+
+/// Like [`reflect_slice_to_js_array`], converting as `settings` say rather than by default.
+pub fn reflect_slice_to_js_array_with_settings(
+    values: &[&dyn Reflect],
+    settings: &ConversionSettings,
+    ctx: &mut Context,
+) -> JsResult<JsArray> {
+    Ok(try_reflect_slice_to_js_array_with_settings(
+        values, settings, ctx,
+    )?)
+}
+
+/// Like [`reflect_slice_to_js_array_with_settings`], returning a [`ConversionError`] for Rust
+/// callers.
+pub fn try_reflect_slice_to_js_array_with_settings(
+    values: &[&dyn Reflect],
+    settings: &ConversionSettings,
+    ctx: &mut Context,
+) -> Result<JsArray, ConversionError> {
+    slice_to_js_array(values, settings, ctx)
+}
+
+fn slice_to_js_array(
+    values: &[&dyn Reflect],
+    settings: &ConversionSettings,
+    ctx: &mut Context,
+) -> Result<JsArray, ConversionError> {
+    let steps = values
+        .iter()
+        .rev()
+        .map(|value| Step::Convert(*value, 1, None))
+        .collect();
+    let converted = run_steps(steps, settings, &mut BoaEngine::new(ctx), None)?;
+    Ok(JsArray::from_iter(converted, ctx))
+}
+
+/// A step in converting a value. Like the conversions from JS, values are walked with an explicit
+/// stack rather than by recursing, so deeply nested data can't overflow the native stack.
+/// A step in converting a value. Like the conversions from JS, values are walked with an explicit
+/// stack rather than by recursing, so deeply nested data can't overflow the native stack.
+enum Step<'a> {
+    /// Convert a value, nested at a depth. The segment of its path is only given while there
+    /// are hooks to run.
+    Convert(&'a dyn Reflect, usize, Option<PathSegment>),
+    /// Run the post hooks on a value once it is converted, and leave its path.
+    Leave(&'a dyn Reflect),
+    /// Collect the last converted values into an object keyed by the struct's field names.
+    Struct(&'a dyn Struct),
+    /// Collect the last `len` converted values into an array.
+    Array(usize),
+    /// Collect the last `len` converted keys and values into a `Map`.
+    Map(usize),
+    /// Collect the last converted values into an object for the enum's variant.
+    Enum(&'a dyn Enum),
+}
+
+fn to_js_value(
+    value: &dyn Reflect,
+    settings: &ConversionSettings,
+    ctx: &mut Context,
+    report: Option<&mut ConversionReport>,
+) -> Result<JsValue, ConversionError> {
+    to_engine_value(value, settings, &mut BoaEngine::new(ctx), report)
+}
+
+/// Convert a reflected value into a value of any [`JsEngine`], in the shape
+/// [`reflect_to_js_value_with_settings`] gives it in Boa.
+pub fn reflect_to_engine_value<E: JsEngine>(
+    value: &dyn Reflect,
+    settings: &ConversionSettings,
+    engine: &mut E,
+) -> Result<E::Value, ConversionError> {
+    to_engine_value(value, settings, engine, None)
+}
+
+fn to_engine_value<E: JsEngine>(
+    value: &dyn Reflect,
+    settings: &ConversionSettings,
+    engine: &mut E,
+    report: Option<&mut ConversionReport>,
+) -> Result<E::Value, ConversionError> {
+    let mut converted = run_steps(
+        vec![Step::Convert(value, 1, None)],
+        settings,
+        engine,
+        report,
+    )?;
+    Ok(converted.pop().unwrap_or_default())
+}
+
+/// Run conversion steps, returning the values of the steps given in the order they ran.
+fn run_steps<'a, E: JsEngine>(
+    mut steps: Vec<Step<'a>>,
+    settings: &ConversionSettings,
+    engine: &mut E,
+    mut report: Option<&mut ConversionReport>,
+) -> Result<Vec<E::Value>, ConversionError> {
+    let mut hooked = engine.has_post_hooks().then(Hooked::default);
+    let mut converted = Vec::new();
+    let mut strings = RepeatedStrings::default();
+    while let Some(step) = steps.pop() {
+        let value = match step {
+            Step::Convert(value, depth, segment) => {
+                if let Some(report) = report.as_deref_mut() {
+                    report.value(value.reflect_kind(), depth);
+                }
+                if let Some(hooked) = &mut hooked {
+                    hooked.enter(value, segment);
+                }
+                settings.check_depth(depth, || value.reflect_type_path())?;
+                if let Some(own) = engine.convert(value) {
+                    converted.push(own?);
+                    Hooked::leave(&mut hooked, value, &mut converted, engine)?;
+                    continue;
+                }
+                // Common primitives are found by their type first, skipping the match on the
+                // value's kind.
+                let visited = match Primitive::lookup(value) {
+                    Some(primitive) => Visited::Leaf(primitive),
+                    None => visit(value),
+                };
+                let (step, children) = match visited {
+                    Visited::Aggregate(step, children) => (step, children),
+                    Visited::Leaf(primitive) => {
+                        let primitive = match settings.numbers {
+                            NumberPolicy::Exact => primitive,
+                            NumberPolicy::Lossy => primitive.into_number(),
+                        };
+                        if let Some(report) = report.as_deref_mut() {
+                            report.string(primitive.string_len());
+                        }
+                        let made = strings.value(primitive, engine);
+                        #[cfg(feature = "verbose")]
+                        trace!(
+                            target: "bevy_boa_reflect::conversions",
+                            "{depth}: {} {} -> {}",
+                            value.reflect_type_path(),
+                            crate::verbose::reflect_summary(value),
+                            engine.summary(&made),
+                        );
+                        converted.push(made);
+                        Hooked::leave(&mut hooked, value, &mut converted, engine)?;
+                        continue;
+                    }
+                };
+                #[cfg(feature = "verbose")]
+                trace!(
+                    target: "bevy_boa_reflect::conversions",
+                    "{depth}: {} -> object with {} children",
+                    value.reflect_type_path(),
+                    children.len(),
+                );
+                if let Some(report) = report.as_deref_mut() {
+                    report.object();
+                    report.string(step_names_len(&step));
+                }
+                let segments = match hooked {
+                    Some(_) => child_segments(&step, &children),
+                    None => Vec::new(),
+                };
+                if hooked.is_some() {
+                    steps.push(Step::Leave(value));
+                }
+                steps.push(step);
+                let start = steps.len();
+                let mut segments = segments.into_iter();
+                steps.extend(
+                    children
+                        .into_iter()
+                        .map(|child| Step::Convert(child, depth + 1, segments.next())),
+                );
+                steps[start..].reverse();
+                continue;
+            }
+            Step::Leave(value) => {
+                Hooked::leave(&mut hooked, value, &mut converted, engine)?;
+                continue;
+            }
+            Step::Struct(s) => struct_to_object(s, &mut converted, settings, engine)?,
+            Step::Array(len) => engine.array(take_last(&mut converted, len))?,
+            Step::Map(len) => engine.map(take_last(&mut converted, len * 2))?,
+            Step::Enum(e) => enum_to_object(e, &mut converted, settings, engine)?,
+        };
+        converted.push(value);
+    }
+    Ok(converted)
+}
+
+/// The path to the value a conversion is converting, for the engine's hooks.
+#[derive(Default)]
+struct Hooked {
+    path: FieldPath,
+}
+
+impl Hooked {
+    /// Start converting a value, at a segment within its parent or as a new root.
+    fn enter(&mut self, value: &dyn Reflect, segment: Option<PathSegment>) {
+        match segment {
+            Some(segment) => self.path.segments.push(segment),
+            None => {
+                self.path.root = Some(value.reflect_short_type_path().to_owned());
+                self.path.segments.clear();
+            }
+        }
+    }
+
+    /// Run the post hooks on a value just converted, and leave its path.
+    fn leave<E: JsEngine>(
+        hooked: &mut Option<Self>,
+        value: &dyn Reflect,
+        converted: &mut [E::Value],
+        engine: &mut E,
+    ) -> Result<(), ConversionError> {
+        let (Some(hooked), Some(made)) = (hooked, converted.last_mut()) else {
+            return Ok(());
+        };
+        let type_path = value.reflect_type_path();
+        engine
+            .post_hook(type_path, &hooked.path, made)
+            .map_err(|err| err.with_path(hooked.path.clone()))?;
+        hooked.path.segments.pop();
+        Ok(())
+    }
+}
+
+/// The path segments of a value's children, in the order `visit` gives them.
+fn child_segments(step: &Step, children: &[&dyn Reflect]) -> Vec<PathSegment> {
+    match step {
+        Step::Struct(s) => (0..s.field_len())
+            .map(|idx| PathSegment::Field(s.name_at(idx).unwrap_or_default().to_owned()))
+            .collect(),
+        Step::Enum(e) => (0..e.field_len())
+            .map(|idx| match e.name_at(idx) {
+                Some(name) => PathSegment::Field(name.to_owned()),
+                None => PathSegment::Index(idx),
+            })
+            .collect(),
+        // Keys and values alternate, and both are found under the key.
+        Step::Map(_) => children
+            .chunks(2)
+            .flat_map(|entry| {
+                let key = PathSegment::Key(key_display(entry[0]));
+                [key.clone(), key]
+            })
+            .collect(),
+        _ => (0..children.len()).map(PathSegment::Index).collect(),
     }
-    Ok(array.into())
 }
 
-fn reflect_tuple_to_js_array(tuple: &dyn Tuple, context: &mut Context) -> JsResult<JsValue> {
-    let array = JsArray::new(context);
-    for field in tuple.iter_fields() {
-        let js_value = reflect_to_js_value(field, context)?;
-        array.push(js_value, context)?;
+/// A map key as scripts would display it.
+fn key_display(key: &dyn Reflect) -> String {
+    match Primitive::lookup(key) {
+        Some(Primitive::String(s)) => s.into_owned(),
+        Some(Primitive::Null) => "null".to_owned(),
+        Some(Primitive::Boolean(v)) => v.to_string(),
+        Some(Primitive::Integer(v)) => v.to_string(),
+        Some(Primitive::BigInt(v)) => format!("{v}n"),
+        Some(Primitive::BigUint(v)) => format!("{v}n"),
+        Some(Primitive::Rational(v)) => v.to_string(),
+        None => format!("{key:?}"),
     }
-    Ok(array.into())
 }
 
-fn reflect_list_to_js_array(list: &dyn List, context: &mut Context) -> JsResult<JsValue> {
-    let array = JsArray::new(context);
-    for item in list.iter() {
-        let js_value = reflect_to_js_value(item, context)?;
-        array.push(js_value, context)?;
+/// Strings made during a conversion, so short strings that repeat, like tags or names, are
+/// made once and shared rather than copied for each value.
+struct RepeatedStrings<'a, V> {
+    strings: HashMap<&'a str, V>,
+}
+
+impl<V> Default for RepeatedStrings<'_, V> {
+    fn default() -> Self {
+        Self {
+            strings: HashMap::default(),
+        }
     }
-    Ok(array.into())
 }
 
-fn reflect_array_to_js_array(array: &dyn Array, context: &mut Context) -> JsResult<JsValue> {
-    let js_array = JsArray::new(context);
-    for i in 0..array.len() {
-        let item = array.get(i).unwrap();
-        let js_value = reflect_to_js_value(item, context)?;
-        js_array.push(js_value, context)?;
+impl<'a, V: Clone> RepeatedStrings<'a, V> {
+    /// Strings up to this many bytes are kept. Longer ones rarely repeat.
+    const MAX_LEN: usize = 32;
+
+    fn value<E: JsEngine<Value = V>>(&mut self, primitive: Primitive<'a>, engine: &mut E) -> V {
+        match primitive {
+            Primitive::String(Cow::Borrowed(s)) if s.len() <= Self::MAX_LEN => self
+                .strings
+                .entry(s)
+                .or_insert_with(|| engine.primitive(Primitive::String(Cow::Borrowed(s))))
+                .clone(),
+            primitive => engine.primitive(primitive),
+        }
+    }
+}
+
+/// What a value turned out to be when visited.
+enum Visited<'a> {
+    /// A value converted directly.
+    Leaf(Primitive<'a>),
+    /// A value made of its children, collected by a step once they are converted.
+    Aggregate(Step<'a>, Vec<&'a dyn Reflect>),
+}
+
+fn visit(value: &dyn Reflect) -> Visited<'_> {
+    let (step, children): (Step, Vec<&dyn Reflect>) = match value.reflect_ref() {
+        ReflectRef::Struct(s) => (Step::Struct(s), s.iter_fields().collect()),
+        ReflectRef::TupleStruct(t) => (Step::Array(t.field_len()), t.iter_fields().collect()),
+        ReflectRef::Tuple(t) => (Step::Array(t.field_len()), t.iter_fields().collect()),
+        ReflectRef::List(l) => (Step::Array(l.len()), l.iter().collect()),
+        ReflectRef::Array(a) => (Step::Array(a.len()), a.iter().collect()),
+        ReflectRef::Map(m) => (
+            Step::Map(m.len()),
+            m.iter().flat_map(|(key, value)| [key, value]).collect(),
+        ),
+        ReflectRef::Enum(e) => (
+            Step::Enum(e),
+            e.iter_fields().map(|field| field.value()).collect(),
+        ),
+        // Value types without a primitive to read become `null`.
+        ReflectRef::Value(_) => return Visited::Leaf(Primitive::Null),
+    };
+    Visited::Aggregate(step, children)
+}
+
+/// The bytes of property names a step creates.
+fn step_names_len(step: &Step) -> usize {
+    match step {
+        Step::Struct(s) => (0..s.field_len())
+            .filter_map(|idx| s.name_at(idx))
+            .map(str::len)
+            .sum(),
+        Step::Enum(e) => {
+            let names = (0..e.field_len())
+                .filter_map(|idx| e.name_at(idx))
+                .map(str::len)
+                .sum::<usize>();
+            names + e.variant_name().len()
+        }
+        _ => 0,
     }
-    Ok(js_array.into())
 }
 
-fn reflect_map_to_js_map(map: &dyn Map, context: &mut Context) -> JsResult<JsValue> {
-    let js_map = JsMap::new(context);
-    for (key, value) in map.iter() {
-        let key_value = reflect_to_js_value(key, context)?;
-        let value_value = reflect_to_js_value(value, context)?;
-        js_map.set(key_value, value_value, context)?;
+/// Take the converted values of a step's children off the end of the converted values.
+/// Drained in place, so objects are built straight from the converted values without collecting
+/// them into a new `Vec` first.
+pub(crate) fn take_last<V>(converted: &mut Vec<V>, len: usize) -> Drain<'_, V> {
+    converted.drain(converted.len().saturating_sub(len)..)
+}
+
+fn struct_to_object<E: JsEngine>(
+    reflect_struct: &dyn Struct,
+    converted: &mut Vec<E::Value>,
+    settings: &ConversionSettings,
+    engine: &mut E,
+) -> Result<E::Value, ConversionError> {
+    let values = take_last(converted, reflect_struct.field_len());
+    if !settings.renames() {
+        return engine.object(ObjectKeys::Struct(reflect_struct), values);
     }
-    Ok(js_map.into())
+    let keys = field_names(reflect_struct)?
+        .into_iter()
+        .map(|name| Key::Name(settings.rename.apply(name)))
+        .collect::<Vec<_>>();
+    engine.object(ObjectKeys::Keys(&keys), values)
 }
 
-fn reflect_enum_to_js_value(enum_value: &dyn Enum, context: &mut Context) -> JsResult<JsValue> {
-    let variant_name = enum_value.variant_name();
-    let mut obj = enum_value
-        .iter_fields()
-        .map(|field_value| {
-            let name = field_value
-                .name()
-                .ok_or_else(|| JsError::from_opaque(js_str!("Could not read field name").into()))?;
-            let js_value = reflect_to_js_value(field_value.value(), context)?;
-            let js_str = JsString::from(name);
-            Ok((js_str, js_value))
+/// The object for an enum value in the representation the settings ask for. Tagged, that's the
+/// variant's fields, keyed by their names or their indices for tuple variants, and its name
+/// under `__variant`. Externally, it's the variant's fields under its name, as serde represents
+/// enums by default, or just the name for unit variants.
+fn enum_to_object<E: JsEngine>(
+    enum_value: &dyn Enum,
+    converted: &mut Vec<E::Value>,
+    settings: &ConversionSettings,
+    engine: &mut E,
+) -> Result<E::Value, ConversionError> {
+    let len = enum_value.field_len();
+    let mut keys = (0..len)
+        .map(|idx| match enum_value.name_at(idx) {
+            Some(name) => Key::Name(settings.rename.apply(name)),
+            None => Key::Index(idx),
         })
-        .collect::<JsResult<Vec<(JsString, JsValue)>>>()?
-        .iter()
-        .fold(ObjectInitializer::new(context), |mut obj, (k, v)| {
-            obj.property(k.clone(), v.clone(), Attribute::all());
-            obj
-        });
-
-    obj.property(
-        js_str!("__variant"),
-        JsValue::String(variant_name.into()),
-        Attribute::all(),
-    );
-    Ok(obj.build().into())
-}
-
-fn primitive_to_js_value(value: &dyn Reflect, _context: &mut Context) -> JsResult<JsValue> {
-    let value = value.try_as_reflect().ok_or_else(|| {
-        JsError::from_opaque(js_str!("Could not convert value to reflect").into())
-    })?;
-    Ok(match value {
-        v if v.is::<bool>() => JsValue::Boolean(*v.downcast_ref::<bool>().unwrap()),
-        v if v.is::<i8>() => JsValue::Integer(*v.downcast_ref::<i8>().unwrap() as i32),
-        v if v.is::<i16>() => JsValue::Integer(*v.downcast_ref::<i16>().unwrap() as i32),
-        v if v.is::<i32>() => JsValue::Integer(*v.downcast_ref::<i32>().unwrap()),
-        v if v.is::<i64>() => JsValue::BigInt((*v.downcast_ref::<i64>().unwrap()).into()),
-        v if v.is::<isize>() => {
-            JsValue::BigInt((*v.downcast_ref::<isize>().unwrap() as i64).into())
+        .collect::<Vec<_>>();
+    let variant = engine.variant_name(enum_value);
+    let fields = match (settings.enums, enum_value.variant_type()) {
+        (EnumRepresentation::Tagged, _) => {
+            keys.push(Key::Name(Cow::Borrowed("__variant")));
+            converted.push(variant);
+            return engine.object(ObjectKeys::Keys(&keys), take_last(converted, len + 1));
         }
-        v if v.is::<u8>() => JsValue::Integer(*v.downcast_ref::<u8>().unwrap() as i32),
-        v if v.is::<u16>() => JsValue::Integer(*v.downcast_ref::<u16>().unwrap() as i32),
-        v if v.is::<u32>() => JsValue::BigInt((*v.downcast_ref::<u32>().unwrap() as u64).into()),
-        v if v.is::<u64>() => JsValue::BigInt((*v.downcast_ref::<u64>().unwrap()).into()),
-        v if v.is::<usize>() => {
-            JsValue::BigInt((*v.downcast_ref::<usize>().unwrap() as u64).into())
+        (EnumRepresentation::External, VariantType::Unit) => return Ok(variant),
+        (EnumRepresentation::External, VariantType::Tuple) => {
+            engine.array(take_last(converted, len))?
         }
-        v if v.is::<f32>() => JsValue::Rational(*v.downcast_ref::<f32>().unwrap() as f64),
-        v if v.is::<f64>() => JsValue::Rational(*v.downcast_ref::<f64>().unwrap()),
-        v if v.is::<String>() => {
-            JsValue::String(v.downcast_ref::<String>().unwrap().clone().into())
+        (EnumRepresentation::External, VariantType::Struct) => {
+            engine.object(ObjectKeys::Keys(&keys), take_last(converted, len))?
         }
-        v if v.is::<&str>() => JsValue::String((*v.downcast_ref::<&str>().unwrap()).into()),
-        _ => JsValue::Null,
-    })
+    };
+    converted.push(fields);
+    let keys = [Key::Name(Cow::Borrowed(enum_value.variant_name()))];
+    engine.object(ObjectKeys::Keys(&keys), take_last(converted, 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::any::TypeId;
+    use std::collections::BTreeMap;
+
+    use bevy_reflect::{FromReflect, GetTypeRegistration, ReflectMut, TypePath, TypeRegistry};
+    use boa_engine::js_str;
+
+    use crate::from::{js_value_to_typed_reflect, js_value_to_typed_with_settings};
+
+    use super::*;
+
+    #[derive(Reflect, Debug, Clone, PartialEq)]
+    struct Stats {
+        health: u32,
+        name: String,
+    }
+
+    #[derive(Reflect, Debug, Clone, PartialEq)]
+    struct Position(f32, f32);
+
+    #[derive(Reflect, Debug, Clone, PartialEq)]
+    enum Mode {
+        Idle,
+        Walk(f32, bool),
+        Move { x: f32, y: f32 },
+        Attack { target: u64, power: i128 },
+    }
+
+    #[derive(Reflect, Debug, Clone, PartialEq)]
+    struct Everything {
+        stats: Stats,
+        position: Position,
+        tuple: (u8, String, bool),
+        list: Vec<i32>,
+        array: [u16; 3],
+        map: BTreeMap<String, f64>,
+        modes: Vec<Mode>,
+        option: Option<Mode>,
+        none: Option<u32>,
+        big: u64,
+        huge: i128,
+        unsigned: u128,
+    }
+
+    #[derive(Reflect, Debug, Default)]
+    #[reflect(no_field_bounds)]
+    struct Tree {
+        children: Vec<Tree>,
+    }
+
+    fn registry() -> TypeRegistry {
+        let mut registry = TypeRegistry::default();
+        registry.register::<Everything>();
+        registry.register::<Tree>();
+        registry
+    }
+
+    fn everything() -> Everything {
+        Everything {
+            stats: Stats {
+                health: 80,
+                name: "orc".into(),
+            },
+            position: Position(1.5, -2.0),
+            tuple: (7, "seven".into(), true),
+            list: vec![1, -2, 3],
+            array: [4, 5, 6],
+            map: BTreeMap::from([("a".into(), 0.5), ("b".into(), -1.0)]),
+            modes: vec![
+                Mode::Idle,
+                Mode::Walk(0.25, false),
+                Mode::Move { x: 1.0, y: -0.5 },
+                Mode::Attack {
+                    target: u64::MAX,
+                    power: i128::MIN,
+                },
+            ],
+            option: Some(Mode::Walk(1.0, true)),
+            none: None,
+            big: u64::MAX,
+            huge: i128::MAX,
+            unsigned: u128::MAX,
+        }
+    }
+
+    fn round_trip<T: FromReflect + TypePath + GetTypeRegistration>(
+        value: &T,
+        settings: &ConversionSettings,
+    ) -> T {
+        let mut registry = TypeRegistry::default();
+        registry.register::<T>();
+        let mut ctx = Context::default();
+        let js_value = try_reflect_to_js_value_with_settings(value, settings, &mut ctx).unwrap();
+        js_value_to_typed_with_settings(js_value, &registry, settings, &mut ctx).unwrap()
+    }
+
+    #[test]
+    fn every_kind_round_trips_with_tagged_enums() {
+        let value = everything();
+        assert_eq!(round_trip(&value, &ConversionSettings::DEFAULT), value);
+    }
+
+    #[test]
+    fn every_kind_round_trips_with_external_enums() {
+        let settings = ConversionSettings::DEFAULT.with_enums(EnumRepresentation::External);
+        let value = everything();
+        assert_eq!(round_trip(&value, &settings), value);
+    }
+
+    /// The value as `JSON.stringify` writes it.
+    fn stringify(value: JsValue, ctx: &mut Context) -> String {
+        let json = ctx.global_object().get(js_str!("JSON"), ctx).unwrap();
+        let json = json.as_object().unwrap();
+        let stringify = json.get(js_str!("stringify"), ctx).unwrap();
+        let stringify = stringify.as_callable().unwrap();
+        let string = stringify.call(&json.clone().into(), &[value], ctx).unwrap();
+        string.as_string().unwrap().to_std_string_escaped()
+    }
+
+    #[test]
+    fn enum_variants_take_the_shape_of_their_representation() {
+        let mut ctx = Context::default();
+        let external = ConversionSettings::DEFAULT.with_enums(EnumRepresentation::External);
+        let cases = [
+            (Mode::Idle, r#"{"__variant":"Idle"}"#, r#""Idle""#),
+            (
+                Mode::Walk(0.5, true),
+                r#"{"0":0.5,"1":true,"__variant":"Walk"}"#,
+                r#"{"Walk":[0.5,true]}"#,
+            ),
+            (
+                Mode::Move { x: 2.5, y: -1.0 },
+                r#"{"x":2.5,"y":-1,"__variant":"Move"}"#,
+                r#"{"Move":{"x":2.5,"y":-1}}"#,
+            ),
+        ];
+        for (mode, tagged_json, external_json) in cases {
+            let tagged = try_reflect_to_js_value(&mode, &mut ctx).unwrap();
+            assert_eq!(stringify(tagged, &mut ctx), tagged_json);
+            let value = try_reflect_to_js_value_with_settings(&mode, &external, &mut ctx).unwrap();
+            assert_eq!(stringify(value, &mut ctx), external_json);
+        }
+    }
+
+    #[test]
+    fn sixty_four_bit_and_wider_integers_become_bigints() {
+        let mut ctx = Context::default();
+        for (value, expected) in [
+            (&u64::MAX as &dyn Reflect, u64::MAX.to_string()),
+            (&i128::MIN, i128::MIN.to_string()),
+            (&u128::MAX, u128::MAX.to_string()),
+        ] {
+            let js_value = try_reflect_to_js_value(value, &mut ctx).unwrap();
+            let JsValue::BigInt(int) = js_value else {
+                panic!("{} became {js_value:?}", value.reflect_type_path());
+            };
+            assert_eq!(int.to_string(), expected);
+        }
+    }
+
+    // Sets are opaque values to this version of reflection, with no kind of their own, so only
+    // `Set`s from scripts are read, as lists.
+    #[test]
+    fn js_sets_read_as_lists() {
+        let mut ctx = Context::default();
+        let set = ctx
+            .eval(boa_engine::Source::from_bytes("new Set([3, 1, 3, 2])"))
+            .unwrap();
+        let list = crate::from::try_js_value_to_reflect(set, &mut ctx).unwrap();
+        let ReflectRef::List(list) = list.reflect_ref() else {
+            panic!("a set should read as a list");
+        };
+        let items = list
+            .iter()
+            .map(|item| item.downcast_ref::<f32>().copied())
+            .collect::<Vec<_>>();
+        assert_eq!(items, [Some(3.0), Some(1.0), Some(2.0)]);
+    }
+
+    #[test]
+    fn deeply_nested_values_convert_without_overflowing() {
+        const DEPTH: usize = 20_000;
+        let registry = registry();
+        let mut ctx = Context::default();
+        let mut tree = Tree::default();
+        for _ in 0..DEPTH {
+            tree = Tree {
+                children: vec![tree],
+            };
+        }
+        let js_value = try_reflect_to_js_value(&tree, &mut ctx).unwrap();
+        let back =
+            js_value_to_typed_reflect(js_value, TypeId::of::<Tree>(), &registry, &mut ctx).unwrap();
+
+        // Walked, and dropped, without recursing too.
+        let mut depth = 0;
+        let mut next = Some(back);
+        while let Some(mut node) = next.take() {
+            let ReflectMut::Struct(node) = node.reflect_mut() else {
+                panic!("a tree node should be a struct");
+            };
+            let ReflectMut::List(children) = node.field_mut("children").unwrap().reflect_mut()
+            else {
+                panic!("a tree's children should be a list");
+            };
+            next = children.pop();
+            depth += 1;
+        }
+        assert_eq!(depth, DEPTH + 1);
+        let mut next = tree.children.pop();
+        while let Some(mut node) = next {
+            next = node.children.pop();
+        }
+    }
 }
@@ -0,0 +1,77 @@
+use std::ops::{Deref, DerefMut};
+
+use bevy_reflect::{FromReflect, GetTypeRegistration, Reflect, TypePath};
+use boa_engine::{Context, JsError, JsResult, JsValue};
+
+use crate::boa_conversions::reflect_try_from_js;
+use crate::into::reflect_to_js_value;
+
+/// A reflected value converted from or into JS through the standard conversion traits, for code
+/// that would rather use `try_into` and `?` than this crate's own traits:
+///
+/// ```no_run
+/// # use bevy_boa_reflect::Js;
+/// # use bevy_reflect::Reflect;
+/// # #[derive(Reflect)]
+/// # struct Health { current: f32 }
+/// # let mut ctx = boa_engine::Context::default();
+/// # let value = boa_engine::JsValue::undefined();
+/// let Js(health): Js<Health> = (value, &mut ctx).try_into()?;
+/// let value = Js(health).try_into_js(&mut ctx)?;
+/// # Ok::<(), boa_engine::JsError>(())
+/// ```
+///
+/// Converting from JS uses the registry [`reflect_try_from_js`] does. The orphan rules don't
+/// allow implementing `TryFrom<Js<T>>` for `JsValue` with a context alongside, so converting into
+/// JS is [`try_into_js`](Self::try_into_js).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Js<T>(pub T);
+
+impl<T> Js<T> {
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T: Reflect> Js<T> {
+    /// Convert the value into a `JsValue`.
+    pub fn try_into_js(self, ctx: &mut Context) -> JsResult<JsValue> {
+        reflect_to_js_value(&self.0, ctx)
+    }
+}
+
+impl<T> Deref for Js<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for Js<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T> TryFrom<(JsValue, &mut Context)> for Js<T>
+where
+    T: FromReflect + TypePath + GetTypeRegistration,
+{
+    type Error = JsError;
+
+    fn try_from((value, ctx): (JsValue, &mut Context)) -> Result<Self, JsError> {
+        reflect_try_from_js(&value, ctx).map(Js)
+    }
+}
+
+impl<T> TryFrom<(&JsValue, &mut Context)> for Js<T>
+where
+    T: FromReflect + TypePath + GetTypeRegistration,
+{
+    type Error = JsError;
+
+    fn try_from((value, ctx): (&JsValue, &mut Context)) -> Result<Self, JsError> {
+        reflect_try_from_js(value, ctx).map(Js)
+    }
+}
@@ -0,0 +1,107 @@
+use bevy::ecs::query::{QueryItem, ReadOnlyQueryData};
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy::reflect::TypePath;
+use boa_engine::{Context, JsValue};
+
+use crate::errors::ConversionError;
+use crate::into::try_reflect_slice_to_js_array;
+use crate::templates::ObjectTemplate;
+
+/// A query converting the components of each entity it matches to JS, for handing a query's data
+/// to a script in a few lines:
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_boa_reflect::{JsCtx, JsQuery};
+/// # use boa_engine::JsResult;
+/// # #[derive(Component, Reflect)]
+/// # struct Health { current: f32 }
+/// fn send_enemies(enemies: JsQuery<(Transform, Health)>, mut js: JsCtx) -> JsResult<()> {
+///     for (entity, value) in enemies.to_js(js.context())? {
+///         // `value` is `{ Transform: { ... }, Health: { ... } }`.
+///         # let _ = (entity, value);
+///     }
+///     Ok(())
+/// }
+/// ```
+///
+/// Each entity's components become an object keyed by their short type paths. Every component is
+/// converted in a single batch, as [`reflect_slice_to_js_array`](crate::reflect_slice_to_js_array)
+/// does, rather than one entity at a time.
+#[derive(SystemParam)]
+pub struct JsQuery<'w, 's, C: JsComponents> {
+    query: Query<'w, 's, (Entity, <C as JsComponents>::Data)>,
+}
+
+impl<C: JsComponents> JsQuery<'_, '_, C> {
+    /// The entities the query matches, with their components converted to JS.
+    pub fn to_js(&self, ctx: &mut Context) -> Result<Vec<(Entity, JsValue)>, ConversionError> {
+        let mut entities = Vec::new();
+        let mut components = Vec::new();
+        for (entity, item) in self.query.iter() {
+            entities.push(entity);
+            C::components(item, &mut components);
+        }
+        let converted = try_reflect_slice_to_js_array(&components, ctx)?;
+        let template = ObjectTemplate::new(C::names());
+        let len = C::names().len() as u64;
+        entities
+            .into_iter()
+            .enumerate()
+            .map(|(idx, entity)| {
+                let start = idx as u64 * len;
+                let values = (start..start + len)
+                    .map(|i| converted.get(i, ctx))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok((entity, template.create(values, ctx).into()))
+            })
+            .collect()
+    }
+
+    /// The query converted from, for reading components without converting them.
+    pub fn query(&self) -> &Query<'_, '_, (Entity, C::Data)> {
+        &self.query
+    }
+}
+
+/// A tuple of reflected component types a [`JsQuery`] converts, like `(Transform, Health)`.
+pub trait JsComponents: 'static {
+    type Data: ReadOnlyQueryData;
+
+    /// The short type paths of the components, which name them in the converted objects.
+    fn names() -> Vec<&'static str>;
+
+    /// Add the components of a query item to `components`, in the order of the tuple.
+    fn components<'a>(item: QueryItem<'a, Self::Data>, components: &mut Vec<&'a dyn Reflect>);
+}
+
+macro_rules! impl_js_components {
+    ($($component:ident),*) => {
+        impl<$($component: Component + Reflect + TypePath),*> JsComponents for ($($component,)*) {
+            type Data = ($(&'static $component,)*);
+
+            fn names() -> Vec<&'static str> {
+                vec![$($component::short_type_path()),*]
+            }
+
+            #[allow(non_snake_case)]
+            fn components<'a>(
+                item: QueryItem<'a, Self::Data>,
+                components: &mut Vec<&'a dyn Reflect>,
+            ) {
+                let ($($component,)*) = item;
+                $(components.push($component);)*
+            }
+        }
+    };
+}
+
+impl_js_components!(A);
+impl_js_components!(A, B);
+impl_js_components!(A, B, C);
+impl_js_components!(A, B, C, D);
+impl_js_components!(A, B, C, D, E);
+impl_js_components!(A, B, C, D, E, F);
+impl_js_components!(A, B, C, D, E, F, G);
+impl_js_components!(A, B, C, D, E, F, G, H);
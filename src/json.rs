@@ -0,0 +1,151 @@
+use bevy_reflect::{FromReflect, Reflect, TypePath, TypeRegistry};
+use boa_engine::builtins::map::ordered_map::OrderedMap;
+use boa_engine::object::builtins::JsArray;
+use boa_engine::object::ObjectInitializer;
+use boa_engine::property::Attribute;
+use boa_engine::{Context, JsBigInt, JsError, JsNativeError, JsResult, JsString, JsValue};
+use serde_json::{Map, Number, Value};
+
+#[cfg(feature = "bevy")]
+use crate::classes::instance_of;
+use crate::errors::ConversionError;
+use crate::from::{js_map_entries, js_value_to_typed_with_settings};
+use crate::into::try_reflect_to_js_value_with_settings;
+use crate::settings::ConversionSettings;
+
+/// How deeply nested a value can be before conversion gives up, which also catches cycles.
+const MAX_DEPTH: usize = 64;
+
+/// Convert a reflected value to a JSON string, through the same conversion and settings that
+/// give scripts the value, so the JSON is exactly the form scripts see, as
+/// [`js_value_to_json`] writes it.
+pub fn reflect_to_json_string(
+    value: &dyn Reflect,
+    settings: &ConversionSettings,
+    ctx: &mut Context,
+) -> Result<String, ConversionError> {
+    let js_value = try_reflect_to_js_value_with_settings(value, settings, ctx)?;
+    Ok(js_value_to_json(&js_value, ctx)?.to_string())
+}
+
+/// Convert a JSON string into a registered type, reading it as scripts' values are read with the
+/// same settings. Reads what [`reflect_to_json_string`] writes back into an equal value.
+pub fn json_string_to_reflect<T: FromReflect + TypePath>(
+    json: &str,
+    registry: &TypeRegistry,
+    settings: &ConversionSettings,
+    ctx: &mut Context,
+) -> Result<T, ConversionError> {
+    let json = serde_json::from_str::<Value>(json)
+        .map_err(|err| JsError::from(JsNativeError::syntax().with_message(err.to_string())))?;
+    let value = json_to_js_value(&json, ctx);
+    js_value_to_typed_with_settings(value, registry, settings, ctx)
+}
+
+/// Convert JSON to a JS value. Integers that don't fit in an `i32` become `BigInt`s, so 64-bit
+/// integers keep every digit; conversions read them into floats and integers alike.
+pub fn json_to_js_value(json: &Value, ctx: &mut Context) -> JsValue {
+    match json {
+        Value::Null => JsValue::Null,
+        Value::Bool(b) => JsValue::Boolean(*b),
+        Value::Number(n) => {
+            if let Some(i) = n.as_i64() {
+                i32::try_from(i).map_or_else(|_| JsBigInt::from(i).into(), JsValue::Integer)
+            } else if let Some(u) = n.as_u64() {
+                JsBigInt::from(u).into()
+            } else {
+                JsValue::Rational(n.as_f64().unwrap_or(f64::NAN))
+            }
+        }
+        Value::String(s) => JsString::from(s.as_str()).into(),
+        Value::Array(items) => {
+            let items = items
+                .iter()
+                .map(|item| json_to_js_value(item, ctx))
+                .collect::<Vec<_>>();
+            JsArray::from_iter(items, ctx).into()
+        }
+        Value::Object(map) => {
+            let values = map
+                .iter()
+                .map(|(key, value)| (JsString::from(key.as_str()), json_to_js_value(value, ctx)))
+                .collect::<Vec<_>>();
+            let mut obj = ObjectInitializer::new(ctx);
+            for (key, value) in values {
+                obj.property(key, value, Attribute::all());
+            }
+            obj.build().into()
+        }
+    }
+}
+
+/// Convert a JS value to JSON. `undefined` and functions become `null`, `BigInt`s become numbers
+/// where they fit and strings otherwise, maps become arrays of entries, and instances of generated
+/// classes become their fields.
+pub fn js_value_to_json(value: &JsValue, ctx: &mut Context) -> JsResult<Value> {
+    to_json(value, 0, ctx)
+}
+
+fn to_json(value: &JsValue, depth: usize, ctx: &mut Context) -> JsResult<Value> {
+    if depth > MAX_DEPTH {
+        return Err(JsNativeError::typ()
+            .with_message("Value is too deeply nested to convert to JSON")
+            .into());
+    }
+    Ok(match value {
+        JsValue::Undefined | JsValue::Null => Value::Null,
+        JsValue::Boolean(b) => Value::Bool(*b),
+        JsValue::Integer(i) => Value::from(*i),
+        JsValue::Rational(f) => Number::from_f64(*f).map_or(Value::Null, Value::Number),
+        JsValue::String(s) => Value::String(s.to_std_string_escaped()),
+        JsValue::BigInt(b) => {
+            let digits = b.to_string();
+            digits
+                .parse::<i64>()
+                .map(Value::from)
+                .or_else(|_| digits.parse::<u64>().map(Value::from))
+                .unwrap_or(Value::String(digits))
+        }
+        JsValue::Symbol(_) => {
+            return Err(JsNativeError::typ()
+                .with_message("Symbols cannot be converted to JSON")
+                .into())
+        }
+        JsValue::Object(obj) => {
+            #[cfg(feature = "bevy")]
+            if let Some((_, fields)) = instance_of(obj) {
+                return to_json(&fields.into(), depth + 1, ctx);
+            }
+            if obj.is_callable() {
+                return Ok(Value::Null);
+            }
+            if obj.is_array() {
+                let array = JsArray::from_object(obj.clone())?;
+                let mut items = Vec::new();
+                for idx in 0..array.length(ctx)? {
+                    items.push(to_json(&array.get(idx, ctx)?, depth + 1, ctx)?);
+                }
+                return Ok(Value::Array(items));
+            }
+            let entries = js_map_entries(value, "JSON", ctx)?;
+            if obj.is::<OrderedMap<JsValue>>() {
+                let entries = entries
+                    .into_iter()
+                    .map(|(key, value)| {
+                        Ok(Value::Array(vec![
+                            to_json(&key, depth + 1, ctx)?,
+                            to_json(&value, depth + 1, ctx)?,
+                        ]))
+                    })
+                    .collect::<JsResult<Vec<_>>>()?;
+                return Ok(Value::Array(entries));
+            }
+            let mut map = Map::new();
+            for (key, value) in entries {
+                let key = key.to_string(ctx)?.to_std_string_escaped();
+                map.insert(key, to_json(&value, depth + 1, ctx)?);
+            }
+            Value::Object(map)
+        }
+    })
+}
@@ -1,12 +1,250 @@
-use bevy::reflect::Reflect;
+use bevy_reflect::Reflect;
 use boa_engine::{Context, JsResult, JsValue};
 
+#[cfg(feature = "bevy")]
+mod access;
+#[cfg(feature = "bevy")]
+mod animation;
+#[cfg(feature = "bevy")]
+mod audio;
+#[cfg(feature = "bevy")]
+mod bind;
+#[cfg(feature = "bevy")]
+mod bindings;
+mod boa_conversions;
+#[cfg(feature = "bevy")]
+mod brp;
+mod chunked;
+#[cfg(feature = "bevy")]
+mod classes;
+#[cfg(feature = "bevy")]
+mod columns;
+#[cfg(feature = "bevy")]
+mod console;
+mod converters;
+#[cfg(feature = "debugger")]
+mod debugger;
+#[cfg(feature = "bevy")]
+mod determinism;
+mod direct;
+mod encoding;
+mod engine;
+mod errors;
+#[cfg(feature = "bevy")]
+mod ext;
+mod failure;
+#[cfg(feature = "fetch")]
+mod fetch;
 mod from;
+#[cfg(feature = "bevy")]
+mod functions;
+mod hooks;
+#[cfg(feature = "bevy")]
+mod inspect;
 mod into;
+mod js;
+#[cfg(feature = "bevy")]
+mod js_query;
+mod json;
+#[cfg(feature = "bevy")]
+mod memo;
+#[cfg(feature = "bevy")]
+mod metadata;
+#[cfg(feature = "bevy")]
+mod methods;
+mod msgpack;
+mod parallel;
+#[cfg(feature = "bevy")]
+mod persistence;
+#[cfg(feature = "bevy")]
+mod plugin;
+mod pool;
+pub mod prelude;
+#[cfg(feature = "bevy")]
+mod profiling;
+#[cfg(feature = "bevy")]
+mod proxies;
+#[cfg(feature = "bevy")]
+mod quarantine;
+#[cfg(feature = "remote")]
+mod remote;
+mod report;
+mod ron_format;
+#[cfg(feature = "bevy")]
+mod runtime;
+#[cfg(feature = "bevy")]
+mod script;
+#[cfg(feature = "bevy")]
+mod script_host;
+mod serde_backend;
+mod settings;
+mod structured_clone;
+#[cfg(feature = "bevy")]
+mod system_param;
+mod templates;
+#[cfg(feature = "test-utils")]
+pub mod test_utils;
+#[cfg(feature = "bevy")]
+mod testing;
+#[cfg(feature = "trace")]
+mod trace;
+#[cfg(feature = "bevy")]
+mod typescript;
+#[cfg(feature = "verbose")]
+mod verbose;
+#[cfg(feature = "bevy")]
+mod watch;
+#[cfg(feature = "bevy")]
+mod workers;
 
-/// Trait for converting a type into a `JsValue`.
+#[cfg(feature = "bevy")]
+pub use access::provide_world;
+#[cfg(feature = "bevy")]
+pub use animation::{ScriptAnimationPlugin, ANIMATION_BINDING};
+#[cfg(feature = "bevy")]
+pub use audio::{ScriptAudioPlugin, AUDIO_BINDING};
+#[cfg(feature = "bevy")]
+pub use bind::{register_class, register_const};
+#[cfg(feature = "bevy")]
+pub use bindings::bus::{bus_binding, deliver_events, BUS_BINDING};
+#[cfg(feature = "bevy")]
+pub use bindings::commands::{
+    commands_binding, reflect_components, ScriptCommandQueue, COMMANDS_BINDING,
+};
+#[cfg(feature = "bevy")]
+pub use bindings::reflect::{
+    default_value, describe, get_path, reflect_binding, set_path, types, REFLECT_BINDING,
+};
+#[cfg(feature = "bevy")]
+pub use bindings::storage::{
+    storage_binding, ScriptStorage, StorageFormat, StorageSettings, STORAGE_BINDING,
+};
+#[cfg(feature = "bevy")]
+pub use bindings::world::{clone_entity, world_binding, EntityNameIndex, WORLD_BINDING};
+pub use boa_conversions::reflect_try_from_js;
+#[cfg(feature = "bevy")]
+pub use brp::{
+    brp_value_to_reflect, brp_value_to_typed, entity_components_to_brp, reflect_to_brp_value,
+};
+pub use chunked::{ChunkedConversion, ConversionProgress};
+#[cfg(feature = "bevy")]
+pub use classes::{reflect_class, register_type_classes};
+#[cfg(feature = "bevy")]
+pub use columns::query_to_js_columns;
+#[cfg(feature = "bevy")]
+pub use console::ScriptConsolePlugin;
+pub use converters::{JsConverter, JsConverters};
+#[cfg(feature = "debugger")]
+pub use debugger::ScriptDebugPlugin;
+#[cfg(feature = "bevy")]
+pub use determinism::ScriptDeterminism;
+#[doc(hidden)]
+pub use direct::__private;
+pub use direct::{FromJs, IntoJs};
+pub use encoding::register_text_encoding;
+pub use engine::{BoaEngine, Collection, JsEngine, Key, ObjectKeys, Primitive, Read};
+pub use errors::{
+    ConversionError, ConversionErrorKind, ConversionErrors, ConversionIssue, FieldPath,
+    JsValueKind, PathSegment, ScriptErrorLocation, SourceMap,
+};
+#[cfg(feature = "bevy")]
+pub use errors::{ScriptError, ScriptResultExt};
+#[cfg(feature = "bevy")]
+pub use ext::{ContextWorldExt, EntityCommandsJsExt, WorldJsExt};
+pub use failure::ConversionFailurePolicy;
+#[cfg(feature = "fetch")]
+pub use fetch::{ScriptFetchPlugin, FETCH_BINDING};
+pub use from::{
+    can_convert, can_convert_with_settings, engine_value_to_reflect, engine_value_to_typed_reflect,
+    js_array_to_typed_reflect_vec, js_array_to_typed_reflect_vec_with_settings,
+    js_array_to_typed_vec, js_value_to_reflect, js_value_to_reflect_with_settings,
+    js_value_to_typed, js_value_to_typed_all, js_value_to_typed_lenient, js_value_to_typed_reflect,
+    js_value_to_typed_reflect_all, js_value_to_typed_reflect_lenient,
+    js_value_to_typed_reflect_with_report, js_value_to_typed_reflect_with_settings,
+    js_value_to_typed_with_settings, try_js_value_to_reflect,
+    try_js_value_to_reflect_with_settings, JsValueConverter,
+};
+#[cfg(feature = "bevy")]
+pub use functions::{register_fn, IntoReflectFunction, ReflectFunction, ScriptFunctions};
+pub use hooks::{ConversionHook, ConversionHooks, HookAction};
+#[cfg(feature = "bevy")]
+pub use inspect::eval_on_entity;
+pub use into::{
+    reflect_slice_to_js_array, reflect_slice_to_js_array_with_settings, reflect_to_engine_value,
+    reflect_to_js_value, reflect_to_js_value_with_report, reflect_to_js_value_with_settings,
+    try_reflect_slice_to_js_array, try_reflect_slice_to_js_array_with_settings,
+    try_reflect_to_js_value, try_reflect_to_js_value_with_report,
+    try_reflect_to_js_value_with_settings,
+};
+pub use js::Js;
+#[cfg(feature = "bevy")]
+pub use js_query::{JsComponents, JsQuery};
+pub use json::{
+    js_value_to_json, json_string_to_reflect, json_to_js_value, reflect_to_json_string,
+};
+#[cfg(feature = "bevy")]
+pub use memo::{component_to_js_value, ComponentCache};
+#[cfg(feature = "bevy")]
+pub use metadata::{ScriptManifest, ScriptMetadata, METADATA_EXPORT};
+#[cfg(feature = "bevy")]
+pub use methods::{ScriptMethod, ScriptMethods};
+pub use msgpack::{
+    js_value_to_msgpack, msgpack_to_js_value, msgpack_to_reflect, reflect_to_msgpack,
+};
+pub use parallel::{
+    reflect_slice_to_js_array_parallel, reflect_to_js_value_parallel,
+    try_reflect_slice_to_js_array_parallel, try_reflect_to_js_value_parallel, PARALLEL_THRESHOLD,
+};
+#[cfg(feature = "bevy")]
+pub use persistence::{restore_script_state, snapshot_script_state};
+#[cfg(feature = "bevy")]
+pub use plugin::BoaScriptPlugin;
+pub use pool::ContextPool;
+#[cfg(feature = "bevy")]
+pub use profiling::{ProfileSample, ScriptProfiler, SCRIPT_DIAGNOSTICS_ROOT};
+#[cfg(feature = "bevy")]
+pub use proxies::{entity_proxy, EntityProxies};
+#[cfg(feature = "bevy")]
+pub use quarantine::{QuarantinePolicy, QuarantineScope, ScriptQuarantine};
+#[cfg(feature = "remote")]
+pub use remote::{process_eval_request, EVAL_METHOD};
+pub use report::ConversionReport;
+pub use ron_format::{js_value_to_ron, ron_to_js_value};
+#[cfg(feature = "bevy")]
+pub use runtime::{
+    entity_to_js_value, js_value_to_entity, ScriptIsolation, ScriptRuntime, EXPORTS_BINDING,
+    HOST_BINDING, MIGRATE_HOOK, PARAMS_BINDING, RUN_HOOK, STATE_BINDING, UPDATE_HOOK,
+};
+#[cfg(feature = "bevy")]
+pub use script::{Script, ScriptAsset, ScriptAssetLoader, ScriptScope, ScriptSettings};
+#[cfg(feature = "bevy")]
+pub use script_host::{
+    APIProvider, APIProviders, BoaEvent, BoaScriptHost, Recipients, ScriptEvent, ScriptHost,
+    ScriptHostAppExt,
+};
+pub use serde_backend::{
+    js_value_to_typed_with_backend, reflect_to_js_value_with_backend, reflect_to_serde_js_value,
+    serde_js_value_to_reflect, ConversionBackend,
+};
+pub use settings::{ConversionSettings, EnumRepresentation, NumberPolicy, RenameRule};
+pub use structured_clone::{register_structured_clone, structured_clone};
+#[cfg(feature = "bevy")]
+pub use system_param::JsCtx;
+pub use templates::share_strings;
+#[cfg(feature = "bevy")]
+pub use testing::{ScriptTestHarness, ScriptTestReport, ScriptTestResult, TEST_PREFIX};
+#[cfg(feature = "bevy")]
+pub use typescript::{type_declarations, write_type_declarations};
+#[cfg(feature = "bevy")]
+pub use watch::{tweak_value, ScriptWatches, WatchId, WatchResult};
+#[cfg(feature = "bevy")]
+pub use workers::{ScriptWorkerPlugin, WORKER_BINDING};
+
+/// Trait for converting a type into a `JsValue`. See [`ToJsValue`] for converting through a
+/// reference.
 pub trait IntoJsValue {
-    /// Convert the type into a `JsValue`, panicking if the conversion fails.
+    /// Convert the type into a `JsValue`, handling a failure as the global
+    /// [`ConversionFailurePolicy`] says, which panics by default.
     fn into_js_value(self, ctx: &mut Context) -> JsValue;
 
     /// Convert the type into a `JsValue`, returning an error if the conversion fails.
@@ -18,7 +256,7 @@ where
     T: Reflect,
 {
     fn into_js_value(self, ctx: &mut Context) -> JsValue {
-        into::reflect_to_js_value(&self, ctx).unwrap()
+        failure::recover(into::reflect_to_js_value(&self, ctx), || JsValue::Null)
     }
 
     fn try_into_js_value(self, ctx: &mut Context) -> JsResult<JsValue> {
@@ -26,21 +264,55 @@ where
     }
 }
 
-/// Trait for converting a [`JsValue`] into a type.
-pub trait FromJsValue {
-    /// Convert a `JsValue` into the type, panicking if the conversion fails.
-    fn from_js_value(value: JsValue, ctx: &mut Context) -> Self;
+/// Trait for converting a borrowed value into a `JsValue`, so systems can expose components
+/// and resources through references without cloning them first.
+pub trait ToJsValue {
+    /// Convert the value into a `JsValue`, handling a failure as the global
+    /// [`ConversionFailurePolicy`] says, which panics by default.
+    fn to_js_value(&self, ctx: &mut Context) -> JsValue;
 
-    /// Convert a `JsValue` into the type, returning an error if the conversion fails.
-    fn try_from_js_value(value: JsValue, ctx: &mut Context) -> JsResult<Self>;
+    /// Convert the value into a `JsValue`, returning an error if the conversion fails.
+    fn try_to_js_value(&self, ctx: &mut Context) -> JsResult<JsValue>;
 }
 
-impl<T> FromJsValue for T
+impl<T> ToJsValue for T
 where
     T: Reflect,
 {
+    fn to_js_value(&self, ctx: &mut Context) -> JsValue {
+        failure::recover(into::reflect_to_js_value(self, ctx), || JsValue::Null)
+    }
+
+    fn try_to_js_value(&self, ctx: &mut Context) -> JsResult<JsValue> {
+        into::reflect_to_js_value(self, ctx)
+    }
+}
+
+impl ToJsValue for dyn Reflect {
+    fn to_js_value(&self, ctx: &mut Context) -> JsValue {
+        failure::recover(into::reflect_to_js_value(self, ctx), || JsValue::Null)
+    }
+
+    fn try_to_js_value(&self, ctx: &mut Context) -> JsResult<JsValue> {
+        into::reflect_to_js_value(self, ctx)
+    }
+}
+
+/// Trait for converting a [`JsValue`] into a type. `Box<dyn Reflect>` converts a value into
+/// whatever reflected value its shape suggests, for tooling that doesn't know what type to expect.
+/// See [`Js`] for converting into a registered type.
+pub trait FromJsValue: Sized {
+    /// Convert a `JsValue` into the type, handling a failure as the global
+    /// [`ConversionFailurePolicy`] says, which panics by default.
+    fn from_js_value(value: JsValue, ctx: &mut Context) -> Self;
+
+    /// Convert a `JsValue` into the type, returning an error if the conversion fails.
+    fn try_from_js_value(value: JsValue, ctx: &mut Context) -> JsResult<Self>;
+}
+
+impl FromJsValue for Box<dyn Reflect> {
     fn from_js_value(value: JsValue, ctx: &mut Context) -> Self {
-        from::js_value_to_reflect(value, ctx).unwrap()
+        failure::recover(from::js_value_to_reflect(value, ctx), || Box::new(()))
     }
 
     fn try_from_js_value(value: JsValue, ctx: &mut Context) -> JsResult<Self> {
@@ -0,0 +1,332 @@
+use std::any::TypeId;
+
+use bevy::ecs::component::{ComponentId, Tick};
+use bevy::ecs::reflect::{AppTypeRegistry, ReflectComponent};
+use bevy::prelude::*;
+use bevy::reflect::{ReflectRef, TypeInfo, TypeRegistry};
+use boa_engine::gc::GcRefCell;
+use boa_engine::{Context, Finalize, JsData, JsString, JsValue, Trace};
+
+use crate::converters::JsConverters;
+use crate::errors::{ConversionError, FieldPath};
+use crate::hooks::ConversionHooks;
+use crate::into::try_reflect_to_js_value;
+
+/// Components converted by [`component_to_js_value`], kept in a context so a component that
+/// hasn't changed since it was last converted is returned as the same object instead of converted
+/// again. Unchanged components then cost nothing to expose each frame.
+///
+/// When a component does change, only its changed fields are converted and set on the object
+/// converted before, keeping a copy of each component to compare against. In a context with
+/// [`ConversionHooks`], a changed component is converted again whole
+/// instead, as hooks can depend on the whole value and where each field is in it.
+///
+/// The cache is opt in, as scripts see the same object until the component changes, including
+/// any changes they made to it, and fields scripts changed are only overwritten once the
+/// component's own value for them changes. Entries for despawned entities stay until
+/// [`clear`](Self::clear) is called.
+#[derive(Debug, Default, Trace, Finalize, JsData)]
+pub struct ComponentCache {
+    values: GcRefCell<bevy::utils::HashMap<CacheKey, CachedValue>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Trace, Finalize)]
+struct CacheKey {
+    #[unsafe_ignore_trace]
+    entity: Entity,
+    #[unsafe_ignore_trace]
+    component: ComponentId,
+}
+
+#[derive(Debug, Trace, Finalize)]
+struct CachedValue {
+    /// When the component last changed as of its conversion.
+    #[unsafe_ignore_trace]
+    changed: Tick,
+    value: JsValue,
+    /// The component as it was converted, to tell which fields changed when it next changes.
+    #[unsafe_ignore_trace]
+    reflected: Box<dyn Reflect>,
+}
+
+impl ComponentCache {
+    /// Start caching components converted in a context.
+    pub fn enable(ctx: &mut Context) {
+        if !ctx.has_data::<Self>() {
+            ctx.insert_data(Self::default());
+        }
+    }
+
+    /// Stop caching components converted in a context, dropping those cached so far.
+    pub fn disable(ctx: &mut Context) {
+        ctx.remove_data::<Self>();
+    }
+
+    /// Drop the components cached in a context, e.g. after despawning many entities.
+    pub fn clear(ctx: &mut Context) {
+        if let Some(cache) = ctx.get_data::<Self>() {
+            cache.values.borrow_mut().clear();
+        }
+    }
+}
+
+/// Convert a component of an entity to JS, reusing the last conversion if the context has a
+/// [`ComponentCache`] and the component hasn't changed since. Returns `None` if the entity doesn't
+/// have the component.
+pub fn component_to_js_value(
+    world: &World,
+    entity: Entity,
+    type_id: TypeId,
+    ctx: &mut Context,
+) -> Result<Option<JsValue>, ConversionError> {
+    let registry = world.resource::<AppTypeRegistry>().read();
+    let reflect_component = reflect_component(&registry, type_id)?;
+    let Some(entity_ref) = world.get_entity(entity) else {
+        return Ok(None);
+    };
+    let Some(reflected) = reflect_component.reflect(entity_ref) else {
+        return Ok(None);
+    };
+    let ticks = world
+        .components()
+        .get_id(type_id)
+        .and_then(|id| Some((id, entity_ref.get_change_ticks_by_id(id)?)));
+    let (Some((component, ticks)), true) = (ticks, ctx.has_data::<ComponentCache>()) else {
+        return try_reflect_to_js_value(reflected, ctx).map(Some);
+    };
+    let key = CacheKey { entity, component };
+    let changed = ticks.last_changed_tick();
+    let cached = ctx.get_data::<ComponentCache>().and_then(|cache| {
+        let mut values = cache.values.borrow_mut();
+        match values.get(&key) {
+            Some(cached) if cached.changed == changed => Some(Ok(cached.value.clone())),
+            _ => values.remove(&key).map(Err),
+        }
+    });
+    let value = match cached {
+        Some(Ok(value)) => return Ok(Some(value)),
+        Some(Err(stale)) if !ConversionHooks::has_post_hooks(ctx) => {
+            update_js_value(&stale.value, stale.reflected.as_ref(), reflected, ctx)?
+        }
+        _ => try_reflect_to_js_value(reflected, ctx)?,
+    };
+    if let Some(cache) = ctx.get_data::<ComponentCache>() {
+        let cached = CachedValue {
+            changed,
+            value: value.clone(),
+            reflected: reflected.clone_value(),
+        };
+        cache.values.borrow_mut().insert(key, cached);
+    }
+    Ok(Some(value))
+}
+
+/// Bring a value converted earlier up to date, converting only what changed. The fields of
+/// structs are compared with their previous values and only those that differ are converted and
+/// set on the existing object, so an object that changed in one field keeps the rest of its tree.
+/// Other values, fields whose type can't be compared, and types with a
+/// [`JsConverter`](crate::JsConverter) are converted again whole. Recursion follows struct fields
+/// alone, so its depth is bounded by how deeply the types nest.
+fn update_js_value(
+    js_value: &JsValue,
+    previous: &dyn Reflect,
+    current: &dyn Reflect,
+    ctx: &mut Context,
+) -> Result<JsValue, ConversionError> {
+    if previous.reflect_partial_eq(current) == Some(true) {
+        return Ok(js_value.clone());
+    }
+    let (ReflectRef::Struct(previous_struct), ReflectRef::Struct(current_struct), Some(obj)) = (
+        previous.reflect_ref(),
+        current.reflect_ref(),
+        js_value.as_object(),
+    ) else {
+        return try_reflect_to_js_value(current, ctx);
+    };
+    let type_id = current.as_any().type_id();
+    // Types with converters of their own may not convert to objects keyed by their fields.
+    let converted =
+        JsConverters::of(ctx).is_some_and(|converters| converters.contains_key(&type_id));
+    // The previous value is a clone, which is dynamic, so compare the type it represents.
+    let previous_type = previous.get_represented_type_info().map(TypeInfo::type_id);
+    if current.is_dynamic() || previous_type != Some(type_id) || converted {
+        return try_reflect_to_js_value(current, ctx);
+    }
+    for (idx, current_field) in current_struct.iter_fields().enumerate() {
+        let (Some(name), Some(previous_field)) =
+            (current_struct.name_at(idx), previous_struct.field_at(idx))
+        else {
+            return try_reflect_to_js_value(current, ctx);
+        };
+        let key = JsString::from(name);
+        let field_value = obj.get(key.clone(), ctx)?;
+        let updated = update_js_value(&field_value, previous_field, current_field, ctx)?;
+        if !updated.strict_equals(&field_value) {
+            obj.set(key, updated, false, ctx)?;
+        }
+    }
+    Ok(js_value.clone())
+}
+
+/// The `ReflectComponent` of a registered component type.
+pub(crate) fn reflect_component(
+    registry: &TypeRegistry,
+    type_id: TypeId,
+) -> Result<&ReflectComponent, ConversionError> {
+    let registration = registry
+        .get(type_id)
+        .ok_or_else(|| ConversionError::Unregistered {
+            type_path: format!("{type_id:?}"),
+            path: FieldPath::default(),
+        })?;
+    registration
+        .data::<ReflectComponent>()
+        .ok_or_else(|| ConversionError::Unsupported {
+            type_path: registration.type_info().type_path().to_owned(),
+            path: FieldPath::default(),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use boa_engine::js_str;
+
+    use crate::errors::PathSegment;
+    use crate::hooks::HookAction;
+
+    use super::*;
+
+    #[derive(Component, Reflect)]
+    #[reflect(Component)]
+    struct Account {
+        name: String,
+        password: String,
+    }
+
+    #[derive(Component, Reflect, Default)]
+    #[reflect(Component)]
+    struct Mover {
+        position: Position,
+        limits: Position,
+        speed: f32,
+    }
+
+    #[derive(Reflect, Default, PartialEq)]
+    #[reflect(PartialEq)]
+    struct Position {
+        x: f32,
+        y: f32,
+    }
+
+    fn mover_world() -> (World, Entity) {
+        let mut world = World::new();
+        world.init_resource::<AppTypeRegistry>();
+        world
+            .resource::<AppTypeRegistry>()
+            .write()
+            .register::<Mover>();
+        let entity = world.spawn(Mover::default()).id();
+        (world, entity)
+    }
+
+    fn convert_mover(world: &World, entity: Entity, ctx: &mut Context) -> JsValue {
+        component_to_js_value(world, entity, TypeId::of::<Mover>(), ctx)
+            .unwrap()
+            .unwrap()
+    }
+
+    fn same(a: &JsValue, b: &JsValue) -> bool {
+        a.strict_equals(b)
+    }
+
+    fn redact(_: &str, path: &FieldPath, _: &JsValue) -> HookAction {
+        match path.segments.last() {
+            Some(PathSegment::Field(field)) if field == "password" => {
+                HookAction::Replace(JsValue::null())
+            }
+            _ => HookAction::Keep,
+        }
+    }
+
+    fn convert(world: &World, entity: Entity, ctx: &mut Context) -> JsValue {
+        component_to_js_value(world, entity, TypeId::of::<Account>(), ctx)
+            .unwrap()
+            .unwrap()
+    }
+
+    fn field(value: &JsValue, name: &str, ctx: &mut Context) -> JsValue {
+        value
+            .as_object()
+            .unwrap()
+            .get(JsString::from(name), ctx)
+            .unwrap()
+    }
+
+    #[test]
+    fn changed_components_run_hooks() {
+        let mut world = World::new();
+        world.init_resource::<AppTypeRegistry>();
+        world
+            .resource::<AppTypeRegistry>()
+            .write()
+            .register::<Account>();
+        let entity = world
+            .spawn(Account {
+                name: "player".to_owned(),
+                password: "hunter2".to_owned(),
+            })
+            .id();
+        let mut ctx = Context::default();
+        ComponentCache::enable(&mut ctx);
+        ConversionHooks::register_post(&mut ctx, redact);
+
+        let first = convert(&world, entity, &mut ctx);
+        assert!(field(&first, "password", &mut ctx).is_null());
+
+        world.increment_change_tick();
+        world.get_mut::<Account>(entity).unwrap().password = "hunter3".to_owned();
+        let changed = convert(&world, entity, &mut ctx);
+        assert!(field(&changed, "password", &mut ctx).is_null());
+        assert_eq!(
+            field(&changed, "name", &mut ctx),
+            JsValue::from(js_str!("player"))
+        );
+    }
+
+    #[test]
+    fn unchanged_components_are_the_same_object() {
+        let (world, entity) = mover_world();
+        let mut ctx = Context::default();
+        let uncached = convert_mover(&world, entity, &mut ctx);
+        assert!(!same(&uncached, &convert_mover(&world, entity, &mut ctx)));
+
+        ComponentCache::enable(&mut ctx);
+        let first = convert_mover(&world, entity, &mut ctx);
+        world.increment_change_tick();
+        assert!(same(&first, &convert_mover(&world, entity, &mut ctx)));
+
+        ComponentCache::clear(&mut ctx);
+        assert!(!same(&first, &convert_mover(&world, entity, &mut ctx)));
+    }
+
+    #[test]
+    fn changed_components_are_patched_in_place() {
+        let (mut world, entity) = mover_world();
+        let mut ctx = Context::default();
+        ComponentCache::enable(&mut ctx);
+        let first = convert_mover(&world, entity, &mut ctx);
+        let position = field(&first, "position", &mut ctx);
+        let limits = field(&first, "limits", &mut ctx);
+
+        world.increment_change_tick();
+        let mut mover = world.get_mut::<Mover>(entity).unwrap();
+        mover.position.x = 2.0;
+        mover.speed = 3.0;
+        let changed = convert_mover(&world, entity, &mut ctx);
+        assert!(same(&first, &changed));
+        assert!(same(&position, &field(&changed, "position", &mut ctx)));
+        assert!(same(&limits, &field(&changed, "limits", &mut ctx)));
+        assert_eq!(field(&position, "x", &mut ctx), JsValue::new(2.0));
+        assert_eq!(field(&changed, "speed", &mut ctx), JsValue::new(3.0));
+    }
+}
@@ -0,0 +1,161 @@
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use boa_engine::object::builtins::JsArray;
+use boa_engine::{js_str, Context, JsObject, JsResult, JsString, JsValue};
+
+use crate::script::ScriptAsset;
+
+/// The export a script can describe itself with, as an alternative to a header comment.
+pub const METADATA_EXPORT: &str = "metadata";
+
+/// What a script says about itself, for mod managers and compatibility checks. Scripts declare it
+/// in a header comment,
+///
+/// ```js
+/// // @name Jetpack
+/// // @version 1.2.0
+/// // @author someone
+/// // @requires world, commands
+/// ```
+///
+/// or by exporting it as `exports.metadata = { name, version, author, requires: [...] }`, which
+/// takes precedence over the header once the script has been evaluated.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScriptManifest {
+    pub name: Option<String>,
+    pub version: Option<String>,
+    pub author: Option<String>,
+    /// The globals the script expects the engine to provide, e.g. `world` or `commands`.
+    pub requires: Vec<String>,
+}
+
+impl ScriptManifest {
+    /// Read the `@key value` lines from the comments at the top of a script. Returns `None` if it
+    /// has none.
+    pub fn from_header(source: &str) -> Option<Self> {
+        let mut manifest = Self::default();
+        let mut found = false;
+        let mut in_block = false;
+        for line in source.lines() {
+            let line = line.trim();
+            let text = if in_block {
+                if let Some(end) = line.find("*/") {
+                    in_block = false;
+                    &line[..end]
+                } else {
+                    line
+                }
+            } else if let Some(comment) = line.strip_prefix("//") {
+                comment
+            } else if let Some(comment) = line.strip_prefix("/*") {
+                match comment.find("*/") {
+                    Some(end) => &comment[..end],
+                    None => {
+                        in_block = true;
+                        comment
+                    }
+                }
+            } else if line.is_empty() {
+                continue;
+            } else {
+                break;
+            };
+            let text = text.trim().trim_start_matches('*').trim();
+            let Some((key, value)) = text
+                .strip_prefix('@')
+                .map(|text| text.split_once(char::is_whitespace).unwrap_or((text, "")))
+            else {
+                continue;
+            };
+            let value = value.trim();
+            match key {
+                "name" => manifest.name = Some(value.to_owned()),
+                "version" => manifest.version = Some(value.to_owned()),
+                "author" => manifest.author = Some(value.to_owned()),
+                "requires" => manifest.requires.extend(
+                    value
+                        .split(|c: char| c == ',' || c.is_whitespace())
+                        .filter(|name| !name.is_empty())
+                        .map(str::to_owned),
+                ),
+                _ => continue,
+            }
+            found = true;
+        }
+        found.then_some(manifest)
+    }
+
+    /// Read the manifest a script exported as `metadata`, if it exported one.
+    pub fn from_exports(exports: &JsObject, ctx: &mut Context) -> JsResult<Option<Self>> {
+        let metadata = exports.get(JsString::from(METADATA_EXPORT), ctx)?;
+        let Some(metadata) = metadata.as_object() else {
+            return Ok(None);
+        };
+        let string = |value: JsValue, ctx: &mut Context| -> JsResult<Option<String>> {
+            if value.is_null_or_undefined() {
+                return Ok(None);
+            }
+            Ok(Some(value.to_string(ctx)?.to_std_string_escaped()))
+        };
+        let mut requires = Vec::new();
+        let required = metadata.get(js_str!("requires"), ctx)?;
+        if let Some(required) = required.as_object().filter(|obj| obj.is_array()) {
+            let required = JsArray::from_object(required.clone())?;
+            for idx in 0..required.length(ctx)? {
+                requires.extend(string(required.get(idx, ctx)?, ctx)?);
+            }
+        }
+        Ok(Some(Self {
+            name: string(metadata.get(js_str!("name"), ctx)?, ctx)?,
+            version: string(metadata.get(js_str!("version"), ctx)?, ctx)?,
+            author: string(metadata.get(js_str!("author"), ctx)?, ctx)?,
+            requires,
+        }))
+    }
+}
+
+/// The manifests of the loaded scripts, kept up to date as scripts load, reload and unload.
+#[derive(Resource, Default)]
+pub struct ScriptMetadata {
+    scripts: HashMap<AssetId<ScriptAsset>, ScriptMetadataEntry>,
+}
+
+struct ScriptMetadataEntry {
+    manifest: ScriptManifest,
+    missing: Vec<String>,
+}
+
+impl ScriptMetadata {
+    /// The manifest a script declared, if it declared one.
+    pub fn get(&self, id: impl Into<AssetId<ScriptAsset>>) -> Option<&ScriptManifest> {
+        self.scripts.get(&id.into()).map(|entry| &entry.manifest)
+    }
+
+    /// The bindings a script requires that the engine doesn't provide. Empty for scripts that
+    /// are compatible, or that didn't declare a manifest.
+    pub fn missing_bindings(&self, id: impl Into<AssetId<ScriptAsset>>) -> &[String] {
+        self.scripts
+            .get(&id.into())
+            .map_or(&[], |entry| entry.missing.as_slice())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (AssetId<ScriptAsset>, &ScriptManifest)> {
+        self.scripts
+            .iter()
+            .map(|(id, entry)| (*id, &entry.manifest))
+    }
+
+    pub(crate) fn insert(
+        &mut self,
+        id: AssetId<ScriptAsset>,
+        manifest: ScriptManifest,
+        missing: Vec<String>,
+    ) {
+        self.scripts
+            .insert(id, ScriptMetadataEntry { manifest, missing });
+    }
+
+    pub(crate) fn remove(&mut self, id: AssetId<ScriptAsset>) {
+        self.scripts.remove(&id);
+    }
+}
@@ -0,0 +1,50 @@
+use std::any::TypeId;
+use std::sync::Arc;
+
+use bevy::prelude::*;
+use bevy::reflect::TypePath;
+use bevy::utils::HashMap;
+use boa_engine::{Context, JsNativeError, JsResult, JsValue};
+
+/// A method called on a reflected value from a script, with the script's arguments.
+pub type ScriptMethod =
+    Arc<dyn Fn(&mut dyn Reflect, &[JsValue], &mut Context) -> JsResult<JsValue> + Send + Sync>;
+
+/// Methods attached to the prototypes of the classes generated for reflected types, so scripts can
+/// call `timer.tick(dt)` straight into Rust. bevy_reflect 0.14 has no function reflection, so
+/// methods are registered here rather than discovered from the type registry.
+#[derive(Resource, Default, Clone)]
+pub struct ScriptMethods {
+    methods: HashMap<TypeId, Vec<(&'static str, ScriptMethod)>>,
+}
+
+impl ScriptMethods {
+    /// Register a method for `T`. Changes the method makes to the value are written back to the
+    /// script object it was called on.
+    pub fn register<T, F>(&mut self, name: &'static str, method: F) -> &mut Self
+    where
+        T: Reflect + TypePath,
+        F: Fn(&mut T, &[JsValue], &mut Context) -> JsResult<JsValue> + Send + Sync + 'static,
+    {
+        let method: ScriptMethod = Arc::new(move |value, args, ctx| {
+            let value = value.downcast_mut::<T>().ok_or_else(|| {
+                JsNativeError::typ().with_message("Method called on a value of the wrong type")
+            })?;
+            method(value, args, ctx)
+        });
+        let methods = self.methods.entry(TypeId::of::<T>()).or_default();
+        methods.retain(|(existing, _)| *existing != name);
+        methods.push((name, method));
+        self
+    }
+
+    /// The methods registered for a type.
+    pub fn get(&self, type_id: TypeId) -> &[(&'static str, ScriptMethod)] {
+        self.methods.get(&type_id).map_or(&[], Vec::as_slice)
+    }
+
+    /// The types that have methods registered.
+    pub fn types(&self) -> impl Iterator<Item = TypeId> + '_ {
+        self.methods.keys().copied()
+    }
+}
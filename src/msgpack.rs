@@ -0,0 +1,448 @@
+use bevy_reflect::{FromReflect, Reflect, TypePath, TypeRegistry};
+use boa_engine::builtins::map::ordered_map::OrderedMap;
+use boa_engine::object::builtins::{
+    JsArray, JsFloat32Array, JsFloat64Array, JsInt16Array, JsInt32Array, JsInt8Array, JsMap,
+    JsTypedArray, JsUint16Array, JsUint32Array, JsUint8Array,
+};
+use boa_engine::object::ObjectInitializer;
+use boa_engine::property::Attribute;
+use boa_engine::{Context, JsBigInt, JsError, JsNativeError, JsResult, JsString, JsValue};
+
+#[cfg(feature = "bevy")]
+use crate::classes::instance_of;
+use crate::errors::ConversionError;
+use crate::from::{js_map_entries, js_value_to_typed_with_settings};
+use crate::into::try_reflect_to_js_value_with_settings;
+use crate::settings::ConversionSettings;
+
+/// How deeply nested a value can be before encoding gives up, which also catches cycles.
+const MAX_DEPTH: usize = 64;
+
+/// Extension types for the values MessagePack has no type of its own for.
+const EXT_UNDEFINED: i8 = 0;
+/// A `BigInt`, as its decimal digits.
+const EXT_BIGINT: i8 = 1;
+/// A `Map`, as an array of its keys and values in turn.
+const EXT_MAP: i8 = 2;
+/// A typed array other than `Uint8Array`, as a kind byte and its elements in little endian.
+const EXT_TYPED_ARRAY: i8 = 3;
+
+/// Convert a reflected value to MessagePack, through the same conversion and settings that give
+/// scripts the value, for save games and sending script state over the network.
+pub fn reflect_to_msgpack(
+    value: &dyn Reflect,
+    settings: &ConversionSettings,
+    ctx: &mut Context,
+) -> Result<Vec<u8>, ConversionError> {
+    let js_value = try_reflect_to_js_value_with_settings(value, settings, ctx)?;
+    Ok(js_value_to_msgpack(&js_value, ctx)?)
+}
+
+/// Convert MessagePack into a registered type, reading it as scripts' values are read with the
+/// same settings. Reads what [`reflect_to_msgpack`] writes back into an equal value.
+pub fn msgpack_to_reflect<T: FromReflect + TypePath>(
+    bytes: &[u8],
+    registry: &TypeRegistry,
+    settings: &ConversionSettings,
+    ctx: &mut Context,
+) -> Result<T, ConversionError> {
+    let value = msgpack_to_js_value(bytes, ctx)?;
+    js_value_to_typed_with_settings(value, registry, settings, ctx)
+}
+
+/// Encode a JS value as MessagePack. Unlike JSON, `BigInt`s, `Map`s, `undefined` and typed
+/// arrays keep their types: `Uint8Array`s become binary, and the rest use extension types.
+/// Functions become `nil`, and instances of generated classes become their fields.
+pub fn js_value_to_msgpack(value: &JsValue, ctx: &mut Context) -> JsResult<Vec<u8>> {
+    let mut out = Vec::new();
+    encode(value, 0, &mut out, ctx)?;
+    Ok(out)
+}
+
+/// Decode MessagePack into a JS value, reading back what [`js_value_to_msgpack`] writes.
+pub fn msgpack_to_js_value(bytes: &[u8], ctx: &mut Context) -> JsResult<JsValue> {
+    let mut reader = Reader { bytes, pos: 0 };
+    let value = reader.decode(0, ctx)?;
+    if reader.pos != bytes.len() {
+        return Err(invalid("trailing bytes after the value"));
+    }
+    Ok(value)
+}
+
+fn encode(value: &JsValue, depth: usize, out: &mut Vec<u8>, ctx: &mut Context) -> JsResult<()> {
+    if depth > MAX_DEPTH {
+        return Err(JsNativeError::typ()
+            .with_message("Value is too deeply nested to convert to MessagePack")
+            .into());
+    }
+    match value {
+        JsValue::Null => out.push(0xc0),
+        JsValue::Undefined => write_ext(EXT_UNDEFINED, &[0], out),
+        JsValue::Boolean(b) => out.push(if *b { 0xc3 } else { 0xc2 }),
+        JsValue::Integer(i) => write_int(i64::from(*i), out),
+        JsValue::Rational(f) => {
+            out.push(0xcb);
+            out.extend_from_slice(&f.to_be_bytes());
+        }
+        JsValue::String(s) => write_str(&s.to_std_string_escaped(), out),
+        JsValue::BigInt(b) => write_ext(EXT_BIGINT, b.to_string().as_bytes(), out),
+        JsValue::Symbol(_) => {
+            return Err(JsNativeError::typ()
+                .with_message("Symbols cannot be converted to MessagePack")
+                .into())
+        }
+        JsValue::Object(obj) => {
+            #[cfg(feature = "bevy")]
+            if let Some((_, fields)) = instance_of(obj) {
+                return encode(&fields.into(), depth + 1, out, ctx);
+            }
+            if obj.is_callable() {
+                out.push(0xc0);
+            } else if obj.is_array() {
+                let array = JsArray::from_object(obj.clone())?;
+                let len = array.length(ctx)?;
+                write_header(len as usize, [0x90, 0xdc, 0xdd], 16, out);
+                for idx in 0..len {
+                    encode(&array.get(idx, ctx)?, depth + 1, out, ctx)?;
+                }
+            } else if let Ok(typed) = JsTypedArray::from_object(obj.clone()) {
+                encode_typed_array(&typed, out, ctx)?;
+            } else if obj.is::<OrderedMap<JsValue>>() {
+                let entries = js_map_entries(value, "MessagePack", ctx)?;
+                let mut inner = Vec::new();
+                write_header(entries.len() * 2, [0x90, 0xdc, 0xdd], 16, &mut inner);
+                for (key, value) in entries {
+                    encode(&key, depth + 1, &mut inner, ctx)?;
+                    encode(&value, depth + 1, &mut inner, ctx)?;
+                }
+                write_ext(EXT_MAP, &inner, out);
+            } else {
+                let entries = js_map_entries(value, "MessagePack", ctx)?;
+                write_header(entries.len(), [0x80, 0xde, 0xdf], 16, out);
+                for (key, value) in entries {
+                    write_str(&key.to_string(ctx)?.to_std_string_escaped(), out);
+                    encode(&value, depth + 1, out, ctx)?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The typed arrays extension types hold, by the kind byte they're written with.
+const TYPED_ARRAYS: [&str; 7] = [
+    "Int8Array",
+    "Int16Array",
+    "Uint16Array",
+    "Int32Array",
+    "Uint32Array",
+    "Float32Array",
+    "Float64Array",
+];
+
+fn encode_typed_array(typed: &JsTypedArray, out: &mut Vec<u8>, ctx: &mut Context) -> JsResult<()> {
+    let tag = typed
+        .to_string_tag(ctx)?
+        .to_string(ctx)?
+        .to_std_string_escaped();
+    let len = typed.length(ctx)?;
+    let mut numbers = Vec::with_capacity(len);
+    for idx in 0..len {
+        numbers.push(typed.at(idx as i64, ctx)?.to_number(ctx)?);
+    }
+    if tag == "Uint8Array" {
+        let bytes = numbers.iter().map(|n| *n as u8).collect::<Vec<_>>();
+        write_header(bytes.len(), [0xc4, 0xc5, 0xc6], 0, out);
+        out.extend_from_slice(&bytes);
+        return Ok(());
+    }
+    let kind = TYPED_ARRAYS
+        .iter()
+        .position(|name| *name == tag)
+        .ok_or_else(|| {
+            JsNativeError::typ().with_message(format!("{tag} cannot be converted to MessagePack"))
+        })?;
+    let mut payload = vec![kind as u8];
+    for n in numbers {
+        match kind {
+            0 => payload.extend_from_slice(&(n as i8).to_le_bytes()),
+            1 => payload.extend_from_slice(&(n as i16).to_le_bytes()),
+            2 => payload.extend_from_slice(&(n as u16).to_le_bytes()),
+            3 => payload.extend_from_slice(&(n as i32).to_le_bytes()),
+            4 => payload.extend_from_slice(&(n as u32).to_le_bytes()),
+            5 => payload.extend_from_slice(&(n as f32).to_le_bytes()),
+            _ => payload.extend_from_slice(&n.to_le_bytes()),
+        }
+    }
+    write_ext(EXT_TYPED_ARRAY, &payload, out);
+    Ok(())
+}
+
+fn write_int(n: i64, out: &mut Vec<u8>) {
+    match n {
+        0..=0x7f => out.push(n as u8),
+        -32..=-1 => out.push(n as i8 as u8),
+        0x80..=0xff => out.extend_from_slice(&[0xcc, n as u8]),
+        0x100..=0xffff => {
+            out.push(0xcd);
+            out.extend_from_slice(&(n as u16).to_be_bytes());
+        }
+        0x1_0000..=0xffff_ffff => {
+            out.push(0xce);
+            out.extend_from_slice(&(n as u32).to_be_bytes());
+        }
+        -0x80..=-33 => out.extend_from_slice(&[0xd0, n as i8 as u8]),
+        -0x8000..=-0x81 => {
+            out.push(0xd1);
+            out.extend_from_slice(&(n as i16).to_be_bytes());
+        }
+        -0x8000_0000..=-0x8001 => {
+            out.push(0xd2);
+            out.extend_from_slice(&(n as i32).to_be_bytes());
+        }
+        _ => {
+            out.push(0xd3);
+            out.extend_from_slice(&n.to_be_bytes());
+        }
+    }
+}
+
+fn write_str(s: &str, out: &mut Vec<u8>) {
+    if s.len() < 32 {
+        out.push(0xa0 | s.len() as u8);
+    } else {
+        write_header(s.len(), [0xd9, 0xda, 0xdb], 0, out);
+    }
+    out.extend_from_slice(s.as_bytes());
+}
+
+/// Write the length of a string, binary, array or map, with the fix form if it fits below
+/// `fix_limit`, then the 8, 16 or 32-bit forms. Arrays and maps have no 8-bit form, so give them
+/// their fix form's marker in its place, which is never used for lengths past the limit.
+fn write_header(len: usize, markers: [u8; 3], fix_limit: usize, out: &mut Vec<u8>) {
+    let [first, marker16, marker32] = markers;
+    if len < fix_limit {
+        out.push(first | len as u8);
+    } else if fix_limit == 0 && len <= 0xff {
+        out.extend_from_slice(&[first, len as u8]);
+    } else if len <= 0xffff {
+        out.push(marker16);
+        out.extend_from_slice(&(len as u16).to_be_bytes());
+    } else {
+        out.push(marker32);
+        out.extend_from_slice(&(len as u32).to_be_bytes());
+    }
+}
+
+fn write_ext(ext: i8, data: &[u8], out: &mut Vec<u8>) {
+    match data.len() {
+        1 => out.push(0xd4),
+        2 => out.push(0xd5),
+        4 => out.push(0xd6),
+        8 => out.push(0xd7),
+        16 => out.push(0xd8),
+        len => write_header(len, [0xc7, 0xc8, 0xc9], 0, out),
+    }
+    out.push(ext as u8);
+    out.extend_from_slice(data);
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> JsResult<&'a [u8]> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or_else(|| invalid("unexpected end of input"))?;
+        let taken = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(taken)
+    }
+
+    fn fixed<const N: usize>(&mut self) -> JsResult<[u8; N]> {
+        let mut array = [0; N];
+        array.copy_from_slice(self.take(N)?);
+        Ok(array)
+    }
+
+    fn byte(&mut self) -> JsResult<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    /// A big endian length of 1, 2 or 4 bytes.
+    fn len(&mut self, size: usize) -> JsResult<usize> {
+        Ok(match size {
+            1 => usize::from(self.byte()?),
+            2 => usize::from(u16::from_be_bytes(self.fixed()?)),
+            _ => u32::from_be_bytes(self.fixed()?) as usize,
+        })
+    }
+
+    fn decode(&mut self, depth: usize, ctx: &mut Context) -> JsResult<JsValue> {
+        if depth > MAX_DEPTH {
+            return Err(invalid("value is too deeply nested"));
+        }
+        let marker = self.byte()?;
+        Ok(match marker {
+            0x00..=0x7f => JsValue::Integer(i32::from(marker)),
+            0x80..=0x8f => self.map(usize::from(marker & 0x0f), depth, ctx)?,
+            0x90..=0x9f => self.array(usize::from(marker & 0x0f), depth, ctx)?,
+            0xa0..=0xbf => self.string(usize::from(marker & 0x1f))?,
+            0xc0 => JsValue::Null,
+            0xc2 => JsValue::Boolean(false),
+            0xc3 => JsValue::Boolean(true),
+            0xc4..=0xc6 => {
+                let len = self.len(1 << (marker - 0xc4))?;
+                let bytes = self.take(len)?.to_vec();
+                JsUint8Array::from_iter(bytes, ctx)?.into()
+            }
+            0xc7..=0xc9 => {
+                let len = self.len(1 << (marker - 0xc7))?;
+                self.ext(len, ctx)?
+            }
+            0xca => JsValue::Rational(f64::from(f32::from_be_bytes(self.fixed()?))),
+            0xcb => JsValue::Rational(f64::from_be_bytes(self.fixed()?)),
+            0xcc => JsValue::Integer(i32::from(self.byte()?)),
+            0xcd => JsValue::Integer(i32::from(u16::from_be_bytes(self.fixed()?))),
+            0xce => number(i64::from(u32::from_be_bytes(self.fixed()?))),
+            0xcf => {
+                let n = u64::from_be_bytes(self.fixed()?);
+                i64::try_from(n).map_or(JsValue::Rational(n as f64), number)
+            }
+            0xd0 => JsValue::Integer(i32::from(self.byte()? as i8)),
+            0xd1 => JsValue::Integer(i32::from(i16::from_be_bytes(self.fixed()?))),
+            0xd2 => JsValue::Integer(i32::from_be_bytes(self.fixed()?)),
+            0xd3 => number(i64::from_be_bytes(self.fixed()?)),
+            0xd4..=0xd8 => self.ext(1 << (marker - 0xd4), ctx)?,
+            0xd9..=0xdb => {
+                let len = self.len(1 << (marker - 0xd9))?;
+                self.string(len)?
+            }
+            0xdc | 0xdd => {
+                let len = self.len(if marker == 0xdc { 2 } else { 4 })?;
+                self.array(len, depth, ctx)?
+            }
+            0xde | 0xdf => {
+                let len = self.len(if marker == 0xde { 2 } else { 4 })?;
+                self.map(len, depth, ctx)?
+            }
+            0xe0..=0xff => JsValue::Integer(i32::from(marker as i8)),
+            0xc1 => return Err(invalid("0xc1 is never used")),
+        })
+    }
+
+    fn string(&mut self, len: usize) -> JsResult<JsValue> {
+        let s = std::str::from_utf8(self.take(len)?)
+            .map_err(|_| invalid("a string is not valid UTF-8"))?;
+        Ok(JsString::from(s).into())
+    }
+
+    fn items(&mut self, len: usize, depth: usize, ctx: &mut Context) -> JsResult<Vec<JsValue>> {
+        // Each item takes at least a byte, which bounds what a corrupt length can allocate.
+        let mut items = Vec::with_capacity(len.min(self.bytes.len() - self.pos));
+        for _ in 0..len {
+            items.push(self.decode(depth + 1, ctx)?);
+        }
+        Ok(items)
+    }
+
+    fn array(&mut self, len: usize, depth: usize, ctx: &mut Context) -> JsResult<JsValue> {
+        let items = self.items(len, depth, ctx)?;
+        Ok(JsArray::from_iter(items, ctx).into())
+    }
+
+    fn map(&mut self, len: usize, depth: usize, ctx: &mut Context) -> JsResult<JsValue> {
+        let mut properties = Vec::new();
+        for _ in 0..len {
+            let key = self.decode(depth + 1, ctx)?.to_string(ctx)?;
+            properties.push((key, self.decode(depth + 1, ctx)?));
+        }
+        let mut obj = ObjectInitializer::new(ctx);
+        for (key, value) in properties {
+            obj.property(key, value, Attribute::all());
+        }
+        Ok(obj.build().into())
+    }
+
+    fn ext(&mut self, len: usize, ctx: &mut Context) -> JsResult<JsValue> {
+        let ext = self.byte()? as i8;
+        let data = self.take(len)?;
+        Ok(match ext {
+            EXT_UNDEFINED => JsValue::undefined(),
+            EXT_BIGINT => std::str::from_utf8(data)
+                .ok()
+                .and_then(JsBigInt::from_string)
+                .ok_or_else(|| invalid("a BigInt has invalid digits"))?
+                .into(),
+            EXT_MAP => {
+                let mut inner = Reader {
+                    bytes: data,
+                    pos: 0,
+                };
+                let items = inner.decode(0, ctx)?;
+                let items = JsArray::from_object(
+                    items
+                        .as_object()
+                        .filter(|obj| obj.is_array())
+                        .cloned()
+                        .ok_or_else(|| invalid("a Map is not an array of entries"))?,
+                )?;
+                let map = JsMap::new(ctx);
+                for idx in (0..items.length(ctx)?).step_by(2) {
+                    map.set(items.get(idx, ctx)?, items.get(idx + 1, ctx)?, ctx)?;
+                }
+                map.into()
+            }
+            EXT_TYPED_ARRAY => {
+                let (&kind, elements) = data
+                    .split_first()
+                    .ok_or_else(|| invalid("a typed array has no kind"))?;
+                typed_array(kind, elements, ctx)?
+            }
+            _ => return Err(invalid(&format!("unknown extension type {ext}"))),
+        })
+    }
+}
+
+fn typed_array(kind: u8, elements: &[u8], ctx: &mut Context) -> JsResult<JsValue> {
+    macro_rules! read {
+        ($ty:ty, $array:ty) => {{
+            let size = std::mem::size_of::<$ty>();
+            if elements.len() % size != 0 {
+                return Err(invalid("a typed array has a partial element"));
+            }
+            let values = elements.chunks_exact(size).map(|chunk| {
+                let mut bytes = [0; std::mem::size_of::<$ty>()];
+                bytes.copy_from_slice(chunk);
+                <$ty>::from_le_bytes(bytes)
+            });
+            <$array>::from_iter(values, ctx)?.into()
+        }};
+    }
+    Ok(match kind {
+        0 => read!(i8, JsInt8Array),
+        1 => read!(i16, JsInt16Array),
+        2 => read!(u16, JsUint16Array),
+        3 => read!(i32, JsInt32Array),
+        4 => read!(u32, JsUint32Array),
+        5 => read!(f32, JsFloat32Array),
+        6 => read!(f64, JsFloat64Array),
+        _ => return Err(invalid(&format!("unknown typed array kind {kind}"))),
+    })
+}
+
+/// An integer from other encoders, which may not fit in an `i32`.
+fn number(n: i64) -> JsValue {
+    i32::try_from(n).map_or(JsValue::Rational(n as f64), JsValue::Integer)
+}
+
+fn invalid(message: &str) -> JsError {
+    JsNativeError::syntax()
+        .with_message(format!("Invalid MessagePack: {message}"))
+        .into()
+}
@@ -0,0 +1,432 @@
+use std::any::TypeId;
+
+use bevy_reflect::prelude::*;
+use bevy_reflect::{ReflectRef, StructInfo, TypeInfo};
+use bevy_tasks::{ComputeTaskPool, ParallelSlice, TaskPool};
+use bevy_utils::HashSet;
+use boa_engine::object::builtins::JsArray;
+use boa_engine::{Context, JsResult, JsValue};
+
+use crate::converters::JsConverters;
+use crate::engine::{entries_to_js_map, variant_to_js_object, Primitive};
+use crate::errors::{ConversionError, FieldPath};
+use crate::hooks::ConversionHooks;
+use crate::into::{take_last, try_reflect_to_js_value};
+use crate::templates::{ObjectTemplate, ObjectTemplates};
+
+/// Lists and maps with at least this many items are converted in parallel by
+/// [`reflect_to_js_value_parallel`]. Below it, spreading the work over threads costs more than it
+/// saves.
+pub const PARALLEL_THRESHOLD: usize = 4096;
+
+/// Like [`reflect_to_js_value`](crate::reflect_to_js_value), splitting big lists and maps across
+/// the compute task pool. Their items are walked and their numbers and strings read out on
+/// the pool's threads, then the JS values are made from what was read on the calling thread, as a
+/// context can only be used from its own thread. Other values, and lists and maps with fewer than
+/// [`PARALLEL_THRESHOLD`] items, are converted as usual, as is everything on `wasm32` and in
+/// contexts with [`ConversionHooks`], which run with each value as it is made.
+pub fn reflect_to_js_value_parallel(value: &dyn Reflect, ctx: &mut Context) -> JsResult<JsValue> {
+    Ok(try_reflect_to_js_value_parallel(value, ctx)?)
+}
+
+/// Like [`reflect_to_js_value_parallel`], returning a [`ConversionError`] for Rust callers.
+pub fn try_reflect_to_js_value_parallel(
+    value: &dyn Reflect,
+    ctx: &mut Context,
+) -> Result<JsValue, ConversionError> {
+    // The web has no threads for the pool to run on, so there is nothing to gain from planning
+    // conversions apart from making them.
+    if cfg!(target_arch = "wasm32") || ConversionHooks::has_post_hooks(ctx) {
+        return try_reflect_to_js_value(value, ctx);
+    }
+    let (items, collect): (Vec<&dyn Reflect>, Planned) = match value.reflect_ref() {
+        ReflectRef::List(l) if l.len() >= PARALLEL_THRESHOLD => {
+            (l.iter().collect(), Planned::Array(l.len()))
+        }
+        ReflectRef::Array(a) if a.len() >= PARALLEL_THRESHOLD => {
+            (a.iter().collect(), Planned::Array(a.len()))
+        }
+        ReflectRef::Map(m) if m.len() >= PARALLEL_THRESHOLD => (
+            m.iter().flat_map(|(key, value)| [key, value]).collect(),
+            Planned::Map(m.len()),
+        ),
+        _ => return try_reflect_to_js_value(value, ctx),
+    };
+    #[cfg(feature = "trace")]
+    let _span = bevy_utils::tracing::info_span!(
+        "reflect_to_js_value_parallel",
+        type_path = value.reflect_type_path(),
+        elements = items.len(),
+    )
+    .entered();
+    let mut converted = Vec::new();
+    for planned in plan_parallel(&items, ctx)? {
+        build(planned, &mut converted, ctx)?;
+    }
+    build(collect, &mut converted, ctx)?;
+    Ok(converted.pop().unwrap_or_default())
+}
+
+/// Like [`reflect_slice_to_js_array`](crate::reflect_slice_to_js_array), walking the values on
+/// the compute task pool as [`reflect_to_js_value_parallel`] does when there are at least
+/// [`PARALLEL_THRESHOLD`] of them.
+pub fn reflect_slice_to_js_array_parallel(
+    values: &[&dyn Reflect],
+    ctx: &mut Context,
+) -> JsResult<JsArray> {
+    Ok(try_reflect_slice_to_js_array_parallel(values, ctx)?)
+}
+
+/// Like [`reflect_slice_to_js_array_parallel`], returning a [`ConversionError`] for Rust callers.
+pub fn try_reflect_slice_to_js_array_parallel(
+    values: &[&dyn Reflect],
+    ctx: &mut Context,
+) -> Result<JsArray, ConversionError> {
+    if values.len() < PARALLEL_THRESHOLD || ConversionHooks::has_post_hooks(ctx) {
+        return crate::into::try_reflect_slice_to_js_array(values, ctx);
+    }
+    #[cfg(feature = "trace")]
+    let _span = bevy_utils::tracing::info_span!(
+        "reflect_slice_to_js_array_parallel",
+        elements = values.len()
+    )
+    .entered();
+    let mut converted = Vec::with_capacity(values.len());
+    for planned in plan_parallel(values, ctx)? {
+        build(planned, &mut converted, ctx)?;
+    }
+    Ok(JsArray::from_iter(converted, ctx))
+}
+
+/// A conversion worked out without a context, so on any thread. Values come after their children,
+/// in the order their JS values are made.
+enum Planned {
+    Value(Primitive<'static>),
+    /// A value with a converter registered for its type, copied to convert once back on the
+    /// context's thread.
+    Converted(Box<dyn Reflect>),
+    /// Collect the last converted values into an object keyed by the struct type's field names.
+    /// Only dynamic structs own their names, as other types' come from their static type info.
+    Struct(&'static StructInfo),
+    DynamicStruct(Vec<String>),
+    /// Collect the last `len` converted values into an array.
+    Array(usize),
+    /// Collect the last `len` converted keys and values into a `Map`.
+    Map(usize),
+    /// Collect the last converted values into an object for the variant, keyed by the names of
+    /// its fields, or their indices for tuple variants.
+    Enum {
+        variant: String,
+        names: Vec<Option<String>>,
+    },
+}
+
+/// Plan the conversions of values on the compute task pool, in chunks of about the same size for
+/// each of its threads. The plans come back in the order of the values.
+fn plan_parallel(values: &[&dyn Reflect], ctx: &Context) -> Result<Vec<Planned>, ConversionError> {
+    let converters = JsConverters::of(ctx);
+    // Converters are plain functions, so the types they're for can go to other threads.
+    let converted_types = converters
+        .iter()
+        .flat_map(|converters| converters.keys().copied())
+        .collect::<HashSet<_>>();
+    let pool = ComputeTaskPool::get_or_init(TaskPool::default);
+    let chunk_size = values.len().div_ceil(pool.thread_num().max(1));
+    let chunks = values.par_chunk_map(pool, chunk_size, |_, chunk| {
+        let mut planned = Vec::new();
+        for value in chunk {
+            plan(*value, &converted_types, &mut planned)?;
+        }
+        Ok(planned)
+    });
+    let mut planned = Vec::new();
+    for chunk in chunks {
+        planned.extend(chunk.map_err(UnnamedField::into_conversion_error)?);
+    }
+    Ok(planned)
+}
+
+/// A struct field without a name, found while planning. Conversion errors can hold JS values, so
+/// can't be sent back from the pool's threads.
+struct UnnamedField {
+    type_path: String,
+    index: usize,
+}
+
+impl UnnamedField {
+    fn into_conversion_error(self) -> ConversionError {
+        ConversionError::UnnamedField {
+            type_path: self.type_path,
+            path: FieldPath::default(),
+            index: self.index,
+        }
+    }
+}
+
+/// Plan the conversion of a value, walking it with an explicit stack like the conversion itself.
+fn plan(
+    value: &dyn Reflect,
+    converted_types: &HashSet<TypeId>,
+    planned: &mut Vec<Planned>,
+) -> Result<(), UnnamedField> {
+    enum Visit<'a> {
+        Enter(&'a dyn Reflect),
+        Leave(Planned),
+    }
+
+    let mut stack = vec![Visit::Enter(value)];
+    while let Some(visit) = stack.pop() {
+        let value = match visit {
+            Visit::Enter(value) => value,
+            Visit::Leave(collect) => {
+                planned.push(collect);
+                continue;
+            }
+        };
+        if converted_types.contains(&value.as_any().type_id()) {
+            planned.push(Planned::Converted(value.clone_value()));
+            continue;
+        }
+        if let Some(primitive) = Primitive::lookup(value) {
+            planned.push(Planned::Value(primitive.into_owned()));
+            continue;
+        }
+        let (collect, children): (Planned, Vec<&dyn Reflect>) = match value.reflect_ref() {
+            ReflectRef::Struct(s) => {
+                let collect = match s.get_represented_type_info() {
+                    Some(TypeInfo::Struct(info)) if !s.is_dynamic() => Planned::Struct(info),
+                    _ => Planned::DynamicStruct(
+                        (0..s.field_len())
+                            .map(|idx| {
+                                s.name_at(idx)
+                                    .map(str::to_owned)
+                                    .ok_or_else(|| UnnamedField {
+                                        type_path: s.reflect_type_path().to_owned(),
+                                        index: idx,
+                                    })
+                            })
+                            .collect::<Result<_, _>>()?,
+                    ),
+                };
+                (collect, s.iter_fields().collect())
+            }
+            ReflectRef::TupleStruct(t) => {
+                (Planned::Array(t.field_len()), t.iter_fields().collect())
+            }
+            ReflectRef::Tuple(t) => (Planned::Array(t.field_len()), t.iter_fields().collect()),
+            ReflectRef::List(l) => (Planned::Array(l.len()), l.iter().collect()),
+            ReflectRef::Array(a) => (Planned::Array(a.len()), a.iter().collect()),
+            ReflectRef::Map(m) => (
+                Planned::Map(m.len()),
+                m.iter().flat_map(|(key, value)| [key, value]).collect(),
+            ),
+            ReflectRef::Enum(e) => (
+                Planned::Enum {
+                    variant: e.variant_name().to_owned(),
+                    names: (0..e.field_len())
+                        .map(|idx| e.name_at(idx).map(str::to_owned))
+                        .collect(),
+                },
+                e.iter_fields().map(|field| field.value()).collect(),
+            ),
+            ReflectRef::Value(_) => {
+                planned.push(Planned::Value(Primitive::Null));
+                continue;
+            }
+        };
+        stack.push(Visit::Leave(collect));
+        let start = stack.len();
+        stack.extend(children.into_iter().map(Visit::Enter));
+        stack[start..].reverse();
+    }
+    Ok(())
+}
+
+/// Make the JS value for a planned step, from the values converted before it.
+fn build(
+    planned: Planned,
+    converted: &mut Vec<JsValue>,
+    ctx: &mut Context,
+) -> Result<(), ConversionError> {
+    let value = match planned {
+        Planned::Value(primitive) => primitive.into_js_value(),
+        Planned::Converted(value) => {
+            let type_id = value.as_any().type_id();
+            match JsConverters::of(ctx).and_then(|converters| converters.get(&type_id).copied()) {
+                Some(convert) => convert(value.as_ref(), ctx)?,
+                None => try_reflect_to_js_value(value.as_ref(), ctx)?,
+            }
+        }
+        Planned::Struct(info) => {
+            let template = ObjectTemplates::struct_info(ctx, info);
+            template
+                .create(take_last(converted, info.field_len()), ctx)
+                .into()
+        }
+        Planned::DynamicStruct(names) => {
+            let template = ObjectTemplate::new(names.iter().map(String::as_str));
+            template
+                .create(take_last(converted, names.len()), ctx)
+                .into()
+        }
+        Planned::Array(len) => JsArray::from_iter(take_last(converted, len), ctx).into(),
+        Planned::Map(len) => entries_to_js_map(take_last(converted, len * 2), ctx)?.into(),
+        Planned::Enum { variant, names } => {
+            let values = take_last(converted, names.len());
+            let variant = ObjectTemplates::of(ctx).intern(&variant);
+            variant_to_js_object(variant, names.iter().map(Option::as_deref), values, ctx)
+        }
+    };
+    converted.push(value);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use boa_engine::{js_str, JsObject};
+
+    use crate::errors::PathSegment;
+    use crate::hooks::HookAction;
+
+    use super::*;
+
+    #[derive(Reflect)]
+    struct Account {
+        name: String,
+        password: String,
+    }
+
+    #[derive(Reflect)]
+    enum Shape {
+        Point,
+        Circle(f32),
+        Rect { w: f32, h: u64 },
+    }
+
+    #[derive(Reflect)]
+    struct Item {
+        id: u64,
+        label: Option<String>,
+        shape: Shape,
+        tags: Vec<String>,
+        counts: bevy_utils::HashMap<String, u32>,
+        ratio: f64,
+    }
+
+    fn item(idx: usize) -> Item {
+        Item {
+            id: u64::MAX - idx as u64,
+            label: idx.is_multiple_of(3).then(|| format!("item {idx}")),
+            shape: match idx % 3 {
+                0 => Shape::Point,
+                1 => Shape::Circle(idx as f32 / 2.0),
+                _ => Shape::Rect {
+                    w: 1.5,
+                    h: idx as u64,
+                },
+            },
+            tags: vec!["a".to_owned(); idx % 4],
+            counts: [("seen".to_owned(), idx as u32)].into_iter().collect(),
+            ratio: if idx == 7 { f64::NAN } else { idx as f64 / 3.0 },
+        }
+    }
+
+    /// Whether two converted values have the same prototypes, keys in the same order, and
+    /// values.
+    fn deep_equal(a: &JsValue, b: &JsValue, ctx: &mut Context) -> bool {
+        let (Some(a), Some(b)) = (a.as_object(), b.as_object()) else {
+            return JsValue::same_value(a, b);
+        };
+        if a.prototype() != b.prototype() {
+            return false;
+        }
+        let entries = |obj: &JsObject, ctx: &mut Context| {
+            let value = JsValue::from(obj.clone());
+            crate::from::js_map_entries(&value, "Map", ctx).ok()
+        };
+        if let (Some(a), Some(b)) = (entries(a, ctx), entries(b, ctx)) {
+            return a.len() == b.len()
+                && a.iter()
+                    .zip(&b)
+                    .all(|((a_key, a_value), (b_key, b_value))| {
+                        deep_equal(a_key, b_key, ctx) && deep_equal(a_value, b_value, ctx)
+                    });
+        }
+        let keys = a.own_property_keys(ctx).unwrap();
+        keys == b.own_property_keys(ctx).unwrap()
+            && keys.into_iter().all(|key| {
+                let a = a.get(key.clone(), ctx).unwrap();
+                let b = b.get(key, ctx).unwrap();
+                deep_equal(&a, &b, ctx)
+            })
+    }
+
+    fn redact(_: &str, path: &FieldPath, _: &JsValue) -> HookAction {
+        match path.segments.last() {
+            Some(PathSegment::Field(field)) if field == "password" => {
+                HookAction::Replace(JsValue::null())
+            }
+            _ => HookAction::Keep,
+        }
+    }
+
+    fn accounts() -> Vec<Account> {
+        (0..PARALLEL_THRESHOLD)
+            .map(|idx| Account {
+                name: format!("player {idx}"),
+                password: "hunter2".to_owned(),
+            })
+            .collect()
+    }
+
+    fn password(accounts: &JsValue, idx: usize, ctx: &mut Context) -> JsValue {
+        let account = accounts.as_object().unwrap().get(idx, ctx).unwrap();
+        account
+            .as_object()
+            .unwrap()
+            .get(js_str!("password"), ctx)
+            .unwrap()
+    }
+
+    #[test]
+    fn parallel_conversions_run_hooks() {
+        let accounts = accounts();
+        let mut ctx = Context::default();
+        ConversionHooks::register_post(&mut ctx, redact);
+
+        let converted = reflect_to_js_value_parallel(&accounts, &mut ctx).unwrap();
+        let last = PARALLEL_THRESHOLD - 1;
+        assert!(password(&converted, last, &mut ctx).is_null());
+
+        let values = accounts
+            .iter()
+            .map(|a| a as &dyn Reflect)
+            .collect::<Vec<_>>();
+        let converted = reflect_slice_to_js_array_parallel(&values, &mut ctx).unwrap();
+        assert!(password(&converted.into(), last, &mut ctx).is_null());
+    }
+
+    #[test]
+    fn parallel_conversions_match_sequential_ones() {
+        let mut ctx = Context::default();
+        let items = (0..PARALLEL_THRESHOLD).map(item).collect::<Vec<_>>();
+        let parallel = reflect_to_js_value_parallel(&items, &mut ctx).unwrap();
+        let sequential = crate::reflect_to_js_value(&items, &mut ctx).unwrap();
+        assert!(deep_equal(&parallel, &sequential, &mut ctx));
+
+        let values = items.iter().map(|a| a as &dyn Reflect).collect::<Vec<_>>();
+        let slice = reflect_slice_to_js_array_parallel(&values, &mut ctx).unwrap();
+        assert!(deep_equal(&slice.into(), &sequential, &mut ctx));
+
+        let other = (1..=PARALLEL_THRESHOLD).map(item).collect::<Vec<_>>();
+        let other = crate::reflect_to_js_value(&other, &mut ctx).unwrap();
+        assert!(!deep_equal(&other, &sequential, &mut ctx));
+
+        let map = (0..PARALLEL_THRESHOLD)
+            .map(|idx| (idx as u32, item(idx)))
+            .collect::<bevy_utils::HashMap<_, _>>();
+        let parallel = reflect_to_js_value_parallel(&map, &mut ctx).unwrap();
+        let sequential = crate::reflect_to_js_value(&map, &mut ctx).unwrap();
+        assert!(deep_equal(&parallel, &sequential, &mut ctx));
+    }
+}
@@ -0,0 +1,35 @@
+use bevy::prelude::*;
+use boa_engine::{JsNativeError, JsResult};
+
+use crate::runtime::ScriptRuntime;
+
+/// Snapshot the `state` object an entity's script keeps, as the reflected MessagePack bytes
+/// [`ScriptRuntime::snapshot_state`] encodes it to, which can be written to a save file alongside
+/// its components.
+pub fn snapshot_script_state(world: &mut World, entity: Entity) -> JsResult<Box<dyn Reflect>> {
+    let mut runtime = world
+        .get_non_send_resource_mut::<ScriptRuntime>()
+        .ok_or_else(|| {
+            JsNativeError::error().with_message("The script runtime is not available")
+        })?;
+    runtime.snapshot_state(entity)?.ok_or_else(|| {
+        JsNativeError::typ()
+            .with_message(format!("Entity {entity} has no script instance"))
+            .into()
+    })
+}
+
+/// Restore the `state` object of an entity's script from a [`snapshot_script_state`] snapshot.
+/// Restoring before the script attaches, as happens when loading a save, is fine.
+pub fn restore_script_state(
+    world: &mut World,
+    entity: Entity,
+    state: &dyn Reflect,
+) -> JsResult<()> {
+    let mut runtime = world
+        .get_non_send_resource_mut::<ScriptRuntime>()
+        .ok_or_else(|| {
+            JsNativeError::error().with_message("The script runtime is not available")
+        })?;
+    runtime.restore_state(entity, state)
+}
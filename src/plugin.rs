@@ -0,0 +1,437 @@
+use bevy::ecs::system::SystemState;
+use bevy::prelude::*;
+use boa_engine::{Context, JsNativeError};
+
+use crate::access::provide_world;
+use crate::bindings::commands::{commands_binding, ScriptCommandQueue, COMMANDS_BINDING};
+use crate::bindings::reflect::{reflect_binding, REFLECT_BINDING};
+use crate::bindings::storage::{
+    save_script_storage, storage_binding, ScriptStorage, StorageSettings, STORAGE_BINDING,
+};
+use crate::bindings::world::{world_binding, EntityNameIndex, RemovedCursors, WORLD_BINDING};
+use crate::classes::register_type_classes;
+use crate::determinism::{advance_script_clock, ScriptDeterminism};
+use crate::errors::{ScriptError, ScriptErrorLocation};
+use crate::functions::ScriptFunctions;
+use crate::metadata::{ScriptManifest, ScriptMetadata};
+use crate::methods::ScriptMethods;
+use crate::profiling::{publish_script_diagnostics, ScriptProfiler};
+use crate::quarantine::{QuarantinePolicy, QuarantineScope, ScriptQuarantine};
+use crate::runtime::{entity_to_js_value, ScriptIsolation, ScriptRuntime, UPDATE_HOOK};
+use crate::script::{Script, ScriptAsset, ScriptAssetLoader};
+use crate::watch::{evaluate_watches, ScriptWatches, WatchResult};
+
+/// Adds the script asset type and a [`ScriptRuntime`] that evaluates scripts as they load.
+#[derive(Default)]
+pub struct BoaScriptPlugin {
+    /// How scripts are scoped relative to each other.
+    pub isolation: ScriptIsolation,
+    /// Run scripts deterministically, with `Math.random` seeded from this and `Date.now` and
+    /// `performance.now` following virtual time. See [`ScriptDeterminism`].
+    pub deterministic_seed: Option<u64>,
+    /// Stop running scripts that keep throwing. See [`ScriptQuarantine`].
+    pub quarantine: Option<QuarantinePolicy>,
+    /// Give scripts a `storage` global kept on disk. See [`ScriptStorage`].
+    pub storage: Option<StorageSettings>,
+}
+
+impl Plugin for BoaScriptPlugin {
+    fn build(&self, app: &mut App) {
+        let profiler = ScriptProfiler::default();
+        let mut runtime = ScriptRuntime::new(self.isolation);
+        runtime.set_profiler(Some(profiler.clone()));
+        if let Some(policy) = self.quarantine {
+            app.insert_resource(ScriptQuarantine::new(policy));
+        }
+        if let Some(seed) = self.deterministic_seed {
+            let determinism = ScriptDeterminism::new(seed);
+            if let Err(err) = runtime.set_determinism(determinism.clone()) {
+                error!("Error making scripts deterministic: {err}");
+            }
+            app.insert_resource(determinism)
+                .add_systems(Update, advance_script_clock.before(evaluate_scripts));
+        }
+        if let Some(settings) = &self.storage {
+            match ScriptStorage::load(settings.clone()) {
+                Ok(storage) => {
+                    app.insert_resource(storage)
+                        .add_systems(Last, save_script_storage);
+                }
+                Err(err) => error!(
+                    "Error loading script storage from {}: {err}",
+                    settings.path.display()
+                ),
+            }
+        }
+        app.init_asset::<ScriptAsset>()
+            .register_type::<Script>()
+            .init_asset_loader::<ScriptAssetLoader>()
+            .init_resource::<ScriptMethods>()
+            .init_resource::<ScriptFunctions>()
+            .init_resource::<ScriptCommandQueue>()
+            .init_resource::<EntityNameIndex>()
+            .init_resource::<ScriptWatches>()
+            .init_resource::<ScriptMetadata>()
+            .add_event::<WatchResult>()
+            .add_event::<ScriptError>()
+            .insert_resource(profiler)
+            .insert_non_send_resource(runtime)
+            .add_systems(
+                Startup,
+                (register_classes, register_functions, register_bindings),
+            )
+            .add_systems(
+                Update,
+                (
+                    evaluate_scripts,
+                    index_entity_names,
+                    run_scripts,
+                    deliver_script_events,
+                    apply_script_commands,
+                    evaluate_watches,
+                    publish_script_diagnostics,
+                )
+                    .chain(),
+            );
+    }
+}
+
+fn register_classes(
+    mut runtime: NonSendMut<ScriptRuntime>,
+    registry: Res<AppTypeRegistry>,
+    methods: Res<ScriptMethods>,
+) {
+    if let Err(err) = register_type_classes(&mut runtime, &registry, &methods) {
+        error!("Error registering script classes: {err}");
+    }
+}
+
+fn register_functions(
+    mut runtime: NonSendMut<ScriptRuntime>,
+    registry: Res<AppTypeRegistry>,
+    functions: Res<ScriptFunctions>,
+    profiler: Res<ScriptProfiler>,
+) {
+    for function in functions.iter() {
+        let function = function.clone().with_profiler(profiler.clone());
+        function.register_types(&mut registry.write());
+        let name = function.name().to_owned();
+        let registry = registry.clone();
+        let build = move |ctx: &mut Context| Ok(function.to_js_function(&registry, ctx));
+        if let Err(err) = runtime.register_global(&name, build) {
+            error!("Error registering script function {name}: {err}");
+        }
+    }
+}
+
+fn register_bindings(
+    mut runtime: NonSendMut<ScriptRuntime>,
+    registry: Res<AppTypeRegistry>,
+    commands: Res<ScriptCommandQueue>,
+    names: Res<EntityNameIndex>,
+    storage: Option<Res<ScriptStorage>>,
+) {
+    let reflect_registry = registry.clone();
+    let commands_registry = registry.clone();
+    let commands = commands.clone();
+    let names = names.clone();
+    let result = runtime
+        .register_global(REFLECT_BINDING, move |ctx| {
+            reflect_binding(&reflect_registry, ctx)
+        })
+        .and_then(|()| {
+            runtime.register_global(COMMANDS_BINDING, move |ctx| {
+                commands_binding(&commands, &commands_registry, ctx)
+            })
+        })
+        .and_then(|()| {
+            runtime.register_global(WORLD_BINDING, move |ctx| world_binding(&names, ctx))
+        })
+        .and_then(|()| match storage {
+            Some(storage) => {
+                let storage = storage.clone();
+                runtime.register_global(STORAGE_BINDING, move |ctx| storage_binding(&storage, ctx))
+            }
+            None => Ok(()),
+        });
+    if let Err(err) = result {
+        error!("Error registering script bindings: {err}");
+    }
+}
+
+fn evaluate_scripts(
+    mut runtime: NonSendMut<ScriptRuntime>,
+    mut events: EventReader<AssetEvent<ScriptAsset>>,
+    scripts: Res<Assets<ScriptAsset>>,
+    mut errors: EventWriter<ScriptError>,
+    mut quarantine: Option<ResMut<ScriptQuarantine>>,
+    mut metadata: ResMut<ScriptMetadata>,
+) {
+    for event in events.read() {
+        match *event {
+            AssetEvent::Added { id } | AssetEvent::Modified { id } => {
+                let Some(script) = scripts.get(id) else {
+                    continue;
+                };
+                if let Some(quarantine) = quarantine.as_mut() {
+                    quarantine.release(id);
+                }
+                let result = runtime.evaluate(id, script);
+                if let Err(err) = &result {
+                    let location =
+                        ScriptErrorLocation::new(&script.path, script.source_map.as_ref(), err);
+                    error!("Error evaluating script {location}: {err}");
+                    errors.send(ScriptError::new(
+                        Some(id),
+                        None,
+                        err,
+                        Some(location),
+                        runtime.context(),
+                    ));
+                }
+                update_metadata(&mut runtime, id, script, &mut metadata);
+                if result.is_ok() && matches!(event, AssetEvent::Modified { .. }) {
+                    migrate_states(&mut runtime, id, &mut errors);
+                }
+            }
+            AssetEvent::Removed { id } | AssetEvent::Unused { id } => {
+                runtime.remove(id);
+                metadata.remove(id);
+            }
+            AssetEvent::LoadedWithDependencies { .. } => {}
+        }
+    }
+}
+
+/// Carry the state of the entities running a reloaded script over to its new version.
+fn migrate_states(
+    runtime: &mut ScriptRuntime,
+    id: AssetId<ScriptAsset>,
+    errors: &mut EventWriter<ScriptError>,
+) {
+    match runtime.migrate_states(id) {
+        Ok(failures) => {
+            for (entity, err) in failures {
+                let error = runtime.script_error(Some(id), Some(entity), &err);
+                error!("Error migrating script state {error}");
+                errors.send(error);
+            }
+        }
+        Err(err) => {
+            let error = runtime.script_error(Some(id), None, &err);
+            error!("Error migrating script state {error}");
+            errors.send(error);
+        }
+    }
+}
+
+/// Record the manifest a script declared, warning if it needs bindings the engine doesn't have.
+fn update_metadata(
+    runtime: &mut ScriptRuntime,
+    id: AssetId<ScriptAsset>,
+    script: &ScriptAsset,
+    metadata: &mut ScriptMetadata,
+) {
+    let exported = match runtime.exports(id).cloned() {
+        Some(exports) => {
+            ScriptManifest::from_exports(&exports, runtime.context()).unwrap_or_else(|err| {
+                warn!("Error reading metadata of script {}: {err}", script.path);
+                None
+            })
+        }
+        None => None,
+    };
+    let Some(manifest) = exported.or_else(|| ScriptManifest::from_header(&script.source)) else {
+        metadata.remove(id);
+        return;
+    };
+    let missing = manifest
+        .requires
+        .iter()
+        .filter(|name| !runtime.has_binding(name))
+        .cloned()
+        .collect::<Vec<_>>();
+    if !missing.is_empty() {
+        warn!(
+            "Script {} requires bindings that are not available: {}",
+            script.path,
+            missing.join(", ")
+        );
+    }
+    metadata.insert(id, manifest, missing);
+}
+
+fn index_entity_names(
+    index: Res<EntityNameIndex>,
+    names: Query<(Entity, &Name), Changed<Name>>,
+    mut removed: RemovedComponents<Name>,
+) {
+    for entity in removed.read() {
+        index.remove(entity);
+    }
+    for (entity, name) in &names {
+        index.insert(entity, name.as_str());
+    }
+}
+
+/// The scripted entities, and those whose scripts were removed.
+type ScriptsState = SystemState<(
+    Query<'static, 'static, (Entity, Ref<'static, Script>)>,
+    RemovedComponents<'static, 'static, Script>,
+)>;
+
+fn run_scripts(world: &mut World, state: &mut ScriptsState) {
+    let (scripts, mut removed) = state.get(world);
+    let removed = removed.read().collect::<Vec<_>>();
+    let runtime = world.non_send_resource::<ScriptRuntime>();
+    let quarantine = world.get_resource::<ScriptQuarantine>();
+    // Only clone the scripts that need attaching, since that copies their params.
+    let scripts = scripts
+        .iter()
+        .filter(|(entity, script)| {
+            !quarantine
+                .is_some_and(|quarantine| quarantine.is_quarantined(script.handle.id(), *entity))
+        })
+        .map(|(entity, script)| {
+            let attach =
+                (script.is_changed() || !runtime.is_attached(entity)).then(|| script.clone());
+            (entity, script.handle.id(), attach)
+        })
+        .collect::<Vec<_>>();
+    if let Some(mut cursors) = world.get_resource_mut::<RemovedCursors>() {
+        for entity in &removed {
+            cursors.remove_instance(*entity);
+        }
+    }
+    if let Some(mut quarantine) = world.get_resource_mut::<ScriptQuarantine>() {
+        for entity in &removed {
+            quarantine.remove_entity(*entity);
+        }
+    }
+
+    let registry = world.resource::<AppTypeRegistry>().clone();
+    let outcomes = with_runtime(world, |runtime| {
+        let mut outcomes = Vec::new();
+        for entity in removed {
+            runtime.detach(entity);
+        }
+        for (entity, id, attach) in scripts {
+            if runtime.exports(id).is_none() {
+                continue;
+            }
+            if let Some(mut script) = attach {
+                let result = script
+                    .read_params(&registry.read())
+                    .map_err(|err| {
+                        JsNativeError::typ()
+                            .with_message(format!("Invalid scene params: {err}"))
+                            .into()
+                    })
+                    .and_then(|params| {
+                        script.params = params;
+                        runtime.attach(entity, &script)
+                    });
+                if let Err(err) = result {
+                    error!("Error converting script params for {entity}: {err}");
+                    let error = runtime.script_error(Some(id), Some(entity), &err);
+                    outcomes.push((id, entity, Some(error)));
+                    continue;
+                }
+            }
+            let result = runtime.call_hook(entity, UPDATE_HOOK, &[entity_to_js_value(entity)]);
+            let error = result.err().map(|err| {
+                let error = runtime.script_error(Some(id), Some(entity), &err);
+                error!("Error running script {error}");
+                error
+            });
+            outcomes.push((id, entity, error));
+        }
+        outcomes
+    });
+
+    let mut errors = Vec::new();
+    let mut quarantine = world.get_resource_mut::<ScriptQuarantine>();
+    for (id, entity, error) in outcomes.unwrap_or_default() {
+        if let Some(quarantine) = quarantine.as_mut() {
+            if quarantine.record(id, entity, error.is_none()) {
+                let policy = quarantine.policy();
+                match policy.scope {
+                    QuarantineScope::PerEntity => warn!(
+                        "Disabling script for {entity} after {} consecutive errors",
+                        policy.max_consecutive_errors
+                    ),
+                    QuarantineScope::PerScript => warn!(
+                        "Disabling script {id} after {} consecutive errors",
+                        policy.max_consecutive_errors
+                    ),
+                }
+            }
+        }
+        errors.extend(error);
+    }
+    world.send_event_batch(errors);
+}
+
+fn deliver_script_events(world: &mut World) {
+    let errors = with_runtime(world, |runtime| {
+        runtime
+            .deliver_events()
+            .into_iter()
+            .map(|(script, err)| {
+                error!("Error handling script event: {err}");
+                runtime.script_error(script, None, &err)
+            })
+            .collect::<Vec<_>>()
+    });
+    world.send_event_batch(errors.unwrap_or_default());
+}
+
+/// Run `f` with the script runtime taken out of the world, so the world can be provided to the
+/// bindings scripts call. Returns `None` if there is no runtime.
+pub(crate) fn with_runtime<R>(
+    world: &mut World,
+    f: impl FnOnce(&mut ScriptRuntime) -> R,
+) -> Option<R> {
+    let mut runtime = world.remove_non_send_resource::<ScriptRuntime>()?;
+    let result = provide_world(world, || f(&mut runtime));
+    world.insert_non_send_resource(runtime);
+    Some(result)
+}
+
+fn apply_script_commands(world: &mut World) {
+    let queue = world.resource::<ScriptCommandQueue>().clone();
+    queue.apply(world);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn name_index_follows_renames_and_despawns() {
+        let mut world = World::new();
+        world.init_resource::<EntityNameIndex>();
+        let mut schedule = Schedule::default();
+        schedule.add_systems(index_entity_names);
+        let index = world.resource::<EntityNameIndex>().clone();
+
+        let first = world.spawn(Name::new("Enemy")).id();
+        let second = world.spawn(Name::new("Enemy")).id();
+        let player = world.spawn(Name::new("Player")).id();
+        schedule.run(&mut world);
+        assert_eq!(index.get("Enemy"), [first, second]);
+        assert_eq!(index.get("Player"), [player]);
+
+        world.entity_mut(first).insert(Name::new("Boss"));
+        world.entity_mut(player).remove::<Name>();
+        schedule.run(&mut world);
+        assert_eq!(index.get("Enemy"), [second]);
+        assert_eq!(index.get("Boss"), [first]);
+        assert!(index.get("Player").is_empty());
+
+        world.despawn(second);
+        schedule.run(&mut world);
+        assert!(index.get("Enemy").is_empty());
+        assert_eq!(index.get("Boss"), [first]);
+    }
+}
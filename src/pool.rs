@@ -0,0 +1,175 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use boa_engine::job::{FutureJob, JobQueue, NativeJob, SimpleJobQueue};
+use boa_engine::{Context, Finalize, JsData, JsResult, Trace};
+
+#[cfg(feature = "bevy")]
+use crate::memo::ComponentCache;
+use crate::templates::ObjectTemplates;
+
+/// Registers what scripts expect into a context's current realm.
+type ContextSetup = Box<dyn Fn(&mut Context) -> JsResult<()>>;
+
+/// A pool of contexts with their bindings already set up, for running scripts in short lived
+/// contexts, e.g. one per job, without paying for creating a context and registering every
+/// global each time. Contexts are made ahead of time by [`warm`](Self::warm), or on demand when
+/// the pool runs dry.
+///
+/// Contexts from the same pool share their object templates and interned strings, as
+/// [`share_strings`](crate::share_strings) does.
+///
+/// Returned contexts are reset by entering a fresh realm and setting it up again, which drops
+/// every global scripts defined but is much cheaper than a new context. Promise jobs still
+/// queued are dropped without running, and a [`ComponentCache`](crate::ComponentCache) is
+/// cleared, as its objects belong to the old realm. Other data inserted into a context with
+/// [`Context::insert_data`] lives on with it, so the reset is only as complete as the setup
+/// makes it. Contexts can't leave the thread they were created on, so the pool is a non-send
+/// resource when kept in a world.
+pub struct ContextPool {
+    setup: ContextSetup,
+    templates: ObjectTemplates,
+    idle: Vec<Context>,
+    /// The most idle contexts kept. Contexts returned beyond it are dropped.
+    capacity: usize,
+}
+
+impl ContextPool {
+    /// Contexts kept idle unless [`with_capacity`](Self::with_capacity) says otherwise.
+    pub const DEFAULT_CAPACITY: usize = 8;
+
+    /// A pool of contexts set up by `setup`, which registers the globals, classes and bindings
+    /// scripts expect. It's run for each new context and each reset.
+    pub fn new(setup: impl Fn(&mut Context) -> JsResult<()> + 'static) -> Self {
+        Self {
+            setup: Box::new(setup),
+            templates: ObjectTemplates::default(),
+            idle: Vec::new(),
+            capacity: Self::DEFAULT_CAPACITY,
+        }
+    }
+
+    /// Keep at most `capacity` idle contexts.
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        self.idle.truncate(capacity);
+        self
+    }
+
+    /// Make contexts until `count` are idle, or the pool is full, e.g. during a loading screen.
+    pub fn warm(&mut self, count: usize) -> JsResult<()> {
+        while self.idle.len() < count.min(self.capacity) {
+            let ctx = self.create()?;
+            self.idle.push(ctx);
+        }
+        Ok(())
+    }
+
+    /// A context ready to run scripts, made now if none are idle.
+    pub fn take(&mut self) -> JsResult<Context> {
+        match self.idle.pop() {
+            Some(ctx) => Ok(ctx),
+            None => self.create(),
+        }
+    }
+
+    /// Give a context back to the pool once its scripts are done. It's reset before it's handed
+    /// out again, so the next user doesn't see the globals, pending jobs or cached components
+    /// scripts left behind. Fails if setting the fresh realm up fails, dropping the context.
+    pub fn give_back(&mut self, mut ctx: Context) -> JsResult<()> {
+        if self.idle.len() >= self.capacity {
+            return Ok(());
+        }
+        if let Some(jobs) = ctx.get_data::<PoolJobs>() {
+            jobs.0.clear();
+        }
+        #[cfg(feature = "bevy")]
+        ComponentCache::clear(&mut ctx);
+        let realm = ctx.create_realm()?;
+        ctx.enter_realm(realm);
+        (self.setup)(&mut ctx)?;
+        self.idle.push(ctx);
+        Ok(())
+    }
+
+    /// The number of contexts ready to be taken.
+    pub fn idle(&self) -> usize {
+        self.idle.len()
+    }
+
+    fn create(&self) -> JsResult<Context> {
+        let jobs = Rc::new(PoolJobQueue::default());
+        let mut ctx = Context::builder().job_queue(jobs.clone()).build()?;
+        ctx.insert_data(PoolJobs(jobs));
+        ctx.insert_data(self.templates.clone());
+        (self.setup)(&mut ctx)?;
+        Ok(ctx)
+    }
+}
+
+/// The job queue of a pooled context, which can drop the jobs queued when the context is reset.
+/// Jobs are queued in a [`SimpleJobQueue`] that is swapped for an empty one to clear them.
+#[derive(Default)]
+struct PoolJobQueue(RefCell<Rc<SimpleJobQueue>>);
+
+impl PoolJobQueue {
+    fn clear(&self) {
+        *self.0.borrow_mut() = Rc::default();
+    }
+
+    /// The queue jobs go in, not borrowed while jobs run, as they queue more.
+    fn queue(&self) -> Rc<SimpleJobQueue> {
+        self.0.borrow().clone()
+    }
+}
+
+impl JobQueue for PoolJobQueue {
+    fn enqueue_promise_job(&self, job: NativeJob, context: &mut Context) {
+        self.queue().enqueue_promise_job(job, context);
+    }
+
+    fn run_jobs(&self, context: &mut Context) {
+        self.queue().run_jobs(context);
+    }
+
+    fn enqueue_future_job(&self, future: FutureJob, context: &mut Context) {
+        self.queue().enqueue_future_job(future, context);
+    }
+}
+
+/// Where a pooled context's job queue is kept, to be cleared by [`ContextPool::give_back`].
+#[derive(Trace, Finalize, JsData)]
+struct PoolJobs(#[unsafe_ignore_trace] Rc<PoolJobQueue>);
+
+#[cfg(test)]
+mod tests {
+    use boa_engine::{js_string, Source};
+
+    use super::*;
+
+    #[test]
+    fn returned_contexts_lose_globals_and_pending_jobs() {
+        let mut pool = ContextPool::new(|ctx| {
+            ctx.register_global_property(js_string!("setUp"), true, Default::default())?;
+            Ok(())
+        })
+        .with_capacity(1);
+        let mut ctx = pool.take().unwrap();
+        ctx.eval(Source::from_bytes(
+            "var leaked = 1; Promise.resolve().then(() => { globalThis.ran = true; });",
+        ))
+        .unwrap();
+        let old_realm = ctx.realm().clone();
+        pool.give_back(ctx).unwrap();
+        assert_eq!(pool.idle(), 1);
+
+        let mut ctx = pool.take().unwrap();
+        ctx.run_jobs();
+        let check = "[typeof leaked, typeof ran, setUp].join()";
+        let fresh = ctx.eval(Source::from_bytes(check)).unwrap();
+        assert_eq!(fresh.display().to_string(), "\"undefined,undefined,true\"");
+        ctx.enter_realm(old_realm);
+        let old = ctx.eval(Source::from_bytes(check)).unwrap();
+        assert_eq!(old.display().to_string(), "\"number,undefined,true\"");
+    }
+}
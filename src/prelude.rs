@@ -0,0 +1,20 @@
+//! The traits, plugins, settings and errors most code using the crate needs, along with the Boa
+//! types in their signatures, so one import covers both:
+//!
+//! ```no_run
+//! use bevy_boa_reflect::prelude::*;
+//! ```
+
+pub use boa_engine::{js_string, Context, JsError, JsObject, JsResult, JsString, JsValue};
+
+pub use crate::{
+    impl_boa_conversions, ConversionBackend, ConversionError, ConversionErrorKind,
+    ConversionErrors, ConversionFailurePolicy, ConversionHooks, ConversionSettings,
+    EnumRepresentation, FieldPath, FromJs, FromJsValue, HookAction, IntoJs, IntoJsValue, Js,
+    JsConverters, NumberPolicy, RenameRule, ToJsValue,
+};
+#[cfg(feature = "bevy")]
+pub use crate::{
+    js_bind, BoaScriptPlugin, ContextWorldExt, JsCtx, JsQuery, Script, ScriptAsset,
+    ScriptConsolePlugin, ScriptError, ScriptResultExt, ScriptRuntime, WorldJsExt,
+};
@@ -0,0 +1,72 @@
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+
+use bevy::diagnostic::{Diagnostic, DiagnosticMeasurement, DiagnosticPath, DiagnosticsStore};
+use bevy::prelude::*;
+use bevy::utils::{HashMap, Instant};
+
+/// The root of the diagnostic paths script timings are published under, e.g.
+/// `scripts/player.js/update/time` and `scripts/fn/spawnExplosion/calls`.
+pub const SCRIPT_DIAGNOSTICS_ROOT: &str = "scripts";
+
+/// The time spent in, and number of calls to, something scripts run.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ProfileSample {
+    pub time: Duration,
+    pub calls: u64,
+}
+
+/// Collects how long scripts, their hooks and the Rust functions they call take each frame. The
+/// plugin publishes the samples as diagnostics, so they show up next to the frame time.
+#[derive(Resource, Clone, Default)]
+pub struct ScriptProfiler(Arc<Mutex<HashMap<String, ProfileSample>>>);
+
+impl ScriptProfiler {
+    /// Record a call, under a `/` separated name.
+    pub fn record(&self, name: &str, time: Duration) {
+        let mut samples = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        let sample = samples.entry_ref(name).or_default();
+        sample.time += time;
+        sample.calls += 1;
+    }
+
+    /// Take the samples recorded since the last call.
+    pub fn take(&self) -> HashMap<String, ProfileSample> {
+        std::mem::take(&mut *self.0.lock().unwrap_or_else(PoisonError::into_inner))
+    }
+}
+
+/// Turn a script's asset path into a single diagnostic path component.
+pub(crate) fn profile_name(path: &str) -> String {
+    path.split('/')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// Publish the samples recorded this frame as `<name>/time`, in milliseconds, and `<name>/calls`.
+pub(crate) fn publish_script_diagnostics(
+    profiler: Res<ScriptProfiler>,
+    store: Option<ResMut<DiagnosticsStore>>,
+) {
+    let samples = profiler.take();
+    let Some(mut store) = store else {
+        return;
+    };
+    let now = Instant::now();
+    for (name, sample) in samples {
+        let measurements = [
+            ("time", "ms", sample.time.as_secs_f64() * 1000.0),
+            ("calls", "", sample.calls as f64),
+        ];
+        for (measure, suffix, value) in measurements {
+            let path = DiagnosticPath::new(format!("{SCRIPT_DIAGNOSTICS_ROOT}/{name}/{measure}"));
+            if store.get(&path).is_none() {
+                store.add(Diagnostic::new(path.clone()).with_suffix(suffix));
+            }
+            if let Some(diagnostic) = store.get_mut(&path) {
+                diagnostic.add_measurement(DiagnosticMeasurement { time: now, value });
+            }
+        }
+    }
+}
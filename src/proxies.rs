@@ -0,0 +1,252 @@
+use std::any::TypeId;
+
+use bevy::ecs::reflect::AppTypeRegistry;
+use bevy::prelude::*;
+use boa_engine::gc::GcRefCell;
+use boa_engine::object::ObjectInitializer;
+use boa_engine::{
+    js_str, js_string, Context, Finalize, JsData, JsNativeError, JsObject, JsResult, JsString,
+    JsValue, NativeFunction, Trace,
+};
+
+use crate::access::with_world;
+use crate::bindings::commands::ScriptCommandQueue;
+use crate::bindings::world::clone_entity;
+use crate::errors::{ConversionError, FieldPath};
+use crate::memo::{component_to_js_value, ComponentCache};
+use crate::runtime::{entity_to_js_value, js_value_to_entity};
+
+/// Objects standing for entities, kept in a context so each entity is the same object every
+/// frame rather than a fresh one. The objects are updated in place by [`entity_proxy`], so a
+/// scene full of scripted entities doesn't leave thousands of objects a frame for the garbage
+/// collector.
+///
+/// Components are kept up to date through a [`ComponentCache`], which enabling the proxies also
+/// enables, so changes scripts make to a proxy's components last until the component changes.
+/// Proxies of despawned entities are dropped the next time they're asked for, or by
+/// [`remove`](Self::remove) and [`clear`](Self::clear).
+#[derive(Debug, Default, Trace, Finalize, JsData)]
+pub struct EntityProxies {
+    proxies: GcRefCell<bevy::utils::HashMap<ProxyKey, JsObject>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Trace, Finalize)]
+struct ProxyKey(#[unsafe_ignore_trace] Entity);
+
+impl EntityProxies {
+    /// Start keeping proxies for entities in a context.
+    pub fn enable(ctx: &mut Context) {
+        ComponentCache::enable(ctx);
+        if !ctx.has_data::<Self>() {
+            ctx.insert_data(Self::default());
+        }
+    }
+
+    /// Stop keeping proxies in a context, dropping those kept so far. The component cache stays
+    /// enabled.
+    pub fn disable(ctx: &mut Context) {
+        ctx.remove_data::<Self>();
+    }
+
+    /// Drop the proxy of an entity, e.g. when it's despawned.
+    pub fn remove(ctx: &mut Context, entity: Entity) {
+        if let Some(proxies) = ctx.get_data::<Self>() {
+            proxies.proxies.borrow_mut().remove(&ProxyKey(entity));
+        }
+    }
+
+    /// Drop every proxy kept in a context.
+    pub fn clear(ctx: &mut Context) {
+        if let Some(proxies) = ctx.get_data::<Self>() {
+            proxies.proxies.borrow_mut().clear();
+        }
+    }
+}
+
+/// The prototype entity proxies share, made once per context.
+#[derive(Trace, Finalize, JsData)]
+struct EntityPrototype(JsObject);
+
+/// The object standing for an entity, with an `entity` property holding the entity and a property
+/// for each of the given components it has, named by the component's short type path:
+///
+/// ```js
+/// { entity: 12n, Transform: { translation: ..., rotation: ..., scale: ... } }
+/// ```
+///
+/// Proxies have methods acting on their entity: `entity.clone()` spawns a copy of it, as
+/// [`clone_entity`] does, and returns the copy's proxy, and `entity.despawnRecursive()` despawns
+/// it with its children once scripts have run for the frame.
+///
+/// If the context has [`EntityProxies`], the same object is returned each time, with only the
+/// components that changed since last time set on it. Otherwise a new object is made. Returns
+/// `None` if the entity doesn't exist.
+pub fn entity_proxy(
+    world: &World,
+    entity: Entity,
+    components: &[TypeId],
+    ctx: &mut Context,
+) -> Result<Option<JsObject>, ConversionError> {
+    if world.get_entity(entity).is_none() {
+        EntityProxies::remove(ctx, entity);
+        return Ok(None);
+    }
+    let key = ProxyKey(entity);
+    let kept = ctx
+        .get_data::<EntityProxies>()
+        .and_then(|proxies| proxies.proxies.borrow().get(&key).cloned());
+    let proxy = match kept {
+        Some(proxy) => proxy,
+        None => {
+            let proxy = JsObject::with_object_proto(ctx.intrinsics());
+            proxy.set_prototype(Some(entity_prototype(ctx)));
+            proxy.set(js_str!("entity"), entity_to_js_value(entity), false, ctx)?;
+            if let Some(proxies) = ctx.get_data::<EntityProxies>() {
+                proxies.proxies.borrow_mut().insert(key, proxy.clone());
+            }
+            proxy
+        }
+    };
+    for type_id in components {
+        let name = JsString::from(component_name(world, *type_id)?);
+        match component_to_js_value(world, entity, *type_id, ctx)? {
+            Some(value) => {
+                // Components that haven't changed are the same object as before, which is left
+                // alone.
+                let current = proxy.get(name.clone(), ctx)?;
+                if !current.strict_equals(&value) {
+                    proxy.set(name, value, false, ctx)?;
+                }
+            }
+            None => {
+                proxy.delete_property_or_throw(name, ctx)?;
+            }
+        }
+    }
+    Ok(Some(proxy))
+}
+
+/// The name of a component's property on a proxy.
+/// The prototype of entity proxies in a context, made the first time it's needed.
+fn entity_prototype(ctx: &mut Context) -> JsObject {
+    if let Some(prototype) = ctx.get_data::<EntityPrototype>() {
+        return prototype.0.clone();
+    }
+    let clone = NativeFunction::from_fn_ptr(|this, _, ctx| {
+        let entity = proxy_entity(this, ctx)?;
+        let proxy = with_world(|world| {
+            let copy = clone_entity(world, entity)?;
+            Some(entity_proxy(world, copy, &[], ctx))
+        })?
+        .ok_or_else(|| {
+            JsNativeError::typ().with_message(format!("Entity {entity} does not exist"))
+        })??;
+        Ok(proxy.map_or(JsValue::null(), JsValue::from))
+    });
+    let despawn_recursive = NativeFunction::from_fn_ptr(|this, _, ctx| {
+        let entity = proxy_entity(this, ctx)?;
+        let despawn = move |world: &mut World| {
+            if let Some(entity_mut) = world.get_entity_mut(entity) {
+                entity_mut.despawn_recursive();
+            }
+        };
+        with_world(|world| match world.get_resource::<ScriptCommandQueue>() {
+            Some(queue) => queue.push(despawn),
+            None => despawn(world),
+        })?;
+        Ok(JsValue::undefined())
+    });
+    let prototype = ObjectInitializer::new(ctx)
+        .function(clone, js_string!("clone"), 0)
+        .function(despawn_recursive, js_string!("despawnRecursive"), 0)
+        .build();
+    ctx.insert_data(EntityPrototype(prototype.clone()));
+    prototype
+}
+
+/// The entity of the proxy a method was called on.
+fn proxy_entity(this: &JsValue, ctx: &mut Context) -> JsResult<Entity> {
+    let proxy = this
+        .as_object()
+        .ok_or_else(|| JsNativeError::typ().with_message("Receiver is not an entity"))?;
+    js_value_to_entity(&proxy.get(js_str!("entity"), ctx)?)
+}
+
+fn component_name(world: &World, type_id: TypeId) -> Result<&'static str, ConversionError> {
+    let registry = world.resource::<AppTypeRegistry>().read();
+    registry
+        .get_type_info(type_id)
+        .map(|info| info.type_path_table().short_path())
+        .ok_or_else(|| ConversionError::Unregistered {
+            type_path: format!("{type_id:?}"),
+            path: FieldPath::default(),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Component, Reflect)]
+    #[reflect(Component)]
+    struct Health {
+        current: u32,
+    }
+
+    fn world() -> (World, Entity) {
+        let mut world = World::new();
+        world.init_resource::<AppTypeRegistry>();
+        world
+            .resource::<AppTypeRegistry>()
+            .write()
+            .register::<Health>();
+        let entity = world.spawn(Health { current: 5 }).id();
+        (world, entity)
+    }
+
+    fn proxy(world: &World, entity: Entity, ctx: &mut Context) -> Option<JsObject> {
+        entity_proxy(world, entity, &[TypeId::of::<Health>()], ctx).unwrap()
+    }
+
+    fn current(proxy: &JsObject, ctx: &mut Context) -> String {
+        let health = proxy.get(js_str!("Health"), ctx).unwrap();
+        let current = health.as_object().unwrap().get(js_str!("current"), ctx);
+        current.unwrap().display().to_string()
+    }
+
+    #[test]
+    fn proxies_are_kept_and_updated_in_place() {
+        let (mut world, entity) = world();
+        let mut ctx = Context::default();
+        let fresh = proxy(&world, entity, &mut ctx).unwrap();
+        assert!(!JsObject::equals(
+            &fresh,
+            &proxy(&world, entity, &mut ctx).unwrap()
+        ));
+
+        EntityProxies::enable(&mut ctx);
+        let kept = proxy(&world, entity, &mut ctx).unwrap();
+        let health = kept.get(js_str!("Health"), &mut ctx).unwrap();
+        world.increment_change_tick();
+        world.get_mut::<Health>(entity).unwrap().current = 3;
+        let updated = proxy(&world, entity, &mut ctx).unwrap();
+        assert!(JsObject::equals(&kept, &updated));
+        assert!(health.strict_equals(&updated.get(js_str!("Health"), &mut ctx).unwrap()));
+        assert_eq!(current(&updated, &mut ctx), "3n");
+        assert_eq!(
+            js_value_to_entity(&updated.get(js_str!("entity"), &mut ctx).unwrap()).unwrap(),
+            entity
+        );
+
+        world.entity_mut(entity).remove::<Health>();
+        let removed = proxy(&world, entity, &mut ctx).unwrap();
+        assert!(!removed
+            .has_own_property(js_str!("Health"), &mut ctx)
+            .unwrap());
+
+        world.despawn(entity);
+        assert!(proxy(&world, entity, &mut ctx).is_none());
+        let kept = ctx.get_data::<EntityProxies>().unwrap();
+        assert!(kept.proxies.borrow().is_empty());
+    }
+}
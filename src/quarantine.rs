@@ -0,0 +1,178 @@
+use bevy::prelude::*;
+use bevy::utils::{HashMap, HashSet};
+
+use crate::script::ScriptAsset;
+
+/// What a failing script is disabled for.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum QuarantineScope {
+    /// Stop running a script for an entity it keeps failing on, while other entities keep it.
+    #[default]
+    PerEntity,
+    /// Stop running a script for every entity once it keeps failing, on any of them.
+    PerScript,
+}
+
+/// When to stop running a script that keeps throwing.
+#[derive(Debug, Clone, Copy)]
+pub struct QuarantinePolicy {
+    /// How many times in a row a script's hook can throw before it is disabled.
+    pub max_consecutive_errors: u32,
+    pub scope: QuarantineScope,
+}
+
+impl Default for QuarantinePolicy {
+    fn default() -> Self {
+        Self {
+            max_consecutive_errors: 10,
+            scope: QuarantineScope::default(),
+        }
+    }
+}
+
+/// Tracks failing scripts and disables them under a [`QuarantinePolicy`], so one broken mod
+/// doesn't spam errors or corrupt state every frame. A script is enabled again when it is
+/// reloaded, or when [`ScriptQuarantine::release`] is called.
+#[derive(Resource, Debug, Default)]
+pub struct ScriptQuarantine {
+    policy: QuarantinePolicy,
+    /// Consecutive errors, keyed by script and, for per-entity quarantine, entity.
+    errors: HashMap<(AssetId<ScriptAsset>, Option<Entity>), u32>,
+    quarantined: HashSet<(AssetId<ScriptAsset>, Option<Entity>)>,
+}
+
+impl ScriptQuarantine {
+    pub fn new(policy: QuarantinePolicy) -> Self {
+        Self {
+            policy,
+            ..default()
+        }
+    }
+
+    pub fn policy(&self) -> QuarantinePolicy {
+        self.policy
+    }
+
+    /// Whether a script has been disabled for an entity.
+    pub fn is_quarantined(&self, script: AssetId<ScriptAsset>, entity: Entity) -> bool {
+        self.quarantined.contains(&self.key(script, entity))
+    }
+
+    /// The disabled scripts, with the entity they are disabled for under per-entity quarantine.
+    pub fn quarantined(&self) -> impl Iterator<Item = (AssetId<ScriptAsset>, Option<Entity>)> + '_ {
+        self.quarantined.iter().copied()
+    }
+
+    /// Enable a script again, for every entity, and forget its errors.
+    pub fn release(&mut self, script: AssetId<ScriptAsset>) {
+        self.errors.retain(|(id, _), _| *id != script);
+        self.quarantined.retain(|(id, _)| *id != script);
+    }
+
+    /// Record whether a script's hook succeeded. Returns `true` if the script was just disabled.
+    pub(crate) fn record(
+        &mut self,
+        script: AssetId<ScriptAsset>,
+        entity: Entity,
+        ok: bool,
+    ) -> bool {
+        let key = self.key(script, entity);
+        if ok {
+            self.errors.remove(&key);
+            return false;
+        }
+        let errors = self.errors.entry(key).or_default();
+        *errors += 1;
+        if *errors < self.policy.max_consecutive_errors {
+            return false;
+        }
+        self.errors.remove(&key);
+        self.quarantined.insert(key)
+    }
+
+    /// Forget an entity whose script was removed.
+    pub(crate) fn remove_entity(&mut self, entity: Entity) {
+        self.errors.retain(|(_, key), _| *key != Some(entity));
+        self.quarantined.retain(|(_, key)| *key != Some(entity));
+    }
+
+    fn key(
+        &self,
+        script: AssetId<ScriptAsset>,
+        entity: Entity,
+    ) -> (AssetId<ScriptAsset>, Option<Entity>) {
+        match self.policy.scope {
+            QuarantineScope::PerEntity => (script, Some(entity)),
+            QuarantineScope::PerScript => (script, None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn script(id: u128) -> AssetId<ScriptAsset> {
+        Handle::<ScriptAsset>::weak_from_u128(id).id()
+    }
+
+    #[test]
+    fn scripts_are_quarantined_after_consecutive_errors() {
+        let mut quarantine = ScriptQuarantine::new(QuarantinePolicy {
+            max_consecutive_errors: 3,
+            scope: QuarantineScope::PerEntity,
+        });
+        let (door, lamp) = (Entity::from_raw(1), Entity::from_raw(2));
+        assert!(!quarantine.record(script(1), door, false));
+        assert!(!quarantine.record(script(1), door, false));
+        // A success starts the count over.
+        assert!(!quarantine.record(script(1), door, true));
+        assert!(!quarantine.record(script(1), door, false));
+        assert!(!quarantine.record(script(1), door, false));
+        assert!(!quarantine.is_quarantined(script(1), door));
+        assert!(quarantine.record(script(1), door, false));
+        assert!(quarantine.is_quarantined(script(1), door));
+        assert!(!quarantine.is_quarantined(script(1), lamp));
+        assert!(!quarantine.is_quarantined(script(2), door));
+    }
+
+    #[test]
+    fn per_script_quarantine_counts_every_entity() {
+        let mut quarantine = ScriptQuarantine::new(QuarantinePolicy {
+            max_consecutive_errors: 2,
+            scope: QuarantineScope::PerScript,
+        });
+        let (door, lamp) = (Entity::from_raw(1), Entity::from_raw(2));
+        assert!(!quarantine.record(script(1), door, false));
+        assert!(quarantine.record(script(1), lamp, false));
+        assert!(quarantine.is_quarantined(script(1), door));
+        assert!(quarantine.is_quarantined(script(1), Entity::from_raw(3)));
+        assert_eq!(
+            quarantine.quarantined().collect::<Vec<_>>(),
+            [(script(1), None)]
+        );
+    }
+
+    #[test]
+    fn released_scripts_run_again_with_a_clean_count() {
+        let mut quarantine = ScriptQuarantine::new(QuarantinePolicy {
+            max_consecutive_errors: 2,
+            scope: QuarantineScope::PerEntity,
+        });
+        let (door, lamp) = (Entity::from_raw(1), Entity::from_raw(2));
+        quarantine.record(script(1), door, false);
+        quarantine.record(script(1), door, false);
+        quarantine.record(script(1), lamp, false);
+        quarantine.record(script(2), door, false);
+        quarantine.record(script(2), door, false);
+
+        quarantine.release(script(1));
+        assert!(!quarantine.is_quarantined(script(1), door));
+        assert!(quarantine.is_quarantined(script(2), door));
+        // The lamp's earlier error was forgotten too.
+        assert!(!quarantine.record(script(1), lamp, false));
+
+        quarantine.remove_entity(door);
+        assert!(!quarantine.is_quarantined(script(2), door));
+    }
+}
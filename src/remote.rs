@@ -0,0 +1,35 @@
+use bevy::prelude::*;
+use serde_json::Value;
+
+use crate::json::js_value_to_json;
+use crate::plugin::with_runtime;
+
+/// The remote method evaluating a JS snippet against the running app, taking
+/// `{ "source": "..." }` and returning the result as JSON.
+///
+/// The Bevy Remote Protocol only ships with bevy 0.15, so there is no server to register this
+/// with yet. [`process_eval_request`] has the shape of a remote method handler, ready to be added
+/// with `RemotePlugin::with_method(EVAL_METHOD, process_eval_request)` once the crate moves to it,
+/// and can be run through [`World::run_system_once_with`] by other tooling until then.
+pub const EVAL_METHOD: &str = "boa/eval";
+
+/// Evaluate the snippet in the request's `source` in the script context, with the world bindings
+/// available, and convert the result to JSON.
+pub fn process_eval_request(
+    In(params): In<Option<Value>>,
+    world: &mut World,
+) -> Result<Value, String> {
+    let source = params
+        .as_ref()
+        .and_then(|params| params.get("source"))
+        .and_then(Value::as_str)
+        .ok_or_else(|| format!("{EVAL_METHOD} expects a `source` string"))?
+        .to_owned();
+    with_runtime(world, |runtime| {
+        runtime
+            .eval(&source)
+            .and_then(|value| js_value_to_json(&value, runtime.context()))
+            .map_err(|err| err.to_string())
+    })
+    .unwrap_or_else(|| Err("The script runtime is not available".to_owned()))
+}
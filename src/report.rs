@@ -0,0 +1,100 @@
+use std::fmt;
+
+use bevy_reflect::{ReflectKind, TypeInfo};
+
+/// What a conversion cost, for working out what a component costs to pass between Rust and
+/// scripts. Returned alongside the value by
+/// [`reflect_to_js_value_with_report`](crate::reflect_to_js_value_with_report) and
+/// [`js_value_to_typed_reflect_with_report`](crate::js_value_to_typed_reflect_with_report).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConversionReport {
+    /// The JS objects, arrays and maps, or the dynamic reflect values, the conversion built.
+    pub objects_created: usize,
+    /// The bytes of string values and property names copied between Rust and JS.
+    pub string_bytes: usize,
+    /// How deeply the value was nested. A lone number has depth 1.
+    pub max_depth: usize,
+    kinds: [usize; 8],
+}
+
+impl ConversionReport {
+    /// How many values of a kind were converted.
+    pub fn count(&self, kind: ReflectKind) -> usize {
+        self.kinds[kind_index(kind)]
+    }
+
+    /// How many values were converted, of every kind.
+    pub fn total(&self) -> usize {
+        self.kinds.iter().sum()
+    }
+
+    pub(crate) fn value(&mut self, kind: ReflectKind, depth: usize) {
+        self.kinds[kind_index(kind)] += 1;
+        self.max_depth = self.max_depth.max(depth);
+    }
+
+    pub(crate) fn object(&mut self) {
+        self.objects_created += 1;
+    }
+
+    pub(crate) fn string(&mut self, bytes: usize) {
+        self.string_bytes += bytes;
+    }
+}
+
+impl fmt::Display for ConversionReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} values, {} objects created, {} string bytes copied, depth {}",
+            self.total(),
+            self.objects_created,
+            self.string_bytes,
+            self.max_depth
+        )?;
+        for (kind, count) in KINDS.iter().zip(self.kinds) {
+            if count > 0 {
+                write!(f, "\n  {kind:?}: {count}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+const KINDS: [ReflectKind; 8] = [
+    ReflectKind::Struct,
+    ReflectKind::TupleStruct,
+    ReflectKind::Tuple,
+    ReflectKind::List,
+    ReflectKind::Array,
+    ReflectKind::Map,
+    ReflectKind::Enum,
+    ReflectKind::Value,
+];
+
+fn kind_index(kind: ReflectKind) -> usize {
+    match kind {
+        ReflectKind::Struct => 0,
+        ReflectKind::TupleStruct => 1,
+        ReflectKind::Tuple => 2,
+        ReflectKind::List => 3,
+        ReflectKind::Array => 4,
+        ReflectKind::Map => 5,
+        ReflectKind::Enum => 6,
+        ReflectKind::Value => 7,
+    }
+}
+
+/// The kind of value a type converts to.
+pub(crate) fn type_kind(type_info: &TypeInfo) -> ReflectKind {
+    match type_info {
+        TypeInfo::Struct(_) => ReflectKind::Struct,
+        TypeInfo::TupleStruct(_) => ReflectKind::TupleStruct,
+        TypeInfo::Tuple(_) => ReflectKind::Tuple,
+        TypeInfo::List(_) => ReflectKind::List,
+        TypeInfo::Array(_) => ReflectKind::Array,
+        TypeInfo::Map(_) => ReflectKind::Map,
+        TypeInfo::Enum(_) => ReflectKind::Enum,
+        TypeInfo::Value(_) => ReflectKind::Value,
+    }
+}
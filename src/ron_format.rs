@@ -0,0 +1,57 @@
+use bevy_reflect::serde::{ReflectDeserializer, ReflectSerializer};
+use bevy_reflect::{ReflectFromReflect, TypeRegistry};
+use boa_engine::{Context, JsError, JsNativeError, JsResult, JsValue};
+use ron::ser::PrettyConfig;
+use serde::de::DeserializeSeed;
+
+use crate::from::js_value_to_typed_reflect;
+use crate::into::reflect_to_js_value;
+
+/// Convert a JS value into a registered type and write it as RON, in the form scenes write
+/// components, so values scripts make can be saved into Bevy's scene files:
+///
+/// ```no_run
+/// # use bevy_boa_reflect::js_value_to_ron;
+/// # let registry = bevy_reflect::TypeRegistry::new();
+/// # let ctx = &mut boa_engine::Context::default();
+/// # let value = boa_engine::JsValue::undefined();
+/// let ron = js_value_to_ron(value, "my_game::Health", &registry, ctx)?;
+/// // {"my_game::Health": (current: 10.0, max: 20.0)}
+/// # Ok::<(), boa_engine::JsError>(())
+/// ```
+pub fn js_value_to_ron(
+    value: JsValue,
+    type_path: &str,
+    registry: &TypeRegistry,
+    ctx: &mut Context,
+) -> JsResult<String> {
+    let registration = registry.get_with_type_path(type_path).ok_or_else(|| {
+        JsNativeError::typ().with_message(format!("{type_path} is not a registered type"))
+    })?;
+    let value = js_value_to_typed_reflect(value, registration.type_id(), registry, ctx)?;
+    let serializer = ReflectSerializer::new(value.as_ref(), registry);
+    ron::ser::to_string_pretty(&serializer, PrettyConfig::default())
+        .map_err(|err| ron_error(type_path, err))
+}
+
+/// Read a value written as RON in the form scenes write components, the type's path mapped to
+/// its value, and convert it to JS.
+pub fn ron_to_js_value(ron: &str, registry: &TypeRegistry, ctx: &mut Context) -> JsResult<JsValue> {
+    let mut deserializer = ron::Deserializer::from_str(ron).map_err(|err| ron_error("RON", err))?;
+    let value = ReflectDeserializer::new(registry)
+        .deserialize(&mut deserializer)
+        .map_err(|err| ron_error("RON", err))?;
+    // Deserialized values are dynamic. Concrete values convert as the type itself would.
+    let concrete = value.get_represented_type_info().and_then(|info| {
+        registry
+            .get_type_data::<ReflectFromReflect>(info.type_id())?
+            .from_reflect(value.as_ref())
+    });
+    reflect_to_js_value(concrete.as_deref().unwrap_or(value.as_ref()), ctx)
+}
+
+fn ron_error(what: &str, err: impl std::fmt::Display) -> JsError {
+    JsNativeError::syntax()
+        .with_message(format!("Invalid {what}: {err}"))
+        .into()
+}
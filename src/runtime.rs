@@ -0,0 +1,950 @@
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::path::Path;
+
+use bevy::ecs::component::Tick;
+use bevy::prelude::*;
+use bevy::reflect::{FromReflect, TypePath, TypeRegistry};
+use bevy::utils::{HashMap, Instant};
+use boa_engine::object::IntegrityLevel;
+use boa_engine::property::Attribute;
+use boa_engine::realm::Realm;
+use boa_engine::{
+    Context, JsBigInt, JsError, JsNativeError, JsObject, JsResult, JsString, JsValue,
+    Script as CompiledScript, Source,
+};
+use boa_runtime::Console;
+
+use crate::access::{run_instance, with_world};
+use crate::bindings::bus::{
+    bus_binding, deliver_events, event_bus, remove_script_handlers, set_current_script, BUS_BINDING,
+};
+use crate::determinism::{install_determinism, ScriptDeterminism};
+use crate::encoding::register_text_encoding;
+use crate::errors::{ScriptError, ScriptErrorLocation, SourceMap};
+use crate::from::{js_value_to_int, js_value_to_typed};
+use crate::into::reflect_to_js_value;
+use crate::msgpack::{js_value_to_msgpack, msgpack_to_js_value};
+use crate::profiling::{profile_name, ScriptProfiler};
+use crate::script::{Script, ScriptAsset, ScriptScope};
+use crate::structured_clone::register_structured_clone;
+
+/// The global name the engine bindings are exposed under in every realm.
+pub const HOST_BINDING: &str = "bevy";
+
+/// The global a script assigns its hooks to, e.g. `exports.update = (entity) => {}`.
+pub const EXPORTS_BINDING: &str = "exports";
+
+/// The global holding the parameters of the entity a script is running for.
+pub const PARAMS_BINDING: &str = "params";
+
+/// The global holding the persistent state of the entity a script is running for, which survives
+/// reattaching and can be saved with [`ScriptRuntime::snapshot_state`].
+pub const STATE_BINDING: &str = "state";
+
+/// The hook called for every entity running a script, once per frame.
+pub const UPDATE_HOOK: &str = "update";
+
+/// The hook called with each entity's previous state when a script is hot reloaded, returning the
+/// state the new version should continue with.
+pub const MIGRATE_HOOK: &str = "migrate";
+
+/// The function [`ScriptRuntime::run_script_returning`] calls.
+pub const RUN_HOOK: &str = "run";
+
+/// How scripts are scoped relative to each other.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ScriptIsolation {
+    /// All scripts evaluate in the context's global realm and share globals.
+    #[default]
+    Shared,
+    /// Each script asset gets its own realm, so scripts cannot see or overwrite
+    /// each other's globals. Engine bindings are built again in every realm, with functions and
+    /// prototypes of that realm, so a script replacing one, or patching a method on one, only
+    /// changes what it sees itself. The `bevy` host object in each realm is frozen.
+    PerScript,
+}
+
+/// Builds a binding in the context's current realm, run once for every realm scripts evaluate in.
+type BindingBuilder = Box<dyn Fn(&mut Context) -> JsResult<JsValue>>;
+
+/// Owns the Boa [`Context`] that scripts run in, along with the realms created for them.
+pub struct ScriptRuntime {
+    context: Context,
+    host: JsObject,
+    bus: JsObject,
+    frozen: bool,
+    bindings: Vec<(JsString, BindingBuilder)>,
+    globals: Vec<(JsString, BindingBuilder)>,
+    isolation: ScriptIsolation,
+    realms: HashMap<AssetId<ScriptAsset>, Realm>,
+    shared_realms: HashMap<String, Realm>,
+    exports: HashMap<AssetId<ScriptAsset>, JsObject>,
+    instances: HashMap<Entity, ScriptInstance>,
+    /// States restored for entities whose scripts haven't been attached yet, as snapshot bytes
+    /// until the realm they belong in is known.
+    pending_states: HashMap<Entity, Vec<u8>>,
+    scripts: HashMap<AssetId<ScriptAsset>, ScriptInfo>,
+    /// Scripts compiled so far, by the [`source_hash`] of their source, with one for each realm
+    /// the source was evaluated in. Evaluating the same source again in a realm, from any script,
+    /// skips parsing and compiling it. Boa binds a compiled script to the realm it was compiled
+    /// for, and its names to the context's interner, so the compiled form can't be shared with
+    /// other realms or runtimes.
+    ///
+    /// Only realms that are evaluated in again are cached for: the global realm under
+    /// [`ScriptIsolation::Shared`] and the realms of named shared scopes. Scripts in realms of
+    /// their own get a fresh realm each time they're evaluated, so their compiled form could never
+    /// be reused and isn't kept.
+    compiled: HashMap<u64, Vec<CompiledScript>>,
+    profiler: Option<ScriptProfiler>,
+    determinism: Option<ScriptDeterminism>,
+}
+
+/// What the runtime keeps about an evaluated script for reporting on it.
+struct ScriptInfo {
+    path: String,
+    /// The name the script's timings are recorded under.
+    profile_name: String,
+    source_map: Option<SourceMap>,
+    /// The [`source_hash`] of the script's source as it was last evaluated.
+    source_hash: u64,
+    /// The realm the script was last evaluated in, which its compiled form belongs to.
+    realm: Realm,
+}
+
+/// A script attached to an entity.
+struct ScriptInstance {
+    script: AssetId<ScriptAsset>,
+    params: JsValue,
+    state: JsObject,
+    /// The change tick the instance's hooks last ran at, for change detection in scripts.
+    last_run: Tick,
+}
+
+impl ScriptRuntime {
+    pub fn new(isolation: ScriptIsolation) -> Self {
+        let mut context = Context::default();
+        let bus = event_bus();
+        let bus_facade = bus.clone();
+        let globals: Vec<(JsString, BindingBuilder)> = vec![(
+            JsString::from(BUS_BINDING),
+            Box::new(move |ctx| Ok(bus_binding(&bus_facade, ctx)?.into())),
+        )];
+        let host = install_globals(&[], &globals, &mut context)
+            .expect("failed to install script globals")
+            .expect("a new context has no globals installed");
+        Self {
+            context,
+            host,
+            bus,
+            frozen: false,
+            bindings: Vec::new(),
+            globals,
+            isolation,
+            realms: HashMap::default(),
+            shared_realms: HashMap::default(),
+            exports: HashMap::default(),
+            instances: HashMap::default(),
+            pending_states: HashMap::default(),
+            scripts: HashMap::default(),
+            compiled: HashMap::default(),
+            profiler: None,
+            determinism: None,
+        }
+    }
+
+    /// The context scripts are evaluated in.
+    pub fn context(&mut self) -> &mut Context {
+        &mut self.context
+    }
+
+    /// The object holding the engine bindings in the context's global realm, exposed to scripts
+    /// as `bevy`. Every other realm has a host object of its own.
+    pub fn host(&self) -> &JsObject {
+        &self.host
+    }
+
+    pub fn isolation(&self) -> ScriptIsolation {
+        self.isolation
+    }
+
+    /// Record the time spent in each script and hook with a profiler.
+    pub fn set_profiler(&mut self, profiler: Option<ScriptProfiler>) {
+        self.profiler = profiler;
+    }
+
+    /// Seed `Math.random` and pin `Date.now` and `performance.now` to game time in every realm.
+    /// Like bindings, this can only be set before the first script is evaluated.
+    pub fn set_determinism(&mut self, determinism: ScriptDeterminism) -> JsResult<()> {
+        if self.frozen {
+            return Err(JsNativeError::error()
+                .with_message("Determinism cannot be set after scripts have run")
+                .into());
+        }
+        install_determinism(&determinism, &mut self.context)?;
+        self.determinism = Some(determinism);
+        Ok(())
+    }
+
+    /// Whether scripts can see a global or engine binding with this name.
+    pub fn has_binding(&mut self, name: &str) -> bool {
+        let name = JsString::from(name);
+        self.globals
+            .iter()
+            .chain(&self.bindings)
+            .any(|(global, _)| *global == name)
+    }
+
+    /// Add an engine binding to the host object, built by `build` in the global realm now and in
+    /// every realm created for scripts later. Bindings can only be registered before the first
+    /// script is evaluated, after which host objects are frozen.
+    pub fn register_binding<V: Into<JsValue>>(
+        &mut self,
+        name: &str,
+        build: impl Fn(&mut Context) -> JsResult<V> + 'static,
+    ) -> JsResult<()> {
+        if self.frozen {
+            return Err(JsNativeError::error()
+                .with_message("Bindings cannot be registered after scripts have run")
+                .into());
+        }
+        let name = JsString::from(name);
+        let build: BindingBuilder = Box::new(move |ctx| build(ctx).map(Into::into));
+        let value = build(&mut self.context)?;
+        self.host
+            .set(name.clone(), value, true, &mut self.context)?;
+        self.bindings.push((name, build));
+        Ok(())
+    }
+
+    /// Add a global visible to every script, built by `build` in the global realm now and in
+    /// every realm created for scripts later, so scripts in their own realms each get their own.
+    /// Like bindings, globals can only be registered before the first script is evaluated.
+    pub fn register_global<V: Into<JsValue>>(
+        &mut self,
+        name: &str,
+        build: impl Fn(&mut Context) -> JsResult<V> + 'static,
+    ) -> JsResult<()> {
+        if self.frozen {
+            return Err(JsNativeError::error()
+                .with_message("Globals cannot be registered after scripts have run")
+                .into());
+        }
+        let name = JsString::from(name);
+        let build: BindingBuilder = Box::new(move |ctx| build(ctx).map(Into::into));
+        let value = build(&mut self.context)?;
+        self.context
+            .register_global_property(name.clone(), value, Attribute::all())?;
+        self.globals.push((name, build));
+        Ok(())
+    }
+
+    /// The realm a script evaluates in, if it doesn't use the context's global realm.
+    pub fn realm(&self, id: AssetId<ScriptAsset>) -> Option<&Realm> {
+        self.realms.get(&id)
+    }
+
+    /// Evaluate a script, creating a fresh realm for it when scripts are isolated. Scripts in
+    /// a named shared scope reuse that scope's realm, so their globals survive reloads.
+    pub fn evaluate(
+        &mut self,
+        id: AssetId<ScriptAsset>,
+        script: &ScriptAsset,
+    ) -> JsResult<JsValue> {
+        #[cfg(feature = "trace")]
+        let _span = bevy::log::info_span!("script_evaluate", script = script.path).entered();
+        self.freeze_host()?;
+        let own_realm = matches!(
+            (&script.scope, self.isolation),
+            (ScriptScope::Default, ScriptIsolation::PerScript) | (ScriptScope::Isolated, _)
+        );
+        let realm = match (&script.scope, self.isolation) {
+            (ScriptScope::Default, ScriptIsolation::Shared) => {
+                self.realms.remove(&id);
+                None
+            }
+            (ScriptScope::Default, ScriptIsolation::PerScript) | (ScriptScope::Isolated, _) => {
+                let realm = self.context.create_realm()?;
+                self.realms.insert(id, realm.clone());
+                Some(realm)
+            }
+            (ScriptScope::Shared(name), _) => {
+                let realm = match self.shared_realms.get(name) {
+                    Some(realm) => realm.clone(),
+                    None => {
+                        let realm = self.context.create_realm()?;
+                        self.shared_realms.insert(name.clone(), realm.clone());
+                        realm
+                    }
+                };
+                self.realms.insert(id, realm.clone());
+                Some(realm)
+            }
+        };
+        let source_hash = source_hash(script);
+        let compiled = self.compiled.get(&source_hash).cloned().unwrap_or_default();
+        remove_script_handlers(&self.bus, id);
+        set_current_script(&self.bus, Some(id));
+        let result = self.in_realm(realm, |ctx| {
+            // Made in the script's own realm, so it can't reach the prototypes of any other.
+            let exports = JsObject::with_object_proto(ctx.intrinsics());
+            ctx.global_object().set(
+                JsString::from(EXPORTS_BINDING),
+                exports.clone(),
+                false,
+                ctx,
+            )?;
+            let compiled = compiled
+                .into_iter()
+                .find(|compiled| compiled.realm() == ctx.realm());
+            let compiled = match compiled {
+                Some(compiled) => compiled,
+                None => {
+                    let path = Path::new(&script.path);
+                    let source = Source::from_reader(script.source.as_bytes(), Some(path));
+                    CompiledScript::parse(source, None, ctx)?
+                }
+            };
+            let result = compiled.evaluate(ctx)?;
+            Ok((result, compiled, exports))
+        });
+        set_current_script(&self.bus, None);
+        let (result, compiled, exports) = result?;
+        self.release_compiled(id);
+        let realm = compiled.realm().clone();
+        if !own_realm {
+            let compiled_for = self.compiled.entry(source_hash).or_default();
+            if !compiled_for.iter().any(|other| *other.realm() == realm) {
+                compiled_for.push(compiled);
+            }
+        }
+        self.exports.insert(id, exports);
+        self.scripts.insert(
+            id,
+            ScriptInfo {
+                path: script.path.clone(),
+                profile_name: profile_name(&script.path),
+                source_map: script.source_map.clone(),
+                source_hash,
+                realm,
+            },
+        );
+        Ok(result)
+    }
+
+    /// Evaluate a snippet in the context's global realm, outside of any script, e.g. from a
+    /// debugging console.
+    pub fn eval(&mut self, source: &str) -> JsResult<JsValue> {
+        self.freeze_host()?;
+        self.context.eval(Source::from_bytes(source))
+    }
+
+    /// The hooks a script assigned to `exports` when it was last evaluated.
+    pub fn exports(&self, id: AssetId<ScriptAsset>) -> Option<&JsObject> {
+        self.exports.get(&id)
+    }
+
+    /// Call a function a script exported, outside of any entity.
+    pub fn call_export(
+        &mut self,
+        id: AssetId<ScriptAsset>,
+        name: &str,
+        args: &[JsValue],
+    ) -> JsResult<JsValue> {
+        #[cfg(feature = "trace")]
+        let _span = bevy::log::info_span!(
+            "script_export",
+            script = self.scripts.get(&id).map_or("", |info| info.path.as_str()),
+            export = name,
+        )
+        .entered();
+        let exports =
+            self.exports.get(&id).cloned().ok_or_else(|| {
+                JsNativeError::error().with_message("Script has not been evaluated")
+            })?;
+        let realm = self.realms.get(&id).cloned();
+        self.in_realm(realm, |ctx| {
+            let function = exports.get(JsString::from(name), ctx)?;
+            let function = function.as_callable().ok_or_else(|| {
+                JsNativeError::typ()
+                    .with_message(format!("Script does not export a {name} function"))
+            })?;
+            function.call(&exports.clone().into(), args, ctx)
+        })
+    }
+
+    /// Call a script's exported `run` function with reflected arguments, converting its return
+    /// value into `T`. The registry must contain `T` and the types of its fields.
+    pub fn run_script_returning<T: FromReflect + TypePath>(
+        &mut self,
+        script: impl Into<AssetId<ScriptAsset>>,
+        args: &[&dyn Reflect],
+        registry: &TypeRegistry,
+    ) -> JsResult<T> {
+        let args = args
+            .iter()
+            .map(|arg| reflect_to_js_value(*arg, &mut self.context))
+            .collect::<JsResult<Vec<_>>>()?;
+        let value = self.call_export(script.into(), RUN_HOOK, &args)?;
+        Ok(js_value_to_typed(value, registry, &mut self.context)?)
+    }
+
+    /// Migrate the state of every entity running a script after it has been re-evaluated, by
+    /// calling its `migrate(oldState)` export and keeping the object it returns. States are left as
+    /// they are if the script doesn't export `migrate`, or it returns something other than an
+    /// object. Returns the errors thrown by `migrate`, by entity.
+    pub fn migrate_states(&mut self, id: AssetId<ScriptAsset>) -> JsResult<Vec<(Entity, JsError)>> {
+        let Some(exports) = self.exports.get(&id).cloned() else {
+            return Ok(Vec::new());
+        };
+        let realm = self.realms.get(&id).cloned();
+        let migrate = self.in_realm(realm.clone(), |ctx| {
+            exports.get(JsString::from(MIGRATE_HOOK), ctx)
+        })?;
+        let Some(migrate) = migrate.as_callable().cloned() else {
+            return Ok(Vec::new());
+        };
+        #[cfg(feature = "trace")]
+        let _span = bevy::log::info_span!(
+            "script_migrate",
+            script = self.scripts.get(&id).map_or("", |info| info.path.as_str()),
+        )
+        .entered();
+        let instances = self
+            .instances
+            .iter()
+            .filter(|(_, instance)| instance.script == id)
+            .map(|(entity, instance)| (*entity, instance.state.clone()))
+            .collect::<Vec<_>>();
+        let mut errors = Vec::new();
+        set_current_script(&self.bus, Some(id));
+        for (entity, state) in instances {
+            let result = self.in_realm(realm.clone(), |ctx| {
+                migrate.call(&exports.clone().into(), &[state.into()], ctx)
+            });
+            match result {
+                Ok(JsValue::Object(state)) => {
+                    if let Some(instance) = self.instances.get_mut(&entity) {
+                        instance.state = state;
+                    }
+                }
+                Ok(_) => {}
+                Err(err) => errors.push((entity, err)),
+            }
+        }
+        set_current_script(&self.bus, None);
+        Ok(errors)
+    }
+
+    /// Whether a script instance exists for the entity.
+    pub fn is_attached(&self, entity: Entity) -> bool {
+        self.instances.contains_key(&entity)
+    }
+
+    /// Create the script instance for an entity, converting its parameters. An instance replacing
+    /// an earlier one keeps its state. Parameters and new states are made in the script's realm.
+    pub fn attach(&mut self, entity: Entity, script: &Script) -> JsResult<()> {
+        let realm = self.realms.get(&script.handle.id()).cloned();
+        let params = self.in_realm(realm.clone(), |ctx| match &script.params {
+            Some(params) => reflect_to_js_value(params.as_ref(), ctx),
+            None => Ok(JsValue::undefined()),
+        })?;
+        let state = match self.instances.get(&entity) {
+            Some(instance) => instance.state.clone(),
+            None => match self.pending_states.remove(&entity) {
+                Some(bytes) => self.in_realm(realm, |ctx| snapshot_to_state(&bytes, ctx))?,
+                None => self.in_realm(realm, |ctx| {
+                    Ok(JsObject::with_object_proto(ctx.intrinsics()))
+                })?,
+            },
+        };
+        self.instances.insert(
+            entity,
+            ScriptInstance {
+                script: script.handle.id(),
+                params,
+                state,
+                last_run: Tick::new(0),
+            },
+        );
+        Ok(())
+    }
+
+    /// Drop the script instance for an entity.
+    pub fn detach(&mut self, entity: Entity) {
+        self.instances.remove(&entity);
+        self.pending_states.remove(&entity);
+    }
+
+    /// The state an entity's script keeps, exposed to its hooks as `state`.
+    pub fn state(&self, entity: Entity) -> Option<&JsObject> {
+        self.instances.get(&entity).map(|instance| &instance.state)
+    }
+
+    /// Encode the state an entity's script keeps as MessagePack, for save games, returned as the
+    /// reflected `Vec<u8>` of the bytes. Numbers, `BigInt`s, `Map`s and typed arrays keep their
+    /// types, so the state restores exactly. States nested too deeply, or holding cycles, can't
+    /// be encoded. Returns `None` if the entity has no script instance.
+    pub fn snapshot_state(&mut self, entity: Entity) -> JsResult<Option<Box<dyn Reflect>>> {
+        let Some(state) = self.state(entity).cloned() else {
+            return Ok(None);
+        };
+        let bytes = js_value_to_msgpack(&state.into(), &mut self.context)?;
+        Ok(Some(Box::new(bytes)))
+    }
+
+    /// Replace the state an entity's script keeps with one from [`Self::snapshot_state`]. If the
+    /// script hasn't been attached yet, e.g. right after a save game is loaded, the snapshot is
+    /// kept until it is, and only then made into a state in the script's realm. A snapshot that
+    /// doesn't hold an object fails then rather than now.
+    pub fn restore_state(&mut self, entity: Entity, state: &dyn Reflect) -> JsResult<()> {
+        // Snapshots read back from a save file are dynamic lists rather than `Vec<u8>`s.
+        let bytes = Vec::<u8>::from_reflect(state).ok_or_else(|| {
+            JsNativeError::typ().with_message("Script state must be a snapshot's bytes")
+        })?;
+        let Some(instance) = self.instances.get(&entity) else {
+            self.pending_states.insert(entity, bytes);
+            return Ok(());
+        };
+        let realm = self.realms.get(&instance.script).cloned();
+        let state = self.in_realm(realm, |ctx| snapshot_to_state(&bytes, ctx))?;
+        if let Some(instance) = self.instances.get_mut(&entity) {
+            instance.state = state;
+        }
+        Ok(())
+    }
+
+    /// Call one of the hooks exported by an entity's script, with `params` set to the entity's
+    /// parameters. Returns `undefined` if the script doesn't export the hook.
+    pub fn call_hook(&mut self, entity: Entity, hook: &str, args: &[JsValue]) -> JsResult<JsValue> {
+        let Some(instance) = self.instances.get(&entity) else {
+            return Ok(JsValue::undefined());
+        };
+        let Some(exports) = self.exports.get(&instance.script).cloned() else {
+            return Ok(JsValue::undefined());
+        };
+        let script = instance.script;
+        #[cfg(feature = "trace")]
+        let _span = bevy::log::info_span!(
+            "script_hook",
+            script = self
+                .scripts
+                .get(&script)
+                .map_or("", |info| info.path.as_str()),
+            hook,
+            ?entity,
+        )
+        .entered();
+        let realm = self.realms.get(&script).cloned();
+        let params = instance.params.clone();
+        let state = instance.state.clone();
+        let last_run = instance.last_run;
+        let this_run = with_world(|world| world.read_change_tick()).ok();
+        set_current_script(&self.bus, Some(script));
+        let start = Instant::now();
+        let result = run_instance(entity, last_run, || {
+            self.in_realm(realm, |ctx| {
+                let hook = exports.get(JsString::from(hook), ctx)?;
+                let Some(hook) = hook.as_callable() else {
+                    return Ok(JsValue::undefined());
+                };
+                ctx.global_object()
+                    .set(JsString::from(PARAMS_BINDING), params, false, ctx)?;
+                ctx.global_object()
+                    .set(JsString::from(STATE_BINDING), state, false, ctx)?;
+                hook.call(&exports.clone().into(), args, ctx)
+            })
+        });
+        set_current_script(&self.bus, None);
+        if let (Some(profiler), Some(info)) = (&self.profiler, self.scripts.get(&script)) {
+            let name = &info.profile_name;
+            let elapsed = start.elapsed();
+            profiler.record(name, elapsed);
+            profiler.record(&format!("{name}/{hook}"), elapsed);
+        }
+        if let (Some(this_run), Some(instance)) = (this_run, self.instances.get_mut(&entity)) {
+            instance.last_run = this_run;
+        }
+        result
+    }
+
+    /// Locate an error thrown by an evaluated script, mapped back to the original source if the
+    /// script has a source map. Returns `None` if the script hasn't been evaluated.
+    pub fn locate_error(
+        &self,
+        id: AssetId<ScriptAsset>,
+        err: &JsError,
+    ) -> Option<ScriptErrorLocation> {
+        let info = self.scripts.get(&id)?;
+        Some(ScriptErrorLocation::new(
+            &info.path,
+            info.source_map.as_ref(),
+            err,
+        ))
+    }
+
+    /// Describe an error a script threw, for sending as a [`ScriptError`] event.
+    pub fn script_error(
+        &mut self,
+        script: Option<AssetId<ScriptAsset>>,
+        entity: Option<Entity>,
+        err: &JsError,
+    ) -> ScriptError {
+        let location = script.and_then(|script| self.locate_error(script, err));
+        ScriptError::new(script, entity, err, location, &mut self.context)
+    }
+
+    /// The event bus scripts share, which the `bus` object in each realm emits to and subscribes
+    /// on.
+    pub fn bus(&self) -> &JsObject {
+        &self.bus
+    }
+
+    /// Deliver the events scripts emitted on the bus since the last delivery, in the order they
+    /// were emitted. Returns the errors thrown by handlers, with the scripts that subscribed them.
+    pub fn deliver_events(&mut self) -> Vec<(Option<AssetId<ScriptAsset>>, JsError)> {
+        deliver_events(&self.bus, &mut self.context)
+    }
+
+    /// The realm shared by every script in the named scope, if any have been evaluated.
+    pub fn shared_realm(&self, scope: &str) -> Option<&Realm> {
+        self.shared_realms.get(scope)
+    }
+
+    /// Drop any state held for a script that has been unloaded, including its bus handlers.
+    /// Shared realms outlive the scripts that use them.
+    pub fn remove(&mut self, id: AssetId<ScriptAsset>) {
+        remove_script_handlers(&self.bus, id);
+        self.release_compiled(id);
+        self.realms.remove(&id);
+        self.exports.remove(&id);
+    }
+
+    /// Forget what the runtime knows of a script, along with its compiled form unless another
+    /// script has the same source in the same realm.
+    fn release_compiled(&mut self, id: AssetId<ScriptAsset>) {
+        let Some(info) = self.scripts.remove(&id) else {
+            return;
+        };
+        let shared = self
+            .scripts
+            .values()
+            .any(|other| other.source_hash == info.source_hash && other.realm == info.realm);
+        if shared {
+            return;
+        }
+        if let Some(compiled) = self.compiled.get_mut(&info.source_hash) {
+            compiled.retain(|compiled| *compiled.realm() != info.realm);
+            if compiled.is_empty() {
+                self.compiled.remove(&info.source_hash);
+            }
+        }
+    }
+
+    fn freeze_host(&mut self) -> JsResult<()> {
+        if !self.frozen {
+            self.host
+                .set_integrity_level(IntegrityLevel::Frozen, &mut self.context)?;
+            self.frozen = true;
+        }
+        Ok(())
+    }
+
+    fn in_realm<R>(
+        &mut self,
+        realm: Option<Realm>,
+        f: impl FnOnce(&mut Context) -> JsResult<R>,
+    ) -> JsResult<R> {
+        let Some(realm) = realm else {
+            return f(&mut self.context);
+        };
+        let previous = self.context.enter_realm(realm);
+        let result = install_globals(&self.bindings, &self.globals, &mut self.context)
+            .and_then(|host| {
+                // Realms are only made once scripts run, when hosts are frozen.
+                let Some(host) = host else {
+                    return Ok(());
+                };
+                host.set_integrity_level(IntegrityLevel::Frozen, &mut self.context)?;
+                match &self.determinism {
+                    Some(determinism) => install_determinism(determinism, &mut self.context),
+                    None => Ok(()),
+                }
+            })
+            .and_then(|()| f(&mut self.context));
+        self.context.enter_realm(previous);
+        result
+    }
+}
+
+impl Default for ScriptRuntime {
+    fn default() -> Self {
+        Self::new(ScriptIsolation::default())
+    }
+}
+
+/// Install the console, the text encoding classes, `structuredClone`, a host object holding the
+/// engine bindings and the registered globals into the context's current realm, building each
+/// binding and global in it. Returns the host object, or `None` if the realm already had one.
+fn install_globals(
+    bindings: &[(JsString, BindingBuilder)],
+    globals: &[(JsString, BindingBuilder)],
+    ctx: &mut Context,
+) -> JsResult<Option<JsObject>> {
+    let global = ctx.global_object();
+    if global.has_own_property(JsString::from(HOST_BINDING), ctx)? {
+        return Ok(None);
+    }
+    let console = Console::init(ctx);
+    ctx.register_global_property(Console::NAME, console, Attribute::all())?;
+    register_text_encoding(ctx)?;
+    register_structured_clone(ctx)?;
+    let host = JsObject::with_null_proto();
+    for (name, build) in bindings {
+        let value = build(ctx)?;
+        host.set(name.clone(), value, true, ctx)?;
+    }
+    ctx.register_global_property(
+        JsString::from(HOST_BINDING),
+        host.clone(),
+        Attribute::empty(),
+    )?;
+    for (name, build) in globals {
+        let value = build(ctx)?;
+        ctx.register_global_property(name.clone(), value, Attribute::all())?;
+    }
+    Ok(Some(host))
+}
+
+/// Make a snapshot from [`ScriptRuntime::snapshot_state`] into a state in the current realm.
+fn snapshot_to_state(bytes: &[u8], ctx: &mut Context) -> JsResult<JsObject> {
+    msgpack_to_js_value(bytes, ctx)?
+        .as_object()
+        .cloned()
+        .ok_or_else(|| {
+            JsNativeError::typ()
+                .with_message("Script state must be an object")
+                .into()
+        })
+}
+
+/// A hash of a script's source, which decides what it compiles to. Scripts with the same source
+/// in the same realm share a compiled form, so errors in them are reported against the path of
+/// whichever was compiled first.
+fn source_hash(script: &ScriptAsset) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    script.source.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Represent an entity in scripts as a `BigInt` of its bits, matching how `u64`s convert.
+pub fn entity_to_js_value(entity: Entity) -> JsValue {
+    JsValue::BigInt(JsBigInt::from(entity.to_bits()))
+}
+
+/// Read an entity passed from a script, either as the `BigInt` of its bits or a safe integer.
+pub fn js_value_to_entity(value: &JsValue) -> JsResult<Entity> {
+    let bits = js_value_to_int::<u64>(value, "bevy_ecs::entity::Entity")?;
+    Entity::try_from_bits(bits).map_err(|_| {
+        JsNativeError::typ()
+            .with_message(format!("{bits} is not a valid entity"))
+            .into()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use boa_engine::js_str;
+
+    use super::*;
+
+    const STATEFUL: &str = r#"
+        exports.fill = () => {
+            state.big = 2n ** 70n;
+            state.precise = 0.1 + 0.2;
+            state.large = 2 ** 53 - 1;
+            state.small = 16777217;
+            state.nested = { list: [1, "a", null, undefined], map: new Map([[1, { two: 2 }]]) };
+            state.bytes = new Float64Array([Math.PI]);
+        };
+        exports.check = () => [
+            state.big === 2n ** 70n,
+            state.precise === 0.1 + 0.2,
+            state.large === 2 ** 53 - 1,
+            state.small === 16777217,
+            state.nested.list.length === 4 && state.nested.list[1] === "a",
+            state.nested.list[2] === null && state.nested.list[3] === undefined,
+            state.nested.map instanceof Map && state.nested.map.get(1).two === 2,
+            state.bytes instanceof Float64Array && state.bytes[0] === Math.PI,
+        ].join();
+        exports.cycle = () => { state.self = state; };
+        exports.ownRealm = () => Object.getPrototypeOf(state) === Object.prototype;
+    "#;
+
+    fn attached(runtime: &mut ScriptRuntime, entities: &[Entity]) {
+        let mut assets = Assets::<ScriptAsset>::default();
+        let handle = assets.add(ScriptAsset {
+            path: "stateful.js".into(),
+            source: STATEFUL.into(),
+            scope: ScriptScope::Default,
+            source_map: None,
+        });
+        let script = assets.get(&handle).unwrap().clone();
+        runtime.evaluate(handle.id(), &script).unwrap();
+        for entity in entities {
+            runtime
+                .attach(*entity, &Script::new(handle.clone()))
+                .unwrap();
+        }
+    }
+
+    #[test]
+    fn scripts_with_the_same_source_share_a_compiled_form() {
+        let mut runtime = ScriptRuntime::new(ScriptIsolation::Shared);
+        let mut assets = Assets::<ScriptAsset>::default();
+        let ids = ["a.js", "b.js"].map(|path| {
+            let script = ScriptAsset {
+                path: path.into(),
+                source: "exports.answer = () => 42;".into(),
+                scope: ScriptScope::Default,
+                source_map: None,
+            };
+            let id = assets.add(script.clone()).id();
+            runtime.evaluate(id, &script).unwrap();
+            id
+        });
+        let compiled = |runtime: &ScriptRuntime| runtime.compiled.values().flatten().count();
+        assert_eq!(compiled(&runtime), 1);
+
+        runtime.remove(ids[0]);
+        assert_eq!(compiled(&runtime), 1);
+        runtime.remove(ids[1]);
+        assert_eq!(compiled(&runtime), 0);
+    }
+
+    #[test]
+    fn scripts_in_realms_of_their_own_keep_no_compiled_form() {
+        let mut runtime = ScriptRuntime::new(ScriptIsolation::PerScript);
+        let mut assets = Assets::<ScriptAsset>::default();
+        let script = |scope| ScriptAsset {
+            path: "a.js".into(),
+            source: "exports.answer = () => 42;".into(),
+            scope,
+            source_map: None,
+        };
+        let own = script(ScriptScope::Default);
+        let own_id = assets.add(own.clone()).id();
+        runtime.evaluate(own_id, &own).unwrap();
+        runtime.evaluate(own_id, &own).unwrap();
+        assert!(runtime.compiled.is_empty());
+
+        let shared = script(ScriptScope::Shared("ui".into()));
+        let shared_id = assets.add(shared.clone()).id();
+        runtime.evaluate(shared_id, &shared).unwrap();
+        runtime.evaluate(shared_id, &shared).unwrap();
+        assert_eq!(runtime.compiled.values().flatten().count(), 1);
+    }
+
+    #[test]
+    fn snapshots_restore_state_exactly() {
+        let (saved, loaded) = (Entity::from_raw(1), Entity::from_raw(2));
+        let mut runtime = ScriptRuntime::new(ScriptIsolation::PerScript);
+        attached(&mut runtime, &[saved, loaded]);
+        runtime.call_hook(saved, "fill", &[]).unwrap();
+        let snapshot = runtime.snapshot_state(saved).unwrap().unwrap();
+
+        // Save files hold the bytes as a dynamic list.
+        runtime
+            .restore_state(loaded, snapshot.clone_value().as_ref())
+            .unwrap();
+        let check = runtime.call_hook(loaded, "check", &[]).unwrap();
+        assert_eq!(
+            check.as_string().unwrap().to_std_string_escaped(),
+            ["true"; 8].join(",")
+        );
+    }
+
+    #[test]
+    fn states_restored_before_attaching_are_kept() {
+        let (saved, loaded) = (Entity::from_raw(1), Entity::from_raw(2));
+        let mut runtime = ScriptRuntime::default();
+        attached(&mut runtime, &[saved]);
+        runtime.call_hook(saved, "fill", &[]).unwrap();
+        let snapshot = runtime.snapshot_state(saved).unwrap().unwrap();
+        runtime.restore_state(loaded, snapshot.as_ref()).unwrap();
+
+        attached(&mut runtime, &[loaded]);
+        let check = runtime.call_hook(loaded, "check", &[]).unwrap();
+        assert_eq!(
+            check.as_string().unwrap().to_std_string_escaped(),
+            ["true"; 8].join(",")
+        );
+    }
+
+    #[test]
+    fn states_restored_before_attaching_are_made_in_the_scripts_realm() {
+        let (saved, loaded) = (Entity::from_raw(1), Entity::from_raw(2));
+        let mut runtime = ScriptRuntime::new(ScriptIsolation::PerScript);
+        attached(&mut runtime, &[saved]);
+        let snapshot = runtime.snapshot_state(saved).unwrap().unwrap();
+        runtime.restore_state(loaded, snapshot.as_ref()).unwrap();
+
+        attached(&mut runtime, &[loaded]);
+        let own_realm = runtime.call_hook(loaded, "ownRealm", &[]).unwrap();
+        assert_eq!(own_realm, JsValue::from(true));
+    }
+
+    #[test]
+    fn isolated_scripts_patching_bindings_only_change_their_own() {
+        let mut runtime = ScriptRuntime::new(ScriptIsolation::PerScript);
+        runtime
+            .register_global("api", |ctx| {
+                let get = crate::bindings::native_function(ctx, "get", 0, |_, _, _| Ok(1.into()));
+                let api = JsObject::with_object_proto(ctx.intrinsics());
+                api.set(js_str!("get"), get, true, ctx)?;
+                Ok(api)
+            })
+            .unwrap();
+        let mut assets = Assets::<ScriptAsset>::default();
+        let mut evaluate = |runtime: &mut ScriptRuntime, source: &str| {
+            let script = ScriptAsset {
+                path: "patch.js".into(),
+                source: source.into(),
+                scope: ScriptScope::Default,
+                source_map: None,
+            };
+            let id = assets.add(script.clone()).id();
+            runtime.evaluate(id, &script).unwrap();
+            id
+        };
+        let patching = evaluate(
+            &mut runtime,
+            r#"
+                api.get = () => "spy";
+                Object.getPrototypeOf(api).spied = true;
+                Object.getPrototypeOf(bus.emit).call = () => "spy";
+                exports.run = () => api.get();
+            "#,
+        );
+        let other = evaluate(
+            &mut runtime,
+            "exports.run = () => [api.get.call(api), api.spied, bus.emit.call === undefined];",
+        );
+
+        let patched = runtime.call_export(patching, "run", &[]).unwrap();
+        assert_eq!(patched, JsValue::from(js_str!("spy")));
+        let other = runtime.call_export(other, "run", &[]).unwrap();
+        let other = other.to_string(runtime.context()).unwrap();
+        assert_eq!(other.to_std_string_escaped(), "1,,false");
+        let host = runtime.eval("Object.isFrozen(bevy)").unwrap();
+        assert_eq!(host, JsValue::from(true));
+    }
+
+    #[test]
+    fn cyclic_states_fail_to_snapshot() {
+        let entity = Entity::from_raw(1);
+        let mut runtime = ScriptRuntime::default();
+        attached(&mut runtime, &[entity]);
+        runtime.call_hook(entity, "cycle", &[]).unwrap();
+        assert!(runtime.snapshot_state(entity).is_err());
+    }
+}
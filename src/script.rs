@@ -0,0 +1,288 @@
+use std::path::Path;
+
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, AsyncReadExt, LoadContext};
+use bevy::prelude::*;
+use bevy::reflect::serde::{ReflectDeserializer, ReflectSerializer};
+use bevy::reflect::{ReflectFromReflect, TypeRegistry};
+use boa_engine::interner::Interner;
+use boa_engine::parser::Parser;
+use boa_engine::Source;
+use serde::de::DeserializeSeed;
+use serde::{Deserialize, Serialize};
+
+use crate::errors::{source_mapping_url, SourceMap, SourceMappingUrl};
+
+/// A JavaScript source file loaded through the asset server.
+#[derive(Asset, TypePath, Debug, Clone)]
+pub struct ScriptAsset {
+    /// The asset path the script was loaded from, used when reporting errors.
+    pub path: String,
+    /// The script's source text.
+    pub source: String,
+    /// Which realm the script evaluates in.
+    pub scope: ScriptScope,
+    /// The source map of a transpiled or bundled script, used to report errors against the
+    /// original sources.
+    pub source_map: Option<SourceMap>,
+}
+
+/// Selects the realm a script evaluates in.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ScriptScope {
+    /// Follow the plugin's [`ScriptIsolation`](crate::ScriptIsolation) mode.
+    #[default]
+    Default,
+    /// Evaluate in the script's own realm.
+    Isolated,
+    /// Share a realm, and its globals, with every other script in the same named scope.
+    /// Library scripts defining helpers can be grouped with the gameplay scripts using them.
+    Shared(String),
+}
+
+/// Settings for loading a [`ScriptAsset`], usually provided through a `.meta` file or
+/// [`AssetServer::load_with_settings`].
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScriptSettings {
+    pub scope: ScriptScope,
+}
+
+/// Loads `.js` files as [`ScriptAsset`]s.
+///
+/// Scripts are parsed here, on the asset loader's thread, and a script with syntax errors fails
+/// to load: a broken hot reload never reaches the main thread, and the previous version keeps
+/// running. Boa compiles a script into the context it runs in, on that context's thread, so the
+/// parsed script can't be handed over and scripts are still compiled when first evaluated.
+#[derive(Default)]
+pub struct ScriptAssetLoader;
+
+impl AssetLoader for ScriptAssetLoader {
+    type Asset = ScriptAsset;
+    type Settings = ScriptSettings;
+    type Error = std::io::Error;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        settings: &'a ScriptSettings,
+        load_context: &'a mut LoadContext<'_>,
+    ) -> Result<ScriptAsset, std::io::Error> {
+        let mut source = String::new();
+        reader.read_to_string(&mut source).await?;
+        let source_map = match source_mapping_url(&source) {
+            Some(SourceMappingUrl::Inline(json)) => SourceMap::parse(&json),
+            Some(SourceMappingUrl::Path(url)) => {
+                let path = match load_context.path().parent() {
+                    Some(parent) => parent.join(&url),
+                    None => url.into(),
+                };
+                match load_context.read_asset_bytes(path).await {
+                    Ok(json) => SourceMap::parse(&json),
+                    Err(err) => {
+                        warn!(
+                            "Could not load source map for {}: {err}",
+                            load_context.path().display()
+                        );
+                        None
+                    }
+                }
+            }
+            None => None,
+        };
+        check_syntax(&source, load_context.path())?;
+        Ok(ScriptAsset {
+            path: load_context.path().display().to_string(),
+            source,
+            scope: settings.scope.clone(),
+            source_map,
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["js"]
+    }
+}
+
+/// Parse a script with a throwaway interner, only to find syntax errors.
+fn check_syntax(source: &str, path: &Path) -> Result<(), std::io::Error> {
+    let mut interner = Interner::default();
+    Parser::new(Source::from_reader(source.as_bytes(), Some(path)))
+        .parse_script(&mut interner)
+        .map(drop)
+        .map_err(|err| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("Syntax error in {}: {err}", path.display()),
+            )
+        })
+}
+
+/// Attaches a script asset to an entity.
+///
+/// The component is reflected, so scripts can be attached in scene files. Scenes can't hold
+/// `params`, which may be any reflected value, so they hold `scene_params` instead, the
+/// parameters written as RON in the form scenes write components:
+///
+/// ```ron
+/// "bevy_boa_reflect::script::Script": (
+///     handle: Weak(Uuid(uuid: "...")),
+///     scene_params: Some("{\"my_game::Door\": (locked: true)}"),
+/// ),
+/// ```
+#[derive(Component, Reflect, Debug, Default)]
+#[reflect(Component, Default)]
+pub struct Script {
+    pub handle: Handle<ScriptAsset>,
+    /// A reflected value exposed to the script as `params` whenever it runs for this entity.
+    #[reflect(ignore)]
+    pub params: Option<Box<dyn Reflect>>,
+    /// The parameters as RON, read with the app's type registry when the script is attached if
+    /// `params` isn't set. See [`Script::write_scene_params`].
+    pub scene_params: Option<String>,
+}
+
+impl Script {
+    pub fn new(handle: Handle<ScriptAsset>) -> Self {
+        Self {
+            handle,
+            params: None,
+            scene_params: None,
+        }
+    }
+
+    /// Set the parameters the script sees as `params`, letting one script implement a
+    /// behavior reused with different settings.
+    pub fn with_params(mut self, params: impl Reflect) -> Self {
+        self.params = Some(Box::new(params));
+        self
+    }
+
+    /// Write `params` into `scene_params`, so they're kept when the entity is saved into a
+    /// scene. The parameters' type and the types of its fields must be registered.
+    pub fn write_scene_params(&mut self, registry: &TypeRegistry) -> Result<(), ron::Error> {
+        if let Some(params) = &self.params {
+            let serializer = ReflectSerializer::new(params.as_ref(), registry);
+            self.scene_params = Some(ron::ser::to_string(&serializer)?);
+        }
+        Ok(())
+    }
+
+    /// The parameters the script sees as `params`: `params` if set, or else `scene_params` read
+    /// back into the type they were written from.
+    pub fn read_params(
+        &self,
+        registry: &TypeRegistry,
+    ) -> Result<Option<Box<dyn Reflect>>, ron::Error> {
+        let value = match (&self.params, &self.scene_params) {
+            (Some(params), _) => params.clone_value(),
+            (None, Some(ron)) => {
+                let mut deserializer = ron::Deserializer::from_str(ron)?;
+                ReflectDeserializer::new(registry).deserialize(&mut deserializer)?
+            }
+            (None, None) => return Ok(None),
+        };
+        // Cloned and deserialized values are dynamic, and become the type they stand for where it
+        // reflects `FromReflect`, so they convert as it would.
+        let concrete = value.get_represented_type_info().and_then(|info| {
+            registry
+                .get_type_data::<ReflectFromReflect>(info.type_id())?
+                .from_reflect(value.as_ref())
+        });
+        Ok(Some(concrete.unwrap_or(value)))
+    }
+}
+
+impl Clone for Script {
+    fn clone(&self) -> Self {
+        Self {
+            handle: self.handle.clone(),
+            params: self.params.as_ref().map(|params| params.clone_value()),
+            scene_params: self.scene_params.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::entity::EntityHashMap;
+    use bevy::scene::serde::SceneDeserializer;
+    use bevy::scene::DynamicScene;
+
+    use super::*;
+
+    #[derive(Reflect, Debug, Default, PartialEq)]
+    struct DoorParams {
+        locked: bool,
+        code: u32,
+        label: String,
+    }
+
+    #[test]
+    fn syntax_errors_fail_the_load() {
+        let path = Path::new("scripts/door.js");
+        assert!(check_syntax("const open = (door) => { door.locked = false; };", path).is_ok());
+        let err = check_syntax("const open = (door => {", path).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        assert!(err
+            .to_string()
+            .starts_with("Syntax error in scripts/door.js"));
+    }
+
+    #[test]
+    fn scene_params_round_trip_through_a_scene() {
+        let registry = AppTypeRegistry::default();
+        registry.write().register::<Script>();
+        registry.write().register::<DoorParams>();
+        let params = DoorParams {
+            locked: true,
+            code: 1234,
+            label: "vault".into(),
+        };
+
+        let mut world = World::new();
+        world.insert_resource(registry.clone());
+        let mut script = Script::new(Handle::default()).with_params(DoorParams {
+            locked: true,
+            code: 1234,
+            label: "vault".into(),
+        });
+        script.write_scene_params(&registry.read()).unwrap();
+        world.spawn(script);
+        let ron = DynamicScene::from_world(&world)
+            .serialize(&registry.read())
+            .unwrap();
+
+        let mut deserializer = ron::Deserializer::from_str(&ron).unwrap();
+        let scene = SceneDeserializer {
+            type_registry: &registry.read(),
+        }
+        .deserialize(&mut deserializer)
+        .unwrap();
+        let mut loaded = World::new();
+        loaded.insert_resource(registry.clone());
+        scene
+            .write_to_world(&mut loaded, &mut EntityHashMap::default())
+            .unwrap();
+
+        let script = loaded.query::<&Script>().single(&loaded);
+        assert!(script.params.is_none());
+        let read = script.read_params(&registry.read()).unwrap().unwrap();
+        assert_eq!(read.downcast_ref::<DoorParams>(), Some(&params));
+    }
+
+    #[test]
+    fn params_set_in_rust_win_over_scene_params() {
+        let registry = AppTypeRegistry::default();
+        registry.write().register::<DoorParams>();
+        let mut script = Script::new(Handle::default()).with_params(DoorParams::default());
+        script.scene_params = Some("{\"not::Registered\": ()}".into());
+        let read = script.read_params(&registry.read()).unwrap().unwrap();
+        assert_eq!(
+            read.downcast_ref::<DoorParams>(),
+            Some(&DoorParams::default())
+        );
+
+        script.params = None;
+        assert!(script.read_params(&registry.read()).is_err());
+    }
+}
@@ -0,0 +1,391 @@
+//! An adapter shaped like bevy_mod_scripting's script hosts, for projects structured around
+//! them: hosts registered with [`ScriptHostAppExt::add_script_host`], APIs attached by
+//! [`APIProvider`]s, and hooks called by events sent to [`Recipients`].
+//!
+//! ```no_run
+//! # use bevy::prelude::*;
+//! # use bevy_boa_reflect::*;
+//! # struct LifeApi;
+//! # impl APIProvider for LifeApi {
+//! #     type APITarget = ScriptRuntime;
+//! #     fn attach_api(&mut self, _: &mut ScriptRuntime) -> boa_engine::JsResult<()> { Ok(()) }
+//! # }
+//! # #[derive(Component)]
+//! # struct Player;
+//! # let mut app = App::new();
+//! app.add_plugins(BoaScriptPlugin::default())
+//!     .add_script_host::<BoaScriptHost>(PostUpdate)
+//!     .add_api_provider::<BoaScriptHost>(Box::new(LifeApi));
+//!
+//! fn on_level_up(mut events: EventWriter<BoaEvent>, player: Query<Entity, With<Player>>) {
+//!     let player = player.single();
+//!     events.send(BoaEvent::new("on_level_up").with_arg(3u32).to(Recipients::Entity(player)));
+//! }
+//! ```
+//!
+//! Scripts are the crate's own: a [`Script`] component attaches one script to an entity, and the
+//! hooks events call are functions the script exports, called as `hook(entity, ...args)` like
+//! `update` is.
+
+use bevy::ecs::event::ManualEventReader;
+use bevy::ecs::schedule::ScheduleLabel;
+use bevy::prelude::*;
+use boa_engine::JsResult;
+
+use crate::into::reflect_to_js_value;
+use crate::plugin::with_runtime;
+use crate::quarantine::ScriptQuarantine;
+use crate::runtime::{entity_to_js_value, ScriptRuntime};
+use crate::script::{Script, ScriptAsset};
+
+/// A scripting backend that loads scripts, has APIs attached to it and hands events to scripts.
+pub trait ScriptHost: Send + Sync + 'static {
+    /// The events the host hands to scripts.
+    type ScriptEvent: ScriptEvent;
+    /// What [`APIProvider`]s attach their APIs to.
+    type APITarget;
+
+    /// Load a script from its source, returning a handle to attach to entities. `name` is what
+    /// errors report the script as, and what [`Recipients::ScriptName`] matches.
+    fn load_script(
+        world: &mut World,
+        name: &str,
+        source: &[u8],
+    ) -> std::io::Result<Handle<ScriptAsset>>;
+
+    /// Add the host's events, resources and systems to an app, handing events to scripts in the
+    /// given schedule.
+    fn register_with_app(app: &mut App, schedule: impl ScheduleLabel);
+
+    /// Hand events to the scripts they're for.
+    fn handle_events(world: &mut World, events: &[Self::ScriptEvent]);
+}
+
+/// An event handed to scripts.
+pub trait ScriptEvent: Event + Clone {
+    fn recipients(&self) -> &Recipients;
+}
+
+/// Which scripts an event is handed to.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Recipients {
+    /// Every entity running a script.
+    #[default]
+    All,
+    /// The script running for an entity.
+    Entity(Entity),
+    /// Every entity running a script asset.
+    Script(AssetId<ScriptAsset>),
+    /// Every entity running a script loaded from a path, or under a name given to
+    /// [`ScriptHost::load_script`].
+    ScriptName(String),
+}
+
+impl Recipients {
+    /// Whether an entity running a script is one of the recipients.
+    pub fn is_recipient(&self, entity: Entity, script: AssetId<ScriptAsset>, name: &str) -> bool {
+        match self {
+            Self::All => true,
+            Self::Entity(recipient) => *recipient == entity,
+            Self::Script(recipient) => *recipient == script,
+            Self::ScriptName(recipient) => recipient == name,
+        }
+    }
+}
+
+/// Attaches an API to a script host, e.g. globals registered on the [`ScriptRuntime`].
+pub trait APIProvider: Send + Sync + 'static {
+    /// What the API is attached to.
+    type APITarget;
+
+    /// Attach the API. Called once at startup, before any script runs.
+    fn attach_api(&mut self, api: &mut Self::APITarget) -> JsResult<()>;
+
+    /// Add what the API needs to the app, e.g. resources its functions read.
+    fn register_with_app(&self, _app: &mut App) {}
+}
+
+/// The API providers added to a host.
+#[derive(Resource)]
+pub struct APIProviders<H: ScriptHost> {
+    providers: Vec<Box<dyn APIProvider<APITarget = H::APITarget>>>,
+}
+
+impl<H: ScriptHost> Default for APIProviders<H> {
+    fn default() -> Self {
+        Self {
+            providers: Vec::new(),
+        }
+    }
+}
+
+impl<H: ScriptHost> APIProviders<H> {
+    pub fn push(&mut self, provider: Box<dyn APIProvider<APITarget = H::APITarget>>) {
+        self.providers.push(provider);
+    }
+
+    /// Attach every provider's API.
+    pub fn attach_all(&mut self, api: &mut H::APITarget) -> JsResult<()> {
+        self.providers
+            .iter_mut()
+            .try_for_each(|provider| provider.attach_api(api))
+    }
+}
+
+/// Adds script hosts and their API providers to an app.
+pub trait ScriptHostAppExt {
+    /// Add a script host, handing its events to scripts in the given schedule.
+    fn add_script_host<H: ScriptHost>(&mut self, schedule: impl ScheduleLabel) -> &mut Self;
+
+    /// Add an API provider to a script host.
+    fn add_api_provider<H: ScriptHost>(
+        &mut self,
+        provider: Box<dyn APIProvider<APITarget = H::APITarget>>,
+    ) -> &mut Self;
+}
+
+impl ScriptHostAppExt for App {
+    fn add_script_host<H: ScriptHost>(&mut self, schedule: impl ScheduleLabel) -> &mut Self {
+        self.init_resource::<APIProviders<H>>();
+        H::register_with_app(self, schedule);
+        self
+    }
+
+    fn add_api_provider<H: ScriptHost>(
+        &mut self,
+        provider: Box<dyn APIProvider<APITarget = H::APITarget>>,
+    ) -> &mut Self {
+        provider.register_with_app(self);
+        self.world_mut()
+            .get_resource_or_insert_with(APIProviders::<H>::default)
+            .push(provider);
+        self
+    }
+}
+
+/// A [`ScriptHost`] backed by the [`ScriptRuntime`] of the
+/// [`BoaScriptPlugin`](crate::BoaScriptPlugin), which has to be added along with it. API
+/// providers attach to the runtime, and [`BoaEvent`]s call the hooks scripts export.
+#[derive(Default)]
+pub struct BoaScriptHost;
+
+impl ScriptHost for BoaScriptHost {
+    type ScriptEvent = BoaEvent;
+    type APITarget = ScriptRuntime;
+
+    fn load_script(
+        world: &mut World,
+        name: &str,
+        source: &[u8],
+    ) -> std::io::Result<Handle<ScriptAsset>> {
+        let source = std::str::from_utf8(source)
+            .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))?;
+        let script = ScriptAsset {
+            path: name.to_owned(),
+            source: source.to_owned(),
+            scope: default(),
+            source_map: None,
+        };
+        Ok(world.resource_mut::<Assets<ScriptAsset>>().add(script))
+    }
+
+    fn register_with_app(app: &mut App, schedule: impl ScheduleLabel) {
+        app.add_event::<BoaEvent>()
+            .add_systems(Startup, attach_apis)
+            .add_systems(schedule, handle_script_events::<Self>);
+    }
+
+    fn handle_events(world: &mut World, events: &[BoaEvent]) {
+        let names = {
+            let mut scripts = world.query::<(Entity, &Script)>();
+            let assets = world.resource::<Assets<ScriptAsset>>();
+            let quarantine = world.get_resource::<ScriptQuarantine>();
+            scripts
+                .iter(world)
+                .filter(|(entity, script)| {
+                    !quarantine.is_some_and(|quarantine| {
+                        quarantine.is_quarantined(script.handle.id(), *entity)
+                    })
+                })
+                .map(|(entity, script)| {
+                    let id = script.handle.id();
+                    let name = assets.get(id).map(|asset| asset.path.clone());
+                    (entity, id, name.unwrap_or_default())
+                })
+                .collect::<Vec<_>>()
+        };
+        let errors = with_runtime(world, |runtime| {
+            let mut errors = Vec::new();
+            for event in events {
+                for (entity, id, name) in &names {
+                    if !event.recipients.is_recipient(*entity, *id, name) {
+                        continue;
+                    }
+                    if !runtime.is_attached(*entity) {
+                        continue;
+                    }
+                    let result = event
+                        .args
+                        .iter()
+                        .map(|arg| reflect_to_js_value(arg.as_ref(), runtime.context()))
+                        .collect::<JsResult<Vec<_>>>()
+                        .and_then(|args| {
+                            let args = [vec![entity_to_js_value(*entity)], args].concat();
+                            runtime.call_hook(*entity, &event.hook_name, &args)
+                        });
+                    if let Err(err) = result {
+                        let error = runtime.script_error(Some(*id), Some(*entity), &err);
+                        error!("Error handling script event {}: {error}", event.hook_name);
+                        errors.push(error);
+                    }
+                }
+            }
+            errors
+        });
+        let Some(errors) = errors else {
+            warn!("The BoaScriptHost needs the BoaScriptPlugin");
+            return;
+        };
+        world.send_event_batch(errors);
+    }
+}
+
+/// An event calling a hook exported by the scripts it's for, as `hook(entity, ...args)`.
+#[derive(Event, Debug)]
+pub struct BoaEvent {
+    pub hook_name: String,
+    pub args: Vec<Box<dyn Reflect>>,
+    pub recipients: Recipients,
+}
+
+impl BoaEvent {
+    /// An event calling a hook, for every entity running a script.
+    pub fn new(hook_name: impl Into<String>) -> Self {
+        Self {
+            hook_name: hook_name.into(),
+            args: Vec::new(),
+            recipients: Recipients::All,
+        }
+    }
+
+    /// Add an argument, passed to the hook after the entity.
+    pub fn with_arg(mut self, arg: impl Reflect) -> Self {
+        self.args.push(Box::new(arg));
+        self
+    }
+
+    /// Send the event to some scripts only.
+    pub fn to(mut self, recipients: Recipients) -> Self {
+        self.recipients = recipients;
+        self
+    }
+}
+
+impl Clone for BoaEvent {
+    fn clone(&self) -> Self {
+        Self {
+            hook_name: self.hook_name.clone(),
+            args: self.args.iter().map(|arg| arg.clone_value()).collect(),
+            recipients: self.recipients.clone(),
+        }
+    }
+}
+
+impl ScriptEvent for BoaEvent {
+    fn recipients(&self) -> &Recipients {
+        &self.recipients
+    }
+}
+
+fn attach_apis(world: &mut World) {
+    let Some(mut providers) = world.remove_resource::<APIProviders<BoaScriptHost>>() else {
+        return;
+    };
+    match world.get_non_send_resource_mut::<ScriptRuntime>() {
+        Some(mut runtime) => {
+            if let Err(err) = providers.attach_all(&mut runtime) {
+                error!("Error attaching script APIs: {err}");
+            }
+        }
+        None => warn!("The BoaScriptHost needs the BoaScriptPlugin"),
+    }
+    world.insert_resource(providers);
+}
+
+/// Hand the events sent since the host last ran to its scripts.
+fn handle_script_events<H: ScriptHost>(
+    world: &mut World,
+    mut reader: Local<ManualEventReader<H::ScriptEvent>>,
+) {
+    let events = reader
+        .read(world.resource::<Events<H::ScriptEvent>>())
+        .cloned()
+        .collect::<Vec<_>>();
+    if !events.is_empty() {
+        H::handle_events(world, &events);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use boa_engine::Source;
+
+    use super::*;
+    use crate::errors::ScriptError;
+    use crate::runtime::ScriptIsolation;
+
+    const HOOKS: &str = r#"
+        globalThis.calls ??= [];
+        exports.on_level_up = (entity, level) => { calls.push(`${entity}:${level}`); };
+        exports.on_hit = () => { throw new Error("missed"); };
+    "#;
+
+    #[test]
+    fn events_call_the_hooks_of_their_recipients() {
+        let mut world = World::new();
+        world.init_resource::<Assets<ScriptAsset>>();
+        world.init_resource::<Events<ScriptError>>();
+        let handle = BoaScriptHost::load_script(&mut world, "hooks.js", HOOKS.as_bytes()).unwrap();
+        let script = Script::new(handle.clone());
+        let first = world.spawn(script.clone()).id();
+        let second = world.spawn(script.clone()).id();
+
+        let mut runtime = ScriptRuntime::new(ScriptIsolation::Shared);
+        let asset = world
+            .resource::<Assets<ScriptAsset>>()
+            .get(&handle)
+            .unwrap()
+            .clone();
+        runtime.evaluate(handle.id(), &asset).unwrap();
+        runtime.attach(first, &script).unwrap();
+        runtime.attach(second, &script).unwrap();
+        world.insert_non_send_resource(runtime);
+
+        BoaScriptHost::handle_events(
+            &mut world,
+            &[
+                BoaEvent::new("on_level_up")
+                    .with_arg(2.0)
+                    .to(Recipients::Entity(second)),
+                BoaEvent::new("on_level_up")
+                    .with_arg(3.0)
+                    .to(Recipients::ScriptName("hooks.js".into())),
+                BoaEvent::new("on_level_up")
+                    .with_arg(4.0)
+                    .to(Recipients::ScriptName("other.js".into())),
+                BoaEvent::new("on_hit").to(Recipients::Entity(first)),
+            ],
+        );
+
+        let mut runtime = world.remove_non_send_resource::<ScriptRuntime>().unwrap();
+        let ctx = runtime.context();
+        let calls = ctx.eval(Source::from_bytes("calls.join()")).unwrap();
+        let calls = calls.to_string(ctx).unwrap().to_std_string_escaped();
+        assert_eq!(
+            calls,
+            format!("{1}:2,{0}:3,{1}:3", first.to_bits(), second.to_bits())
+        );
+        let errors = world.resource::<Events<ScriptError>>();
+        assert_eq!(errors.len(), 1);
+    }
+}
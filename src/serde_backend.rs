@@ -0,0 +1,99 @@
+use bevy_reflect::prelude::*;
+use bevy_reflect::serde::{ReflectDeserializer, ReflectSerializer};
+use bevy_reflect::{FromReflect, ReflectFromReflect, TypePath, TypeRegistry};
+use boa_engine::{Context, JsError, JsNativeError, JsValue};
+use serde::de::DeserializeSeed;
+
+use crate::errors::{ConversionError, FieldPath};
+use crate::from::js_value_to_typed_with_settings;
+use crate::into::try_reflect_to_js_value_with_settings;
+use crate::json::{js_value_to_json, json_to_js_value};
+use crate::settings::ConversionSettings;
+
+/// Which way a conversion goes between reflected values and JS, chosen per call with the
+/// `*_with_backend` functions.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ConversionBackend {
+    /// The crate's own conversion, walking the value's reflection, with its
+    /// [`ConversionSettings`], converters and hooks.
+    #[default]
+    Reflect,
+    /// Bevy's serde representation: the value goes through `ReflectSerializer` to JSON, and
+    /// from there to JS, so scripts see exactly what bevy writes, the value under its type path,
+    /// e.g. `{ "my_game::Health": { current: 10.0, max: 20.0 } }`. Slower, and settings,
+    /// converters and hooks don't apply.
+    Serde,
+}
+
+/// Convert a reflected value to JS with the given backend.
+pub fn reflect_to_js_value_with_backend(
+    value: &dyn Reflect,
+    backend: ConversionBackend,
+    registry: &TypeRegistry,
+    settings: &ConversionSettings,
+    ctx: &mut Context,
+) -> Result<JsValue, ConversionError> {
+    match backend {
+        ConversionBackend::Reflect => try_reflect_to_js_value_with_settings(value, settings, ctx),
+        ConversionBackend::Serde => reflect_to_serde_js_value(value, registry, ctx),
+    }
+}
+
+/// Convert a JS value into a registered type with the given backend, reading back what
+/// [`reflect_to_js_value_with_backend`] writes with the same backend.
+pub fn js_value_to_typed_with_backend<T: FromReflect + TypePath>(
+    value: JsValue,
+    backend: ConversionBackend,
+    registry: &TypeRegistry,
+    settings: &ConversionSettings,
+    ctx: &mut Context,
+) -> Result<T, ConversionError> {
+    match backend {
+        ConversionBackend::Reflect => {
+            js_value_to_typed_with_settings(value, registry, settings, ctx)
+        }
+        ConversionBackend::Serde => {
+            let value = serde_js_value_to_reflect(&value, registry, ctx)?;
+            T::from_reflect(value.as_ref()).ok_or_else(|| ConversionError::FromReflect {
+                type_path: T::type_path().to_owned(),
+                path: FieldPath::default(),
+            })
+        }
+    }
+}
+
+/// Convert a reflected value to JS through bevy's serde representation. See
+/// [`ConversionBackend::Serde`].
+pub fn reflect_to_serde_js_value(
+    value: &dyn Reflect,
+    registry: &TypeRegistry,
+    ctx: &mut Context,
+) -> Result<JsValue, ConversionError> {
+    let json = serde_json::to_value(ReflectSerializer::new(value, registry))
+        .map_err(|err| serde_error(value.reflect_type_path(), err))?;
+    Ok(json_to_js_value(&json, ctx))
+}
+
+/// Convert a JS value in bevy's serde representation, a value under its type path, into the
+/// registered type it names.
+pub fn serde_js_value_to_reflect(
+    value: &JsValue,
+    registry: &TypeRegistry,
+    ctx: &mut Context,
+) -> Result<Box<dyn Reflect>, ConversionError> {
+    let json = js_value_to_json(value, ctx)?;
+    let value = ReflectDeserializer::new(registry)
+        .deserialize(json)
+        .map_err(|err| serde_error("a serde value", err))?;
+    // Deserialized values are dynamic. Concrete values are what the type itself gives.
+    let concrete = value.get_represented_type_info().and_then(|info| {
+        registry
+            .get_type_data::<ReflectFromReflect>(info.type_id())?
+            .from_reflect(value.as_ref())
+    });
+    Ok(concrete.unwrap_or(value))
+}
+
+fn serde_error(what: &str, err: impl std::fmt::Display) -> ConversionError {
+    JsError::from(JsNativeError::typ().with_message(format!("Cannot convert {what}: {err}"))).into()
+}
@@ -0,0 +1,178 @@
+use std::borrow::Cow;
+
+use crate::errors::{ConversionError, FieldPath};
+
+/// How a conversion treats numbers, enums, field names, unexpected input and deep nesting, given
+/// to the `*_with_settings` variants of the conversion functions. The default is what the plain
+/// functions do:
+///
+/// ```no_run
+/// # use bevy_boa_reflect::*;
+/// # let ctx = &mut boa_engine::Context::default();
+/// # let transform = 0.5_f32;
+/// let settings = ConversionSettings::default()
+///     .with_rename(RenameRule::CamelCase)
+///     .with_enums(EnumRepresentation::External)
+///     .strict(true)
+///     .with_max_depth(32);
+/// let value = reflect_to_js_value_with_settings(&transform, &settings, ctx)?;
+/// # Ok::<(), boa_engine::JsError>(())
+/// ```
+///
+/// A value is read back with the settings it was made with. Object templates are only cached
+/// for structs converted without renaming. As a resource, the settings are what
+/// [`JsCtx`](crate::JsCtx) converts with.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "bevy", derive(bevy::prelude::Resource))]
+pub struct ConversionSettings {
+    pub(crate) numbers: NumberPolicy,
+    pub(crate) enums: EnumRepresentation,
+    pub(crate) rename: RenameRule,
+    pub(crate) strict: bool,
+    pub(crate) max_depth: Option<usize>,
+}
+
+impl ConversionSettings {
+    /// The settings the conversion functions without settings use.
+    pub(crate) const DEFAULT: Self = Self {
+        numbers: NumberPolicy::Exact,
+        enums: EnumRepresentation::Tagged,
+        rename: RenameRule::None,
+        strict: false,
+        max_depth: None,
+    };
+
+    pub fn with_numbers(mut self, numbers: NumberPolicy) -> Self {
+        self.numbers = numbers;
+        self
+    }
+
+    pub fn with_enums(mut self, enums: EnumRepresentation) -> Self {
+        self.enums = enums;
+        self
+    }
+
+    pub fn with_rename(mut self, rename: RenameRule) -> Self {
+        self.rename = rename;
+        self
+    }
+
+    /// Reject values from JS that would otherwise be coerced or ignored: booleans that aren't
+    /// `true` or `false`, `BigInt`s for floats, and properties a type has no field for.
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Fail on values nested deeper than `max_depth`, counting the value converted as depth 1,
+    /// e.g. to bound the work a script can cause with a self-referencing shape.
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    pub fn numbers(&self) -> NumberPolicy {
+        self.numbers
+    }
+
+    pub fn enums(&self) -> EnumRepresentation {
+        self.enums
+    }
+
+    pub fn rename(&self) -> RenameRule {
+        self.rename
+    }
+
+    pub fn is_strict(&self) -> bool {
+        self.strict
+    }
+
+    pub fn max_depth(&self) -> Option<usize> {
+        self.max_depth
+    }
+
+    /// Fail if a value at `depth` is nested too deeply.
+    pub(crate) fn check_depth<'a>(
+        &self,
+        depth: usize,
+        type_path: impl FnOnce() -> &'a str,
+    ) -> Result<(), ConversionError> {
+        match self.max_depth {
+            Some(max_depth) if depth > max_depth => Err(ConversionError::TooDeep {
+                type_path: type_path().to_owned(),
+                path: FieldPath::default(),
+                max_depth,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Whether field names are renamed, which the cached templates aren't built for.
+    pub(crate) fn renames(&self) -> bool {
+        !matches!(self.rename, RenameRule::None)
+    }
+}
+
+/// How numbers that don't fit a JS number exactly are converted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NumberPolicy {
+    /// 64 bit integers become `BigInt`s, so they keep every digit, and integers are only read
+    /// from whole numbers.
+    #[default]
+    Exact,
+    /// 64 bit integers become plain numbers, which scripts can do arithmetic on but which lose
+    /// precision past 2^53, and integers read from fractional numbers are truncated.
+    Lossy,
+}
+
+/// How enum values are represented in JS.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EnumRepresentation {
+    /// An object with the variant's fields and its name under `__variant`, e.g.
+    /// `{ x: 1, y: 2, __variant: "Move" }`. Unit variants may be read from their name alone.
+    #[default]
+    Tagged,
+    /// Unit variants as their name, and other variants as an object with the variant's fields
+    /// under its name, as an object or an array for tuple variants, e.g.
+    /// `{ Move: { x: 1, y: 2 } }`. This is how serde represents enums by default.
+    External,
+}
+
+/// How struct field names are renamed to make property names.
+#[derive(Debug, Clone, Copy, Default)]
+pub enum RenameRule {
+    /// Properties are named after their fields.
+    #[default]
+    None,
+    /// `snake_case` field names become `camelCase` properties, as is usual in JS.
+    CamelCase,
+    /// Field names are renamed by a function.
+    Custom(fn(&str) -> String),
+}
+
+impl RenameRule {
+    /// The property name for a field.
+    pub fn apply<'a>(&self, name: &'a str) -> Cow<'a, str> {
+        match self {
+            Self::None => Cow::Borrowed(name),
+            Self::CamelCase => Cow::Owned(camel_case(name)),
+            Self::Custom(rename) => Cow::Owned(rename(name)),
+        }
+    }
+}
+
+fn camel_case(name: &str) -> String {
+    let mut renamed = String::with_capacity(name.len());
+    let mut upper = false;
+    for c in name.chars() {
+        if c == '_' && !renamed.is_empty() {
+            upper = true;
+        } else if upper {
+            renamed.extend(c.to_uppercase());
+            upper = false;
+        } else {
+            renamed.push(c);
+        }
+    }
+    renamed
+}
@@ -0,0 +1,261 @@
+use bevy_utils::HashMap;
+use boa_engine::builtins::map::ordered_map::OrderedMap;
+use boa_engine::builtins::set::ordered_set::OrderedSet;
+use boa_engine::object::builtins::{
+    JsArray, JsArrayBuffer, JsDataView, JsDate, JsMap, JsSet, JsTypedArray,
+};
+use boa_engine::property::PropertyKey;
+use boa_engine::{
+    js_string, Context, JsNativeError, JsObject, JsResult, JsString, JsValue, NativeFunction,
+};
+
+#[cfg(feature = "bevy")]
+use crate::classes::{instance_of, new_instance};
+use crate::from::js_map_entries;
+
+/// Objects nested deeper than this can't be cloned, rather than overflowing the stack.
+const MAX_DEPTH: usize = 256;
+
+/// Register `structuredClone` in the context's current realm. The script runtime registers it in
+/// every realm it creates, and in workers.
+pub fn register_structured_clone(ctx: &mut Context) -> JsResult<()> {
+    let function = NativeFunction::from_fn_ptr(|_, args, ctx| {
+        structured_clone(&args.first().cloned().unwrap_or_default(), ctx)
+    });
+    ctx.register_global_builtin_callable(js_string!("structuredClone"), 1, function)?;
+    Ok(())
+}
+
+/// Deep copy a value the way `structuredClone` does, for the shapes conversions make: plain
+/// objects, including enums and entity proxies, arrays, `Map`s, `Set`s, typed arrays,
+/// `DataView`s, `ArrayBuffer`s, `Date`s and instances of generated classes, which stay instances
+/// of their class. Primitives, entities and other `BigInt`s among them, are copied as they are.
+/// Objects reached more than once, including through cycles, are cloned once and shared in the
+/// copy as they were in the original, so views of one buffer share its copy.
+///
+/// Functions and symbols can't be cloned and throw, as they do on the web. Other objects are
+/// copied as plain objects of their enumerable own data properties, without calling getters.
+pub fn structured_clone(value: &JsValue, ctx: &mut Context) -> JsResult<JsValue> {
+    clone_value(value, &mut HashMap::default(), 0, ctx)
+}
+
+fn clone_value(
+    value: &JsValue,
+    memo: &mut HashMap<JsObject, JsObject>,
+    depth: usize,
+    ctx: &mut Context,
+) -> JsResult<JsValue> {
+    let obj = match value {
+        JsValue::Object(obj) => obj,
+        JsValue::Symbol(_) => return Err(data_clone_error("Symbols")),
+        primitive => return Ok(primitive.clone()),
+    };
+    if let Some(clone) = memo.get(obj) {
+        return Ok(clone.clone().into());
+    }
+    if depth > MAX_DEPTH {
+        return Err(JsNativeError::range()
+            .with_message("Value is too deeply nested to clone")
+            .into());
+    }
+    if obj.is_callable() {
+        return Err(data_clone_error("Functions"));
+    }
+
+    #[cfg(feature = "bevy")]
+    if let Some((type_id, fields)) = instance_of(obj) {
+        let clone_fields = JsObject::with_object_proto(ctx.intrinsics());
+        let clone = new_instance(obj, type_id, clone_fields.clone());
+        memo.insert(obj.clone(), clone.clone());
+        memo.insert(fields.clone(), clone_fields.clone());
+        copy_properties(&fields, &clone_fields, memo, depth, ctx)?;
+        return Ok(clone.into());
+    }
+    if let Ok(typed) = JsTypedArray::from_object(obj.clone()) {
+        // Views keep sharing a buffer in the copy, as the buffer is cloned once.
+        let buffer = clone_value(&typed.buffer(ctx)?, memo, depth + 1, ctx)?;
+        let constructor = typed_array_constructor(&typed, ctx)?;
+        let offset = typed.byte_offset(ctx)?;
+        let length = typed.length(ctx)?;
+        let clone = constructor.construct(&[buffer, offset.into(), length.into()], None, ctx)?;
+        memo.insert(obj.clone(), clone.clone());
+        return Ok(clone.into());
+    }
+    if let Ok(view) = JsDataView::from_object(obj.clone()) {
+        let buffer = clone_value(&view.buffer(ctx)?, memo, depth + 1, ctx)?;
+        let offset = view.byte_offset(ctx)?;
+        let length = view.byte_length(ctx)?;
+        let constructor = ctx.intrinsics().constructors().data_view().constructor();
+        let clone = constructor.construct(&[buffer, offset.into(), length.into()], None, ctx)?;
+        memo.insert(obj.clone(), clone.clone());
+        return Ok(clone.into());
+    }
+    if let Ok(buffer) = JsArrayBuffer::from_object(obj.clone()) {
+        let bytes = buffer.data().map(|data| data.to_vec()).unwrap_or_default();
+        let clone: JsObject = JsArrayBuffer::from_byte_block(bytes, ctx)?.into();
+        memo.insert(obj.clone(), clone.clone());
+        return Ok(clone.into());
+    }
+    if let Ok(date) = JsDate::from_object(obj.clone()) {
+        let time = date.get_time(ctx)?;
+        let constructor = ctx.intrinsics().constructors().date().constructor();
+        let clone = constructor.construct(&[time], None, ctx)?;
+        memo.insert(obj.clone(), clone.clone());
+        return Ok(clone.into());
+    }
+    if obj.is::<OrderedMap<JsValue>>() {
+        let clone = JsMap::new(ctx);
+        memo.insert(obj.clone(), clone.clone().into());
+        let entries = js_map_entries(value, "Map", ctx)?;
+        for (key, value) in entries {
+            let key = clone_value(&key, memo, depth + 1, ctx)?;
+            let value = clone_value(&value, memo, depth + 1, ctx)?;
+            clone.set(key, value, ctx)?;
+        }
+        return Ok(clone.into());
+    }
+    let set_values = obj
+        .downcast_ref::<OrderedSet>()
+        .map(|set| set.iter().cloned().collect::<Vec<_>>());
+    if let Some(values) = set_values {
+        let clone = JsSet::new(ctx);
+        memo.insert(obj.clone(), clone.clone().into());
+        for value in values {
+            clone.add(clone_value(&value, memo, depth + 1, ctx)?, ctx)?;
+        }
+        return Ok(clone.into());
+    }
+    if obj.is_array() {
+        let array = JsArray::from_object(obj.clone())?;
+        let clone = JsArray::new(ctx);
+        memo.insert(obj.clone(), clone.clone().into());
+        for idx in 0..array.length(ctx)? {
+            let item = clone_value(&array.get(idx, ctx)?, memo, depth + 1, ctx)?;
+            clone.push(item, ctx)?;
+        }
+        return Ok(clone.into());
+    }
+
+    let clone = JsObject::with_object_proto(ctx.intrinsics());
+    memo.insert(obj.clone(), clone.clone());
+    copy_properties(obj, &clone, memo, depth, ctx)?;
+    Ok(clone.into())
+}
+
+/// Clone the enumerable own data properties of one object onto another, leaving out those keyed
+/// by symbols. Accessors are left out too rather than called, so cloning never runs script code.
+fn copy_properties(
+    from: &JsObject,
+    to: &JsObject,
+    memo: &mut HashMap<JsObject, JsObject>,
+    depth: usize,
+    ctx: &mut Context,
+) -> JsResult<()> {
+    for key in from.own_property_keys(ctx)? {
+        if matches!(key, PropertyKey::Symbol(_)) {
+            continue;
+        }
+        let property = from.borrow().properties().get(&key);
+        let Some(value) = property
+            .filter(|property| property.expect_enumerable())
+            .and_then(|property| property.value().cloned())
+        else {
+            continue;
+        };
+        let value = clone_value(&value, memo, depth + 1, ctx)?;
+        to.create_data_property_or_throw(key, value, ctx)?;
+    }
+    Ok(())
+}
+
+/// The constructor of the typed array's kind, in the context's current realm.
+fn typed_array_constructor(typed: &JsTypedArray, ctx: &mut Context) -> JsResult<JsObject> {
+    let tag = typed.to_string_tag(ctx)?;
+    let constructors = ctx.intrinsics().constructors();
+    let constructor = match tag
+        .as_string()
+        .map(JsString::to_std_string_escaped)
+        .as_deref()
+    {
+        Some("Int8Array") => constructors.typed_int8_array(),
+        Some("Uint8Array") => constructors.typed_uint8_array(),
+        Some("Uint8ClampedArray") => constructors.typed_uint8clamped_array(),
+        Some("Int16Array") => constructors.typed_int16_array(),
+        Some("Uint16Array") => constructors.typed_uint16_array(),
+        Some("Int32Array") => constructors.typed_int32_array(),
+        Some("Uint32Array") => constructors.typed_uint32_array(),
+        Some("BigInt64Array") => constructors.typed_bigint64_array(),
+        Some("BigUint64Array") => constructors.typed_biguint64_array(),
+        Some("Float32Array") => constructors.typed_float32_array(),
+        Some("Float64Array") => constructors.typed_float64_array(),
+        _ => return Err(data_clone_error("Unknown typed arrays")),
+    };
+    Ok(constructor.constructor())
+}
+
+fn data_clone_error(what: &str) -> boa_engine::JsError {
+    JsNativeError::typ()
+        .with_message(format!("DataCloneError: {what} cannot be cloned"))
+        .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use boa_engine::Source;
+
+    use super::*;
+
+    fn check(setup: &str, checks: &str) -> String {
+        let mut ctx = Context::default();
+        register_structured_clone(&mut ctx).unwrap();
+        let source = format!("{setup}; const copy = structuredClone(original); [{checks}].join()");
+        let result = ctx.eval(Source::from_bytes(&source)).unwrap();
+        result.as_string().unwrap().to_std_string_escaped()
+    }
+
+    #[test]
+    fn cycles_and_shared_objects_stay_shared() {
+        let result = check(
+            "const shared = { n: 1 }; \
+             const original = { a: shared, b: shared }; \
+             original.self = original",
+            "copy !== original, copy.self === copy, copy.a === copy.b, copy.a !== shared, copy.a.n",
+        );
+        assert_eq!(result, "true,true,true,true,1");
+    }
+
+    #[test]
+    fn maps_and_sets_are_deep_copied() {
+        let result = check(
+            "const key = { k: 1 }; const original = new Map([[key, new Set([key])]])",
+            "copy instanceof Map, copy.size, [...copy.keys()][0] !== key, \
+             [...copy.values()][0].has([...copy.keys()][0])",
+        );
+        assert_eq!(result, "true,1,true,true");
+    }
+
+    #[test]
+    fn only_enumerable_data_properties_are_copied() {
+        let result = check(
+            "let calls = 0; const original = { a: 1, get b() { calls++; return 2; } }; \
+             Object.defineProperty(original, 'hidden', { value: 3, enumerable: false })",
+            "calls, 'a' in copy, 'b' in copy, 'hidden' in copy",
+        );
+        assert_eq!(result, "0,true,false,false");
+    }
+
+    #[test]
+    fn views_of_one_buffer_share_the_cloned_buffer() {
+        let result = check(
+            "const buffer = new ArrayBuffer(8); \
+             const original = [ \
+                 new Uint8Array(buffer), new Uint16Array(buffer, 2, 2), new DataView(buffer, 4) \
+             ]; \
+             original[0][2] = 7",
+            "copy[0].buffer === copy[1].buffer, copy[1].buffer === copy[2].buffer, \
+             copy[0].buffer !== buffer, copy[1] instanceof Uint16Array, \
+             copy[1].byteOffset, copy[1].length, copy[1][0], copy[2].byteLength",
+        );
+        assert_eq!(result, "true,true,true,true,2,2,7,4");
+    }
+}
@@ -0,0 +1,113 @@
+use bevy::ecs::reflect::AppTypeRegistry;
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy::reflect::{FromReflect, TypePath};
+use boa_engine::{Context, JsValue};
+
+use crate::errors::ConversionError;
+use crate::from::js_value_to_typed_with_settings;
+use crate::into::try_reflect_to_js_value_with_settings;
+use crate::runtime::ScriptRuntime;
+use crate::serde_backend::{
+    js_value_to_typed_with_backend, reflect_to_js_value_with_backend, ConversionBackend,
+};
+use crate::settings::ConversionSettings;
+
+/// The script runtime's context, with the type registry and conversion settings conversions
+/// need, for systems handing values to scripts and back:
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_boa_reflect::JsCtx;
+/// # use boa_engine::JsResult;
+/// # #[derive(Component, Reflect)]
+/// # struct Health { current: f32 }
+/// fn expose_health(mut js: JsCtx, health: Query<&Health>) -> JsResult<()> {
+///     for health in &health {
+///         let value = js.to_js(health)?;
+///         # let _ = value;
+///     }
+///     Ok(())
+/// }
+/// ```
+///
+/// Conversions use the [`ConversionSettings`] resource if there is one, and the defaults
+/// otherwise. Like the runtime itself, systems taking a `JsCtx` run on the main thread.
+#[derive(SystemParam)]
+pub struct JsCtx<'w> {
+    runtime: NonSendMut<'w, ScriptRuntime>,
+    registry: Res<'w, AppTypeRegistry>,
+    settings: Option<Res<'w, ConversionSettings>>,
+}
+
+impl JsCtx<'_> {
+    pub fn context(&mut self) -> &mut Context {
+        self.runtime.context()
+    }
+
+    pub fn runtime(&mut self) -> &mut ScriptRuntime {
+        &mut self.runtime
+    }
+
+    pub fn registry(&self) -> &AppTypeRegistry {
+        &self.registry
+    }
+
+    pub fn settings(&self) -> &ConversionSettings {
+        self.settings
+            .as_deref()
+            .unwrap_or(&ConversionSettings::DEFAULT)
+    }
+
+    /// Convert a reflected value to JS.
+    pub fn to_js(&mut self, value: &dyn Reflect) -> Result<JsValue, ConversionError> {
+        let settings = self
+            .settings
+            .as_deref()
+            .unwrap_or(&ConversionSettings::DEFAULT);
+        try_reflect_to_js_value_with_settings(value, settings, self.runtime.context())
+    }
+
+    /// Convert a JS value into a registered type.
+    pub fn from_js<T: FromReflect + TypePath>(
+        &mut self,
+        value: JsValue,
+    ) -> Result<T, ConversionError> {
+        let registry = self.registry.read();
+        let settings = self
+            .settings
+            .as_deref()
+            .unwrap_or(&ConversionSettings::DEFAULT);
+        js_value_to_typed_with_settings(value, &registry, settings, self.runtime.context())
+    }
+
+    /// Convert a reflected value to JS with the given backend.
+    pub fn to_js_with_backend(
+        &mut self,
+        value: &dyn Reflect,
+        backend: ConversionBackend,
+    ) -> Result<JsValue, ConversionError> {
+        let registry = self.registry.read();
+        let settings = self
+            .settings
+            .as_deref()
+            .unwrap_or(&ConversionSettings::DEFAULT);
+        let ctx = self.runtime.context();
+        reflect_to_js_value_with_backend(value, backend, &registry, settings, ctx)
+    }
+
+    /// Convert a JS value into a registered type with the given backend.
+    pub fn from_js_with_backend<T: FromReflect + TypePath>(
+        &mut self,
+        value: JsValue,
+        backend: ConversionBackend,
+    ) -> Result<T, ConversionError> {
+        let registry = self.registry.read();
+        let settings = self
+            .settings
+            .as_deref()
+            .unwrap_or(&ConversionSettings::DEFAULT);
+        let ctx = self.runtime.context();
+        js_value_to_typed_with_backend(value, backend, &registry, settings, ctx)
+    }
+}
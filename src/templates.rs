@@ -0,0 +1,180 @@
+use std::any::TypeId;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::rc::Rc;
+
+use bevy_reflect::{Enum, StructInfo, TypeInfo};
+use boa_engine::property::{PropertyDescriptor, PropertyKey};
+use boa_engine::{Context, Finalize, JsData, JsObject, JsString, JsValue, Trace};
+
+/// The objects a struct type converts to: its field keys in order, each holding a writable,
+/// enumerable and configurable value. Boa keeps its own object templates private, so this builds
+/// objects from keys computed once per type, which also keeps them on a shared shape.
+#[derive(Debug)]
+pub(crate) struct ObjectTemplate {
+    keys: Box<[PropertyKey]>,
+}
+
+impl ObjectTemplate {
+    pub(crate) fn new<'a>(names: impl IntoIterator<Item = &'a str>) -> Self {
+        Self {
+            keys: names
+                .into_iter()
+                .map(|name| JsString::from(name).into())
+                .collect(),
+        }
+    }
+
+    /// The keys of the fields, in field order.
+    pub(crate) fn keys(&self) -> &[PropertyKey] {
+        &self.keys
+    }
+
+    /// Create an object with a value for each field, in field order.
+    pub(crate) fn create(
+        &self,
+        values: impl IntoIterator<Item = JsValue>,
+        ctx: &Context,
+    ) -> JsObject {
+        let obj = JsObject::with_object_proto(ctx.intrinsics());
+        for (key, value) in self.keys.iter().zip(values) {
+            obj.insert_property(
+                key.clone(),
+                PropertyDescriptor::builder()
+                    .value(value)
+                    .writable(true)
+                    .enumerable(true)
+                    .configurable(true),
+            );
+        }
+        obj
+    }
+}
+
+/// Object templates for struct types, made once per context so converting the same type thousands
+/// of times a frame reuses its keys instead of allocating fresh strings each time. Their keys come
+/// from a table of interned strings, so types with fields of the same name share them too.
+///
+/// Boa's strings aren't tied to a context, so contexts on the same thread can share the templates
+/// and strings, see [`share_strings`].
+#[derive(Debug, Default, Clone, Trace, Finalize, JsData)]
+pub(crate) struct ObjectTemplates {
+    #[unsafe_ignore_trace]
+    shared: Rc<SharedTemplates>,
+}
+
+#[derive(Debug, Default)]
+struct SharedTemplates {
+    /// Templates by the type of the struct. Only types with a fixed set of fields are cached, so
+    /// not dynamic structs.
+    templates: RefCell<HashMap<TypeId, Rc<ObjectTemplate>>>,
+    /// The names of enum types' variants, by the type and then the variant's index.
+    variants: RefCell<HashMap<TypeId, Rc<[JsString]>>>,
+    strings: RefCell<HashMap<Box<str>, JsString>>,
+    /// The keys of tuple variant fields, by index.
+    indices: RefCell<Vec<JsString>>,
+}
+
+impl ObjectTemplates {
+    /// The templates of a context, adding them if it has none yet.
+    pub(crate) fn of(ctx: &mut Context) -> Self {
+        if let Some(templates) = ctx.get_data::<Self>() {
+            return templates.clone();
+        }
+        let templates = Self::default();
+        ctx.insert_data(templates.clone());
+        templates
+    }
+
+    /// The template for a type, building it from its field names the first time the type is
+    /// converted in this context.
+    pub(crate) fn get<'a, E>(
+        ctx: &mut Context,
+        type_id: TypeId,
+        names: impl FnOnce() -> Result<Vec<&'a str>, E>,
+    ) -> Result<Rc<ObjectTemplate>, E> {
+        let templates = Self::of(ctx);
+        if let Some(template) = templates.shared.templates.borrow().get(&type_id) {
+            return Ok(template.clone());
+        }
+        let keys = names()?
+            .into_iter()
+            .map(|name| templates.intern(name).into())
+            .collect();
+        let template = Rc::new(ObjectTemplate { keys });
+        templates
+            .shared
+            .templates
+            .borrow_mut()
+            .insert(type_id, template.clone());
+        Ok(template)
+    }
+
+    /// The template for a struct type, from its type info.
+    pub(crate) fn struct_info(ctx: &mut Context, info: &StructInfo) -> Rc<ObjectTemplate> {
+        let template = Self::get(ctx, info.type_id(), || {
+            Ok::<_, Infallible>(info.iter().map(|field| field.name()).collect())
+        });
+        match template {
+            Ok(template) => template,
+            Err(never) => match never {},
+        }
+    }
+
+    /// The name of an enum value's variant. Names are made once per enum type, so enum heavy
+    /// data like state machines converts without allocating its variant names again and again.
+    pub(crate) fn variant_name(&self, enum_value: &dyn Enum) -> JsString {
+        let info = match enum_value.get_represented_type_info() {
+            Some(TypeInfo::Enum(info)) if !enum_value.is_dynamic() => info,
+            _ => return self.intern(enum_value.variant_name()),
+        };
+        let type_id = info.type_id();
+        let names = self.shared.variants.borrow().get(&type_id).cloned();
+        let names = names.unwrap_or_else(|| {
+            let names: Rc<[JsString]> = info
+                .iter()
+                .map(|variant| self.intern(variant.name()))
+                .collect();
+            self.shared
+                .variants
+                .borrow_mut()
+                .insert(type_id, names.clone());
+            names
+        });
+        match names.get(enum_value.variant_index()) {
+            Some(name) => name.clone(),
+            None => self.intern(enum_value.variant_name()),
+        }
+    }
+
+    /// The key of the tuple variant field at an index.
+    pub(crate) fn index(&self, idx: usize) -> JsString {
+        let mut indices = self.shared.indices.borrow_mut();
+        while indices.len() <= idx {
+            let key = JsString::from(indices.len().to_string());
+            indices.push(key);
+        }
+        indices[idx].clone()
+    }
+
+    /// A string made once and shared by everything converted with these templates.
+    pub(crate) fn intern(&self, name: &str) -> JsString {
+        if let Some(string) = self.shared.strings.borrow().get(name) {
+            return string.clone();
+        }
+        let string = JsString::from(name);
+        self.shared
+            .strings
+            .borrow_mut()
+            .insert(name.into(), string.clone());
+        string
+    }
+}
+
+/// Share the object templates and interned strings of one context with another on the same
+/// thread, so a pool of contexts converting the same types keeps a single copy of each type's
+/// field names. Whatever the other context had cached before is dropped.
+pub fn share_strings(from: &mut Context, to: &mut Context) {
+    to.insert_data(ObjectTemplates::of(from));
+}
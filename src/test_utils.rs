@@ -0,0 +1,962 @@
+//! Helpers for testing conversions, converters and scripts, behind the `test-utils` feature:
+//! round-trip assertions, builders for the JS values tests feed in, snapshots of the JS shape of
+//! values, and generators of arbitrary values of reflected types for property tests, seeded by
+//! hand, as a `proptest` [`Strategy`] or through `arbitrary`.
+//!
+//! ```no_run
+//! # use bevy_boa_reflect::test_utils::{assert_roundtrip, reflect_strategy};
+//! # use bevy_reflect::Reflect;
+//! # use proptest::prelude::*;
+//! # #[derive(Reflect, Debug)]
+//! # struct Inventory { slots: Vec<u32> }
+//! proptest! {
+//!     #[test]
+//!     fn inventory_roundtrips(inventory in reflect_strategy::<Inventory>()) {
+//!         assert_roundtrip(inventory);
+//!     }
+//! }
+//! ```
+
+use std::any::TypeId;
+use std::fmt::Debug;
+use std::path::Path;
+
+use arbitrary::{Arbitrary, Unstructured};
+
+use bevy_reflect::{
+    DynamicArray, DynamicEnum, DynamicList, DynamicMap, DynamicStruct, DynamicTuple,
+    DynamicTupleStruct, DynamicVariant, FromReflect, GetTypeRegistration, Map, Reflect,
+    ReflectFromReflect, TypeInfo, TypePath, TypeRegistry, VariantInfo,
+};
+use boa_engine::builtins::map::ordered_map::OrderedMap;
+use boa_engine::builtins::set::ordered_set::OrderedSet;
+use boa_engine::object::builtins::{JsArray, JsArrayBuffer, JsDate, JsTypedArray};
+use boa_engine::object::ObjectInitializer;
+use boa_engine::property::{Attribute, PropertyKey};
+use boa_engine::{js_str, Context, JsNativeError, JsObject, JsResult, JsString, JsValue};
+use proptest::num;
+use proptest::prelude::*;
+use proptest::strategy::{LazyJust, Union};
+
+#[cfg(feature = "bevy")]
+use crate::classes::instance_of;
+use crate::errors::ConversionError;
+use crate::from::{js_map_entries, js_value_to_typed_with_settings};
+use crate::into::try_reflect_to_js_value_with_settings;
+use crate::settings::ConversionSettings;
+
+/// Assert that a value converts to JS and back into an equal value, in a fresh context with the
+/// default settings.
+pub fn assert_roundtrip<T: FromReflect + TypePath + GetTypeRegistration>(value: T) {
+    assert_roundtrip_in(value, &ConversionSettings::DEFAULT, &mut Context::default());
+}
+
+/// Assert that a value converts to JS and back into an equal value, in a context set up by the
+/// test, e.g. with [`JsConverters`](crate::JsConverters) registered, and with given settings.
+pub fn assert_roundtrip_in<T: FromReflect + TypePath + GetTypeRegistration>(
+    value: T,
+    settings: &ConversionSettings,
+    ctx: &mut Context,
+) {
+    let mut registry = TypeRegistry::new();
+    registry.register::<T>();
+    let value = value.as_reflect();
+    let js_value = try_reflect_to_js_value_with_settings(value, settings, ctx)
+        .unwrap_or_else(|err| panic!("{value:?} could not be converted to JS: {err}"));
+    let display = js_value.display().to_string();
+    let converted: T = js_value_to_typed_with_settings(js_value, &registry, settings, ctx)
+        .unwrap_or_else(|err| {
+            panic!("{value:?} could not be converted back from {display}: {err}")
+        });
+    assert!(
+        value.reflect_partial_eq(&converted).unwrap_or(false),
+        "{value:?} converted back from {display} as {:?}",
+        converted.as_reflect()
+    );
+}
+
+/// A plain object with the given properties.
+pub fn js_object<'a>(
+    properties: impl IntoIterator<Item = (&'a str, JsValue)>,
+    ctx: &mut Context,
+) -> JsValue {
+    let mut obj = ObjectInitializer::new(ctx);
+    for (name, value) in properties {
+        obj.property(JsString::from(name), value, Attribute::all());
+    }
+    obj.build().into()
+}
+
+/// An array of the given values.
+pub fn js_array(values: impl IntoIterator<Item = JsValue>, ctx: &mut Context) -> JsValue {
+    JsArray::from_iter(values, ctx).into()
+}
+
+/// An object for an enum variant, with its fields and a `__variant` name, in the shape
+/// conversions give enums.
+pub fn js_variant<'a>(
+    variant: &str,
+    fields: impl IntoIterator<Item = (&'a str, JsValue)>,
+    ctx: &mut Context,
+) -> JsValue {
+    let fields = fields
+        .into_iter()
+        .chain([("__variant", JsValue::from(JsString::from(variant)))]);
+    js_object(fields, ctx)
+}
+
+/// Render a reflected value in the canonical text form of [`js_snapshot`], converted with the
+/// given settings, to pin the exact shape scripts see it in.
+pub fn reflect_snapshot(
+    value: &dyn Reflect,
+    settings: &ConversionSettings,
+    ctx: &mut Context,
+) -> Result<String, ConversionError> {
+    let js_value = try_reflect_to_js_value_with_settings(value, settings, ctx)?;
+    Ok(js_snapshot(&js_value, ctx)?)
+}
+
+/// Render a JS value in a canonical text form for snapshot tests, which stays the same between
+/// runs and platforms for values of the same shape.
+///
+/// Object properties are sorted by key, as are the entries of `Map`s and the values of `Set`s,
+/// so hash map ordering doesn't show. Arrays keep their order. Numbers are written as JS writes
+/// them, so integers and floats holding the same number look alike, and `-0` is `0`. `BigInt`s
+/// end in `n`, strings are quoted as JSON quotes them, and objects with a prototype of their own
+/// are prefixed by their constructor's name, like `Map`, `Uint8Array` or a generated class.
+/// Objects reached again through a cycle are written as `[Circular]`.
+pub fn js_snapshot(value: &JsValue, ctx: &mut Context) -> JsResult<String> {
+    let mut path = Vec::new();
+    render(value, 0, &mut path, ctx)
+}
+
+/// Assert that a reflected value converts to JS in the shape of an expected snapshot, in a fresh
+/// context with the default settings. The expected text can be indented like the test around it,
+/// since common indentation and surrounding blank lines are ignored.
+///
+/// ```no_run
+/// # use bevy_boa_reflect::test_utils::assert_snapshot;
+/// # use bevy_reflect::Reflect;
+/// # #[derive(Reflect)]
+/// # struct Health { current: f32, max: u32 }
+/// assert_snapshot(
+///     &Health { current: 3.0, max: 10 },
+///     r#"
+///     {
+///       "current": 3,
+///       "max": 10,
+///     }
+///     "#,
+/// );
+/// ```
+pub fn assert_snapshot(value: &dyn Reflect, expected: &str) {
+    assert_snapshot_in(
+        value,
+        expected,
+        &ConversionSettings::DEFAULT,
+        &mut Context::default(),
+    );
+}
+
+/// Assert that a reflected value converts to JS in the shape of an expected snapshot, in a
+/// context set up by the test and with given settings.
+pub fn assert_snapshot_in(
+    value: &dyn Reflect,
+    expected: &str,
+    settings: &ConversionSettings,
+    ctx: &mut Context,
+) {
+    let actual = snapshot_or_panic(value, settings, ctx);
+    assert_same_snapshot(value, &dedent(expected), &actual);
+}
+
+/// Assert that a reflected value converts to JS in the shape kept in a golden file, in a fresh
+/// context with the default settings. Relative paths are relative to the working directory, which
+/// `cargo test` sets to the package's root.
+///
+/// Running tests with the `UPDATE_SNAPSHOTS` environment variable set writes the value's snapshot
+/// to the file instead, which is how golden files are first made and how intended changes are
+/// accepted. Without it, a missing file fails the test.
+pub fn assert_golden(value: &dyn Reflect, path: impl AsRef<Path>) {
+    assert_golden_in(
+        value,
+        path,
+        &ConversionSettings::DEFAULT,
+        &mut Context::default(),
+    );
+}
+
+/// Assert that a reflected value converts to JS in the shape kept in a golden file, in a context
+/// set up by the test and with given settings.
+pub fn assert_golden_in(
+    value: &dyn Reflect,
+    path: impl AsRef<Path>,
+    settings: &ConversionSettings,
+    ctx: &mut Context,
+) {
+    let path = path.as_ref();
+    let actual = snapshot_or_panic(value, settings, ctx);
+    if std::env::var_os(UPDATE_SNAPSHOTS).is_some() {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .unwrap_or_else(|err| panic!("could not create {}: {err}", dir.display()));
+        }
+        std::fs::write(path, format!("{actual}\n"))
+            .unwrap_or_else(|err| panic!("could not write {}: {err}", path.display()));
+        return;
+    }
+    let expected = std::fs::read_to_string(path).unwrap_or_else(|err| {
+        panic!(
+            "could not read the snapshot {}: {err}; run with {UPDATE_SNAPSHOTS}=1 to write it",
+            path.display()
+        )
+    });
+    assert_same_snapshot(value, expected.trim_end(), &actual);
+}
+
+/// The environment variable that has [`assert_golden`] write snapshots rather than check them.
+const UPDATE_SNAPSHOTS: &str = "UPDATE_SNAPSHOTS";
+
+/// How deeply nested a value can be rendered, past the cycles that are caught.
+const MAX_SNAPSHOT_DEPTH: usize = 64;
+
+fn snapshot_or_panic(
+    value: &dyn Reflect,
+    settings: &ConversionSettings,
+    ctx: &mut Context,
+) -> String {
+    reflect_snapshot(value, settings, ctx)
+        .unwrap_or_else(|err| panic!("{value:?} could not be converted to JS: {err}"))
+}
+
+fn assert_same_snapshot(value: &dyn Reflect, expected: &str, actual: &str) {
+    if expected == actual {
+        return;
+    }
+    let line = expected
+        .lines()
+        .zip(actual.lines())
+        .position(|(expected, actual)| expected != actual)
+        .unwrap_or_else(|| expected.lines().count().min(actual.lines().count()));
+    panic!(
+        "the snapshot of {} changed from line {}\n--- expected\n{expected}\n+++ actual\n{actual}",
+        value.reflect_type_path(),
+        line + 1
+    );
+}
+
+/// Text without its surrounding blank lines and the indentation its lines share.
+fn dedent(text: &str) -> String {
+    let lines = text
+        .lines()
+        .skip_while(|line| line.trim().is_empty())
+        .collect::<Vec<_>>();
+    let end = lines
+        .iter()
+        .rposition(|line| !line.trim().is_empty())
+        .map_or(0, |idx| idx + 1);
+    let lines = &lines[..end];
+    let indent = lines
+        .iter()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or(0);
+    lines
+        .iter()
+        .map(|line| line.get(indent..).unwrap_or("").trim_end())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn render(
+    value: &JsValue,
+    indent: usize,
+    path: &mut Vec<JsObject>,
+    ctx: &mut Context,
+) -> JsResult<String> {
+    Ok(match value {
+        JsValue::Undefined => "undefined".to_owned(),
+        JsValue::Null => "null".to_owned(),
+        JsValue::Boolean(b) => b.to_string(),
+        JsValue::Integer(_) | JsValue::Rational(_) => value.to_string(ctx)?.to_std_string_escaped(),
+        JsValue::BigInt(b) => format!("{b}n"),
+        JsValue::String(s) => quote(&s.to_std_string_escaped()),
+        JsValue::Symbol(symbol) => symbol.descriptive_string().to_std_string_escaped(),
+        JsValue::Object(obj) => {
+            if path.iter().any(|seen| JsObject::equals(seen, obj)) {
+                return Ok("[Circular]".to_owned());
+            }
+            if path.len() >= MAX_SNAPSHOT_DEPTH {
+                return Err(JsNativeError::range()
+                    .with_message("Value is too deeply nested to snapshot")
+                    .into());
+            }
+            path.push(obj.clone());
+            let rendered = render_object(obj, indent, path, ctx);
+            path.pop();
+            rendered?
+        }
+    })
+}
+
+fn render_object(
+    obj: &JsObject,
+    indent: usize,
+    path: &mut Vec<JsObject>,
+    ctx: &mut Context,
+) -> JsResult<String> {
+    let name = constructor_name(obj, ctx)?;
+    if obj.is_callable() {
+        let function_name = obj.get(js_str!("name"), ctx)?.to_string(ctx)?;
+        return Ok(match function_name.to_std_string_escaped() {
+            function_name if function_name.is_empty() => "[Function]".to_owned(),
+            function_name => format!("[Function {function_name}]"),
+        });
+    }
+    #[cfg(feature = "bevy")]
+    if let Some((_, fields)) = instance_of(obj) {
+        let fields = render_properties(&fields, indent, path, ctx)?;
+        return Ok(prefixed(&name, fields));
+    }
+    if let Ok(date) = JsDate::from_object(obj.clone()) {
+        let time = date.get_time(ctx)?.to_string(ctx)?;
+        return Ok(format!("Date({})", time.to_std_string_escaped()));
+    }
+    if let Ok(buffer) = JsArrayBuffer::from_object(obj.clone()) {
+        let bytes = buffer.data().map(|data| data.to_vec()).unwrap_or_default();
+        let items = bytes.iter().map(u8::to_string).collect();
+        return Ok(prefixed(&name, block("[", items, "]", indent)));
+    }
+    if obj.is_array() || JsTypedArray::from_object(obj.clone()).is_ok() {
+        let len = obj.get(js_str!("length"), ctx)?.to_length(ctx)?;
+        let mut items = Vec::new();
+        for idx in 0..len {
+            items.push(render(&obj.get(idx, ctx)?, indent + 1, path, ctx)?);
+        }
+        let name = if obj.is_array() { None } else { name };
+        return Ok(prefixed(&name, block("[", items, "]", indent)));
+    }
+    if obj.is::<OrderedMap<JsValue>>() {
+        let mut entries = Vec::new();
+        for (key, value) in js_map_entries(&obj.clone().into(), "Map", ctx)? {
+            let key = render(&key, indent + 1, path, ctx)?;
+            let value = render(&value, indent + 1, path, ctx)?;
+            entries.push(format!("{key} => {value}"));
+        }
+        entries.sort();
+        return Ok(prefixed(&name, block("{", entries, "}", indent)));
+    }
+    let set_values = obj
+        .downcast_ref::<OrderedSet>()
+        .map(|set| set.iter().cloned().collect::<Vec<_>>());
+    if let Some(values) = set_values {
+        let mut items = values
+            .iter()
+            .map(|value| render(value, indent + 1, path, ctx))
+            .collect::<JsResult<Vec<_>>>()?;
+        items.sort();
+        return Ok(prefixed(&name, block("[", items, "]", indent)));
+    }
+    let properties = render_properties(obj, indent, path, ctx)?;
+    Ok(prefixed(&name, properties))
+}
+
+/// An object's own properties keyed by strings, sorted by key.
+fn render_properties(
+    obj: &JsObject,
+    indent: usize,
+    path: &mut Vec<JsObject>,
+    ctx: &mut Context,
+) -> JsResult<String> {
+    let mut properties = Vec::new();
+    for key in obj.own_property_keys(ctx)? {
+        let name = match &key {
+            PropertyKey::String(name) => name.to_std_string_escaped(),
+            PropertyKey::Index(idx) => idx.get().to_string(),
+            PropertyKey::Symbol(_) => continue,
+        };
+        let value = render(&obj.get(key, ctx)?, indent + 1, path, ctx)?;
+        properties.push((name, value));
+    }
+    properties.sort_by(|(a, _), (b, _)| a.cmp(b));
+    let properties = properties
+        .into_iter()
+        .map(|(name, value)| format!("{}: {value}", quote(&name)))
+        .collect();
+    Ok(block("{", properties, "}", indent))
+}
+
+/// The name of an object's constructor, unless it's a plain object or array.
+fn constructor_name(obj: &JsObject, ctx: &mut Context) -> JsResult<Option<String>> {
+    let Some(prototype) = obj.prototype() else {
+        return Ok(None);
+    };
+    let intrinsics = ctx.intrinsics().constructors();
+    if JsObject::equals(&prototype, &intrinsics.object().prototype())
+        || JsObject::equals(&prototype, &intrinsics.array().prototype())
+    {
+        return Ok(None);
+    }
+    let name = match prototype.get(js_str!("constructor"), ctx)? {
+        JsValue::Object(constructor) => constructor.get(js_str!("name"), ctx)?,
+        _ => return Ok(None),
+    };
+    Ok(name
+        .as_string()
+        .map(JsString::to_std_string_escaped)
+        .filter(|name| !name.is_empty()))
+}
+
+fn prefixed(name: &Option<String>, rendered: String) -> String {
+    match name {
+        Some(name) => format!("{name} {rendered}"),
+        None => rendered,
+    }
+}
+
+/// Items between delimiters, one to a line and indented one level deeper, or `{}` when empty.
+fn block(open: &str, items: Vec<String>, close: &str, indent: usize) -> String {
+    if items.is_empty() {
+        return format!("{open}{close}");
+    }
+    let inner = "  ".repeat(indent + 1);
+    let mut rendered = format!("{open}\n");
+    for item in items {
+        rendered.push_str(&format!("{inner}{item},\n"));
+    }
+    rendered.push_str(&"  ".repeat(indent));
+    rendered.push_str(close);
+    rendered
+}
+
+fn quote(string: &str) -> String {
+    serde_json::to_string(string).unwrap_or_default()
+}
+
+/// Makes arbitrary values of reflected types from their type info, for property tests of
+/// conversions. Values are made from a seed, so a failing case can be reproduced.
+///
+/// Collections have up to [`ReflectGenerator::with_max_len`] elements, and values stop nesting
+/// past [`ReflectGenerator::with_max_depth`], taking unit variants or empty collections where
+/// they can. Floats are always finite, so generated values equal themselves.
+pub struct ReflectGenerator {
+    rng: u64,
+    max_len: usize,
+    max_depth: usize,
+}
+
+impl ReflectGenerator {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: seed,
+            max_len: 4,
+            max_depth: 4,
+        }
+    }
+
+    pub fn with_max_len(mut self, max_len: usize) -> Self {
+        self.max_len = max_len;
+        self
+    }
+
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// An arbitrary value of a type. Panics if the type has a field of a value type other than
+    /// the primitives and `String`, which have no type info to make values from.
+    pub fn generate<T: FromReflect + GetTypeRegistration>(&mut self) -> T {
+        let mut registry = TypeRegistry::new();
+        registry.register::<T>();
+        let value = self
+            .generate_reflect(TypeId::of::<T>(), &registry)
+            .unwrap_or_else(|| panic!("cannot generate {}", std::any::type_name::<T>()));
+        T::from_reflect(value.as_ref())
+            .unwrap_or_else(|| panic!("generated an invalid {}", std::any::type_name::<T>()))
+    }
+
+    /// An arbitrary value of a registered type, concrete where the type has `ReflectFromReflect`
+    /// and dynamic otherwise. Returns `None` if the type, or one of its fields, isn't registered
+    /// or is an unknown value type.
+    pub fn generate_reflect(
+        &mut self,
+        type_id: TypeId,
+        registry: &TypeRegistry,
+    ) -> Option<Box<dyn Reflect>> {
+        let value = self.value(type_id, registry, 0)?;
+        let from_reflect = registry.get_type_data::<ReflectFromReflect>(type_id);
+        match from_reflect.and_then(|from_reflect| from_reflect.from_reflect(value.as_ref())) {
+            Some(concrete) => Some(concrete),
+            None => Some(value),
+        }
+    }
+
+    fn value(
+        &mut self,
+        type_id: TypeId,
+        registry: &TypeRegistry,
+        depth: usize,
+    ) -> Option<Box<dyn Reflect>> {
+        if let Some(primitive) = self.primitive(type_id) {
+            return Some(primitive);
+        }
+        let type_info = registry.get_type_info(type_id)?;
+        let nested = depth < self.max_depth;
+        let field = |generator: &mut Self, type_id| generator.value(type_id, registry, depth + 1);
+        Some(match type_info {
+            TypeInfo::Struct(info) => {
+                let mut dynamic_struct = DynamicStruct::default();
+                dynamic_struct.set_represented_type(Some(type_info));
+                for field_info in info.iter() {
+                    dynamic_struct
+                        .insert_boxed(field_info.name(), field(self, field_info.type_id())?);
+                }
+                Box::new(dynamic_struct)
+            }
+            TypeInfo::TupleStruct(info) => {
+                let mut dynamic_tuple_struct = DynamicTupleStruct::default();
+                dynamic_tuple_struct.set_represented_type(Some(type_info));
+                for field_info in info.iter() {
+                    dynamic_tuple_struct.insert_boxed(field(self, field_info.type_id())?);
+                }
+                Box::new(dynamic_tuple_struct)
+            }
+            TypeInfo::Tuple(info) => {
+                let mut dynamic_tuple = DynamicTuple::default();
+                dynamic_tuple.set_represented_type(Some(type_info));
+                for field_info in info.iter() {
+                    dynamic_tuple.insert_boxed(field(self, field_info.type_id())?);
+                }
+                Box::new(dynamic_tuple)
+            }
+            TypeInfo::List(info) => {
+                let mut dynamic_list = DynamicList::default();
+                dynamic_list.set_represented_type(Some(type_info));
+                for _ in 0..self.len(nested) {
+                    dynamic_list.push_box(field(self, info.item_type_id())?);
+                }
+                Box::new(dynamic_list)
+            }
+            TypeInfo::Array(info) => {
+                let items = (0..info.capacity())
+                    .map(|_| field(self, info.item_type_id()))
+                    .collect::<Option<Vec<_>>>()?;
+                let mut dynamic_array = DynamicArray::new(items.into_boxed_slice());
+                dynamic_array.set_represented_type(Some(type_info));
+                Box::new(dynamic_array)
+            }
+            TypeInfo::Map(info) => {
+                let mut dynamic_map = DynamicMap::default();
+                dynamic_map.set_represented_type(Some(type_info));
+                for _ in 0..self.len(nested) {
+                    let key = field(self, info.key_type_id())?;
+                    let value = field(self, info.value_type_id())?;
+                    dynamic_map.insert_boxed(key, value);
+                }
+                Box::new(dynamic_map)
+            }
+            TypeInfo::Enum(info) => {
+                // Past the maximum depth, prefer a unit variant so recursive enums end.
+                let units = info
+                    .iter()
+                    .filter(|variant| matches!(variant, VariantInfo::Unit(_)))
+                    .collect::<Vec<_>>();
+                let variant = match (nested, units.is_empty()) {
+                    (false, false) => units[self.below(units.len())],
+                    _ => info.variant_at(self.below(info.variant_len()))?,
+                };
+                let dynamic_variant = match variant {
+                    VariantInfo::Unit(_) => DynamicVariant::Unit,
+                    VariantInfo::Tuple(variant) => {
+                        let mut dynamic_tuple = DynamicTuple::default();
+                        for field_info in variant.iter() {
+                            dynamic_tuple.insert_boxed(field(self, field_info.type_id())?);
+                        }
+                        DynamicVariant::Tuple(dynamic_tuple)
+                    }
+                    VariantInfo::Struct(variant) => {
+                        let mut dynamic_struct = DynamicStruct::default();
+                        for field_info in variant.iter() {
+                            let value = field(self, field_info.type_id())?;
+                            dynamic_struct.insert_boxed(field_info.name(), value);
+                        }
+                        DynamicVariant::Struct(dynamic_struct)
+                    }
+                };
+                let mut dynamic_enum = DynamicEnum::new(variant.name(), dynamic_variant);
+                dynamic_enum.set_represented_type(Some(type_info));
+                Box::new(dynamic_enum)
+            }
+            TypeInfo::Value(_) => return None,
+        })
+    }
+
+    fn primitive(&mut self, type_id: TypeId) -> Option<Box<dyn Reflect>> {
+        let bits = self.next();
+        Some(match type_id {
+            t if t == TypeId::of::<bool>() => Box::new(bits & 1 == 1),
+            t if t == TypeId::of::<i8>() => Box::new(bits as i8),
+            t if t == TypeId::of::<i16>() => Box::new(bits as i16),
+            t if t == TypeId::of::<i32>() => Box::new(bits as i32),
+            t if t == TypeId::of::<i64>() => Box::new(bits as i64),
+            t if t == TypeId::of::<i128>() => Box::new(self.wide() as i128),
+            t if t == TypeId::of::<isize>() => Box::new(bits as isize),
+            t if t == TypeId::of::<u8>() => Box::new(bits as u8),
+            t if t == TypeId::of::<u16>() => Box::new(bits as u16),
+            t if t == TypeId::of::<u32>() => Box::new(bits as u32),
+            t if t == TypeId::of::<u64>() => Box::new(bits),
+            t if t == TypeId::of::<u128>() => Box::new(self.wide()),
+            t if t == TypeId::of::<usize>() => Box::new(bits as usize),
+            t if t == TypeId::of::<f32>() => Box::new(self.float() as f32),
+            t if t == TypeId::of::<f64>() => Box::new(self.float()),
+            t if t == TypeId::of::<String>() => Box::new(self.string()),
+            t if t == TypeId::of::<char>() => Box::new(self.char()),
+            t if t == TypeId::of::<()>() => Box::new(()),
+            _ => return None,
+        })
+    }
+
+    fn wide(&mut self) -> u128 {
+        (u128::from(self.next()) << 64) | u128::from(self.next())
+    }
+
+    fn len(&mut self, nested: bool) -> usize {
+        if nested {
+            self.below(self.max_len + 1)
+        } else {
+            0
+        }
+    }
+
+    /// A finite float, spread over a wide range of magnitudes and signs.
+    fn float(&mut self) -> f64 {
+        let unit = (self.next() >> 11) as f64 / (1u64 << 53) as f64;
+        let exponent = self.below(41) as i32 - 20;
+        let sign = if self.next() & 1 == 1 { -1.0 } else { 1.0 };
+        sign * unit * 2f64.powi(exponent)
+    }
+
+    fn string(&mut self) -> String {
+        let len = self.below(self.max_len * 2 + 1);
+        (0..len).map(|_| self.char()).collect()
+    }
+
+    /// Mostly ASCII, with characters that need escaping or more than one UTF-16 unit.
+    fn char(&mut self) -> char {
+        const SPECIAL: [char; 6] = ['"', '\\', '\n', 'é', '€', '🦀'];
+        match self.below(8) {
+            0 => SPECIAL[self.below(SPECIAL.len())],
+            _ => char::from(b' ' + self.below(95) as u8),
+        }
+    }
+
+    fn below(&mut self, bound: usize) -> usize {
+        (self.next() % bound.max(1) as u64) as usize
+    }
+
+    fn next(&mut self) -> u64 {
+        // SplitMix64, as `ScriptDeterminism` uses.
+        self.rng = self.rng.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.rng;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
+
+/// A generated value of a reflected type.
+type Generated = Box<dyn Reflect>;
+
+/// A `proptest` strategy for values of a reflected type, built from its type info like
+/// [`ReflectGenerator`] makes values, but shrinking a failing case field by field: numbers toward
+/// zero, strings and collections toward empty, and enums toward their first variant.
+///
+/// Collections have up to 4 elements and values nest up to 4 deep, as by default for
+/// [`ReflectGenerator`]. Panics if the type has a field of a value type other than the primitives
+/// and `String`.
+pub fn reflect_strategy<T: FromReflect + GetTypeRegistration + Debug>() -> impl Strategy<Value = T>
+{
+    let mut registry = TypeRegistry::new();
+    registry.register::<T>();
+    value_strategy(TypeId::of::<T>(), &registry, 4, 4)
+        .unwrap_or_else(|| panic!("cannot generate {}", std::any::type_name::<T>()))
+        .prop_map(|value| {
+            T::from_reflect(value.as_ref())
+                .unwrap_or_else(|| panic!("generated an invalid {}", std::any::type_name::<T>()))
+        })
+}
+
+fn value_strategy(
+    type_id: TypeId,
+    registry: &TypeRegistry,
+    max_len: usize,
+    depth: usize,
+) -> Option<BoxedStrategy<Generated>> {
+    if let Some(primitive) = primitive_strategy(type_id) {
+        return Some(primitive);
+    }
+    let type_info: &'static TypeInfo = registry.get_type_info(type_id)?;
+    let max_len = if depth > 0 { max_len } else { 0 };
+    let field = |type_id| value_strategy(type_id, registry, max_len, depth.saturating_sub(1));
+    Some(match type_info {
+        TypeInfo::Struct(info) => {
+            let names = info.iter().map(|field| field.name()).collect::<Vec<_>>();
+            let fields = info.iter().map(|info| field(info.type_id()));
+            all_of(fields.collect::<Option<_>>()?)
+                .prop_map(move |values| {
+                    let mut dynamic_struct = DynamicStruct::default();
+                    dynamic_struct.set_represented_type(Some(type_info));
+                    for (name, value) in names.iter().zip(values) {
+                        dynamic_struct.insert_boxed(*name, value);
+                    }
+                    Box::new(dynamic_struct) as Generated
+                })
+                .boxed()
+        }
+        TypeInfo::TupleStruct(info) => {
+            let fields = info.iter().map(|info| field(info.type_id()));
+            all_of(fields.collect::<Option<_>>()?)
+                .prop_map(move |values| {
+                    let mut dynamic_tuple_struct = DynamicTupleStruct::default();
+                    dynamic_tuple_struct.set_represented_type(Some(type_info));
+                    for value in values {
+                        dynamic_tuple_struct.insert_boxed(value);
+                    }
+                    Box::new(dynamic_tuple_struct) as Generated
+                })
+                .boxed()
+        }
+        TypeInfo::Tuple(info) => {
+            let fields = info.iter().map(|info| field(info.type_id()));
+            all_of(fields.collect::<Option<_>>()?)
+                .prop_map(move |values| {
+                    let mut dynamic_tuple = DynamicTuple::default();
+                    dynamic_tuple.set_represented_type(Some(type_info));
+                    for value in values {
+                        dynamic_tuple.insert_boxed(value);
+                    }
+                    Box::new(dynamic_tuple) as Generated
+                })
+                .boxed()
+        }
+        TypeInfo::List(info) => proptest::collection::vec(field(info.item_type_id())?, 0..=max_len)
+            .prop_map(move |values| {
+                let mut dynamic_list = DynamicList::default();
+                dynamic_list.set_represented_type(Some(type_info));
+                for value in values {
+                    dynamic_list.push_box(value);
+                }
+                Box::new(dynamic_list) as Generated
+            })
+            .boxed(),
+        TypeInfo::Array(info) => {
+            let items = (0..info.capacity()).map(|_| field(info.item_type_id()));
+            all_of(items.collect::<Option<_>>()?)
+                .prop_map(move |values| {
+                    let mut dynamic_array = DynamicArray::new(values.into_boxed_slice());
+                    dynamic_array.set_represented_type(Some(type_info));
+                    Box::new(dynamic_array) as Generated
+                })
+                .boxed()
+        }
+        TypeInfo::Map(info) => {
+            let entry = (field(info.key_type_id())?, field(info.value_type_id())?);
+            proptest::collection::vec(entry, 0..=max_len)
+                .prop_map(move |entries| {
+                    let mut dynamic_map = DynamicMap::default();
+                    dynamic_map.set_represented_type(Some(type_info));
+                    for (key, value) in entries {
+                        dynamic_map.insert_boxed(key, value);
+                    }
+                    Box::new(dynamic_map) as Generated
+                })
+                .boxed()
+        }
+        TypeInfo::Enum(info) => {
+            // Past the maximum depth, only unit variants are taken if there are any, so recursive
+            // enums end.
+            let has_units = info
+                .iter()
+                .any(|variant| matches!(variant, VariantInfo::Unit(_)));
+            let variants = info
+                .iter()
+                .filter(|variant| {
+                    depth > 0 || !has_units || matches!(variant, VariantInfo::Unit(_))
+                })
+                .map(|variant| variant_strategy(variant, type_info, &field))
+                .collect::<Option<Vec<_>>>()?;
+            if variants.is_empty() {
+                return None;
+            }
+            Union::new(variants).boxed()
+        }
+        TypeInfo::Value(_) => return None,
+    })
+}
+
+fn variant_strategy(
+    variant: &'static VariantInfo,
+    type_info: &'static TypeInfo,
+    field: &dyn Fn(TypeId) -> Option<BoxedStrategy<Generated>>,
+) -> Option<BoxedStrategy<Generated>> {
+    let fields = match variant {
+        VariantInfo::Unit(_) => Vec::new(),
+        VariantInfo::Tuple(variant) => variant
+            .iter()
+            .map(|info| field(info.type_id()))
+            .collect::<Option<_>>()?,
+        VariantInfo::Struct(variant) => variant
+            .iter()
+            .map(|info| field(info.type_id()))
+            .collect::<Option<_>>()?,
+    };
+    let strategy = all_of(fields).prop_map(move |values| {
+        let dynamic_variant = match variant {
+            VariantInfo::Unit(_) => DynamicVariant::Unit,
+            VariantInfo::Tuple(_) => {
+                let mut dynamic_tuple = DynamicTuple::default();
+                for value in values {
+                    dynamic_tuple.insert_boxed(value);
+                }
+                DynamicVariant::Tuple(dynamic_tuple)
+            }
+            VariantInfo::Struct(variant) => {
+                let mut dynamic_struct = DynamicStruct::default();
+                for (info, value) in variant.iter().zip(values) {
+                    dynamic_struct.insert_boxed(info.name(), value);
+                }
+                DynamicVariant::Struct(dynamic_struct)
+            }
+        };
+        let mut dynamic_enum = DynamicEnum::new(variant.name(), dynamic_variant);
+        dynamic_enum.set_represented_type(Some(type_info));
+        Box::new(dynamic_enum) as Generated
+    });
+    Some(strategy.boxed())
+}
+
+/// A strategy for a value from each of the strategies, in order.
+fn all_of(strategies: Vec<BoxedStrategy<Generated>>) -> BoxedStrategy<Vec<Generated>> {
+    strategies
+        .into_iter()
+        .fold(LazyJust::new(Vec::new).boxed(), |values, strategy| {
+            (values, strategy)
+                .prop_map(|(mut values, value)| {
+                    values.push(value);
+                    values
+                })
+                .boxed()
+        })
+}
+
+fn primitive_strategy(type_id: TypeId) -> Option<BoxedStrategy<Generated>> {
+    fn boxed<T: Reflect>(strategy: impl Strategy<Value = T> + 'static) -> BoxedStrategy<Generated> {
+        strategy
+            .prop_map(|value| Box::new(value) as Generated)
+            .boxed()
+    }
+
+    Some(match type_id {
+        t if t == TypeId::of::<bool>() => boxed(any::<bool>()),
+        t if t == TypeId::of::<i8>() => boxed(any::<i8>()),
+        t if t == TypeId::of::<i16>() => boxed(any::<i16>()),
+        t if t == TypeId::of::<i32>() => boxed(any::<i32>()),
+        t if t == TypeId::of::<i64>() => boxed(any::<i64>()),
+        t if t == TypeId::of::<i128>() => boxed(any::<i128>()),
+        t if t == TypeId::of::<isize>() => boxed(any::<isize>()),
+        t if t == TypeId::of::<u8>() => boxed(any::<u8>()),
+        t if t == TypeId::of::<u16>() => boxed(any::<u16>()),
+        t if t == TypeId::of::<u32>() => boxed(any::<u32>()),
+        t if t == TypeId::of::<u64>() => boxed(any::<u64>()),
+        t if t == TypeId::of::<u128>() => boxed(any::<u128>()),
+        t if t == TypeId::of::<usize>() => boxed(any::<usize>()),
+        // Floats are finite, so generated values equal themselves.
+        t if t == TypeId::of::<f32>() => {
+            boxed(num::f32::NORMAL | num::f32::SUBNORMAL | num::f32::ZERO)
+        }
+        t if t == TypeId::of::<f64>() => {
+            boxed(num::f64::NORMAL | num::f64::SUBNORMAL | num::f64::ZERO)
+        }
+        t if t == TypeId::of::<String>() => boxed(any::<String>()),
+        t if t == TypeId::of::<char>() => boxed(any::<char>()),
+        t if t == TypeId::of::<()>() => boxed(Just(())),
+        _ => return None,
+    })
+}
+
+/// A reflected value made through `arbitrary`, for fuzzing conversions with `cargo fuzz`. The
+/// unstructured data seeds a [`ReflectGenerator`], so the same data always gives the same value.
+/// Panics as [`ReflectGenerator::generate`] does for types it can't generate.
+pub struct ArbitraryReflect<T>(pub T);
+
+impl<'a, T: FromReflect + GetTypeRegistration> Arbitrary<'a> for ArbitraryReflect<T> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        let seed = u64::arbitrary(u)?;
+        let max_len = u.int_in_range(0..=4)?;
+        let mut generator = ReflectGenerator::new(seed).with_max_len(max_len);
+        Ok(Self(generator.generate()))
+    }
+}
+
+impl<T: Debug> Debug for ArbitraryReflect<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use bevy_reflect::Reflect;
+
+    use super::*;
+
+    #[derive(Reflect, Debug, PartialEq)]
+    struct Inventory {
+        items: Vec<Item>,
+        gold: u64,
+        slots: [Option<u8>; 2],
+        tags: BTreeMap<String, i32>,
+        position: (i32, bool),
+        owner: Owner,
+    }
+
+    #[derive(Reflect, Debug, PartialEq)]
+    struct Owner(String, u128);
+
+    #[derive(Reflect, Debug, PartialEq)]
+    enum Item {
+        Empty,
+        Potion(f32),
+        Sword { damage: i16, name: String },
+    }
+
+    proptest! {
+        #[test]
+        fn strategy_values_round_trip(inventory in reflect_strategy::<Inventory>()) {
+            assert_roundtrip(inventory);
+        }
+    }
+
+    #[test]
+    fn arbitrary_values_round_trip() {
+        let data = (0..=u8::MAX).cycle().take(4096).collect::<Vec<_>>();
+        let mut u = Unstructured::new(&data);
+        for _ in 0..64 {
+            let ArbitraryReflect(inventory) = ArbitraryReflect::<Inventory>::arbitrary(&mut u)
+                .expect("the data should be long enough");
+            assert_roundtrip(inventory);
+        }
+    }
+
+    #[test]
+    fn generated_values_round_trip() {
+        let mut generator = ReflectGenerator::new(7);
+        for _ in 0..256 {
+            assert_roundtrip(generator.generate::<Inventory>());
+        }
+    }
+}
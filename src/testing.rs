@@ -0,0 +1,227 @@
+use std::fmt;
+use std::path::Path;
+
+use bevy::app::PluginsState;
+use bevy::prelude::*;
+use boa_engine::property::PropertyKey;
+use boa_engine::{JsError, JsResult};
+
+use crate::plugin::{with_runtime, BoaScriptPlugin};
+use crate::runtime::ScriptRuntime;
+use crate::script::{ScriptAsset, ScriptScope};
+
+/// The prefix of the exported functions [`ScriptTestHarness`] runs as tests.
+pub const TEST_PREFIX: &str = "test_";
+
+/// Runs the `test_*` functions a script exports in a headless app, so scripts can be tested from
+/// `cargo test`:
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_boa_reflect::ScriptTestHarness;
+/// # #[derive(Reflect)]
+/// # struct Health { current: f32 }
+/// #[test]
+/// fn player_script() {
+///     let mut harness = ScriptTestHarness::new();
+///     harness.app_mut().register_type::<Health>();
+///     harness.run_file("assets/scripts/player.js").assert_passed();
+/// }
+/// ```
+///
+/// Each test runs with the world bindings available, followed by an app update so the commands
+/// it queued are applied and the events it emitted are delivered before the next test.
+pub struct ScriptTestHarness {
+    app: App,
+}
+
+impl ScriptTestHarness {
+    /// Create a harness with the minimal plugins, the asset plugin and [`BoaScriptPlugin`].
+    pub fn new() -> Self {
+        Self::with_plugin(BoaScriptPlugin::default())
+    }
+
+    /// Create a harness with a configured [`BoaScriptPlugin`].
+    pub fn with_plugin(plugin: BoaScriptPlugin) -> Self {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default(), plugin));
+        Self { app }
+    }
+
+    /// The app scripts run in, to register types, functions and methods or spawn the entities a
+    /// test expects before running it.
+    pub fn app_mut(&mut self) -> &mut App {
+        &mut self.app
+    }
+
+    /// Read a script from disk and run its tests.
+    pub fn run_file(&mut self, path: impl AsRef<Path>) -> ScriptTestReport {
+        let path = path.as_ref();
+        let name = path.to_string_lossy().into_owned();
+        match std::fs::read_to_string(path) {
+            Ok(source) => self.run(&name, &source),
+            Err(err) => ScriptTestReport {
+                path: name,
+                error: Some(err.to_string()),
+                results: Vec::new(),
+            },
+        }
+    }
+
+    /// Evaluate a script and run each of its `test_*` exports in the order they were exported.
+    pub fn run(&mut self, path: &str, source: &str) -> ScriptTestReport {
+        self.ready();
+        let asset = ScriptAsset {
+            path: path.to_owned(),
+            source: source.to_owned(),
+            scope: ScriptScope::Default,
+            source_map: None,
+        };
+        // A reserved handle has no asset events, so the plugin doesn't evaluate it a second time.
+        let id = self
+            .app
+            .world()
+            .resource::<Assets<ScriptAsset>>()
+            .reserve_handle()
+            .id();
+        let mut report = ScriptTestReport {
+            path: path.to_owned(),
+            error: None,
+            results: Vec::new(),
+        };
+        let tests = with_runtime(self.app.world_mut(), |runtime| {
+            runtime
+                .evaluate(id, &asset)
+                .and_then(|_| test_names(runtime, id))
+                .map_err(|err| describe_error(runtime, id, &err))
+        });
+        let tests = match tests {
+            Some(Ok(tests)) => tests,
+            Some(Err(err)) => {
+                report.error = Some(err);
+                return report;
+            }
+            None => {
+                report.error = Some("The script runtime is not available".to_owned());
+                return report;
+            }
+        };
+        for name in tests {
+            let error = with_runtime(self.app.world_mut(), |runtime| {
+                runtime
+                    .call_export(id, &name, &[])
+                    .err()
+                    .map(|err| describe_error(runtime, id, &err))
+            })
+            .flatten();
+            self.app.update();
+            report.results.push(ScriptTestResult { name, error });
+        }
+        with_runtime(self.app.world_mut(), |runtime| runtime.remove(id));
+        report
+    }
+
+    /// Finish building the app and run its startup systems, which register the script bindings.
+    fn ready(&mut self) {
+        if self.app.plugins_state() == PluginsState::Cleaned {
+            return;
+        }
+        while self.app.plugins_state() == PluginsState::Adding {
+            bevy::tasks::tick_global_task_pools_on_main_thread();
+        }
+        self.app.finish();
+        self.app.cleanup();
+        self.app.update();
+    }
+}
+
+impl Default for ScriptTestHarness {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn test_names(runtime: &mut ScriptRuntime, id: AssetId<ScriptAsset>) -> JsResult<Vec<String>> {
+    let Some(exports) = runtime.exports(id).cloned() else {
+        return Ok(Vec::new());
+    };
+    let mut names = Vec::new();
+    for key in exports.own_property_keys(runtime.context())? {
+        let PropertyKey::String(name) = &key else {
+            continue;
+        };
+        let name = name.to_std_string_escaped();
+        if !name.starts_with(TEST_PREFIX) {
+            continue;
+        }
+        if exports.get(key, runtime.context())?.is_callable() {
+            names.push(name);
+        }
+    }
+    Ok(names)
+}
+
+fn describe_error(runtime: &ScriptRuntime, id: AssetId<ScriptAsset>, err: &JsError) -> String {
+    match runtime.locate_error(id, err) {
+        Some(location) => format!("{location}: {err}"),
+        None => err.to_string(),
+    }
+}
+
+/// The outcome of running a script's tests.
+#[derive(Debug, Clone)]
+pub struct ScriptTestReport {
+    pub path: String,
+    /// Why the script couldn't be loaded or evaluated, in which case no tests ran.
+    pub error: Option<String>,
+    pub results: Vec<ScriptTestResult>,
+}
+
+/// The outcome of a single `test_*` function.
+#[derive(Debug, Clone)]
+pub struct ScriptTestResult {
+    pub name: String,
+    /// What the test threw, if it failed.
+    pub error: Option<String>,
+}
+
+impl ScriptTestReport {
+    /// Whether the script evaluated and every test passed.
+    pub fn passed(&self) -> bool {
+        self.error.is_none() && self.failures().next().is_none()
+    }
+
+    /// The tests that threw.
+    pub fn failures(&self) -> impl Iterator<Item = &ScriptTestResult> {
+        self.results.iter().filter(|result| result.error.is_some())
+    }
+
+    /// Panic with the failures if the script didn't evaluate or any test failed, failing the Rust
+    /// test running it.
+    #[track_caller]
+    pub fn assert_passed(&self) {
+        if !self.passed() {
+            panic!("{self}");
+        }
+    }
+}
+
+impl fmt::Display for ScriptTestReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(err) = &self.error {
+            return write!(f, "{}: {err}", self.path);
+        }
+        let failed = self.failures().count();
+        write!(
+            f,
+            "{}: {} passed, {failed} failed",
+            self.path,
+            self.results.len() - failed
+        )?;
+        for failure in self.failures() {
+            let err = failure.error.as_deref().unwrap_or_default();
+            write!(f, "\n  {}: {err}", failure.name)?;
+        }
+        Ok(())
+    }
+}
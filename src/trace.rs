@@ -0,0 +1,16 @@
+use bevy_reflect::{Reflect, ReflectRef};
+
+/// How many fields, items or entries a converted value has at its top level, for conversion
+/// spans. Values count as one.
+pub(crate) fn element_count(value: &dyn Reflect) -> usize {
+    match value.reflect_ref() {
+        ReflectRef::Struct(value) => value.field_len(),
+        ReflectRef::TupleStruct(value) => value.field_len(),
+        ReflectRef::Tuple(value) => value.field_len(),
+        ReflectRef::List(value) => value.len(),
+        ReflectRef::Array(value) => value.len(),
+        ReflectRef::Map(value) => value.len(),
+        ReflectRef::Enum(value) => value.field_len(),
+        ReflectRef::Value(_) => 1,
+    }
+}
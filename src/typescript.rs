@@ -0,0 +1,341 @@
+use std::any::TypeId;
+use std::fmt::Write;
+use std::path::Path;
+
+use bevy::ecs::reflect::{ReflectComponent, ReflectResource};
+use bevy::reflect::{TypeInfo, TypeRegistration, TypeRegistry, VariantInfo};
+use bevy::utils::HashMap;
+
+use crate::functions::ScriptFunctions;
+use crate::methods::ScriptMethods;
+
+/// Declarations for the globals every script sees, independent of the registered types.
+const BINDING_DECLARATIONS: &str = r#"/** An entity, as the `BigInt` of its bits. */
+type Entity = bigint;
+
+/** The engine bindings. */
+declare const bevy: Readonly<Record<string, unknown>>;
+
+/** The hooks this script exports, e.g. `exports.update = (entity) => {}`. */
+declare const exports: {
+    update?: (entity: Entity) => void;
+    run?: (...args: unknown[]) => unknown;
+    migrate?: (oldState: Record<string, unknown>) => Record<string, unknown> | void;
+    [hook: string]: unknown;
+};
+
+/** The parameters of the entity the script is running for. */
+declare const params: unknown;
+
+/** State the script keeps for the entity it is running for, included in save games. */
+declare const state: Record<string, unknown>;
+
+declare const reflect: {
+    getPath(target: unknown, path: string): unknown;
+    setPath(target: unknown, path: string, value: unknown): void;
+    describe(typePath: string): TypeDescription | null;
+    default(typePath: string): unknown;
+    types(options?: { filter?: "Component" | "Resource" | "Default" | "Serialize" }): {
+        typePath: string;
+        shortPath: string;
+        Component: boolean;
+        Resource: boolean;
+        Default: boolean;
+        Serialize: boolean;
+    }[];
+};
+
+interface FieldDescription {
+    name?: string;
+    index?: number;
+    type: string;
+    docs: string | null;
+}
+
+interface TypeDescription {
+    typePath: string;
+    shortPath: string;
+    kind: "struct" | "tupleStruct" | "tuple" | "list" | "array" | "map" | "enum" | "value";
+    docs: string | null;
+    fields?: FieldDescription[];
+    variants?: {
+        name: string;
+        kind: "struct" | "tuple" | "unit";
+        docs: string | null;
+        fields?: FieldDescription[];
+    }[];
+    itemType?: string;
+    length?: number;
+    keyType?: string;
+    valueType?: string;
+}
+
+/** The object standing for an entity, with a property for each component asked for. */
+interface EntityProxy {
+    readonly entity: Entity;
+    clone(): EntityProxy | null;
+    despawnRecursive(): void;
+    [component: string]: unknown;
+}
+
+declare const world: {
+    getEntityByName(name: string): Entity | null;
+    getEntitiesByName(name: string): Entity[];
+    entity(entity: Entity): EntityProxy | null;
+    cloneEntity(entity: Entity): Entity;
+    isChanged(entity: Entity, typePath: string): boolean;
+    isAdded(entity: Entity, typePath: string): boolean;
+    removed(typePath: string): Entity[];
+};
+
+declare const commands: {
+    insert(entity: Entity, typePath: string, value: unknown): void;
+    despawnRecursive(entity: Entity): void;
+};
+
+declare const bus: {
+    emit(event: string, payload?: unknown): void;
+    on(event: string, handler: (payload: unknown) => void): void;
+    off(event: string, handler: (payload: unknown) => void): void;
+};
+"#;
+
+/// Generate TypeScript declarations for the script globals, the registered types, the classes
+/// generated for components, resources and types with [`ScriptMethods`], and the
+/// [`ScriptFunctions`], so script authors get completion and type checking in their editors.
+/// Types are declared in the shape they convert to JS in.
+pub fn type_declarations(
+    registry: &TypeRegistry,
+    methods: &ScriptMethods,
+    functions: &ScriptFunctions,
+) -> String {
+    let names = type_names(registry);
+    let mut registrations = registry.iter().collect::<Vec<_>>();
+    registrations.sort_by_key(|registration| registration.type_info().type_path());
+
+    let mut out = String::from("// Generated by bevy_boa_reflect. Do not edit.\n\n");
+    out.push_str(BINDING_DECLARATIONS);
+
+    for registration in &registrations {
+        let Some(name) = names.get(&registration.type_id()) else {
+            continue;
+        };
+        let type_info = registration.type_info();
+        out.push('\n');
+        writeln!(out, "/** `{}` */", type_info.type_path()).unwrap();
+        match type_info {
+            TypeInfo::Struct(info) => {
+                writeln!(out, "interface {name} {{").unwrap();
+                for field in info.iter() {
+                    let ty = ts_type(field.type_id(), registry, &names);
+                    writeln!(out, "    {}: {ty};", field.name()).unwrap();
+                }
+                out.push_str("}\n");
+                // Classes are named after the short path, and skipped where that isn't unique.
+                if *name == type_info.type_path_table().short_path()
+                    && has_class(registration, methods)
+                {
+                    write_class(&mut out, name, registration, methods);
+                }
+            }
+            TypeInfo::TupleStruct(info) => {
+                let fields = info
+                    .iter()
+                    .map(|field| ts_type(field.type_id(), registry, &names))
+                    .collect::<Vec<_>>();
+                writeln!(out, "type {name} = [{}];", fields.join(", ")).unwrap();
+            }
+            TypeInfo::Enum(info) => {
+                let variants = info
+                    .iter()
+                    .map(|variant| ts_variant(variant, registry, &names))
+                    .collect::<Vec<_>>();
+                writeln!(out, "type {name} =\n    | {};", variants.join("\n    | ")).unwrap();
+            }
+            _ => {}
+        }
+    }
+
+    let mut functions = functions.iter().collect::<Vec<_>>();
+    functions.sort_by_key(|function| function.name().to_owned());
+    for function in functions {
+        let args = function
+            .args()
+            .iter()
+            .enumerate()
+            .map(|(idx, type_id)| format!("arg{idx}: {}", ts_type(*type_id, registry, &names)))
+            .collect::<Vec<_>>();
+        writeln!(
+            out,
+            "\ndeclare function {}({}): unknown;",
+            function.name(),
+            args.join(", ")
+        )
+        .unwrap();
+    }
+    out
+}
+
+/// Write the output of [`type_declarations`] to a file, e.g. `scripts/bevy.d.ts`.
+pub fn write_type_declarations(
+    path: impl AsRef<Path>,
+    registry: &TypeRegistry,
+    methods: &ScriptMethods,
+    functions: &ScriptFunctions,
+) -> std::io::Result<()> {
+    std::fs::write(path, type_declarations(registry, methods, functions))
+}
+
+/// Pick a TypeScript name for every struct, tuple struct and enum, using the short type path where
+/// it's unique and the full type path otherwise.
+fn type_names(registry: &TypeRegistry) -> HashMap<TypeId, String> {
+    let declared = registry
+        .iter()
+        .filter(|registration| {
+            matches!(
+                registration.type_info(),
+                TypeInfo::Struct(_) | TypeInfo::TupleStruct(_) | TypeInfo::Enum(_)
+            )
+        })
+        .collect::<Vec<_>>();
+    let mut counts = HashMap::<String, usize>::default();
+    for registration in &declared {
+        let short_path = registration.type_info().type_path_table().short_path();
+        *counts.entry(identifier(short_path)).or_default() += 1;
+    }
+    declared
+        .into_iter()
+        .map(|registration| {
+            let table = registration.type_info().type_path_table();
+            let name = identifier(table.short_path());
+            let name = if counts[&name] > 1 {
+                identifier(table.path())
+            } else {
+                name
+            };
+            (registration.type_id(), name)
+        })
+        .collect()
+}
+
+/// The TypeScript type a value of the given type converts to.
+fn ts_type(type_id: TypeId, registry: &TypeRegistry, names: &HashMap<TypeId, String>) -> String {
+    if let Some(name) = names.get(&type_id) {
+        return name.clone();
+    }
+    let Some(registration) = registry.get(type_id) else {
+        return "unknown".to_owned();
+    };
+    match registration.type_info() {
+        TypeInfo::List(info) => format!("{}[]", ts_type(info.item_type_id(), registry, names)),
+        TypeInfo::Array(info) => format!("{}[]", ts_type(info.item_type_id(), registry, names)),
+        TypeInfo::Map(info) => format!(
+            "Map<{}, {}>",
+            ts_type(info.key_type_id(), registry, names),
+            ts_type(info.value_type_id(), registry, names)
+        ),
+        TypeInfo::Tuple(info) => {
+            let fields = info
+                .iter()
+                .map(|field| ts_type(field.type_id(), registry, names))
+                .collect::<Vec<_>>();
+            format!("[{}]", fields.join(", "))
+        }
+        TypeInfo::Value(_) => ts_primitive(type_id).to_owned(),
+        TypeInfo::Struct(_) | TypeInfo::TupleStruct(_) | TypeInfo::Enum(_) => "unknown".to_owned(),
+    }
+}
+
+/// The TypeScript type of a primitive, matching how primitives convert.
+fn ts_primitive(type_id: TypeId) -> &'static str {
+    match type_id {
+        t if t == TypeId::of::<bool>() => "boolean",
+        t if t == TypeId::of::<i8>()
+            || t == TypeId::of::<i16>()
+            || t == TypeId::of::<i32>()
+            || t == TypeId::of::<u8>()
+            || t == TypeId::of::<u16>()
+            || t == TypeId::of::<f32>()
+            || t == TypeId::of::<f64>() =>
+        {
+            "number"
+        }
+        t if t == TypeId::of::<i64>()
+            || t == TypeId::of::<isize>()
+            || t == TypeId::of::<u32>()
+            || t == TypeId::of::<u64>()
+            || t == TypeId::of::<usize>()
+            || t == TypeId::of::<i128>()
+            || t == TypeId::of::<u128>() =>
+        {
+            "bigint"
+        }
+        t if t == TypeId::of::<String>() || t == TypeId::of::<&'static str>() => "string",
+        _ => "unknown",
+    }
+}
+
+fn ts_variant(
+    variant: &VariantInfo,
+    registry: &TypeRegistry,
+    names: &HashMap<TypeId, String>,
+) -> String {
+    let mut fields = vec![format!("__variant: \"{}\"", variant.name())];
+    match variant {
+        VariantInfo::Struct(info) => {
+            for field in info.iter() {
+                let ty = ts_type(field.type_id(), registry, names);
+                fields.push(format!("{}: {ty}", field.name()));
+            }
+        }
+        VariantInfo::Tuple(info) => {
+            for field in info.iter() {
+                let ty = ts_type(field.type_id(), registry, names);
+                fields.push(format!("{}: {ty}", field.index()));
+            }
+        }
+        VariantInfo::Unit(_) => {}
+    }
+    format!("{{ {} }}", fields.join("; "))
+}
+
+/// Whether [`register_type_classes`](crate::register_type_classes) creates a class for the type.
+fn has_class(registration: &TypeRegistration, methods: &ScriptMethods) -> bool {
+    registration.data::<ReflectComponent>().is_some()
+        || registration.data::<ReflectResource>().is_some()
+        || !methods.get(registration.type_id()).is_empty()
+}
+
+fn write_class(
+    out: &mut String,
+    name: &str,
+    registration: &TypeRegistration,
+    methods: &ScriptMethods,
+) {
+    writeln!(out, "declare class {name} {{").unwrap();
+    writeln!(out, "    constructor(fields?: Partial<{name}>);").unwrap();
+    writeln!(
+        out,
+        "    static readonly typePath: \"{}\";",
+        registration.type_info().type_path()
+    )
+    .unwrap();
+    for (method, _) in methods.get(registration.type_id()) {
+        writeln!(out, "    {method}(...args: unknown[]): unknown;").unwrap();
+    }
+    out.push_str("}\n");
+}
+
+/// Turn a type path into a TypeScript identifier, e.g. `Option<f32>` into `Option_f32`.
+fn identifier(path: &str) -> String {
+    let name = path
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect::<String>();
+    let name = name.trim_matches('_').to_owned();
+    if name.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("_{name}")
+    } else {
+        name
+    }
+}
@@ -0,0 +1,33 @@
+use bevy_reflect::Reflect;
+use boa_engine::JsValue;
+
+/// The longest a logged value gets before it is cut short.
+const MAX_SUMMARY_LEN: usize = 80;
+
+/// A short description of a converted value for conversion logs.
+pub(crate) fn reflect_summary(value: &dyn Reflect) -> String {
+    truncate(format!("{value:?}"))
+}
+
+/// A short description of a JS value for conversion logs. Objects are described by kind alone,
+/// as their contents are logged as they convert.
+pub(crate) fn js_summary(value: &JsValue) -> String {
+    match value {
+        JsValue::Object(obj) if obj.is_array() => "array".to_owned(),
+        JsValue::Object(obj) if obj.is_callable() => "function".to_owned(),
+        JsValue::Object(_) => "object".to_owned(),
+        value => truncate(value.display().to_string()),
+    }
+}
+
+fn truncate(mut summary: String) -> String {
+    if summary.len() > MAX_SUMMARY_LEN {
+        let mut end = MAX_SUMMARY_LEN;
+        while !summary.is_char_boundary(end) {
+            end -= 1;
+        }
+        summary.truncate(end);
+        summary.push_str("...");
+    }
+    summary
+}
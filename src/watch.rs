@@ -0,0 +1,179 @@
+use bevy::ecs::reflect::{AppTypeRegistry, ReflectComponent};
+use bevy::prelude::*;
+use bevy::reflect::{GetPath, TypeInfo};
+use boa_engine::{JsNativeError, JsResult, Source};
+
+use crate::bindings::reflect::{find_registration, path_error};
+use crate::from::js_value_to_typed_reflect;
+use crate::inspect::eval_on_entity_with;
+use crate::plugin::with_runtime;
+
+/// Identifies an expression added to [`ScriptWatches`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct WatchId(u64);
+
+/// Expressions evaluated against entities every frame, like a debugger's watch window. Each
+/// expression sees the entity's components as locals, as with
+/// [`eval_on_entity`](crate::eval_on_entity), and its results are sent as [`WatchResult`] events.
+#[derive(Resource, Default)]
+pub struct ScriptWatches {
+    watches: Vec<Watch>,
+    next_id: u64,
+}
+
+struct Watch {
+    id: WatchId,
+    expression: String,
+    entities: Vec<Entity>,
+}
+
+impl ScriptWatches {
+    /// Watch an expression on the given entities.
+    pub fn add(&mut self, expression: impl Into<String>, entities: Vec<Entity>) -> WatchId {
+        let id = WatchId(self.next_id);
+        self.next_id += 1;
+        self.watches.push(Watch {
+            id,
+            expression: expression.into(),
+            entities,
+        });
+        id
+    }
+
+    /// Change the entities a watch is evaluated against.
+    pub fn set_entities(&mut self, id: WatchId, entities: Vec<Entity>) {
+        if let Some(watch) = self.watches.iter_mut().find(|watch| watch.id == id) {
+            watch.entities = entities;
+        }
+    }
+
+    /// Stop watching an expression.
+    pub fn remove(&mut self, id: WatchId) {
+        self.watches.retain(|watch| watch.id != id);
+    }
+
+    /// The expression a watch evaluates.
+    pub fn expression(&self, id: WatchId) -> Option<&str> {
+        self.watches
+            .iter()
+            .find(|watch| watch.id == id)
+            .map(|watch| watch.expression.as_str())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.watches.is_empty()
+    }
+}
+
+/// The value of a watched expression for an entity this frame.
+#[derive(Event, Debug, Clone)]
+pub struct WatchResult {
+    pub watch: WatchId,
+    pub entity: Entity,
+    /// The value as scripts would display it, or the error the expression threw.
+    pub value: Result<String, String>,
+}
+
+pub(crate) fn evaluate_watches(world: &mut World) {
+    let watches = world.resource::<ScriptWatches>();
+    if watches.is_empty() {
+        return;
+    }
+    let watches = watches
+        .watches
+        .iter()
+        .flat_map(|watch| {
+            watch
+                .entities
+                .iter()
+                .map(|entity| (watch.id, *entity, watch.expression.clone()))
+        })
+        .collect::<Vec<_>>();
+    for (watch, entity, expression) in watches {
+        let value = eval_on_entity_with(world, entity, &expression, |value, _, _| {
+            Ok(value.display().to_string())
+        })
+        .map_err(|err| err.to_string());
+        world.send_event(WatchResult {
+            watch,
+            entity,
+            value,
+        });
+    }
+}
+
+/// Evaluate `source` and write the result into a field of an entity's component, converting it to
+/// the field's type, e.g. `tweak_value(world, player, "Health", "current", "100")`. An empty path
+/// replaces the whole component.
+pub fn tweak_value(
+    world: &mut World,
+    entity: Entity,
+    type_path: &str,
+    path: &str,
+    source: &str,
+) -> JsResult<()> {
+    let registry = world.resource::<AppTypeRegistry>().clone();
+    let (component, field_type_id) = {
+        let registry = registry.read();
+        let registration = find_registration(type_path, &registry).ok_or_else(|| {
+            JsNativeError::typ().with_message(format!("Unknown type {type_path}"))
+        })?;
+        let component = registration
+            .data::<ReflectComponent>()
+            .ok_or_else(|| {
+                JsNativeError::typ().with_message(format!("{type_path} is not a component"))
+            })?
+            .clone();
+        let entity_ref = world.get_entity(entity).ok_or_else(|| {
+            JsNativeError::typ().with_message(format!("Entity {entity} does not exist"))
+        })?;
+        let current = component.reflect(entity_ref).ok_or_else(|| {
+            JsNativeError::typ().with_message(format!("Entity {entity} has no {type_path}"))
+        })?;
+        let field = if path.is_empty() {
+            current
+        } else {
+            current.reflect_path(path).map_err(path_error)?
+        };
+        let field_type_id = field
+            .get_represented_type_info()
+            .map(TypeInfo::type_id)
+            .ok_or_else(|| JsNativeError::typ().with_message("Field type is unknown"))?;
+        (component, field_type_id)
+    };
+
+    let value = with_runtime(world, |runtime| -> JsResult<_> {
+        let ctx = runtime.context();
+        let value = ctx.eval(Source::from_bytes(source))?;
+        Ok(js_value_to_typed_reflect(
+            value,
+            field_type_id,
+            &registry.read(),
+            ctx,
+        )?)
+    })
+    .unwrap_or_else(|| {
+        Err(JsNativeError::error()
+            .with_message("The script runtime is not available")
+            .into())
+    })?;
+
+    // Evaluating the source may have despawned the entity or removed the component.
+    let mut entity_mut = world.get_entity_mut(entity).ok_or_else(|| {
+        JsNativeError::typ().with_message(format!("Entity {entity} does not exist"))
+    })?;
+    let Some(mut current) = component.reflect_mut(&mut entity_mut) else {
+        return Err(JsNativeError::typ()
+            .with_message(format!("Entity {entity} has no {type_path}"))
+            .into());
+    };
+    if path.is_empty() {
+        current.apply(value.as_ref());
+    } else {
+        current
+            .reflect_path_mut(path)
+            .map_err(path_error)?
+            .apply(value.as_ref());
+    }
+    Ok(())
+}
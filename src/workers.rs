@@ -0,0 +1,423 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, Receiver, Sender, TryRecvError};
+use std::sync::Arc;
+
+use bevy::prelude::*;
+use boa_engine::object::{FunctionObjectBuilder, ObjectInitializer};
+use boa_engine::property::Attribute;
+use boa_engine::realm::Realm;
+use boa_engine::{
+    js_str, js_string, Context, Finalize, JsData, JsNativeError, JsObject, JsResult, JsString,
+    JsValue, NativeFunction, Source, Trace,
+};
+use boa_runtime::Console;
+
+use crate::bindings::{arg, in_realm, native_function};
+use crate::encoding::register_text_encoding;
+use crate::msgpack::{js_value_to_msgpack, msgpack_to_js_value};
+use crate::plugin::with_runtime;
+use crate::runtime::ScriptRuntime;
+use crate::structured_clone::register_structured_clone;
+
+/// The global the `Worker` class is installed under.
+pub const WORKER_BINDING: &str = "Worker";
+
+/// Gives scripts a restricted `Worker`, running a script on a thread of its own in a context of
+/// its own. `new Worker(source)` starts a worker from a script's source. Workers and the scripts
+/// that start them talk with `postMessage` and `onmessage`, and messages are copied between
+/// contexts as MessagePack, so they keep `Map`s, `BigInt`s, typed arrays and `undefined`.
+/// `worker.terminate()` stops a worker once it's done with the message it's handling, dropping
+/// any still waiting, and a worker can stop itself with `close()`.
+///
+/// Workers see `console`, `TextEncoder`, `TextDecoder` and `structuredClone`, but none of the
+/// world bindings. Messages from workers are delivered each frame.
+/// Add it next to [`BoaScriptPlugin`](crate::BoaScriptPlugin).
+pub struct ScriptWorkerPlugin {
+    /// The most times a single loop in a worker may go round before it fails with an error
+    /// scripts can't catch. Boa can't interrupt a script, so this is what keeps a worker stuck in
+    /// a loop from running on after it's terminated.
+    pub loop_iteration_limit: u64,
+}
+
+impl Default for ScriptWorkerPlugin {
+    fn default() -> Self {
+        Self {
+            loop_iteration_limit: 100_000_000,
+        }
+    }
+}
+
+impl Plugin for ScriptWorkerPlugin {
+    fn build(&self, app: &mut App) {
+        if cfg!(target_arch = "wasm32") {
+            warn!("Script workers need threads, which the web doesn't give the task pools");
+            return;
+        }
+        let loop_iteration_limit = self.loop_iteration_limit;
+        app.add_systems(Startup, move |world: &mut World| {
+            register_workers(world, loop_iteration_limit)
+        })
+        .add_systems(Update, deliver_worker_messages);
+    }
+}
+
+/// The running workers, kept outside the runtime so their messages can be delivered each frame.
+struct ScriptWorkers(JsObject);
+
+#[derive(Trace, Finalize, JsData)]
+struct WorkerRegistry {
+    #[unsafe_ignore_trace]
+    loop_iteration_limit: u64,
+    workers: Vec<JsObject>,
+}
+
+/// The main context's side of a worker.
+#[derive(Trace, Finalize, JsData)]
+struct WorkerHandle {
+    /// Messages to the worker, until it's terminated.
+    #[unsafe_ignore_trace]
+    inbox: Option<Sender<Vec<u8>>>,
+    #[unsafe_ignore_trace]
+    outbox: Receiver<WorkerEvent>,
+    /// Set when the worker is terminated or closes itself, so it stops before the messages it
+    /// hasn't handled yet.
+    #[unsafe_ignore_trace]
+    closed: Arc<AtomicBool>,
+    /// The realm of the script that started the worker, which messages are made in.
+    realm: Realm,
+}
+
+/// What a worker sends back.
+enum WorkerEvent {
+    Message(Vec<u8>),
+    Error(String),
+    /// The worker stopped. Its context may outlive it until the thread collects garbage, along
+    /// with the senders its functions hold, so this is sent rather than waiting on disconnection.
+    Stopped,
+}
+
+fn register_workers(world: &mut World, loop_iteration_limit: u64) {
+    let Some(mut runtime) = world.get_non_send_resource_mut::<ScriptRuntime>() else {
+        warn!("Script workers need the BoaScriptPlugin");
+        return;
+    };
+    let registry = JsObject::from_proto_and_data(
+        None,
+        WorkerRegistry {
+            loop_iteration_limit,
+            workers: Vec::new(),
+        },
+    );
+    let worker_registry = registry.clone();
+    let result = runtime.register_global(WORKER_BINDING, move |ctx| {
+        let captures = (worker_registry.clone(), worker_prototype(ctx));
+        let constructor =
+            NativeFunction::from_copy_closure_with_captures(construct_worker, captures);
+        Ok(FunctionObjectBuilder::new(ctx.realm(), constructor)
+            .name(js_str!("Worker"))
+            .length(1)
+            .constructor(true)
+            .build())
+    });
+    if let Err(err) = result {
+        error!("Error registering script workers: {err}");
+        return;
+    }
+    world.insert_non_send_resource(ScriptWorkers(registry));
+}
+
+/// `new Worker(source)`: start a worker on a thread of its own. Workers wait on messages for as
+/// long as they run, so they'd hold a task pool's thread the whole time.
+fn construct_worker(
+    this: &JsValue,
+    args: &[JsValue],
+    (registry, prototype): &(JsObject, JsObject),
+    ctx: &mut Context,
+) -> JsResult<JsValue> {
+    if !this.as_object().is_some_and(JsObject::is_constructor) {
+        return Err(JsNativeError::typ()
+            .with_message("Worker must be called with new")
+            .into());
+    }
+    let source = arg(args, 0).to_string(ctx)?.to_std_string_escaped();
+    let mut registry = registry
+        .downcast_mut::<WorkerRegistry>()
+        .ok_or_else(|| JsNativeError::typ().with_message("Worker has no registry"))?;
+    let (inbox, worker_inbox) = channel();
+    let (worker_outbox, outbox) = channel();
+    let closed = Arc::new(AtomicBool::new(false));
+    let worker = Worker {
+        inbox: worker_inbox,
+        outbox: worker_outbox,
+        closed: closed.clone(),
+        loop_iteration_limit: registry.loop_iteration_limit,
+    };
+    std::thread::Builder::new()
+        .name("script worker".to_owned())
+        .spawn(move || worker.run(&source))
+        .map_err(|err| {
+            JsNativeError::error().with_message(format!("Failed to start a worker: {err}"))
+        })?;
+
+    let worker = JsObject::from_proto_and_data(
+        prototype.clone(),
+        WorkerHandle {
+            inbox: Some(inbox),
+            outbox,
+            closed,
+            realm: ctx.realm().clone(),
+        },
+    );
+    worker.set(js_str!("onmessage"), JsValue::null(), false, ctx)?;
+    worker.set(js_str!("onerror"), JsValue::null(), false, ctx)?;
+    registry.workers.push(worker.clone());
+    Ok(worker.into())
+}
+
+/// `postMessage` and `terminate`, for workers seen from the main context.
+fn worker_prototype(ctx: &mut Context) -> JsObject {
+    let post_message = NativeFunction::from_fn_ptr(|this, args, ctx| {
+        let message = js_value_to_msgpack(&arg(args, 0), ctx)?;
+        with_handle(this, |handle| {
+            // Messages to a worker that stopped are dropped, as they are on the web.
+            if let Some(inbox) = &handle.inbox {
+                let _ = inbox.send(message);
+            }
+        })?;
+        Ok(JsValue::undefined())
+    });
+    let terminate = NativeFunction::from_fn_ptr(|this, _, _| {
+        with_handle(this, |handle| {
+            handle.closed.store(true, Ordering::Relaxed);
+            handle.inbox = None;
+        })?;
+        Ok(JsValue::undefined())
+    });
+    ObjectInitializer::new(ctx)
+        .function(post_message, js_string!("postMessage"), 1)
+        .function(terminate, js_string!("terminate"), 0)
+        .build()
+}
+
+fn with_handle<R>(this: &JsValue, f: impl FnOnce(&mut WorkerHandle) -> R) -> JsResult<R> {
+    let mut handle = this
+        .as_object()
+        .and_then(|obj| obj.downcast_mut::<WorkerHandle>())
+        .ok_or_else(|| JsNativeError::typ().with_message("Receiver is not a Worker"))?;
+    Ok(f(&mut handle))
+}
+
+/// Deliver the messages and errors workers sent since the last frame to their `onmessage` and
+/// `onerror` handlers, and forget the workers that stopped.
+fn deliver_worker_messages(world: &mut World) {
+    let Some(registry) = world
+        .get_non_send_resource::<ScriptWorkers>()
+        .map(|workers| workers.0.clone())
+    else {
+        return;
+    };
+    with_runtime(world, |runtime| {
+        let ctx = runtime.context();
+        let workers = match registry.downcast_ref::<WorkerRegistry>() {
+            Some(registry) => registry.workers.clone(),
+            None => return,
+        };
+        let mut stopped = Vec::new();
+        for worker in workers {
+            let (events, running, realm) = match worker.downcast_ref::<WorkerHandle>() {
+                Some(handle) => {
+                    let (events, running) = received(&handle);
+                    (events, running, handle.realm.clone())
+                }
+                None => continue,
+            };
+            for event in events {
+                if let Err(err) = in_realm(&realm, ctx, |ctx| deliver(&worker, event, ctx)) {
+                    error!("Error handling a worker message: {err}");
+                }
+            }
+            if !running {
+                stopped.push(worker);
+            }
+        }
+        if let Some(mut registry) = registry.downcast_mut::<WorkerRegistry>() {
+            registry.workers.retain(|worker| {
+                !stopped
+                    .iter()
+                    .any(|stopped| JsObject::equals(stopped, worker))
+            });
+        }
+        ctx.run_jobs();
+    });
+}
+
+/// The events a worker sent, and whether it's still running.
+fn received(handle: &WorkerHandle) -> (Vec<WorkerEvent>, bool) {
+    let mut events = Vec::new();
+    loop {
+        match handle.outbox.try_recv() {
+            Ok(WorkerEvent::Stopped) => return (events, false),
+            Ok(event) => events.push(event),
+            Err(TryRecvError::Empty) => return (events, handle.inbox.is_some()),
+            Err(TryRecvError::Disconnected) => return (events, false),
+        }
+    }
+}
+
+fn deliver(worker: &JsObject, event: WorkerEvent, ctx: &mut Context) -> JsResult<()> {
+    let (handler, key, value) = match event {
+        WorkerEvent::Message(bytes) => (
+            worker.get(js_str!("onmessage"), ctx)?,
+            js_str!("data"),
+            msgpack_to_js_value(&bytes, ctx)?,
+        ),
+        WorkerEvent::Error(message) => {
+            let handler = worker.get(js_str!("onerror"), ctx)?;
+            if !handler.is_callable() {
+                error!("Uncaught error in a script worker: {message}");
+                return Ok(());
+            }
+            (handler, js_str!("message"), JsString::from(message).into())
+        }
+        WorkerEvent::Stopped => return Ok(()),
+    };
+    let Some(handler) = handler.as_callable() else {
+        return Ok(());
+    };
+    let event = ObjectInitializer::new(ctx)
+        .property(key, value, Attribute::all())
+        .build();
+    handler.call(&worker.clone().into(), &[event.into()], ctx)?;
+    Ok(())
+}
+
+/// The worker's side of a worker, moved onto its thread.
+struct Worker {
+    inbox: Receiver<Vec<u8>>,
+    outbox: Sender<WorkerEvent>,
+    closed: Arc<AtomicBool>,
+    loop_iteration_limit: u64,
+}
+
+impl Worker {
+    /// Run the worker's script in a context of its own, then hand it messages until it's
+    /// terminated or closes itself.
+    fn run(self, source: &str) {
+        /// Tells the main context the worker stopped, however it stops.
+        struct Stopped(Sender<WorkerEvent>);
+
+        impl Drop for Stopped {
+            fn drop(&mut self) {
+                let _ = self.0.send(WorkerEvent::Stopped);
+            }
+        }
+
+        let Self {
+            inbox,
+            outbox,
+            closed,
+            loop_iteration_limit,
+        } = self;
+        let _stopped = Stopped(outbox.clone());
+        let mut ctx = Context::default();
+        ctx.runtime_limits_mut()
+            .set_loop_iteration_limit(loop_iteration_limit);
+        let report = |err: boa_engine::JsError| {
+            let _ = outbox.send(WorkerEvent::Error(err.to_string()));
+        };
+        let started = install_worker_globals(&outbox, &closed, &mut ctx)
+            .and_then(|()| ctx.eval(Source::from_bytes(source)));
+        if let Err(err) = started {
+            return report(err);
+        }
+        ctx.run_jobs();
+        while !closed.load(Ordering::Relaxed) {
+            let Ok(message) = inbox.recv() else {
+                break;
+            };
+            // Messages that were waiting when the worker was terminated are dropped.
+            if closed.load(Ordering::Relaxed) {
+                break;
+            }
+            let result = msgpack_to_js_value(&message, &mut ctx).and_then(|data| {
+                let global = ctx.global_object();
+                let Some(handler) = global
+                    .get(js_str!("onmessage"), &mut ctx)?
+                    .as_callable()
+                    .cloned()
+                else {
+                    return Ok(());
+                };
+                let event = ObjectInitializer::new(&mut ctx)
+                    .property(js_str!("data"), data, Attribute::all())
+                    .build();
+                handler.call(&global.into(), &[event.into()], &mut ctx)?;
+                Ok(())
+            });
+            if let Err(err) = result {
+                report(err);
+            }
+            ctx.run_jobs();
+        }
+    }
+}
+
+/// `self`, `postMessage`, `close`, the console, the text encoding classes and `structuredClone`,
+/// in a worker.
+fn install_worker_globals(
+    outbox: &Sender<WorkerEvent>,
+    closed: &Arc<AtomicBool>,
+    ctx: &mut Context,
+) -> JsResult<()> {
+    let console = Console::init(ctx);
+    ctx.register_global_property(Console::NAME, console, Attribute::all())?;
+    register_text_encoding(ctx)?;
+    register_structured_clone(ctx)?;
+    let global = ctx.global_object();
+    ctx.register_global_property(js_str!("self"), global, Attribute::all())?;
+
+    let outbox = outbox.clone();
+    let post_message = native_function(ctx, "postMessage", 1, move |_, args, ctx| {
+        let message = js_value_to_msgpack(&arg(args, 0), ctx)?;
+        let _ = outbox.send(WorkerEvent::Message(message));
+        Ok(JsValue::undefined())
+    });
+    ctx.register_global_property(js_str!("postMessage"), post_message, Attribute::all())?;
+
+    let closed = closed.clone();
+    let close = native_function(ctx, "close", 0, move |_, _, _| {
+        closed.store(true, Ordering::Relaxed);
+        Ok(JsValue::undefined())
+    });
+    ctx.register_global_property(js_str!("close"), close, Attribute::all())?;
+    ctx.register_global_property(js_str!("onmessage"), JsValue::null(), Attribute::all())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn workers_stuck_in_a_loop_fail_out_of_it() {
+        let (_inbox, worker_inbox) = channel();
+        let (worker_outbox, outbox) = channel();
+        let worker = Worker {
+            inbox: worker_inbox,
+            outbox: worker_outbox,
+            closed: Arc::new(AtomicBool::new(false)),
+            loop_iteration_limit: 1_000,
+        };
+        std::thread::spawn(move || worker.run("try { while (true) {} } catch (err) {}"));
+        let timeout = Duration::from_secs(10);
+        let Ok(WorkerEvent::Error(message)) = outbox.recv_timeout(timeout) else {
+            panic!("the worker should fail out of its loop");
+        };
+        assert!(message.contains("loop"), "{message}");
+        assert!(matches!(
+            outbox.recv_timeout(timeout),
+            Ok(WorkerEvent::Stopped)
+        ));
+    }
+}